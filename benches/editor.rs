@@ -0,0 +1,45 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use pomp::editor::{line_col, next_word_end, pos_from_line_col, prev_word_start};
+use ropey::Rope;
+use std::hint::black_box;
+
+/// See the transforms benchmarks; several megabytes of input with
+/// `--features bench-mode`.
+const LARGE: bool = cfg!(feature = "bench-mode");
+
+/// An input box holding `lines` lines of prose-like text.
+fn sample_input(lines: usize) -> Rope {
+    let line = "the quick brown fox jumps over the lazy dog, https://example.com/a_b?c=1\n";
+    Rope::from(line.repeat(lines).as_str())
+}
+
+/// Cursor math runs on every keypress and every frame, so it should stay
+/// cheap even when a large paste lands in the input box.
+fn bench_cursor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cursor");
+    for lines in [10, 10_000].into_iter().chain(LARGE.then_some(100_000)) {
+        let text = sample_input(lines);
+        let end = text.len_chars();
+        let middle = end / 2;
+        let (middle_line, middle_col) = line_col(&text, middle);
+
+        group.bench_with_input(BenchmarkId::new("line_col", lines), &text, |b, text| {
+            b.iter(|| line_col(black_box(text), black_box(end)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("pos_from_line_col", lines),
+            &text,
+            |b, text| b.iter(|| pos_from_line_col(black_box(text), middle_line, middle_col)),
+        );
+        group.bench_with_input(BenchmarkId::new("word_moves", lines), &text, |b, text| {
+            b.iter(|| {
+                let back = prev_word_start(black_box(text), middle);
+                next_word_end(black_box(text), back)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_cursor);
+criterion_main!(benches);
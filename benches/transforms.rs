@@ -0,0 +1,86 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use pomp::cmds::{
+    CssOptions, base64_decode, base64_encode, css_minify, json_format, json_minify, sha256_hex,
+};
+use std::hint::black_box;
+
+/// Multi-megabyte inputs take a while to run, so they're only included
+/// with `--features bench-mode`.
+const LARGE: bool = cfg!(feature = "bench-mode");
+
+/// Roughly `records` objects of mixed JSON types, about 150 bytes each.
+fn sample_json(records: usize) -> String {
+    let items: Vec<String> = (0..records)
+        .map(|i| {
+            format!(
+                r#"{{"id":{},"name":"user-{}","email":"user{}@example.com","active":{},"score":{}.5,"tags":["a","b","c"]}}"#,
+                i,
+                i,
+                i,
+                i % 2 == 0,
+                i
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+fn sample_css(rules: usize) -> String {
+    (0..rules)
+        .map(|i| {
+            format!(
+                ".card-{} > .title:hover {{\n  color: #ff0000;\n  margin: 0px 0px {}px 0px;\n  background-color: rgba(0, 0, 0, 0.5);\n}}\n",
+                i, i
+            )
+        })
+        .collect()
+}
+
+fn bench_json(c: &mut Criterion) {
+    let mut group = c.benchmark_group("json");
+    for records in [100, 10_000].into_iter().chain(LARGE.then_some(50_000)) {
+        let input = sample_json(records);
+        let formatted = json_format(&input).unwrap();
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("format", records), &input, |b, input| {
+            b.iter(|| json_format(black_box(input)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("minify", records),
+            &formatted,
+            |b, input| b.iter(|| json_minify(black_box(input))),
+        );
+    }
+    group.finish();
+}
+
+fn bench_css(c: &mut Criterion) {
+    let mut group = c.benchmark_group("css");
+    for rules in [100, 5_000].into_iter().chain(LARGE.then_some(40_000)) {
+        let input = sample_css(rules);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("minify", rules), &input, |b, input| {
+            b.iter(|| css_minify(black_box(input), &CssOptions::default()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_base64_and_hash(c: &mut Criterion) {
+    let input = sample_json(if LARGE { 50_000 } else { 10_000 });
+    let encoded = base64_encode(&input);
+
+    let mut group = c.benchmark_group("encoding");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("base64_encode", |b| {
+        b.iter(|| base64_encode(black_box(&input)))
+    });
+    group.bench_function("base64_decode", |b| {
+        b.iter(|| base64_decode(black_box(&encoded)))
+    });
+    group.bench_function("sha256", |b| b.iter(|| sha256_hex(black_box(&input))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_json, bench_css, bench_base64_and_hash);
+criterion_main!(benches);
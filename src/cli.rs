@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::paths::validate_profile;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "A terminal scratchpad for transforming text")]
+pub struct Cli {
+    /// Use a separate config, plugins, scripts and stashes
+    #[arg(long, env = "POMP_PROFILE", value_parser = parse_profile)]
+    pub profile: Option<String>,
+
+    /// Serve transforms over JSON-RPC on this address instead of starting the UI
+    #[arg(long, value_name = "ADDR", conflicts_with = "connect")]
+    pub server: Option<String>,
+
+    /// Serve transforms as Model Context Protocol tools over stdio
+    #[arg(long, conflicts_with_all = ["server", "connect"])]
+    pub mcp: bool,
+
+    /// Load a file into the buffer before the UI starts, or stdin with `-`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["server", "mcp", "connect"])]
+    pub input: Option<PathBuf>,
+
+    /// Run a command on the buffer before the UI starts. Repeat it, or join
+    /// commands with ` | `, to run a pipeline
+    #[arg(
+        long = "run",
+        value_name = "COMMAND",
+        conflicts_with_all = ["server", "mcp", "connect"]
+    )]
+    pub run: Vec<String>,
+
+    /// Print the buffer to stdout on exit and draw the UI on stderr, so
+    /// `result=$(pomp --emit)` captures the result
+    #[arg(long, conflicts_with_all = ["server", "mcp", "connect"])]
+    pub emit: bool,
+
+    /// Run a command on a pomp server, reading the input from stdin
+    #[arg(long, value_name = "ADDR")]
+    pub connect: Option<String>,
+
+    /// The command and its arguments for --connect; lists commands if omitted
+    #[arg(
+        requires = "connect",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    pub command: Vec<String>,
+}
+
+impl Cli {
+    /// The `--run` commands split into single steps. Only a `|` followed by
+    /// another command separates steps, so shell pipes inside `/pipe` stay.
+    pub fn pipeline(&self) -> Vec<String> {
+        self.run
+            .iter()
+            .flat_map(|run| run.split(" | /").enumerate())
+            .map(|(i, step)| match i {
+                0 => step.trim().to_string(),
+                _ => format!("/{}", step.trim()),
+            })
+            .filter(|step| !step.is_empty())
+            .collect()
+    }
+}
+
+fn parse_profile(name: &str) -> Result<String, String> {
+    validate_profile(name).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_steps() {
+        let cli = Cli::parse_from([
+            "pomp",
+            "--input",
+            "data.json",
+            "--run",
+            "/json-minify | /base64-encode",
+            "--run",
+            "/pipe grep -c a | wc -l",
+        ]);
+        assert_eq!(cli.input, Some(PathBuf::from("data.json")));
+        assert_eq!(
+            cli.pipeline(),
+            ["/json-minify", "/base64-encode", "/pipe grep -c a | wc -l"]
+        );
+        assert!(Cli::try_parse_from(["pomp", "--mcp", "--run", "/uuid"]).is_err());
+        assert!(Cli::try_parse_from(["pomp", "--server", "127.0.0.1:1", "--emit"]).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Clipboard backends. The system clipboard isn't available on headless
+//! machines or useful over SSH, where OSC 52 asks the local terminal to copy
+//! instead.
+
+use std::fmt;
+use std::io::Write;
+
+use arboard::Clipboard;
+use base64::{Engine as _, engine::general_purpose};
+
+use crate::config::{ClipboardBackend, ClipboardConfig};
+
+#[derive(Debug, Clone)]
+pub enum ClipboardError {
+    /// No backend could be opened, so copying and pasting are disabled
+    Unavailable(String),
+    Failed(String),
+    /// OSC 52 can only write; reading needs the terminal to answer a query,
+    /// which most refuse to do
+    WriteOnly,
+}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unavailable(reason) => {
+                write!(f, "No clipboard available - {}", reason)
+            }
+            Self::Failed(msg) => {
+                write!(f, "Clipboard error - {}", msg)
+            }
+            Self::WriteOnly => {
+                write!(
+                    f,
+                    "The OSC 52 clipboard can't be read - paste with your terminal instead"
+                )
+            }
+        }
+    }
+}
+
+pub trait ClipboardProvider {
+    fn get_text(&mut self) -> Result<String, ClipboardError>;
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+}
+
+struct SystemClipboard(Clipboard);
+
+impl ClipboardProvider for SystemClipboard {
+    fn get_text(&mut self) -> Result<String, ClipboardError> {
+        self.0
+            .get_text()
+            .map_err(|e| ClipboardError::Failed(e.to_string()))
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.0
+            .set_text(text)
+            .map_err(|e| ClipboardError::Failed(e.to_string()))
+    }
+}
+
+struct Osc52Clipboard;
+
+/// The OSC 52 sequence that sets the clipboard (`c`) to `text`. Inside GNU
+/// screen it has to be wrapped in a DCS passthrough to reach the terminal;
+/// tmux forwards it itself when `set-clipboard` is on (the default).
+pub fn osc52_sequence(text: &str, in_screen: bool) -> String {
+    let sequence = format!(
+        "\x1b]52;c;{}\x07",
+        general_purpose::STANDARD.encode(text.as_bytes())
+    );
+    if in_screen {
+        format!("\x1bP{}\x1b\\", sequence)
+    } else {
+        sequence
+    }
+}
+
+impl ClipboardProvider for Osc52Clipboard {
+    fn get_text(&mut self) -> Result<String, ClipboardError> {
+        Err(ClipboardError::WriteOnly)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        let sequence = osc52_sequence(text, crate::multiplexer::in_screen());
+        let mut stdout = crate::term::output();
+        stdout
+            .write_all(sequence.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(|e| ClipboardError::Failed(e.to_string()))
+    }
+}
+
+/// The clipboard opened at startup, or a copy of why it couldn't be.
+pub fn open(
+    clipboard: &mut Result<Box<dyn ClipboardProvider>, ClipboardError>,
+) -> Result<&mut dyn ClipboardProvider, ClipboardError> {
+    match clipboard {
+        Ok(clipboard) => Ok(clipboard.as_mut()),
+        Err(e) => Err(e.clone()),
+    }
+}
+
+fn over_ssh() -> bool {
+    std::env::var_os("SSH_CONNECTION").is_some() || std::env::var_os("SSH_TTY").is_some()
+}
+
+/// Opens the clipboard for `config`. In `auto` mode OSC 52 stands in for a
+/// missing system clipboard, so only an explicit backend can fail.
+pub fn provider(config: &ClipboardConfig) -> Result<Box<dyn ClipboardProvider>, ClipboardError> {
+    match config.backend {
+        ClipboardBackend::Osc52 => Ok(Box::new(Osc52Clipboard)),
+        ClipboardBackend::None => Err(ClipboardError::Unavailable(
+            "disabled by [clipboard] backend = \"none\"".to_string(),
+        )),
+        // A clipboard on the remote machine isn't the one the user sees
+        ClipboardBackend::Auto if over_ssh() => Ok(Box::new(Osc52Clipboard)),
+        ClipboardBackend::Auto => Ok(match Clipboard::new() {
+            Ok(clipboard) => Box::new(SystemClipboard(clipboard)),
+            Err(_) => Box::new(Osc52Clipboard),
+        }),
+        ClipboardBackend::System => match Clipboard::new() {
+            Ok(clipboard) => Ok(Box::new(SystemClipboard(clipboard))),
+            Err(e) => Err(ClipboardError::Unavailable(format!(
+                "{} (set [clipboard] backend = \"osc52\" to copy through the terminal)",
+                e
+            ))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(osc52_sequence("hi", true), "\x1bP\x1b]52;c;aGk=\x07\x1b\\");
+    }
+
+    #[test]
+    fn test_disabled_backend() {
+        let config = ClipboardConfig {
+            backend: ClipboardBackend::None,
+        };
+        assert!(matches!(
+            provider(&config),
+            Err(ClipboardError::Unavailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_osc52_is_write_only() {
+        assert!(matches!(
+            Osc52Clipboard.get_text(),
+            Err(ClipboardError::WriteOnly)
+        ));
+    }
+}
@@ -0,0 +1,535 @@
+use std::fmt;
+
+use super::dump::{DumpError, parse_dump};
+use super::time::DateTime;
+
+const TAR_BLOCK: usize = 512;
+const CPIO_TRAILER: &str = "TRAILER!!!";
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    InvalidDump(DumpError),
+    NotTar,
+    NotCpio,
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDump(err) => {
+                write!(f, "{}", err)
+            }
+            Self::NotTar => {
+                write!(f, "No tar header at offset 0 (bad checksum or too short)")
+            }
+            Self::NotCpio => {
+                write!(
+                    f,
+                    "No cpio header at offset 0 - expected newc, odc or binary"
+                )
+            }
+        }
+    }
+}
+
+impl From<DumpError> for ArchiveError {
+    fn from(err: DumpError) -> ArchiveError {
+        ArchiveError::InvalidDump(err)
+    }
+}
+
+/// One listed member; `mode` already includes the `ls -l` file type letter.
+struct Entry {
+    offset: usize,
+    mode: String,
+    owner: String,
+    size: u64,
+    mtime: i64,
+    name: String,
+    note: Option<String>,
+}
+
+/// How the walk over the headers ended.
+enum End {
+    Marker(usize),
+    Truncated(usize),
+    Garbage(usize),
+}
+
+/// Formats permission bits like `ls -l`, including setuid/setgid/sticky.
+fn format_mode(kind: char, mode: u32) -> String {
+    let mut formatted = String::from(kind);
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        formatted.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        formatted.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        formatted.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    formatted
+}
+
+/// The file type letter for the `S_IFMT` bits of a full `st_mode`.
+fn mode_kind(mode: u32) -> char {
+    match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '-',
+    }
+}
+
+fn render(kind: &str, entries: &[Entry], end: End) -> String {
+    let owner_width = entries
+        .iter()
+        .map(|e| e.owner.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    let size_width = entries
+        .iter()
+        .map(|e| e.size.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    let mut report = format!(
+        "{:<10}  {:<10}  {:<owner_width$}  {:>size_width$}  {:<20}  NAME",
+        "OFFSET", "MODE", "OWNER", "SIZE", "MTIME"
+    );
+    for entry in entries {
+        report.push_str(&format!(
+            "\n{:#010x}  {:<10}  {:<owner_width$}  {:>size_width$}  {:<20}  {}",
+            entry.offset,
+            entry.mode,
+            entry.owner,
+            entry.size,
+            DateTime::from_unix(entry.mtime).to_string(),
+            entry.name
+        ));
+        if let Some(note) = &entry.note {
+            report.push_str(&format!("  [{}]", note));
+        }
+    }
+
+    let count = match entries.len() {
+        1 => "1 entry".to_string(),
+        n => format!("{} entries", n),
+    };
+    report.push_str(&match end {
+        End::Marker(offset) => {
+            format!("\n\n{} {}, end of archive at {:#010x}", count, kind, offset)
+        }
+        End::Truncated(offset) => format!(
+            "\n\n{} {}, dump ends at {:#010x} before the end of archive",
+            count, kind, offset
+        ),
+        End::Garbage(offset) => format!(
+            "\n\n{} {}, no valid header at {:#010x}",
+            count, kind, offset
+        ),
+    });
+    report
+}
+
+/// Text up to the first NUL, as stored in fixed-width tar fields.
+fn tar_string(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Octal numbers padded with NULs or spaces, or the GNU base-256 encoding
+/// (high bit set) used for sizes over 8 GiB.
+fn tar_number(field: &[u8]) -> Option<u64> {
+    if field.first().is_some_and(|b| b & 0x80 != 0) {
+        return Some(field.iter().enumerate().fold(0u64, |value, (i, b)| {
+            let b = if i == 0 { b & 0x7f } else { *b };
+            (value << 8) | u64::from(b)
+        }));
+    }
+    let text = tar_string(field);
+    let text = text.trim_matches(|c: char| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+/// The header checksum is the byte sum with the checksum field as spaces.
+fn tar_checksum(header: &[u8]) -> u64 {
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            if (148..156).contains(&i) {
+                32
+            } else {
+                u64::from(*b)
+            }
+        })
+        .sum()
+}
+
+/// Reads `path`/`linkpath` overrides from pax extended header records
+/// (`<length> <key>=<value>\n`).
+fn pax_records(data: &[u8]) -> Vec<(String, String)> {
+    let text = String::from_utf8_lossy(data);
+    text.lines()
+        .filter_map(|record| {
+            let (_, record) = record.split_once(' ')?;
+            let (key, value) = record.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+pub fn tar_header(buffer: &str) -> Result<String, ArchiveError> {
+    let bytes = parse_dump(buffer)?;
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    let mut long_name: Option<String> = None;
+    let mut long_link: Option<String> = None;
+
+    let end = loop {
+        let Some(header) = bytes.get(offset..offset + TAR_BLOCK) else {
+            if offset == 0 {
+                return Err(ArchiveError::NotTar);
+            }
+            break End::Truncated(offset.min(bytes.len()));
+        };
+        if header.iter().all(|b| *b == 0) {
+            break End::Marker(offset);
+        }
+
+        let stored_checksum = tar_number(&header[148..156]);
+        let checksum_ok = stored_checksum == Some(tar_checksum(header));
+        let magic = &header[257..263];
+        let is_ustar = magic.starts_with(b"ustar");
+        if !checksum_ok && !is_ustar {
+            if offset == 0 {
+                return Err(ArchiveError::NotTar);
+            }
+            break End::Garbage(offset);
+        }
+
+        let size = tar_number(&header[124..136]).unwrap_or(0);
+        let data_start = offset + TAR_BLOCK;
+        let data_end = data_start.saturating_add(size as usize);
+        let data = bytes.get(data_start..data_end);
+        let typeflag = header[156];
+
+        match typeflag {
+            b'L' | b'K' => {
+                let value = data.map(tar_string);
+                if typeflag == b'L' {
+                    long_name = value;
+                } else {
+                    long_link = value;
+                }
+            }
+            b'x' => {
+                for (key, value) in data.map(pax_records).unwrap_or_default() {
+                    match key.as_str() {
+                        "path" => long_name = Some(value),
+                        "linkpath" => long_link = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            b'g' => {}
+            _ => {
+                let mut name = tar_string(&header[0..100]);
+                // POSIX ustar splits long paths into prefix/name
+                let prefix = tar_string(&header[345..500]);
+                if magic == b"ustar\0" && !prefix.is_empty() {
+                    name = format!("{}/{}", prefix, name);
+                }
+                let name = long_name.take().unwrap_or(name);
+                let link = long_link
+                    .take()
+                    .unwrap_or_else(|| tar_string(&header[157..257]));
+
+                let kind = match typeflag {
+                    b'2' => 'l',
+                    b'3' => 'c',
+                    b'4' => 'b',
+                    b'5' => 'd',
+                    b'6' => 'p',
+                    _ => '-',
+                };
+                let name = match typeflag {
+                    b'1' => format!("{} link to {}", name, link),
+                    b'2' => format!("{} -> {}", name, link),
+                    _ => name,
+                };
+
+                let uname = tar_string(&header[265..297]);
+                let gname = tar_string(&header[297..329]);
+                let owner = if is_ustar && !uname.is_empty() && !gname.is_empty() {
+                    format!("{}/{}", uname, gname)
+                } else {
+                    format!(
+                        "{}/{}",
+                        tar_number(&header[108..116]).unwrap_or(0),
+                        tar_number(&header[116..124]).unwrap_or(0)
+                    )
+                };
+
+                entries.push(Entry {
+                    offset,
+                    mode: format_mode(kind, tar_number(&header[100..108]).unwrap_or(0) as u32),
+                    owner,
+                    size,
+                    mtime: tar_number(&header[136..148]).unwrap_or(0) as i64,
+                    name,
+                    note: (!checksum_ok).then(|| "bad checksum".to_string()),
+                });
+            }
+        }
+
+        offset = data_start + (size as usize).div_ceil(TAR_BLOCK) * TAR_BLOCK;
+    };
+
+    Ok(render("in tar", &entries, end))
+}
+
+/// The fixed-width numeric fields of a cpio header, in stored order.
+struct CpioHeader {
+    mode: u32,
+    uid: u64,
+    gid: u64,
+    mtime: i64,
+    name_size: usize,
+    file_size: usize,
+    header_size: usize,
+    /// Name and data are padded to this alignment
+    align: usize,
+}
+
+fn ascii_number(field: &[u8], radix: u32) -> Option<u64> {
+    u64::from_str_radix(std::str::from_utf8(field).ok()?, radix).ok()
+}
+
+fn cpio_header(bytes: &[u8]) -> Option<CpioHeader> {
+    let magic = bytes.get(0..6)?;
+    if magic == b"070701" || magic == b"070702" {
+        // newc/crc: thirteen 8-digit hex fields after the magic
+        let header = bytes.get(0..110)?;
+        let field = |i: usize| ascii_number(&header[6 + i * 8..14 + i * 8], 16);
+        return Some(CpioHeader {
+            mode: field(1)? as u32,
+            uid: field(2)?,
+            gid: field(3)?,
+            mtime: field(5)? as i64,
+            file_size: field(6)? as usize,
+            name_size: field(11)? as usize,
+            header_size: 110,
+            align: 4,
+        });
+    }
+    if magic == b"070707" {
+        // odc: 6-digit octal fields, with 11 digits for mtime and size
+        let header = bytes.get(0..76)?;
+        let field = |start: usize, len: usize| ascii_number(&header[start..start + len], 8);
+        return Some(CpioHeader {
+            mode: field(18, 6)? as u32,
+            uid: field(24, 6)?,
+            gid: field(30, 6)?,
+            mtime: field(48, 11)? as i64,
+            name_size: field(59, 6)? as usize,
+            file_size: field(65, 11)? as usize,
+            header_size: 76,
+            align: 1,
+        });
+    }
+    // Old binary format: 16-bit words in either byte order, with 32-bit
+    // values stored most significant word first
+    let header = bytes.get(0..26)?;
+    let word: fn([u8; 2]) -> u16 = match [header[0], header[1]] {
+        [0xc7, 0x71] => u16::from_le_bytes,
+        [0x71, 0xc7] => u16::from_be_bytes,
+        _ => return None,
+    };
+    let field = |i: usize| u64::from(word([header[i * 2], header[i * 2 + 1]]));
+    Some(CpioHeader {
+        mode: field(3) as u32,
+        uid: field(4),
+        gid: field(5),
+        mtime: ((field(8) << 16) | field(9)) as i64,
+        name_size: field(10) as usize,
+        file_size: ((field(11) << 16) | field(12)) as usize,
+        header_size: 26,
+        align: 2,
+    })
+}
+
+pub fn cpio_inspect(buffer: &str) -> Result<String, ArchiveError> {
+    let bytes = parse_dump(buffer)?;
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    let end = loop {
+        let Some(header) = cpio_header(&bytes[offset.min(bytes.len())..]) else {
+            if offset == 0 {
+                return Err(ArchiveError::NotCpio);
+            }
+            if offset >= bytes.len() {
+                break End::Truncated(bytes.len());
+            }
+            break End::Garbage(offset);
+        };
+
+        let name_start = offset + header.header_size;
+        let name_end = name_start + header.name_size;
+        let Some(name) = bytes.get(name_start..name_end) else {
+            break End::Truncated(bytes.len());
+        };
+        let name = tar_string(name);
+        if name == CPIO_TRAILER {
+            break End::Marker(offset);
+        }
+
+        let data_start = name_end.next_multiple_of(header.align);
+        let data_end = data_start + header.file_size;
+        let kind = mode_kind(header.mode);
+        let name = match bytes.get(data_start..data_end) {
+            Some(target) if kind == 'l' => {
+                format!("{} -> {}", name, String::from_utf8_lossy(target))
+            }
+            _ => name,
+        };
+
+        entries.push(Entry {
+            offset,
+            mode: format_mode(kind, header.mode),
+            owner: format!("{}/{}", header.uid, header.gid),
+            size: header.file_size as u64,
+            mtime: header.mtime,
+            name,
+            note: None,
+        });
+
+        offset = data_end.next_multiple_of(header.align);
+    };
+
+    Ok(render("in cpio", &entries, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn tar_entry(name: &str, typeflag: u8, mode: u32, data: &[u8]) -> Vec<u8> {
+        let mut header = vec![0u8; TAR_BLOCK];
+        let mut put = |start: usize, value: &[u8]| {
+            header[start..start + value.len()].copy_from_slice(value);
+        };
+        put(0, name.as_bytes());
+        put(100, format!("{:07o}\0", mode).as_bytes());
+        put(108, b"0001750\0");
+        put(116, b"0001750\0");
+        put(124, format!("{:011o}\0", data.len()).as_bytes());
+        put(136, b"14544400200\0");
+        put(156, &[typeflag]);
+        put(257, b"ustar\x0000");
+        let checksum = tar_checksum(&header);
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+        header.extend_from_slice(data);
+        header.resize(TAR_BLOCK + data.len().div_ceil(TAR_BLOCK) * TAR_BLOCK, 0);
+        header
+    }
+
+    #[test]
+    fn test_tar_header_lists_entries() {
+        let mut archive = tar_entry("hello.txt", b'0', 0o644, b"hello\n");
+        archive.extend(tar_entry("bin/", b'5', 0o4755, b""));
+        archive.extend(vec![0; TAR_BLOCK * 2]);
+
+        let result = tar_header(&hex(&archive)).unwrap();
+        assert_eq!(
+            result,
+            "OFFSET      MODE        OWNER      SIZE  MTIME                 NAME\n\
+             0x00000000  -rw-r--r--  1000/1000     6  2024-01-01T00:00:00Z  hello.txt\n\
+             0x00000400  drwsr-xr-x  1000/1000     0  2024-01-01T00:00:00Z  bin/\n\
+             \n\
+             2 entries in tar, end of archive at 0x00000600"
+        );
+    }
+
+    #[test]
+    fn test_tar_header_truncated_and_bad_checksum() {
+        let mut archive = tar_entry("a.txt", b'0', 0o600, &[b'x'; 600]);
+        archive[0] = b'b';
+        archive.truncate(TAR_BLOCK + 10);
+
+        let result = tar_header(&hex(&archive)).unwrap();
+        assert!(result.contains("b.txt  [bad checksum]"));
+        assert!(
+            result.ends_with("1 entry in tar, dump ends at 0x0000020a before the end of archive")
+        );
+        assert!(tar_header("00 01 02 03").is_err());
+    }
+
+    #[test]
+    fn test_cpio_inspect_newc() {
+        let mut archive = String::new();
+        for (name, mode, data) in [
+            ("a.txt", 0o100644, "hello\n"),
+            ("link", 0o120777, "a.txt"),
+            (CPIO_TRAILER, 0, ""),
+        ] {
+            let fields = [
+                1,
+                mode,
+                0,
+                0,
+                1,
+                0,
+                data.len(),
+                0,
+                0,
+                0,
+                0,
+                name.len() + 1,
+                0,
+            ];
+            let mut entry = String::from("070701");
+            for field in fields {
+                entry.push_str(&format!("{:08X}", field));
+            }
+            entry.push_str(name);
+            entry.push('\0');
+            while !entry.len().is_multiple_of(4) {
+                entry.push('\0');
+            }
+            entry.push_str(data);
+            while !entry.len().is_multiple_of(4) {
+                entry.push('\0');
+            }
+            archive.push_str(&entry);
+        }
+
+        let result = cpio_inspect(&hex(archive.as_bytes())).unwrap();
+        assert_eq!(
+            result,
+            "OFFSET      MODE        OWNER  SIZE  MTIME                 NAME\n\
+             0x00000000  -rw-r--r--  0/0       6  1970-01-01T00:00:00Z  a.txt\n\
+             0x0000007c  lrwxrwxrwx  0/0       5  1970-01-01T00:00:00Z  link -> a.txt\n\
+             \n\
+             2 entries in cpio, end of archive at 0x000000f8"
+        );
+        assert!(cpio_inspect("deadbeef").is_err());
+    }
+}
@@ -1,12 +1,12 @@
 use std::fmt;
-use std::string::FromUtf8Error;
 
 use base64::{Engine as _, engine::general_purpose};
 
+use super::dump::bytes_to_text;
+
 #[derive(Debug)]
 pub enum DecodeError {
     Base64DecodeError(()),
-    FromUtf8Error(()),
 }
 
 impl fmt::Display for DecodeError {
@@ -15,9 +15,6 @@ impl fmt::Display for DecodeError {
             Self::Base64DecodeError(..) => {
                 write!(f, "invalid base64 input")
             }
-            Self::FromUtf8Error(..) => {
-                write!(f, "decoded base64 is not valid UTF-8")
-            }
         }
     }
 }
@@ -28,32 +25,99 @@ impl From<base64::DecodeError> for DecodeError {
     }
 }
 
-impl From<FromUtf8Error> for DecodeError {
-    fn from(_err: FromUtf8Error) -> DecodeError {
-        DecodeError::FromUtf8Error(())
+/// Line length for MIME (RFC 2045) base64 bodies.
+pub const MIME_LINE_WIDTH: usize = 76;
+
+#[derive(Debug)]
+pub enum WrapError {
+    InvalidWidth(String),
+    UnknownOption(String),
+}
+
+impl fmt::Display for WrapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidWidth(width) => {
+                write!(f, "Invalid line width '{}'", width)
+            }
+            Self::UnknownOption(option) => {
+                write!(
+                    f,
+                    "Unknown option '{}' - expected --mime or --wrap=<width>",
+                    option
+                )
+            }
+        }
+    }
+}
+
+fn parse_width(width: &str) -> Result<usize, WrapError> {
+    match width.parse::<usize>() {
+        Ok(width) if width > 0 => Ok(width),
+        _ => Err(WrapError::InvalidWidth(width.to_string())),
     }
 }
 
-fn add_base64_padding(input: &str) -> String {
-    let trimmed = input.trim();
-    let padding_needed = (4 - (trimmed.len() % 4)) % 4;
+/// Drops embedded whitespace, so wrapped blobs from emails and certificates
+/// decode, and restores any missing padding.
+pub(super) fn normalize_base64(input: &str) -> String {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let padding_needed = (4 - (compact.len() % 4)) % 4;
     if padding_needed == 0 {
-        trimmed.to_string()
+        compact
     } else {
-        format!("{}{}", trimmed, "=".repeat(padding_needed))
+        format!("{}{}", compact, "=".repeat(padding_needed))
     }
 }
 
+fn wrap(text: &str, width: usize) -> String {
+    text.as_bytes()
+        .chunks(width)
+        // base64 is ASCII, so every chunk is valid UTF-8
+        .map(|chunk| String::from_utf8_lossy(chunk))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decodes to text, or to hex when the bytes aren't UTF-8, so binary
+/// payloads like gzip can go on to /gunzip.
 pub fn base64_decode(buffer: &str) -> Result<String, DecodeError> {
-    let padded = add_base64_padding(buffer);
+    let padded = normalize_base64(buffer);
     let decoded_bytes = general_purpose::STANDARD.decode(&padded)?;
-    let decoded_str = String::from_utf8(decoded_bytes)?;
-    Ok(decoded_str)
+    Ok(bytes_to_text(decoded_bytes))
 }
 
 pub fn base64_encode(buffer: &str) -> String {
-    let encoded = general_purpose::STANDARD.encode(buffer.as_bytes());
-    encoded
+    general_purpose::STANDARD.encode(buffer.as_bytes())
+}
+
+/// `/base64-encode` with `--mime` (76-column lines) or `--wrap=<width>`.
+pub fn base64_encode_with(buffer: &str, args: &[&str]) -> Result<String, WrapError> {
+    let mut width = None;
+    for arg in args {
+        if *arg == "--mime" {
+            width = Some(MIME_LINE_WIDTH);
+        } else if let Some(value) = arg.strip_prefix("--wrap=") {
+            width = Some(parse_width(value)?);
+        } else {
+            return Err(WrapError::UnknownOption(arg.to_string()));
+        }
+    }
+    let encoded = base64_encode(buffer);
+    Ok(match width {
+        Some(width) => wrap(&encoded, width),
+        None => encoded,
+    })
+}
+
+/// Re-wraps existing base64 text at `width` columns (76 by default).
+pub fn base64_split(buffer: &str, width: Option<&str>) -> Result<String, WrapError> {
+    let width = width
+        .map(parse_width)
+        .transpose()?
+        .unwrap_or(MIME_LINE_WIDTH);
+    let compact: String = buffer.chars().filter(|c| !c.is_whitespace()).collect();
+    Ok(wrap(&compact, width))
 }
 
 #[cfg(test)]
@@ -92,6 +156,11 @@ mod tests {
         assert_eq!(result, "Hi");
     }
 
+    #[test]
+    fn test_base64_decode_binary_as_hex() {
+        assert_eq!(base64_decode("//4=").unwrap(), "fffe");
+    }
+
     #[test]
     fn test_base64_encode() {
         let input = "Hello World";
@@ -106,4 +175,34 @@ mod tests {
         let decoded = base64_decode(&encoded).unwrap();
         assert_eq!(decoded, original);
     }
+
+    #[test]
+    fn test_base64_decode_ignores_line_breaks() {
+        let wrapped = "SGVs\r\nbG8g\n  V29y\nbGQ=\n";
+        assert_eq!(base64_decode(wrapped).unwrap(), "Hello World");
+    }
+
+    #[test]
+    fn test_base64_encode_mime_wrapping() {
+        let input = "x".repeat(100);
+        let encoded = base64_encode_with(&input, &["--mime"]).unwrap();
+        let lines: Vec<&str> = encoded.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), MIME_LINE_WIDTH);
+        assert_eq!(base64_decode(&encoded).unwrap(), input);
+
+        let pem_style = base64_encode_with(&input, &["--wrap=64"]).unwrap();
+        assert_eq!(pem_style.lines().next().unwrap().len(), 64);
+        assert!(base64_encode_with(&input, &["--wrap=0"]).is_err());
+        assert!(base64_encode_with(&input, &["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_base64_split_rewraps() {
+        let encoded = base64_encode(&"y".repeat(60));
+        let split = base64_split(&encoded, Some("20")).unwrap();
+        assert!(split.lines().all(|line| line.len() <= 20));
+        assert_eq!(base64_split(&split, Some("100")).unwrap(), encoded);
+        assert_eq!(base64_split(&split, None).unwrap().lines().count(), 2);
+    }
 }
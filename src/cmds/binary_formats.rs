@@ -0,0 +1,818 @@
+use std::fmt;
+
+use serde_json::{Map, Number, Value};
+
+use super::dump::{DumpError, parse_dump};
+use super::time::DateTime;
+
+/// Nested length-delimited fields are only tried as messages this deep.
+const MAX_PROTOBUF_DEPTH: usize = 32;
+
+/// Nesting limit for Thrift containers and FlatBuffers tables, so cyclic or
+/// hostile input can't recurse forever.
+const MAX_NESTING_DEPTH: usize = 32;
+
+const THRIFT_MESSAGE_TYPES: &[&str] = &["call", "reply", "exception", "oneway"];
+
+#[derive(Debug)]
+pub enum BinaryFormatError {
+    InvalidDump(DumpError),
+    Truncated(usize),
+    InvalidByte(u8, usize),
+    InvalidCbor(String),
+    InvalidProtobuf(usize),
+    InvalidThrift(usize),
+    InvalidFlatBuffer(&'static str),
+}
+
+impl fmt::Display for BinaryFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDump(err) => {
+                write!(f, "{}", err)
+            }
+            Self::Truncated(offset) => {
+                write!(f, "Data ends early at offset {:#x}", offset)
+            }
+            Self::InvalidByte(byte, offset) => {
+                write!(f, "Unexpected byte {:#04x} at offset {:#x}", byte, offset)
+            }
+            Self::InvalidCbor(msg) => {
+                write!(f, "Invalid CBOR - {}", msg)
+            }
+            Self::InvalidProtobuf(offset) => {
+                write!(
+                    f,
+                    "Not protobuf wire format - invalid field at offset {:#x}",
+                    offset
+                )
+            }
+            Self::InvalidThrift(offset) => {
+                write!(
+                    f,
+                    "Not Thrift compact protocol - invalid data at offset {:#x}",
+                    offset
+                )
+            }
+            Self::InvalidFlatBuffer(reason) => {
+                write!(f, "Not a FlatBuffer - {}", reason)
+            }
+        }
+    }
+}
+
+impl From<DumpError> for BinaryFormatError {
+    fn from(err: DumpError) -> BinaryFormatError {
+        BinaryFormatError::InvalidDump(err)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn float(value: f64) -> Value {
+    Number::from_f64(value).map_or_else(|| Value::String(value.to_string()), Value::Number)
+}
+
+/// JSON object keys must be strings, so other keys are spelled as JSON.
+fn object_key(key: Value) -> String {
+    match key {
+        Value::String(key) => key,
+        other => other.to_string(),
+    }
+}
+
+/// Renders each decoded value as pretty JSON, one after another for
+/// concatenated streams.
+fn render(values: Vec<Value>) -> String {
+    values
+        .iter()
+        .map(|value| serde_json::to_string_pretty(value).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BinaryFormatError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(BinaryFormatError::Truncated(self.bytes.len()));
+        };
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, BinaryFormatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn uint(&mut self, len: usize) -> Result<u64, BinaryFormatError> {
+        Ok(self
+            .take(len)?
+            .iter()
+            .fold(0, |value, b| (value << 8) | u64::from(*b)))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+}
+
+fn msgpack_string(reader: &mut Reader, len: usize) -> Result<Value, BinaryFormatError> {
+    let bytes = reader.take(len)?;
+    Ok(Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+fn msgpack_array(reader: &mut Reader, len: usize) -> Result<Value, BinaryFormatError> {
+    (0..len)
+        .map(|_| msgpack_value(reader))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+fn msgpack_map(reader: &mut Reader, len: usize) -> Result<Value, BinaryFormatError> {
+    let mut map = Map::new();
+    for _ in 0..len {
+        let key = object_key(msgpack_value(reader)?);
+        map.insert(key, msgpack_value(reader)?);
+    }
+    Ok(Value::Object(map))
+}
+
+/// Extension types become `{"ext": type, "data": hex}`, except the
+/// predefined timestamp (type -1), which is shown as a UTC time.
+fn msgpack_ext(reader: &mut Reader, len: usize) -> Result<Value, BinaryFormatError> {
+    let ext_type = reader.byte()? as i8;
+    let data = reader.take(len)?;
+    let seconds = match (ext_type, data.len()) {
+        (-1, 4) => Some(u32::from_be_bytes(data.try_into().unwrap_or_default()) as i64),
+        (-1, 8) => {
+            Some((u64::from_be_bytes(data.try_into().unwrap_or_default()) & 0x3_ffff_ffff) as i64)
+        }
+        (-1, 12) => Some(i64::from_be_bytes(data[4..].try_into().unwrap_or_default())),
+        _ => None,
+    };
+    if let Some(seconds) = seconds {
+        return Ok(Value::String(DateTime::from_unix(seconds).to_string()));
+    }
+    let mut ext = Map::new();
+    ext.insert("ext".to_string(), Value::from(ext_type));
+    ext.insert("data".to_string(), Value::String(hex(data)));
+    Ok(Value::Object(ext))
+}
+
+fn msgpack_value(reader: &mut Reader) -> Result<Value, BinaryFormatError> {
+    let offset = reader.pos;
+    let marker = reader.byte()?;
+    match marker {
+        0x00..=0x7f => Ok(Value::from(marker)),
+        0x80..=0x8f => msgpack_map(reader, usize::from(marker & 0x0f)),
+        0x90..=0x9f => msgpack_array(reader, usize::from(marker & 0x0f)),
+        0xa0..=0xbf => msgpack_string(reader, usize::from(marker & 0x1f)),
+        0xc0 => Ok(Value::Null),
+        0xc2 => Ok(Value::Bool(false)),
+        0xc3 => Ok(Value::Bool(true)),
+        0xc4..=0xc6 => {
+            let len = reader.uint(1 << (marker - 0xc4))? as usize;
+            Ok(Value::String(hex(reader.take(len)?)))
+        }
+        0xc7..=0xc9 => {
+            let len = reader.uint(1 << (marker - 0xc7))? as usize;
+            msgpack_ext(reader, len)
+        }
+        0xca => Ok(float(f64::from(f32::from_bits(reader.uint(4)? as u32)))),
+        0xcb => Ok(float(f64::from_bits(reader.uint(8)?))),
+        0xcc..=0xcf => Ok(Value::from(reader.uint(1 << (marker - 0xcc))?)),
+        0xd0..=0xd3 => {
+            let len = 1 << (marker - 0xd0);
+            let unsigned = reader.uint(len)?;
+            // Sign-extend from the stored width
+            let shift = 64 - len * 8;
+            Ok(Value::from(((unsigned << shift) as i64) >> shift))
+        }
+        0xd4..=0xd8 => msgpack_ext(reader, 1 << (marker - 0xd4)),
+        0xd9..=0xdb => {
+            let len = reader.uint(1 << (marker - 0xd9))? as usize;
+            msgpack_string(reader, len)
+        }
+        0xdc | 0xdd => {
+            let len = reader.uint(if marker == 0xdc { 2 } else { 4 })? as usize;
+            msgpack_array(reader, len)
+        }
+        0xde | 0xdf => {
+            let len = reader.uint(if marker == 0xde { 2 } else { 4 })? as usize;
+            msgpack_map(reader, len)
+        }
+        0xe0..=0xff => Ok(Value::from(marker as i8)),
+        0xc1 => Err(BinaryFormatError::InvalidByte(marker, offset)),
+    }
+}
+
+pub fn msgpack_to_json(buffer: &str) -> Result<String, BinaryFormatError> {
+    let bytes = parse_dump(buffer)?;
+    let mut reader = Reader {
+        bytes: &bytes,
+        pos: 0,
+    };
+    let mut values = Vec::new();
+    while !reader.is_empty() {
+        values.push(msgpack_value(&mut reader)?);
+    }
+    Ok(render(values))
+}
+
+/// Byte strings become hex, and tags other than the epoch-time tag 1 are
+/// kept as `{"tag": n, "value": ...}`.
+fn cbor_to_value(value: ciborium::Value) -> Value {
+    use ciborium::Value as Cbor;
+    match value {
+        Cbor::Integer(integer) => {
+            let integer = i128::from(integer);
+            i64::try_from(integer)
+                .map(Value::from)
+                .or_else(|_| u64::try_from(integer).map(Value::from))
+                .unwrap_or_else(|_| Value::String(integer.to_string()))
+        }
+        Cbor::Bytes(bytes) => Value::String(hex(&bytes)),
+        Cbor::Float(value) => float(value),
+        Cbor::Text(text) => Value::String(text),
+        Cbor::Bool(value) => Value::Bool(value),
+        Cbor::Null => Value::Null,
+        Cbor::Tag(1, inner) => match cbor_to_value(*inner) {
+            Value::Number(secs) if secs.as_i64().is_some() => {
+                Value::String(DateTime::from_unix(secs.as_i64().unwrap_or_default()).to_string())
+            }
+            other => other,
+        },
+        Cbor::Tag(tag, inner) => {
+            let mut tagged = Map::new();
+            tagged.insert("tag".to_string(), Value::from(tag));
+            tagged.insert("value".to_string(), cbor_to_value(*inner));
+            Value::Object(tagged)
+        }
+        Cbor::Array(items) => Value::Array(items.into_iter().map(cbor_to_value).collect()),
+        Cbor::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (object_key(cbor_to_value(key)), cbor_to_value(value)))
+                .collect(),
+        ),
+        _ => Value::Null,
+    }
+}
+
+pub fn cbor_to_json(buffer: &str) -> Result<String, BinaryFormatError> {
+    let bytes = parse_dump(buffer)?;
+    let mut remaining = bytes.as_slice();
+    let mut values = Vec::new();
+    while !remaining.is_empty() {
+        let value: ciborium::Value = ciborium::from_reader(&mut remaining)
+            .map_err(|e| BinaryFormatError::InvalidCbor(e.to_string()))?;
+        values.push(cbor_to_value(value));
+    }
+    Ok(render(values))
+}
+
+fn read_varint(reader: &mut Reader) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = reader.byte().ok()?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Splits a message into `(field number, value)` pairs, or gives the offset
+/// of the first thing that isn't a valid field.
+fn protobuf_fields(bytes: &[u8]) -> Result<Vec<(u64, Field<'_>)>, usize> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let mut fields = Vec::new();
+    while !reader.is_empty() {
+        let offset = reader.pos;
+        let key = read_varint(&mut reader).ok_or(offset)?;
+        let number = key >> 3;
+        if number == 0 {
+            return Err(offset);
+        }
+        let value = match key & 0x7 {
+            0 => Field::Varint(read_varint(&mut reader).ok_or(offset)?),
+            1 => Field::Fixed64(reader.uint(8).map_err(|_| offset)?.swap_bytes()),
+            2 => {
+                let len = read_varint(&mut reader).ok_or(offset)?;
+                Field::Bytes(reader.take(len as usize).map_err(|_| offset)?)
+            }
+            5 => Field::Fixed32((reader.uint(4).map_err(|_| offset)? as u32).swap_bytes()),
+            // Groups (3 and 4) are long deprecated and unknown types invalid
+            _ => return Err(offset),
+        };
+        fields.push((number, value));
+    }
+    Ok(fields)
+}
+
+/// Whether bytes read better as text: valid UTF-8 without control
+/// characters, which the tag bytes of small field numbers always are.
+fn is_text(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok_and(|text| {
+        !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\t' | '\r'))
+    })
+}
+
+fn protobuf_dump(bytes: &[u8], depth: usize, output: &mut Vec<String>) -> Result<(), usize> {
+    let indent = "  ".repeat(depth);
+    for (number, field) in protobuf_fields(bytes)? {
+        let line = match field {
+            Field::Varint(value) => {
+                let zigzag = (value >> 1) as i64 ^ -((value & 1) as i64);
+                if value as i64 >= 0 {
+                    format!("{}: varint {} (sint {})", number, value, zigzag)
+                } else {
+                    format!("{}: varint {} (int64 {})", number, value, value as i64)
+                }
+            }
+            Field::Fixed64(value) => format!(
+                "{}: fixed64 {:#018x} ({} / double {})",
+                number,
+                value,
+                value,
+                f64::from_bits(value)
+            ),
+            Field::Fixed32(value) => format!(
+                "{}: fixed32 {:#010x} ({} / float {})",
+                number,
+                value,
+                value,
+                f32::from_bits(value)
+            ),
+            Field::Bytes([]) => format!("{}: bytes (empty)", number),
+            Field::Bytes(data) if is_text(data) => {
+                format!("{}: string {:?}", number, String::from_utf8_lossy(data))
+            }
+            Field::Bytes(data) => {
+                let mut nested = Vec::new();
+                if depth < MAX_PROTOBUF_DEPTH && protobuf_dump(data, depth + 1, &mut nested).is_ok()
+                {
+                    output.push(format!("{}{}: message {{", indent, number));
+                    output.extend(nested);
+                    output.push(format!("{}}}", indent));
+                    continue;
+                }
+                format!("{}: bytes {}", number, hex(data))
+            }
+        };
+        output.push(format!("{}{}", indent, line));
+    }
+    Ok(())
+}
+
+/// Dumps protobuf wire format without a schema: field numbers, wire types,
+/// and the likely reading of each value. Length-delimited fields are shown
+/// as text, a nested message, or raw bytes, whichever fits.
+pub fn protobuf_decode(buffer: &str) -> Result<String, BinaryFormatError> {
+    let bytes = parse_dump(buffer)?;
+    let mut output = Vec::new();
+    protobuf_dump(&bytes, 0, &mut output).map_err(BinaryFormatError::InvalidProtobuf)?;
+    Ok(output.join("\n"))
+}
+
+/// Type names of the Thrift compact protocol, indexed by type id.
+fn thrift_type_name(ty: u8) -> &'static str {
+    match ty {
+        1 | 2 => "bool",
+        3 => "byte",
+        4 => "i16",
+        5 => "i32",
+        6 => "i64",
+        7 => "double",
+        // Thrift's string and binary share a wire type
+        8 => "string",
+        9 => "list",
+        10 => "set",
+        11 => "map",
+        12 => "struct",
+        13 => "uuid",
+        _ => "?",
+    }
+}
+
+fn thrift_varint(reader: &mut Reader) -> Result<u64, BinaryFormatError> {
+    let offset = reader.pos;
+    read_varint(reader).ok_or(BinaryFormatError::InvalidThrift(offset))
+}
+
+fn thrift_zigzag(reader: &mut Reader) -> Result<i64, BinaryFormatError> {
+    let value = thrift_varint(reader)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+/// Reads one value of a scalar type, or `None` for containers.
+fn thrift_scalar(
+    reader: &mut Reader,
+    ty: u8,
+    in_container: bool,
+) -> Result<Option<String>, BinaryFormatError> {
+    let text = match ty {
+        // Inside a struct a bool lives in the field header; in containers
+        // it takes a byte of its own
+        1 | 2 if in_container => (reader.byte()? == 1).to_string(),
+        1 => "true".to_string(),
+        2 => "false".to_string(),
+        3 => (reader.byte()? as i8).to_string(),
+        4..=6 => thrift_zigzag(reader)?.to_string(),
+        7 => f64::from_bits(reader.uint(8)?.swap_bytes()).to_string(),
+        8 => {
+            let len = thrift_varint(reader)?;
+            let data = reader.take(len as usize)?;
+            if is_text(data) {
+                format!("{:?}", String::from_utf8_lossy(data))
+            } else {
+                format!("0x{}", hex(data))
+            }
+        }
+        13 => hex(reader.take(16)?),
+        9..=12 => return Ok(None),
+        _ => return Err(BinaryFormatError::InvalidThrift(reader.pos)),
+    };
+    Ok(Some(text))
+}
+
+/// Writes one value as `{prefix}{value}`, spreading structs, maps and
+/// lists that hold anything but scalars over several lines.
+fn thrift_value(
+    reader: &mut Reader,
+    ty: u8,
+    depth: usize,
+    prefix: String,
+    in_container: bool,
+    output: &mut Vec<String>,
+) -> Result<(), BinaryFormatError> {
+    if depth > MAX_NESTING_DEPTH {
+        return Err(BinaryFormatError::InvalidThrift(reader.pos));
+    }
+    let indent = "  ".repeat(depth);
+    if let Some(scalar) = thrift_scalar(reader, ty, in_container)? {
+        // Container headers already name the element types
+        let name = match ty {
+            _ if in_container => String::new(),
+            8 if scalar.starts_with("0x") => "binary ".to_string(),
+            _ => format!("{} ", thrift_type_name(ty)),
+        };
+        output.push(format!("{}{}{}{}", indent, prefix, name, scalar));
+        return Ok(());
+    }
+    match ty {
+        12 => {
+            output.push(format!("{}{}struct {{", indent, prefix));
+            thrift_struct(reader, depth + 1, output)?;
+            output.push(format!("{}}}", indent));
+        }
+        11 => {
+            let len = thrift_varint(reader)?;
+            let types = if len > 0 { reader.byte()? } else { 0 };
+            let (key_type, value_type) = (types >> 4, types & 0x0f);
+            output.push(format!(
+                "{}{}map<{}, {}> {{",
+                indent,
+                prefix,
+                thrift_type_name(key_type),
+                thrift_type_name(value_type)
+            ));
+            for _ in 0..len {
+                let key = thrift_scalar(reader, key_type, true)?
+                    .ok_or(BinaryFormatError::InvalidThrift(reader.pos))?;
+                thrift_value(
+                    reader,
+                    value_type,
+                    depth + 1,
+                    format!("{} => ", key),
+                    true,
+                    output,
+                )?;
+            }
+            output.push(format!("{}}}", indent));
+        }
+        _ => {
+            let header = reader.byte()?;
+            let element_type = header & 0x0f;
+            let len = match header >> 4 {
+                15 => thrift_varint(reader)?,
+                len => u64::from(len),
+            };
+            let kind = if ty == 9 { "list" } else { "set" };
+            let name = format!("{}<{}>", kind, thrift_type_name(element_type));
+            if !(9..=12).contains(&element_type) {
+                let items = (0..len)
+                    .map(|_| {
+                        thrift_scalar(reader, element_type, true)
+                            .map(|item| item.unwrap_or_default())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                output.push(format!(
+                    "{}{}{} [{}]",
+                    indent,
+                    prefix,
+                    name,
+                    items.join(", ")
+                ));
+                return Ok(());
+            }
+            output.push(format!("{}{}{} [", indent, prefix, name));
+            for _ in 0..len {
+                thrift_value(reader, element_type, depth + 1, String::new(), true, output)?;
+            }
+            output.push(format!("{}]", indent));
+        }
+    }
+    Ok(())
+}
+
+fn thrift_struct(
+    reader: &mut Reader,
+    depth: usize,
+    output: &mut Vec<String>,
+) -> Result<(), BinaryFormatError> {
+    let mut last_id: i64 = 0;
+    loop {
+        let header = reader.byte()?;
+        if header == 0 {
+            return Ok(());
+        }
+        let ty = header & 0x0f;
+        // Field ids are stored as a delta from the previous one when small
+        last_id = match header >> 4 {
+            0 => thrift_zigzag(reader)?,
+            delta => last_id + i64::from(delta),
+        };
+        thrift_value(reader, ty, depth, format!("{}: ", last_id), false, output)?;
+    }
+}
+
+/// Dumps Thrift compact protocol without an IDL: field ids, types and
+/// values, with the call name and sequence id of a message envelope shown
+/// first when there is one.
+pub fn thrift_decode(buffer: &str) -> Result<String, BinaryFormatError> {
+    let bytes = parse_dump(buffer)?;
+    let mut reader = Reader {
+        bytes: &bytes,
+        pos: 0,
+    };
+    let mut output = Vec::new();
+    if bytes.first() == Some(&0x82) {
+        reader.byte()?;
+        let kind = reader.byte()? >> 5;
+        let seq = thrift_varint(&mut reader)?;
+        let len = thrift_varint(&mut reader)?;
+        let name = String::from_utf8_lossy(reader.take(len as usize)?).into_owned();
+        let kind = THRIFT_MESSAGE_TYPES
+            .get(usize::from(kind).wrapping_sub(1))
+            .copied()
+            .unwrap_or("message");
+        output.push(format!("{} {:?} (seq {})", kind, name, seq));
+    }
+    thrift_struct(&mut reader, 0, &mut output)?;
+    if !reader.is_empty() {
+        return Err(BinaryFormatError::InvalidThrift(reader.pos));
+    }
+    Ok(output.join("\n"))
+}
+
+fn read_u16(bytes: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(pos..pos + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(pos..pos + 4)?.try_into().ok()?,
+    ))
+}
+
+/// The vtable entries of the table at `table`: its inline size and the
+/// offset of each field, zero for absent ones.
+fn flatbuffer_vtable(bytes: &[u8], table: usize) -> Option<(usize, Vec<usize>)> {
+    let soffset = read_u32(bytes, table)? as i32;
+    let vtable = (table as i64).checked_sub(i64::from(soffset))?;
+    let vtable = usize::try_from(vtable).ok()?;
+    let vtable_size = usize::from(read_u16(bytes, vtable)?);
+    let table_size = usize::from(read_u16(bytes, vtable + 2)?);
+    if vtable_size < 4 || vtable_size % 2 != 0 || table + table_size > bytes.len() {
+        return None;
+    }
+    let fields = (4..vtable_size)
+        .step_by(2)
+        .map(|pos| read_u16(bytes, vtable + pos).map(usize::from))
+        .collect::<Option<Vec<_>>>()?;
+    if fields
+        .iter()
+        .any(|offset| *offset >= table_size.max(4) && *offset != 0)
+    {
+        return None;
+    }
+    Some((table_size, fields))
+}
+
+/// A string at `target`: a length, the bytes and a NUL terminator.
+fn flatbuffer_string(bytes: &[u8], target: usize) -> Option<String> {
+    let len = read_u32(bytes, target)? as usize;
+    let data = bytes.get(target + 4..target.checked_add(4 + len)?)?;
+    (bytes.get(target + 4 + len) == Some(&0) && is_text(data))
+        .then(|| format!("{:?}", String::from_utf8_lossy(data)))
+}
+
+fn flatbuffer_table(
+    bytes: &[u8],
+    table: usize,
+    depth: usize,
+    prefix: String,
+    output: &mut Vec<String>,
+) -> Result<(), BinaryFormatError> {
+    if depth > MAX_NESTING_DEPTH {
+        return Err(BinaryFormatError::InvalidFlatBuffer("tables nest too deep"));
+    }
+    let (table_size, fields) = flatbuffer_vtable(bytes, table)
+        .ok_or(BinaryFormatError::InvalidFlatBuffer("invalid vtable"))?;
+    let indent = "  ".repeat(depth);
+    output.push(format!("{}{}table @{:#x} {{", indent, prefix, table));
+
+    // Without a schema a field's size is only bounded by the next field
+    let mut starts: Vec<usize> = fields.iter().copied().filter(|o| *o != 0).collect();
+    starts.push(table_size);
+    starts.sort_unstable();
+    for (id, &offset) in fields.iter().enumerate() {
+        if offset == 0 {
+            continue;
+        }
+        let size = starts
+            .iter()
+            .find(|start| **start > offset)
+            .map_or(0, |next| next - offset);
+        let pos = table + offset;
+        let field = &bytes[pos..pos + size];
+        let prefix = format!("{}: ", id);
+        let line = if size >= 4 {
+            let target = read_u32(bytes, pos).map(|value| pos + value as usize);
+            let is_table = target.is_some_and(|target| {
+                target != table && flatbuffer_vtable(bytes, target).is_some()
+            });
+            match target {
+                Some(target) if is_table => {
+                    flatbuffer_table(bytes, target, depth + 1, prefix, output)?;
+                    continue;
+                }
+                Some(target) if let Some(string) = flatbuffer_string(bytes, target) => {
+                    format!("string {}", string)
+                }
+                _ if size >= 8 => {
+                    let value = u64::from_le_bytes(field[..8].try_into().unwrap_or_default());
+                    format!("u64 {} / double {}", value, f64::from_bits(value))
+                }
+                _ => {
+                    let value = read_u32(bytes, pos).unwrap_or_default();
+                    format!("u32 {} / float {}", value, f32::from_bits(value))
+                }
+            }
+        } else if size >= 2 {
+            format!("u16 {}", read_u16(bytes, pos).unwrap_or_default())
+        } else {
+            format!("u8 {}", field.first().copied().unwrap_or_default())
+        };
+        output.push(format!("{}  {}{}", indent, prefix, line));
+    }
+    output.push(format!("{}}}", indent));
+    Ok(())
+}
+
+/// Best-effort dump of a FlatBuffer without its schema: walks the tables
+/// through their vtables, showing each field as a nested table or string
+/// where the bytes fit one, otherwise as a scalar sized by the field's slot.
+pub fn flatbuffer_inspect(buffer: &str) -> Result<String, BinaryFormatError> {
+    let bytes = parse_dump(buffer)?;
+    let root = read_u32(&bytes, 0)
+        .map(|offset| offset as usize)
+        .filter(|offset| *offset >= 4 && *offset < bytes.len())
+        .ok_or(BinaryFormatError::InvalidFlatBuffer("no root table offset"))?;
+    let mut output = Vec::new();
+    // The optional file identifier sits between the root offset and the
+    // first vtable
+    if root >= 8
+        && let Some(identifier) = bytes.get(4..8)
+        && identifier.iter().all(|b| b.is_ascii_alphanumeric())
+    {
+        output.push(format!(
+            "file identifier {:?}",
+            String::from_utf8_lossy(identifier)
+        ));
+    }
+    flatbuffer_table(&bytes, root, 0, String::new(), &mut output)?;
+    Ok(output.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_msgpack_to_json() {
+        // {"compact": true, "schema": 0, "n": -3, "f": 1.5, "b": bin[01 02]}
+        let dump = "85 a7 63 6f 6d 70 61 63 74 c3 a6 73 63 68 65 6d 61 00 \
+                    a1 6e fd a1 66 cb 3f f8 00 00 00 00 00 00 a1 62 c4 02 01 02";
+        assert_eq!(
+            msgpack_to_json(dump).unwrap(),
+            "{\n  \"b\": \"0102\",\n  \"compact\": true,\n  \"f\": 1.5,\n  \"n\": -3,\n  \"schema\": 0\n}"
+        );
+        assert!(msgpack_to_json("92 01").is_err());
+    }
+
+    #[test]
+    fn test_msgpack_timestamp_ext() {
+        assert_eq!(
+            msgpack_to_json("d6 ff 65 92 00 80").unwrap(),
+            "\"2024-01-01T00:00:00Z\""
+        );
+    }
+
+    #[test]
+    fn test_cbor_to_json() {
+        // {"a": [1, -2], 3: h'ff', "t": 1(0)}
+        let dump = "a3 61 61 82 01 21 03 41 ff 61 74 c1 00";
+        assert_eq!(
+            cbor_to_json(dump).unwrap(),
+            "{\n  \"3\": \"ff\",\n  \"a\": [\n    1,\n    -2\n  ],\n  \"t\": \"1970-01-01T00:00:00Z\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_protobuf_decode() {
+        // Field 1 = 150, field 2 = "testing", field 3 = {1: 1}
+        let dump = "08 96 01 12 07 74 65 73 74 69 6e 67 1a 02 08 01";
+        assert_eq!(
+            protobuf_decode(dump).unwrap(),
+            "1: varint 150 (sint 75)\n\
+             2: string \"testing\"\n\
+             3: message {\n  \
+             1: varint 1 (sint -1)\n\
+             }"
+        );
+        assert!(protobuf_decode("ff ff").is_err());
+    }
+
+    #[test]
+    fn test_thrift_decode() {
+        let message = "82 21 01 07 67 65 74 55 73 65 72 \
+                       15 54 18 03 62 6f 62 19 35 02 04 06 1c 11 00 1b 01 85 01 61 02 00";
+        assert_eq!(
+            thrift_decode(message).unwrap(),
+            "call \"getUser\" (seq 1)\n\
+             1: i32 42\n\
+             2: string \"bob\"\n\
+             3: list<i32> [1, 2, 3]\n\
+             4: struct {\n  \
+             1: bool true\n\
+             }\n\
+             5: map<string, i32> {\n  \
+             \"a\" => 1\n\
+             }"
+        );
+        assert!(thrift_decode("15 54").is_err());
+    }
+
+    #[test]
+    fn test_flatbuffer_inspect() {
+        // A table of {0: "Orc", 1: 150u16, 2: 3u8} behind a 10-byte vtable
+        let dump = "10 00 00 00 0a 00 0c 00 04 00 08 00 0a 00 00 00 \
+                    0c 00 00 00 08 00 00 00 96 00 03 00 \
+                    03 00 00 00 4f 72 63 00";
+        assert_eq!(
+            flatbuffer_inspect(dump).unwrap(),
+            "table @0x10 {\n  \
+             0: string \"Orc\"\n  \
+             1: u16 150\n  \
+             2: u16 3\n\
+             }"
+        );
+        assert!(flatbuffer_inspect("ff ff ff ff").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+
+use lightningcss::targets::Browsers;
+
+/// Recent releases of each browser, newest last, as (major, minor). A
+/// snapshot from late 2025 - `last N versions` counts back through these.
+const RELEASES: &[(&str, &[(u32, u32)])] = &[
+    (
+        "android",
+        &[(137, 0), (138, 0), (139, 0), (140, 0), (141, 0)],
+    ),
+    (
+        "chrome",
+        &[(137, 0), (138, 0), (139, 0), (140, 0), (141, 0)],
+    ),
+    ("edge", &[(137, 0), (138, 0), (139, 0), (140, 0), (141, 0)]),
+    (
+        "firefox",
+        &[(139, 0), (140, 0), (141, 0), (142, 0), (143, 0)],
+    ),
+    ("ios_saf", &[(16, 6), (17, 6), (18, 5), (18, 6), (26, 0)]),
+    ("opera", &[(118, 0), (119, 0), (120, 0), (121, 0), (122, 0)]),
+    ("safari", &[(16, 6), (17, 6), (18, 5), (18, 6), (26, 0)]),
+    ("samsung", &[(24, 0), (25, 0), (26, 0), (27, 0), (28, 0)]),
+];
+
+const FIREFOX_ESR: u32 = 140;
+
+/// Browserslist names, including the mobile variants lightningcss folds
+/// into their desktop engine.
+const ALIASES: &[(&str, &str)] = &[
+    ("and_chr", "chrome"),
+    ("and_ff", "firefox"),
+    ("android", "android"),
+    ("chrome", "chrome"),
+    ("edge", "edge"),
+    ("explorer", "ie"),
+    ("ff", "firefox"),
+    ("firefox", "firefox"),
+    ("ie", "ie"),
+    ("ios", "ios_saf"),
+    ("ios_saf", "ios_saf"),
+    ("op_mob", "opera"),
+    ("opera", "opera"),
+    ("safari", "safari"),
+    ("samsung", "samsung"),
+];
+
+/// A browserslist query this subset doesn't understand.
+#[derive(Debug)]
+pub struct TargetError(pub String);
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Unsupported target '{}' - try `last 2 versions`, `defaults` or `safari >= 15`",
+            self.0
+        )
+    }
+}
+
+fn encode(major: u32, minor: u32) -> u32 {
+    (major << 16) | (minor << 8)
+}
+
+fn browser(name: &str) -> Option<&'static str> {
+    ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map(|(_, browser)| *browser)
+}
+
+fn slot<'a>(browsers: &'a mut Browsers, name: &str) -> &'a mut Option<u32> {
+    match name {
+        "android" => &mut browsers.android,
+        "chrome" => &mut browsers.chrome,
+        "edge" => &mut browsers.edge,
+        "firefox" => &mut browsers.firefox,
+        "ie" => &mut browsers.ie,
+        "ios_saf" => &mut browsers.ios_saf,
+        "opera" => &mut browsers.opera,
+        "safari" => &mut browsers.safari,
+        _ => &mut browsers.samsung,
+    }
+}
+
+/// Lowers the minimum version for `name`, since every query is OR'd.
+fn include(browsers: &mut Browsers, name: &str, version: u32) {
+    let slot = slot(browsers, name);
+    *slot = Some(slot.map_or(version, |current| current.min(version)));
+}
+
+fn include_last(browsers: &mut Browsers, name: Option<&str>, count: usize) {
+    for (browser, releases) in RELEASES {
+        if name.is_some_and(|name| name != *browser) {
+            continue;
+        }
+        let (major, minor) = releases[releases.len() - count.clamp(1, releases.len())];
+        include(browsers, browser, encode(major, minor));
+    }
+}
+
+/// Parses `14`, `14.1` or the first half of a range like `15.2-15.3`.
+fn parse_version(text: &str) -> Option<(u32, Option<u32>)> {
+    let text = text.split('-').next()?;
+    let mut parts = text.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map(|minor| minor.parse()).transpose().ok()?;
+    Some((major, minor))
+}
+
+fn apply_query(browsers: &mut Browsers, query: &str) -> Result<(), TargetError> {
+    let unsupported = || TargetError(query.to_string());
+    let lowered = query.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    match words.as_slice() {
+        ["defaults"] => {
+            include_last(browsers, None, 2);
+            include(browsers, "firefox", encode(FIREFOX_ESR, 0));
+        }
+        // Dead browsers are only ever included when named, so these
+        // exclusions are already satisfied
+        ["not", "dead"] | ["not", "ie", ..] | ["not", "op_mini", "all"] => {}
+        ["firefox" | "ff", "esr"] => include(browsers, "firefox", encode(FIREFOX_ESR, 0)),
+        ["last", count, "version" | "versions"] => {
+            let count = count.parse().map_err(|_| unsupported())?;
+            include_last(browsers, None, count);
+        }
+        ["last", count, name, "version" | "versions"] => {
+            let count = count.parse().map_err(|_| unsupported())?;
+            let name = browser(name).ok_or_else(unsupported)?;
+            if name == "ie" {
+                include(browsers, name, encode(11, 0));
+            } else {
+                include_last(browsers, Some(name), count);
+            }
+        }
+        [name, op @ (">=" | ">"), version] => {
+            let name = browser(name).ok_or_else(unsupported)?;
+            let (major, minor) = parse_version(version).ok_or_else(unsupported)?;
+            let version = match (*op, minor) {
+                (">", None) => encode(major + 1, 0),
+                (">", Some(minor)) => encode(major, minor + 1),
+                (_, minor) => encode(major, minor.unwrap_or(0)),
+            };
+            include(browsers, name, version);
+        }
+        [name, version] => {
+            let name = browser(name).ok_or_else(unsupported)?;
+            let (major, minor) = parse_version(version).ok_or_else(unsupported)?;
+            include(browsers, name, encode(major, minor.unwrap_or(0)));
+        }
+        _ => return Err(unsupported()),
+    }
+    Ok(())
+}
+
+/// Resolves a browserslist query into the oldest version of each browser
+/// to support. Covers the common forms (`defaults`, `last N versions`,
+/// `<browser> >= <version>`, `firefox esr`, `not dead`) without the
+/// usage statistics `> 0.5%`-style queries need.
+pub fn resolve_targets(query: &str) -> Result<Browsers, TargetError> {
+    let mut browsers = Browsers::default();
+    for part in query.split(',').flat_map(|part| part.split(" or ")) {
+        let part = part.trim();
+        if !part.is_empty() {
+            apply_query(&mut browsers, part)?;
+        }
+    }
+    Ok(browsers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_targets() {
+        let browsers = resolve_targets("last 2 versions, not dead").unwrap();
+        assert_eq!(browsers.chrome, Some(140 << 16));
+        assert_eq!(browsers.safari, Some((18 << 16) | (6 << 8)));
+        assert_eq!(browsers.ie, None);
+
+        let browsers = resolve_targets("Safari >= 14.1 or Chrome > 90, ie 11").unwrap();
+        assert_eq!(browsers.safari, Some((14 << 16) | (1 << 8)));
+        assert_eq!(browsers.chrome, Some(91 << 16));
+        assert_eq!(browsers.ie, Some(11 << 16));
+        assert_eq!(browsers.firefox, None);
+
+        assert_eq!(
+            resolve_targets("defaults").unwrap().firefox,
+            Some(FIREFOX_ESR << 16)
+        );
+        assert!(resolve_targets("> 0.5%").is_err());
+        assert!(resolve_targets("netscape 4").is_err());
+    }
+}
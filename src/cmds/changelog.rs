@@ -0,0 +1,257 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ChangelogError {
+    NoCommits,
+}
+
+impl fmt::Display for ChangelogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoCommits => {
+                write!(f, "No commit messages found")
+            }
+        }
+    }
+}
+
+struct Commit {
+    hash: Option<String>,
+    subject: String,
+    body: Vec<String>,
+}
+
+struct Entry {
+    kind: String,
+    scope: Option<String>,
+    description: String,
+    hash: Option<String>,
+    breaking_note: Option<String>,
+}
+
+fn is_hash(word: &str) -> bool {
+    (7..=40).contains(&word.len()) && word.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Splits `git log` output into commits. Both the default multi-line format
+/// (`commit <sha>` headers with indented messages) and `--oneline` output are
+/// understood; anything else is treated as one commit subject per line.
+fn parse_commits(buffer: &str) -> Vec<Commit> {
+    let mut commits = Vec::new();
+
+    if buffer.lines().any(|line| line.starts_with("commit ")) {
+        let mut current: Option<Commit> = None;
+        for line in buffer.lines() {
+            if let Some(rest) = line.strip_prefix("commit ") {
+                commits.extend(current.take());
+                let hash = rest.split_whitespace().next().map(|h| h.to_string());
+                current = Some(Commit {
+                    hash,
+                    subject: String::new(),
+                    body: Vec::new(),
+                });
+                continue;
+            }
+            let Some(commit) = current.as_mut() else {
+                continue;
+            };
+            // Skip Author:/Date:/Merge: headers, which aren't indented
+            if !line.starts_with(' ') && !line.starts_with('\t') {
+                continue;
+            }
+            let text = line.trim();
+            if commit.subject.is_empty() {
+                if !text.is_empty() {
+                    commit.subject = text.to_string();
+                }
+            } else {
+                commit.body.push(text.to_string());
+            }
+        }
+        commits.extend(current);
+        return commits;
+    }
+
+    for line in buffer.lines() {
+        let mut text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let mut hash = None;
+        if let Some((first, rest)) = text.split_once(' ')
+            && is_hash(first)
+        {
+            hash = Some(first.to_string());
+            text = rest.trim_start();
+            // Drop `--decorate` ref names like "(HEAD -> main, tag: v1.0)"
+            if text.starts_with('(')
+                && let Some(end) = text.find(") ")
+            {
+                text = text[end + 2..].trim_start();
+            }
+        }
+        commits.push(Commit {
+            hash,
+            subject: text.to_string(),
+            body: Vec::new(),
+        });
+    }
+    commits
+}
+
+/// Parses a conventional commit subject such as `feat(parser)!: add X`.
+fn parse_entry(commit: Commit) -> Entry {
+    let breaking_note = commit.body.iter().find_map(|line| {
+        line.strip_prefix("BREAKING CHANGE:")
+            .or_else(|| line.strip_prefix("BREAKING-CHANGE:"))
+            .map(|note| note.trim().to_string())
+    });
+
+    let Some((prefix, description)) = commit.subject.split_once(": ") else {
+        return Entry {
+            kind: String::new(),
+            scope: None,
+            description: commit.subject,
+            hash: commit.hash,
+            breaking_note,
+        };
+    };
+
+    let (prefix, bang) = match prefix.strip_suffix('!') {
+        Some(stripped) => (stripped, true),
+        None => (prefix, false),
+    };
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.trim_end_matches(')').to_string())),
+        None => (prefix, None),
+    };
+
+    let is_conventional = !kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphabetic());
+    if !is_conventional {
+        return Entry {
+            kind: String::new(),
+            scope: None,
+            description: commit.subject.clone(),
+            hash: commit.hash,
+            breaking_note,
+        };
+    }
+
+    let breaking_note = match (bang, breaking_note) {
+        (_, Some(note)) => Some(note),
+        (true, None) => Some(String::new()),
+        (false, None) => None,
+    };
+
+    Entry {
+        kind: kind.to_lowercase(),
+        scope,
+        description: description.trim().to_string(),
+        hash: commit.hash,
+        breaking_note,
+    }
+}
+
+fn format_entry(entry: &Entry, description: &str) -> String {
+    let mut line = String::from("- ");
+    if let Some(scope) = &entry.scope {
+        line.push_str(&format!("**{}:** ", scope));
+    }
+    line.push_str(description);
+    if let Some(hash) = &entry.hash {
+        let short: String = hash.chars().take(7).collect();
+        line.push_str(&format!(" ({})", short));
+    }
+    line
+}
+
+pub fn changelog_format(buffer: &str, version: Option<&str>) -> Result<String, ChangelogError> {
+    let entries: Vec<Entry> = parse_commits(buffer)
+        .into_iter()
+        .filter(|commit| !commit.subject.is_empty())
+        .map(parse_entry)
+        .collect();
+
+    if entries.is_empty() {
+        return Err(ChangelogError::NoCommits);
+    }
+
+    let mut breaking = Vec::new();
+    let mut features = Vec::new();
+    let mut fixes = Vec::new();
+    let mut other = Vec::new();
+
+    for entry in &entries {
+        if let Some(note) = &entry.breaking_note {
+            let description = if note.is_empty() {
+                &entry.description
+            } else {
+                note
+            };
+            breaking.push(format_entry(entry, description));
+        }
+        let line = format_entry(entry, &entry.description);
+        match entry.kind.as_str() {
+            "feat" => features.push(line),
+            "fix" => fixes.push(line),
+            // Housekeeping commits don't belong in release notes
+            "chore" | "ci" | "style" | "test" | "build" => {}
+            _ => other.push(line),
+        }
+    }
+
+    let mut changelog = format!("## {}\n", version.unwrap_or("Unreleased"));
+    for (heading, lines) in [
+        ("⚠ Breaking Changes", breaking),
+        ("Features", features),
+        ("Fixes", fixes),
+        ("Other Changes", other),
+    ] {
+        if lines.is_empty() {
+            continue;
+        }
+        changelog.push_str(&format!("\n### {}\n\n", heading));
+        changelog.push_str(&lines.join("\n"));
+        changelog.push('\n');
+    }
+
+    Ok(changelog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changelog_format_oneline() {
+        let log = "a1b2c3d (HEAD -> main) feat(parser): support nesting\n\
+                   b2c3d4e fix: handle empty input\n\
+                   c3d4e5f chore: bump deps\n\
+                   d4e5f6a feat(api)!: drop v1 endpoints";
+        let result = changelog_format(log, Some("1.2.0")).unwrap();
+        assert_eq!(
+            result,
+            "## 1.2.0\n\
+             \n### ⚠ Breaking Changes\n\n\
+             - **api:** drop v1 endpoints (d4e5f6a)\n\
+             \n### Features\n\n\
+             - **parser:** support nesting (a1b2c3d)\n\
+             - **api:** drop v1 endpoints (d4e5f6a)\n\
+             \n### Fixes\n\n\
+             - handle empty input (b2c3d4e)\n"
+        );
+    }
+
+    #[test]
+    fn test_changelog_format_full_log_breaking_footer() {
+        let log = "commit 0123456789abcdef0123456789abcdef01234567\n\
+                   Author: Jane Doe <jane@example.com>\n\
+                   Date:   Mon Jan 1 00:00:00 2024 +0000\n\
+                   \n    refactor: rename config keys\n\
+                   \n    BREAKING CHANGE: `color` is now `theme`\n";
+        let result = changelog_format(log, None).unwrap();
+        assert!(result.starts_with("## Unreleased\n"));
+        assert!(result.contains("- `color` is now `theme` (0123456)"));
+        assert!(result.contains("### Other Changes\n\n- rename config keys (0123456)"));
+    }
+}
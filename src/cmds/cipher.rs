@@ -0,0 +1,78 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum CipherError {
+    InvalidShift(String),
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidShift(shift) => {
+                write!(f, "Invalid shift '{}' - expected a whole number", shift)
+            }
+        }
+    }
+}
+
+/// Rotates ASCII letters by `shift` places, keeping case. Everything else,
+/// including accented letters, passes through unchanged.
+fn rotate(text: &str, shift: i64) -> String {
+    let shift = shift.rem_euclid(26) as u8;
+    text.chars()
+        .map(|c| {
+            let base = match c {
+                'a'..='z' => b'a',
+                'A'..='Z' => b'A',
+                _ => return c,
+            };
+            ((c as u8 - base + shift) % 26 + base) as char
+        })
+        .collect()
+}
+
+pub fn rot13(buffer: &str) -> String {
+    rotate(buffer, 13)
+}
+
+/// Shifts by the given amount, or lists every shift when none is given so
+/// the readable one can be spotted.
+pub fn caesar(buffer: &str, shift: Option<&str>) -> Result<String, CipherError> {
+    let Some(shift) = shift else {
+        return Ok((1..26)
+            .map(|shift| format!("{:>2}: {}", shift, rotate(buffer, shift)))
+            .collect::<Vec<_>>()
+            .join("\n"));
+    };
+    let shift = shift
+        .trim_start_matches('+')
+        .parse::<i64>()
+        .map_err(|_| CipherError::InvalidShift(shift.to_string()))?;
+    Ok(rotate(buffer, shift))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rot13() {
+        assert_eq!(rot13("Hello, World! é"), "Uryyb, Jbeyq! é");
+        assert_eq!(rot13(&rot13("Why did the chicken")), "Why did the chicken");
+    }
+
+    #[test]
+    fn test_caesar() {
+        assert_eq!(caesar("xyz ABC", Some("3")).unwrap(), "abc DEF");
+        assert_eq!(caesar("abc", Some("-1")).unwrap(), "zab");
+        assert_eq!(caesar("abc", Some("+27")).unwrap(), "bcd");
+        let all = caesar("b", None).unwrap();
+        assert_eq!(all.lines().count(), 25);
+        assert!(all.starts_with(" 1: c\n 2: d\n"));
+        assert!(all.ends_with("25: a"));
+        assert!(matches!(
+            caesar("abc", Some("three")),
+            Err(CipherError::InvalidShift(_))
+        ));
+    }
+}
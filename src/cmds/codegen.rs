@@ -0,0 +1,558 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use super::json::strip_jsonc;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro", "override",
+    "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Words Go style writes in all caps, e.g. `UserID` rather than `UserId`.
+const GO_INITIALISMS: &[&str] = &[
+    "acl", "api", "ascii", "cpu", "css", "dns", "eof", "guid", "html", "http", "https", "id", "ip",
+    "json", "jwt", "os", "sql", "ssh", "tcp", "tls", "ttl", "udp", "ui", "uid", "uri", "url",
+    "utf8", "uuid", "xml",
+];
+
+#[derive(Debug)]
+pub enum CodegenError {
+    InvalidJson(String),
+    NotAnObject,
+    InvalidName(String),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidJson(msg) => {
+                write!(f, "Invalid JSON - {}", msg)
+            }
+            Self::NotAnObject => {
+                write!(f, "Expected a JSON object or an array of objects")
+            }
+            Self::InvalidName(name) => {
+                write!(f, "Invalid type name '{}'", name)
+            }
+        }
+    }
+}
+
+/// The inferred type of one or more JSON samples.
+#[derive(Clone, PartialEq)]
+enum Shape {
+    /// No sample seen yet, e.g. the items of `[]`
+    Unknown,
+    Null,
+    Bool,
+    Int,
+    Float,
+    Str,
+    Array(Box<Shape>),
+    Object(Vec<Field>),
+    Nullable(Box<Shape>),
+    /// Samples that disagree
+    Any,
+}
+
+#[derive(Clone, PartialEq)]
+struct Field {
+    key: String,
+    shape: Shape,
+    optional: bool,
+}
+
+fn infer(value: &Value) -> Shape {
+    match value {
+        Value::Null => Shape::Null,
+        Value::Bool(_) => Shape::Bool,
+        Value::Number(number) if number.is_f64() => Shape::Float,
+        Value::Number(_) => Shape::Int,
+        Value::String(_) => Shape::Str,
+        Value::Array(items) => Shape::Array(Box::new(
+            items.iter().map(infer).fold(Shape::Unknown, merge),
+        )),
+        Value::Object(map) => Shape::Object(
+            map.iter()
+                .map(|(key, value)| Field {
+                    key: key.clone(),
+                    shape: infer(value),
+                    optional: false,
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Combines two samples of the same value, so array items and repeated
+/// fields end up with one type.
+fn merge(a: Shape, b: Shape) -> Shape {
+    match (a, b) {
+        (Shape::Unknown, other) | (other, Shape::Unknown) => other,
+        (Shape::Null, Shape::Null) => Shape::Null,
+        (Shape::Nullable(inner), Shape::Null) | (Shape::Null, Shape::Nullable(inner)) => {
+            Shape::Nullable(inner)
+        }
+        (Shape::Null, other) | (other, Shape::Null) => Shape::Nullable(Box::new(other)),
+        (Shape::Nullable(a), Shape::Nullable(b)) => Shape::Nullable(Box::new(merge(*a, *b))),
+        (Shape::Nullable(a), other) | (other, Shape::Nullable(a)) => {
+            Shape::Nullable(Box::new(merge(*a, other)))
+        }
+        (Shape::Int, Shape::Float) | (Shape::Float, Shape::Int) => Shape::Float,
+        (Shape::Array(a), Shape::Array(b)) => Shape::Array(Box::new(merge(*a, *b))),
+        (Shape::Object(a), Shape::Object(b)) => Shape::Object(merge_fields(a, b)),
+        (a, b) if a == b => a,
+        _ => Shape::Any,
+    }
+}
+
+/// Fields missing from either side become optional.
+fn merge_fields(a: Vec<Field>, mut b: Vec<Field>) -> Vec<Field> {
+    let mut fields = Vec::with_capacity(a.len().max(b.len()));
+    for mut field in a {
+        match b.iter().position(|other| other.key == field.key) {
+            Some(index) => {
+                let other = b.remove(index);
+                field.optional |= other.optional;
+                field.shape = merge(field.shape, other.shape);
+            }
+            None => field.optional = true,
+        }
+        fields.push(field);
+    }
+    fields.extend(b.into_iter().map(|field| Field {
+        optional: true,
+        ..field
+    }));
+    fields
+}
+
+/// A language-neutral type reference, with objects replaced by the name
+/// of their generated definition.
+enum Ty {
+    Bool,
+    Int,
+    Float,
+    Str,
+    Any,
+    Array(Box<Ty>),
+    Named(String),
+    Nullable(Box<Ty>),
+}
+
+struct Struct {
+    name: String,
+    fields: Vec<(String, Ty, bool)>,
+}
+
+/// Splits `userName`, `user_name`, `HTTPServer` or `content-type` into
+/// lowercase words.
+fn words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let chars: Vec<char> = key.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|prev| {
+                prev.is_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn pascal_case(key: &str) -> String {
+    words(key).iter().map(|word| capitalize(word)).collect()
+}
+
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if word.ends_with("sses")
+        || word.ends_with("xes")
+        || word.ends_with("ches")
+        || word.ends_with("shes")
+    {
+        word[..word.len() - 2].to_string()
+    } else if word.ends_with('s') && !word.ends_with("ss") {
+        word[..word.len() - 1].to_string()
+    } else {
+        format!("{}Item", word)
+    }
+}
+
+/// Turns shapes into type references, giving every object a definition
+/// with a unique name. Definitions are listed outermost first.
+fn lower(shape: &Shape, hint: &str, structs: &mut Vec<Struct>) -> Ty {
+    match shape {
+        Shape::Unknown | Shape::Any => Ty::Any,
+        Shape::Null => Ty::Nullable(Box::new(Ty::Any)),
+        Shape::Bool => Ty::Bool,
+        Shape::Int => Ty::Int,
+        Shape::Float => Ty::Float,
+        Shape::Str => Ty::Str,
+        Shape::Array(items) => Ty::Array(Box::new(lower(items, &singular(hint), structs))),
+        Shape::Nullable(inner) => Ty::Nullable(Box::new(lower(inner, hint, structs))),
+        Shape::Object(fields) => {
+            let base = match pascal_case(hint) {
+                name if name.starts_with(|c: char| c.is_alphabetic()) => name,
+                name => format!("Type{}", name),
+            };
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while structs.iter().any(|existing| existing.name == name) {
+                name = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+            let index = structs.len();
+            structs.push(Struct {
+                name: name.clone(),
+                fields: Vec::new(),
+            });
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    let ty = lower(&field.shape, &field.key, structs);
+                    (field.key.clone(), ty, field.optional)
+                })
+                .collect();
+            structs[index].fields = fields;
+            Ty::Named(name)
+        }
+    }
+}
+
+/// Parses the sample and lowers it. A top-level array yields its item
+/// type plus the alias to emit for the array itself.
+fn prepare(buffer: &str, name: Option<&str>) -> Result<(Vec<Struct>, Option<Ty>), CodegenError> {
+    let root = name.unwrap_or("Root");
+    if !root.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !root.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(CodegenError::InvalidName(root.to_string()));
+    }
+    let value: Value = serde_json::from_str(&strip_jsonc(buffer))
+        .map_err(|e| CodegenError::InvalidJson(e.to_string()))?;
+    let mut structs = Vec::new();
+    match infer(&value) {
+        shape @ Shape::Object(_) => {
+            lower(&shape, root, &mut structs);
+            Ok((structs, None))
+        }
+        Shape::Array(items) if matches!(*items, Shape::Object(_)) => {
+            let item = lower(&items, &format!("{}Item", root), &mut structs);
+            Ok((structs, Some(item)))
+        }
+        _ => Err(CodegenError::NotAnObject),
+    }
+}
+
+fn rust_type(ty: &Ty) -> String {
+    match ty {
+        Ty::Bool => "bool".to_string(),
+        Ty::Int => "i64".to_string(),
+        Ty::Float => "f64".to_string(),
+        Ty::Str => "String".to_string(),
+        Ty::Any => "serde_json::Value".to_string(),
+        Ty::Array(items) => format!("Vec<{}>", rust_type(items)),
+        Ty::Named(name) => name.clone(),
+        Ty::Nullable(inner) => format!("Option<{}>", rust_type(inner)),
+    }
+}
+
+fn rust_field_name(key: &str) -> String {
+    let name = words(key).join("_");
+    if name.is_empty() {
+        "field".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else if RUST_KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Serde structs for the sample; fields that are missing from some
+/// samples or sometimes null become `Option`s.
+pub fn json_to_rust(buffer: &str, name: Option<&str>) -> Result<String, CodegenError> {
+    let (structs, alias) = prepare(buffer, name)?;
+    let mut output = String::from("use serde::{Deserialize, Serialize};\n");
+    if let Some(item) = alias {
+        output.push_str(&format!(
+            "\npub type {} = Vec<{}>;\n",
+            name.unwrap_or("Root"),
+            rust_type(&item)
+        ));
+    }
+    for definition in structs {
+        output.push_str("\n#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+        output.push_str(&format!("pub struct {} {{\n", definition.name));
+        for (key, ty, optional) in &definition.fields {
+            let field = rust_field_name(key);
+            if field.trim_start_matches("r#") != key {
+                output.push_str(&format!("    #[serde(rename = {:?})]\n", key));
+            }
+            let ty = match ty {
+                Ty::Nullable(_) => rust_type(ty),
+                _ if *optional => format!("Option<{}>", rust_type(ty)),
+                _ => rust_type(ty),
+            };
+            output.push_str(&format!("    pub {}: {},\n", field, ty));
+        }
+        output.push_str("}\n");
+    }
+    Ok(output.trim_end().to_string())
+}
+
+fn typescript_type(ty: &Ty) -> String {
+    match ty {
+        Ty::Bool => "boolean".to_string(),
+        Ty::Int | Ty::Float => "number".to_string(),
+        Ty::Str => "string".to_string(),
+        Ty::Any => "unknown".to_string(),
+        Ty::Nullable(inner) if matches!(**inner, Ty::Any) => "unknown".to_string(),
+        Ty::Array(items) => match **items {
+            Ty::Nullable(_) => format!("({})[]", typescript_type(items)),
+            _ => format!("{}[]", typescript_type(items)),
+        },
+        Ty::Named(name) => name.clone(),
+        Ty::Nullable(inner) => format!("{} | null", typescript_type(inner)),
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Interfaces for the sample; fields missing from some samples get `?`
+/// and sometimes-null ones `| null`.
+pub fn json_to_typescript(buffer: &str, name: Option<&str>) -> Result<String, CodegenError> {
+    let (structs, alias) = prepare(buffer, name)?;
+    let mut definitions = Vec::new();
+    if let Some(item) = alias {
+        definitions.push(format!(
+            "export type {} = {}[];",
+            name.unwrap_or("Root"),
+            typescript_type(&item)
+        ));
+    }
+    for definition in structs {
+        let mut output = format!("export interface {} {{\n", definition.name);
+        for (key, ty, optional) in &definition.fields {
+            let key = match is_identifier(key) {
+                true => key.clone(),
+                false => format!("{:?}", key),
+            };
+            let optional = if *optional { "?" } else { "" };
+            output.push_str(&format!(
+                "  {}{}: {};\n",
+                key,
+                optional,
+                typescript_type(ty)
+            ));
+        }
+        output.push('}');
+        definitions.push(output);
+    }
+    Ok(definitions.join("\n\n"))
+}
+
+fn go_type(ty: &Ty) -> String {
+    match ty {
+        Ty::Bool => "bool".to_string(),
+        Ty::Int => "int64".to_string(),
+        Ty::Float => "float64".to_string(),
+        Ty::Str => "string".to_string(),
+        Ty::Any => "any".to_string(),
+        Ty::Array(items) => format!("[]{}", go_type(items)),
+        Ty::Named(name) => name.clone(),
+        Ty::Nullable(inner) => go_pointer(inner),
+    }
+}
+
+/// Slices and `any` can already be nil, everything else needs a pointer.
+fn go_pointer(ty: &Ty) -> String {
+    match ty {
+        Ty::Any | Ty::Array(_) | Ty::Nullable(_) => go_type(ty),
+        _ => format!("*{}", go_type(ty)),
+    }
+}
+
+fn go_field_name(key: &str) -> String {
+    let name: String = words(key)
+        .iter()
+        .map(|word| match GO_INITIALISMS.contains(&word.as_str()) {
+            true => word.to_uppercase(),
+            false => capitalize(word),
+        })
+        .collect();
+    match name.starts_with(|c: char| c.is_alphabetic()) {
+        true => name,
+        false => format!("Field{}", name),
+    }
+}
+
+/// Structs with `json` tags, laid out the way gofmt aligns them. Optional
+/// fields become pointers tagged `omitempty`.
+pub fn json_to_go(buffer: &str, name: Option<&str>) -> Result<String, CodegenError> {
+    let (structs, alias) = prepare(buffer, name)?;
+    let mut definitions = Vec::new();
+    if let Some(item) = alias {
+        definitions.push(format!(
+            "type {} []{}",
+            name.unwrap_or("Root"),
+            go_type(&item)
+        ));
+    }
+    for definition in structs {
+        let mut rows: Vec<(String, String, String)> = Vec::new();
+        for (key, ty, optional) in &definition.fields {
+            let base = go_field_name(key);
+            let mut field = base.clone();
+            let mut suffix = 2;
+            while rows.iter().any(|(existing, _, _)| *existing == field) {
+                field = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+            let (ty, tag) = match optional {
+                true => (go_pointer(ty), format!("`json:\"{},omitempty\"`", key)),
+                false => (go_type(ty), format!("`json:\"{}\"`", key)),
+            };
+            rows.push((field, ty, tag));
+        }
+        let name_width = rows.iter().map(|(name, _, _)| name.len()).max();
+        let type_width = rows.iter().map(|(_, ty, _)| ty.len()).max();
+        let mut output = format!("type {} struct {{\n", definition.name);
+        for (field, ty, tag) in &rows {
+            output.push_str(&format!(
+                "\t{:name$} {:ty$} {}\n",
+                field,
+                ty,
+                tag,
+                name = name_width.unwrap_or_default(),
+                ty = type_width.unwrap_or_default()
+            ));
+        }
+        output.push('}');
+        definitions.push(output);
+    }
+    Ok(definitions.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "id": 1,
+        "userName": "ada",
+        "type": "admin",
+        "score": 9.5,
+        "address": {"city": "London", "zip": null},
+        "tags": ["a", "b"],
+        "posts": [
+            {"title": "Hi", "views": 3},
+            {"title": "Again", "views": 4.5, "draft": true}
+        ]
+    }"#;
+
+    #[test]
+    fn test_json_to_rust() {
+        let output = json_to_rust(SAMPLE, None).unwrap();
+        assert!(output.starts_with("use serde::{Deserialize, Serialize};\n\n#[derive("));
+        assert!(output.contains(
+            "    pub r#type: String,\n    #[serde(rename = \"userName\")]\n    pub user_name: String,\n}"
+        ));
+        assert!(
+            output.contains("pub struct Root {\n    pub address: Address,\n    pub id: i64,\n")
+        );
+        assert!(output.contains(
+            "    pub posts: Vec<Post>,\n    pub score: f64,\n    pub tags: Vec<String>,\n"
+        ));
+        assert!(output.contains("    pub zip: Option<serde_json::Value>,\n"));
+        assert!(output.contains(
+            "pub struct Post {\n    pub title: String,\n    pub views: f64,\n    pub draft: Option<bool>,\n}"
+        ));
+
+        let output = json_to_rust(r#"[{"a": 1}, {"a": null}]"#, Some("Row")).unwrap();
+        assert!(output.contains("pub type Row = Vec<RowItem>;"));
+        assert!(output.contains("pub struct RowItem {\n    pub a: Option<i64>,\n}"));
+    }
+
+    #[test]
+    fn test_json_to_typescript() {
+        let output = json_to_typescript(SAMPLE, Some("User")).unwrap();
+        assert!(
+            output.starts_with("export interface User {\n  address: Address;\n  id: number;\n")
+        );
+        assert!(output.contains("  tags: string[];\n  type: string;\n  userName: string;\n}"));
+        assert!(output.contains("export interface Address {\n  city: string;\n  zip: unknown;\n}"));
+        assert!(output.contains("  draft?: boolean;\n"));
+
+        let output = json_to_typescript(r#"{"content-type": "x", "n": [1, null]}"#, None).unwrap();
+        assert!(output.contains("  \"content-type\": string;\n  n: (number | null)[];\n"));
+    }
+
+    #[test]
+    fn test_json_to_go() {
+        let output = json_to_go(SAMPLE, None).unwrap();
+        assert!(output.contains(
+            "type Root struct {\n\tAddress  Address  `json:\"address\"`\n\tID       int64    `json:\"id\"`\n"
+        ));
+        assert!(output.contains("\tUserName string   `json:\"userName\"`\n}"));
+        assert!(output.contains(
+            "type Post struct {\n\tTitle string  `json:\"title\"`\n\tViews float64 `json:\"views\"`\n\tDraft *bool   `json:\"draft,omitempty\"`\n}"
+        ));
+
+        let output = json_to_go("[{\"a\": 1}] // sample", None).unwrap();
+        assert!(output.starts_with("type Root []RootItem\n\ntype RootItem struct {"));
+    }
+
+    #[test]
+    fn test_codegen_errors() {
+        assert!(matches!(
+            json_to_rust("[1, 2]", None),
+            Err(CodegenError::NotAnObject)
+        ));
+        assert!(matches!(
+            json_to_go("{", None),
+            Err(CodegenError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            json_to_typescript("{}", Some("my type")),
+            Err(CodegenError::InvalidName(_))
+        ));
+    }
+}
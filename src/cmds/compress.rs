@@ -0,0 +1,158 @@
+use std::fmt;
+use std::io::{Read, Write};
+
+use base64::{Engine as _, engine::general_purpose};
+use flate2::Compression;
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use ruzstd::decoding::StreamingDecoder;
+use ruzstd::encoding::{CompressionLevel, compress_to_vec};
+
+use super::dump::{DumpError, bytes_to_text, parse_dump};
+
+#[derive(Debug)]
+pub enum CompressError {
+    InvalidDump(DumpError),
+    InvalidLevel(String),
+    Corrupt(&'static str, String),
+}
+
+impl fmt::Display for CompressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDump(err) => {
+                write!(f, "{}", err)
+            }
+            Self::InvalidLevel(level) => {
+                write!(f, "Invalid compression level '{}' - expected 0 to 9", level)
+            }
+            Self::Corrupt(format, msg) => {
+                write!(f, "Not valid {} data - {}", format, msg)
+            }
+        }
+    }
+}
+
+impl From<DumpError> for CompressError {
+    fn from(err: DumpError) -> CompressError {
+        CompressError::InvalidDump(err)
+    }
+}
+
+fn parse_level(level: Option<&str>) -> Result<Compression, CompressError> {
+    match level {
+        None => Ok(Compression::default()),
+        Some(level) => match level.parse::<u32>() {
+            Ok(level) if level <= 9 => Ok(Compression::new(level)),
+            _ => Err(CompressError::InvalidLevel(level.to_string())),
+        },
+    }
+}
+
+fn decompress(format: &'static str, mut decoder: impl Read) -> Result<String, CompressError> {
+    let mut bytes = Vec::new();
+    decoder
+        .read_to_end(&mut bytes)
+        .map_err(|e| CompressError::Corrupt(format, e.to_string()))?;
+    Ok(bytes_to_text(bytes))
+}
+
+/// Compresses the buffer with gzip and returns it as base64, the form it
+/// usually travels in inside JSON and headers.
+pub fn gzip(buffer: &str, level: Option<&str>) -> Result<String, CompressError> {
+    let mut encoder = GzEncoder::new(Vec::new(), parse_level(level)?);
+    // Writing to a Vec can't fail
+    let _ = encoder.write_all(buffer.as_bytes());
+    let bytes = encoder.finish().unwrap_or_default();
+    Ok(general_purpose::STANDARD.encode(bytes))
+}
+
+/// Decompresses a gzip stream given as base64 or a hex dump.
+pub fn gunzip(buffer: &str) -> Result<String, CompressError> {
+    let bytes = parse_dump(buffer)?;
+    decompress("gzip", GzDecoder::new(bytes.as_slice()))
+}
+
+/// Compresses with zlib-wrapped deflate, which is what HTTP's
+/// `Content-Encoding: deflate` means, and returns base64.
+pub fn deflate(buffer: &str, level: Option<&str>) -> Result<String, CompressError> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), parse_level(level)?);
+    let _ = encoder.write_all(buffer.as_bytes());
+    let bytes = encoder.finish().unwrap_or_default();
+    Ok(general_purpose::STANDARD.encode(bytes))
+}
+
+/// Inflates zlib or raw deflate data, told apart by the zlib header.
+pub fn inflate(buffer: &str) -> Result<String, CompressError> {
+    let bytes = parse_dump(buffer)?;
+    // A zlib header is CMF/FLG with method 8 and a checksum divisible by 31
+    let is_zlib = bytes.len() >= 2
+        && bytes[0] & 0x0f == 8
+        && (u16::from(bytes[0]) << 8 | u16::from(bytes[1])).is_multiple_of(31);
+    if is_zlib {
+        decompress("zlib", ZlibDecoder::new(bytes.as_slice()))
+    } else {
+        decompress("deflate", DeflateDecoder::new(bytes.as_slice()))
+    }
+}
+
+/// Compresses with zstd and returns base64. ruzstd only implements its
+/// fastest level, so there's no level argument.
+pub fn zstd_compress(buffer: &str) -> String {
+    let bytes = compress_to_vec(buffer.as_bytes(), CompressionLevel::Fastest);
+    general_purpose::STANDARD.encode(bytes)
+}
+
+/// Decompresses a zstd frame given as base64 or a hex dump.
+pub fn zstd_decompress(buffer: &str) -> Result<String, CompressError> {
+    let bytes = parse_dump(buffer)?;
+    let decoder = StreamingDecoder::new(bytes.as_slice())
+        .map_err(|e| CompressError::Corrupt("zstd", e.to_string()))?;
+    decompress("zstd", decoder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gzip_roundtrip() {
+        let compressed = gzip("{\"a\":1}", None).unwrap();
+        assert!(compressed.starts_with("H4sI"));
+        assert_eq!(gunzip(&compressed).unwrap(), "{\"a\":1}");
+        assert!(gzip("x", Some("10")).is_err());
+    }
+
+    #[test]
+    fn test_inflate_zlib_and_raw() {
+        let compressed = deflate("hello hello hello", Some("9")).unwrap();
+        assert_eq!(inflate(&compressed).unwrap(), "hello hello hello");
+        // Raw deflate of "hi" with no zlib header
+        assert_eq!(inflate("cb c8 04 00").unwrap(), "hi");
+    }
+
+    #[test]
+    fn test_zstd_roundtrip() {
+        let text = "{\"a\":1} {\"a\":1} {\"a\":1}";
+        let compressed = zstd_compress(text);
+        // The zstd magic bytes 28 b5 2f fd encode to "KLUv/"
+        assert!(compressed.starts_with("KLUv/"));
+        assert_eq!(zstd_decompress(&compressed).unwrap(), text);
+        assert!(matches!(
+            zstd_decompress("00 01 02 03"),
+            Err(CompressError::Corrupt("zstd", _))
+        ));
+    }
+
+    #[test]
+    fn test_decompress_binary_and_corrupt() {
+        // gzip of the bytes ff fe
+        let compressed = {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&[0xff, 0xfe]).unwrap();
+            general_purpose::STANDARD.encode(encoder.finish().unwrap())
+        };
+        assert_eq!(gunzip(&compressed).unwrap(), "fffe");
+        assert!(gunzip("00 01 02 03").is_err());
+    }
+}
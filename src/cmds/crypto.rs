@@ -0,0 +1,147 @@
+use std::fmt;
+
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+/// Identifies the format so other files fail with a clear error
+const MAGIC: &[u8] = b"POMP1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug)]
+pub enum CryptoError {
+    EmptyPassphrase,
+    KeyDerivation(String),
+    EncryptFailed,
+    DecryptFailed,
+    InvalidFormat,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyPassphrase => {
+                write!(f, "Passphrase must not be empty")
+            }
+            Self::KeyDerivation(msg) => {
+                write!(f, "Failed to derive key - {}", msg)
+            }
+            Self::EncryptFailed => {
+                write!(f, "Encryption failed")
+            }
+            Self::DecryptFailed => {
+                write!(f, "Wrong passphrase or corrupted data")
+            }
+            Self::InvalidFormat => {
+                write!(f, "Not data encrypted by pomp")
+            }
+        }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, CryptoError> {
+    if passphrase.is_empty() {
+        return Err(CryptoError::EmptyPassphrase);
+    }
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+/// Encrypts with XChaCha20-Poly1305 under an Argon2id key derived from the
+/// passphrase. The output is `MAGIC || salt || nonce || ciphertext`.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, CryptoError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(&key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| CryptoError::EncryptFailed)?;
+
+    let mut output = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    output.extend_from_slice(MAGIC);
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, CryptoError> {
+    let data = data.strip_prefix(MAGIC).ok_or(CryptoError::InvalidFormat)?;
+    if data.len() < SALT_LEN + NONCE_LEN {
+        return Err(CryptoError::InvalidFormat);
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let nonce: [u8; NONCE_LEN] = nonce.try_into().map_err(|_| CryptoError::InvalidFormat)?;
+    let key = derive_key(passphrase, salt)?;
+    XChaCha20Poly1305::new(&key)
+        .decrypt(&XNonce::from(nonce), ciphertext)
+        .map_err(|_| CryptoError::DecryptFailed)
+}
+
+/// Encrypts the buffer for pasting somewhere as a single base64 line.
+pub fn encrypt_text(buffer: &str, passphrase: &str) -> Result<String, CryptoError> {
+    encrypt(buffer.as_bytes(), passphrase).map(|data| general_purpose::STANDARD.encode(data))
+}
+
+/// Reverses `encrypt_text`; whitespace from line-wrapping chat clients is
+/// ignored.
+pub fn decrypt_text(buffer: &str, passphrase: &str) -> Result<String, CryptoError> {
+    let encoded: String = buffer.chars().filter(|c| !c.is_whitespace()).collect();
+    let data = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|_| CryptoError::InvalidFormat)?;
+    let plaintext = decrypt(&data, passphrase)?;
+    Ok(String::from_utf8_lossy(&plaintext).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let encrypted = encrypt(b"secret token", "hunter2").unwrap();
+        assert!(encrypted.starts_with(MAGIC));
+        assert_eq!(decrypt(&encrypted, "hunter2").unwrap(), b"secret token");
+    }
+
+    #[test]
+    fn test_encrypt_text_roundtrip() {
+        let encrypted = encrypt_text("api key: abc", "correct horse").unwrap();
+        assert!(!encrypted.contains('\n'));
+        let wrapped = format!("{}\n{}", &encrypted[..20], &encrypted[20..]);
+        assert_eq!(
+            decrypt_text(&wrapped, "correct horse").unwrap(),
+            "api key: abc"
+        );
+        assert!(matches!(
+            decrypt_text("not base64!", "correct horse"),
+            Err(CryptoError::InvalidFormat)
+        ));
+    }
+
+    #[test]
+    fn test_decrypt_rejects_bad_input() {
+        let encrypted = encrypt(b"secret token", "hunter2").unwrap();
+        assert!(matches!(
+            decrypt(&encrypted, "wrong"),
+            Err(CryptoError::DecryptFailed)
+        ));
+        assert!(matches!(
+            decrypt(b"not encrypted", "hunter2"),
+            Err(CryptoError::InvalidFormat)
+        ));
+        assert!(matches!(
+            encrypt(b"x", ""),
+            Err(CryptoError::EmptyPassphrase)
+        ));
+    }
+}
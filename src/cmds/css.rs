@@ -1,12 +1,43 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
 use std::fmt;
 
+use lightningcss::error::{Error, ParserError};
+use lightningcss::properties::Property;
+use lightningcss::properties::custom::{CustomPropertyName, TokenList, TokenOrValue, Variable};
+use lightningcss::rules::CssRule;
 use lightningcss::stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet};
+use lightningcss::targets::{Features, Targets};
+use lightningcss::traits::ToCss;
+use lightningcss::values::color::CssColor;
+use lightningcss::visitor::{Visit, VisitTypes, Visitor};
+
+use super::browsers::{TargetError, resolve_targets};
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum CssError {
     ParseError(String),
     MinifyError(String),
     FormatError(()),
+    UnknownVarsMode(String),
+    UnsupportedTarget(TargetError),
+    UnknownOption(String),
+    InvalidIndent(String),
+}
+
+/// lightningcss counts lines from 0 and prints them after the file name,
+/// so the position is spelled out the way serde_json reports it.
+fn parse_error(err: Error<ParserError>) -> CssError {
+    match err.loc {
+        Some(loc) => CssError::ParseError(format!(
+            "{} at line {} column {}",
+            err.kind,
+            loc.line + 1,
+            loc.column
+        )),
+        None => CssError::ParseError(err.kind.to_string()),
+    }
 }
 
 impl fmt::Display for CssError {
@@ -21,35 +52,146 @@ impl fmt::Display for CssError {
             Self::FormatError(..) => {
                 write!(f, "Failed to format CSS")
             }
+            Self::UnknownVarsMode(mode) => {
+                write!(f, "Unknown mode '{}' - expected inline", mode)
+            }
+            Self::UnsupportedTarget(err) => {
+                write!(f, "{}", err)
+            }
+            Self::UnknownOption(option) => {
+                write!(
+                    f,
+                    "Unknown option '{}' - expected --indent, --targets or --no-vendor-prefix-removal",
+                    option
+                )
+            }
+            Self::InvalidIndent(width) => {
+                write!(f, "Invalid indent '{}' - expected 0 to 8 or tab", width)
+            }
+        }
+    }
+}
+
+/// Printer settings for `/css-format` and `/css-minify`, given as flags.
+#[derive(Debug, Clone)]
+pub struct CssOptions {
+    /// One level of indentation when formatting
+    pub indent: String,
+    /// Browserslist query to minify for, lowering syntax and dropping
+    /// vendor prefixes those browsers don't need
+    pub targets: Option<String>,
+    /// Leave vendor prefixes exactly as written, even with targets
+    pub keep_vendor_prefixes: bool,
+}
+
+impl Default for CssOptions {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            targets: None,
+            keep_vendor_prefixes: false,
+        }
+    }
+}
+
+impl CssOptions {
+    /// Reads `--indent <n|tab>`, `--targets <query>` and
+    /// `--no-vendor-prefix-removal`. The query runs up to the next flag, so
+    /// it needs no quotes.
+    pub fn parse(args: &[&str]) -> Result<Self, CssError> {
+        let mut options = Self::default();
+        let mut args = args.iter().copied().peekable();
+        while let Some(arg) = args.next() {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (arg, None),
+            };
+            match flag {
+                "--indent" => {
+                    let width = value.or_else(|| args.next()).unwrap_or_default();
+                    options.indent = match width {
+                        "tab" => "\t".to_string(),
+                        width => match width.parse::<usize>() {
+                            Ok(width) if width <= 8 => " ".repeat(width),
+                            _ => return Err(CssError::InvalidIndent(width.to_string())),
+                        },
+                    };
+                }
+                "--targets" => {
+                    let mut query: Vec<&str> = value.into_iter().collect();
+                    while let Some(word) = args.next_if(|word| !word.starts_with("--")) {
+                        query.push(word);
+                    }
+                    options.targets = Some(query.join(" "));
+                }
+                "--no-vendor-prefix-removal" => options.keep_vendor_prefixes = true,
+                _ => return Err(CssError::UnknownOption(arg.to_string())),
+            }
+        }
+        Ok(options)
+    }
+
+    fn targets(&self) -> Result<Targets, CssError> {
+        let mut targets = match &self.targets {
+            Some(query) => Targets::from(
+                resolve_targets(query.trim().trim_matches(['"', '\'']))
+                    .map_err(CssError::UnsupportedTarget)?,
+            ),
+            None => Targets::default(),
+        };
+        if self.keep_vendor_prefixes {
+            targets.exclude |= Features::VendorPrefixes;
         }
+        Ok(targets)
+    }
+}
+
+/// lightningcss always indents by two spaces, so other widths are applied
+/// to its output.
+fn reindent(code: String, indent: &str) -> String {
+    if indent == "  " {
+        return code;
     }
+    let mut reindented = String::with_capacity(code.len());
+    for line in code.split_inclusive('\n') {
+        let content = line.trim_start_matches(' ');
+        let levels = (line.len() - content.len()) / 2;
+        reindented.push_str(&indent.repeat(levels));
+        reindented.push_str(content);
+    }
+    reindented
 }
 
-pub fn css_format(buffer: &str) -> Result<String, CssError> {
-    let stylesheet = StyleSheet::parse(buffer, ParserOptions::default())
-        .map_err(|e| CssError::ParseError(e.to_string()))?;
+pub fn css_format(buffer: &str, options: &CssOptions) -> Result<String, CssError> {
+    let stylesheet = StyleSheet::parse(buffer, ParserOptions::default()).map_err(parse_error)?;
 
     let printer_options = PrinterOptions {
         minify: false,
+        targets: options.targets()?,
         ..Default::default()
     };
 
     stylesheet
         .to_css(printer_options)
-        .map(|result| result.code)
+        .map(|result| reindent(result.code, &options.indent))
         .map_err(|_| CssError::FormatError(()))
 }
 
-pub fn css_minify(buffer: &str) -> Result<String, CssError> {
-    let mut stylesheet = StyleSheet::parse(buffer, ParserOptions::default())
-        .map_err(|e| CssError::ParseError(e.to_string()))?;
+pub fn css_minify(buffer: &str, options: &CssOptions) -> Result<String, CssError> {
+    let mut stylesheet =
+        StyleSheet::parse(buffer, ParserOptions::default()).map_err(parse_error)?;
 
+    let targets = options.targets()?;
     stylesheet
-        .minify(MinifyOptions::default())
+        .minify(MinifyOptions {
+            targets,
+            ..Default::default()
+        })
         .map_err(|e| CssError::MinifyError(e.to_string()))?;
 
     let printer_options = PrinterOptions {
         minify: true,
+        targets,
         ..Default::default()
     };
 
@@ -58,3 +200,571 @@ pub fn css_minify(buffer: &str) -> Result<String, CssError> {
         .map(|result| result.code)
         .map_err(|_| CssError::FormatError(()))
 }
+
+/// Minifies with `targets` so nesting and modern syntax are lowered and
+/// vendor prefixes added, then prints the result readably.
+fn compile(buffer: &str, targets: Targets) -> Result<String, CssError> {
+    let mut stylesheet =
+        StyleSheet::parse(buffer, ParserOptions::default()).map_err(parse_error)?;
+
+    stylesheet
+        .minify(MinifyOptions {
+            targets,
+            ..Default::default()
+        })
+        .map_err(|e| CssError::MinifyError(e.to_string()))?;
+
+    let printer_options = PrinterOptions {
+        targets,
+        ..Default::default()
+    };
+
+    stylesheet
+        .to_css(printer_options)
+        .map(|result| result.code)
+        .map_err(|_| CssError::FormatError(()))
+}
+
+/// Transpiles for a browserslist query given as `--targets "last 2
+/// versions"` (the flag and quotes are optional), defaulting to `defaults`.
+pub fn css_compile(buffer: &str, args: &[&str]) -> Result<String, CssError> {
+    let query = args.join(" ");
+    let query = query.trim();
+    let query = query
+        .strip_prefix("--targets")
+        .map_or(query, |rest| rest.trim_start_matches('='))
+        .trim()
+        .trim_matches(['"', '\''])
+        .trim();
+    let query = if query.is_empty() { "defaults" } else { query };
+    let browsers = resolve_targets(query).map_err(CssError::UnsupportedTarget)?;
+    compile(buffer, Targets::from(browsers))
+}
+
+/// Expands nested rules into flat selectors and leaves everything else as
+/// written, for toolchains that predate CSS nesting.
+pub fn css_flatten(buffer: &str) -> Result<String, CssError> {
+    let stylesheet = StyleSheet::parse(buffer, ParserOptions::default()).map_err(parse_error)?;
+
+    // Nesting is lowered while printing, so no minify pass is needed
+    let printer_options = PrinterOptions {
+        targets: Targets {
+            include: Features::Nesting,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    stylesheet
+        .to_css(printer_options)
+        .map(|result| result.code)
+        .map_err(|_| CssError::FormatError(()))
+}
+
+struct Definition {
+    name: String,
+    value: String,
+    selector: String,
+    /// Set for `:root`/`html` rules outside any at-rule, which apply everywhere
+    global: bool,
+}
+
+/// Records custom property definitions with their selectors, and how often
+/// and where each `var()` is referenced.
+#[derive(Default)]
+struct VarCollector {
+    definitions: Vec<Definition>,
+    usages: Vec<(String, usize)>,
+    /// Each reference as (name, `selector { property }`)
+    locations: Vec<(String, String)>,
+    selectors: Vec<String>,
+    property: Option<String>,
+    at_rule_depth: usize,
+}
+
+impl<'i> Visitor<'i> for VarCollector {
+    type Error = Infallible;
+
+    fn visit_types(&self) -> VisitTypes {
+        VisitTypes::all()
+    }
+
+    fn visit_rule(&mut self, rule: &mut CssRule<'i>) -> Result<(), Self::Error> {
+        match rule {
+            CssRule::Style(style) => {
+                let selector = style
+                    .selectors
+                    .to_css_string(PrinterOptions::default())
+                    .unwrap_or_default();
+                self.selectors.push(selector);
+                rule.visit_children(self)?;
+                self.selectors.pop();
+            }
+            _ => {
+                self.at_rule_depth += 1;
+                rule.visit_children(self)?;
+                self.at_rule_depth -= 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+        if let Property::Custom(custom) = &*property
+            && let CustomPropertyName::Custom(name) = &custom.name
+        {
+            let selector = self.selectors.last().cloned().unwrap_or_default();
+            let global = self.at_rule_depth == 0
+                && self.selectors.len() == 1
+                && matches!(selector.as_str(), ":root" | "html");
+            self.definitions.push(Definition {
+                name: name.0.to_string(),
+                value: property
+                    .value_to_css_string(PrinterOptions::default())
+                    .unwrap_or_default(),
+                selector,
+                global,
+            });
+        }
+        let outer = self
+            .property
+            .replace(property.property_id().name().to_string());
+        property.visit_children(self)?;
+        self.property = outer;
+        Ok(())
+    }
+
+    fn visit_variable(&mut self, var: &mut Variable<'i>) -> Result<(), Self::Error> {
+        let name = var.name.ident.0.to_string();
+        let selector = self.selectors.last().map_or("", String::as_str);
+        let property = self.property.as_deref().unwrap_or_default();
+        self.locations
+            .push((name.clone(), format!("{} {{ {} }}", selector, property)));
+        match self.usages.iter_mut().find(|(used, _)| *used == name) {
+            Some((_, count)) => *count += 1,
+            None => self.usages.push((name, 1)),
+        }
+        var.visit_children(self)
+    }
+}
+
+/// Replaces `var()` references with the resolved values, falling back to
+/// the `var()` fallback when the property is never defined.
+struct VarInliner<'i> {
+    values: HashMap<String, TokenList<'i>>,
+    defined: HashSet<String>,
+}
+
+impl<'i> Visitor<'i> for VarInliner<'i> {
+    type Error = Infallible;
+
+    fn visit_types(&self) -> VisitTypes {
+        VisitTypes::all()
+    }
+
+    fn visit_token_list(&mut self, tokens: &mut TokenList<'i>) -> Result<(), Self::Error> {
+        let mut i = 0;
+        // Names expanded at the current position, so cycles stop expanding
+        let mut expanded = HashSet::new();
+        while i < tokens.0.len() {
+            tokens.0[i].visit_children(self)?;
+            if let TokenOrValue::Var(var) = &tokens.0[i] {
+                let name = var.name.ident.0.to_string();
+                let replacement = match self.values.get(&name) {
+                    Some(value) => Some(value.0.clone()),
+                    None if !self.defined.contains(&name) => {
+                        var.fallback.as_ref().map(|fallback| fallback.0.clone())
+                    }
+                    None => None,
+                };
+                if let Some(replacement) = replacement
+                    && expanded.insert(name)
+                {
+                    tokens.0.splice(i..i + 1, replacement);
+                    continue;
+                }
+            }
+            expanded.clear();
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+fn describe_vars(collector: &VarCollector) -> String {
+    let width = collector
+        .definitions
+        .iter()
+        .map(|definition| definition.name.len())
+        .chain(collector.usages.iter().map(|(name, _)| name.len()))
+        .max()
+        .unwrap_or(0);
+    let is_defined = |name: &str| {
+        collector
+            .definitions
+            .iter()
+            .any(|definition| definition.name == name)
+    };
+
+    let mut sections = Vec::new();
+    if !collector.definitions.is_empty() {
+        let mut section = String::from("Defined:");
+        for definition in &collector.definitions {
+            section.push_str(&format!(
+                "\n  {:<width$}  {}  ({})",
+                definition.name, definition.value, definition.selector
+            ));
+        }
+        sections.push(section);
+    }
+    if !collector.usages.is_empty() {
+        let mut section = String::from("Used:");
+        for (name, count) in &collector.usages {
+            let note = if is_defined(name) {
+                ""
+            } else {
+                "  (undefined)"
+            };
+            section.push_str(&format!("\n  {:<width$}  {}×{}", name, count, note));
+        }
+        sections.push(section);
+    }
+    let mut unused: Vec<&str> = Vec::new();
+    for definition in &collector.definitions {
+        let used = collector
+            .usages
+            .iter()
+            .any(|(name, _)| *name == definition.name);
+        if !used && !unused.contains(&definition.name.as_str()) {
+            unused.push(&definition.name);
+        }
+    }
+    if !unused.is_empty() {
+        sections.push(format!("Unused: {}", unused.join(", ")));
+    }
+    if sections.is_empty() {
+        return "No custom properties found".to_string();
+    }
+    sections.join("\n\n")
+}
+
+/// Lists each custom property with its values and every declaration that
+/// references it.
+pub fn css_extract_vars(buffer: &str) -> Result<String, CssError> {
+    let mut stylesheet =
+        StyleSheet::parse(buffer, ParserOptions::default()).map_err(parse_error)?;
+    let mut collector = VarCollector::default();
+    let _ = stylesheet.visit(&mut collector);
+
+    let mut names: Vec<&str> = Vec::new();
+    for name in collector
+        .definitions
+        .iter()
+        .map(|definition| definition.name.as_str())
+        .chain(collector.usages.iter().map(|(name, _)| name.as_str()))
+    {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        return Ok("No custom properties found".to_string());
+    }
+
+    let entries: Vec<String> = names
+        .iter()
+        .map(|name| {
+            let values: Vec<String> = collector
+                .definitions
+                .iter()
+                .filter(|definition| definition.name == *name)
+                .map(|definition| format!("{} ({})", definition.value, definition.selector))
+                .collect();
+            let mut entry = if values.is_empty() {
+                format!("{}: undefined", name)
+            } else {
+                format!("{}: {}", name, values.join(", "))
+            };
+            let locations: Vec<&str> = collector
+                .locations
+                .iter()
+                .filter(|(used, _)| used == name)
+                .map(|(_, location)| location.as_str())
+                .collect();
+            if locations.is_empty() {
+                entry.push_str("\n  unused");
+            }
+            for location in locations {
+                entry.push_str(&format!("\n  used in {}", location));
+            }
+            entry
+        })
+        .collect();
+    Ok(entries.join("\n"))
+}
+
+/// Counts colors as lightningcss prints them, so `#00AAFF` and `#0af` are
+/// the same entry.
+#[derive(Default)]
+struct ColorCollector {
+    colors: Vec<(String, usize)>,
+    /// Colors seen in the property being visited
+    pending: Vec<String>,
+}
+
+impl<'i> Visitor<'i> for ColorCollector {
+    type Error = Infallible;
+
+    fn visit_types(&self) -> VisitTypes {
+        VisitTypes::all()
+    }
+
+    fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+        property.visit_children(self)?;
+        // Shorthands like `background` fill in defaults (a transparent
+        // color) when parsed; only count colors that were actually written
+        let printed = property
+            .value_to_css_string(PrinterOptions::default())
+            .unwrap_or_default();
+        for color in std::mem::take(&mut self.pending) {
+            if !printed.contains(&color) {
+                continue;
+            }
+            match self.colors.iter_mut().find(|(seen, _)| *seen == color) {
+                Some((_, count)) => *count += 1,
+                None => self.colors.push((color, 1)),
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_color(&mut self, color: &mut CssColor) -> Result<(), Self::Error> {
+        if !matches!(color, CssColor::CurrentColor)
+            && let Ok(printed) = color.to_css_string(PrinterOptions::default())
+        {
+            self.pending.push(printed);
+        }
+        Ok(())
+    }
+}
+
+/// Lists every color in the stylesheet, most used first.
+pub fn css_extract_colors(buffer: &str) -> Result<String, CssError> {
+    let mut stylesheet =
+        StyleSheet::parse(buffer, ParserOptions::default()).map_err(parse_error)?;
+    let mut collector = ColorCollector::default();
+    let _ = stylesheet.visit(&mut collector);
+    if collector.colors.is_empty() {
+        return Ok("No colors found".to_string());
+    }
+
+    // Stable, so ties stay in order of first appearance
+    collector
+        .colors
+        .sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let width = collector
+        .colors
+        .iter()
+        .map(|(color, _)| color.chars().count())
+        .max()
+        .unwrap_or(0);
+    Ok(collector
+        .colors
+        .iter()
+        .map(|(color, count)| format!("{:<width$}  {}×", color, count))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Lists the custom properties defined and used in a stylesheet. With
+/// `inline`, `var()` references are replaced by their value wherever that
+/// value is the same everywhere: defined only on `:root`/`html` and never
+/// overridden elsewhere.
+pub fn css_vars(buffer: &str, mode: Option<&str>) -> Result<String, CssError> {
+    let inline = match mode {
+        None => false,
+        Some("inline") => true,
+        Some(other) => return Err(CssError::UnknownVarsMode(other.to_string())),
+    };
+    let mut stylesheet =
+        StyleSheet::parse(buffer, ParserOptions::default()).map_err(parse_error)?;
+
+    let mut collector = VarCollector::default();
+    let _ = stylesheet.visit(&mut collector);
+    if !inline {
+        return Ok(describe_vars(&collector));
+    }
+
+    let mut values = HashMap::new();
+    let mut defined = HashSet::new();
+    for definition in &collector.definitions {
+        defined.insert(definition.name.clone());
+        let consistent = collector
+            .definitions
+            .iter()
+            .filter(|other| other.name == definition.name)
+            .all(|other| other.global);
+        if consistent && !values.contains_key(&definition.name) {
+            values.insert(definition.name.clone(), None);
+        }
+    }
+    // Take the parsed token lists from the stylesheet itself so the inlined
+    // values print exactly as written
+    for rule in &stylesheet.rules.0 {
+        let CssRule::Style(style) = rule else {
+            continue;
+        };
+        for property in &style.declarations.declarations {
+            if let Property::Custom(custom) = property
+                && let CustomPropertyName::Custom(name) = &custom.name
+                && let Some(slot) = values.get_mut(name.0.as_ref())
+            {
+                *slot = Some(custom.value.clone());
+            }
+        }
+    }
+    let values = values
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect();
+
+    let _ = stylesheet.visit(&mut VarInliner { values, defined });
+    stylesheet
+        .to_css(PrinterOptions::default())
+        .map(|result| result.code)
+        .map_err(|_| CssError::FormatError(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLESHEET: &str = ":root { --brand: #0af; --gap: 4px; --pad: calc(var(--gap) * 2); }
+.card { color: var(--brand); padding: var(--pad); margin: var(--missing, 1px); }
+.compact { --gap: 2px; }";
+
+    #[test]
+    fn test_css_compile_lowers_for_targets() {
+        let css = ".card { user-select: none; & .title { color: lab(50% 20 -30); } }";
+        let result = css_compile(css, &["--targets", "\"safari", "13\""]).unwrap();
+        assert!(result.contains(".card .title {"));
+        assert!(result.contains("-webkit-user-select: none;"));
+        assert!(result.contains("color: color(display-p3"));
+
+        let modern = css_compile(css, &["chrome", ">=", "130"]).unwrap();
+        assert!(modern.contains("& .title"));
+        assert!(!modern.contains("-webkit-"));
+        assert!(matches!(
+            css_compile(css, &["--targets", "> 1%"]),
+            Err(CssError::UnsupportedTarget(_))
+        ));
+    }
+
+    #[test]
+    fn test_css_format_indent() {
+        let css = "@media print { .a { color: red } }";
+        let options = CssOptions::parse(&["--indent", "4"]).unwrap();
+        assert_eq!(
+            css_format(css, &options).unwrap(),
+            "@media print {\n    .a {\n        color: red;\n    }\n}\n"
+        );
+        let tabs = CssOptions::parse(&["--indent=tab"]).unwrap();
+        assert!(
+            css_format(css, &tabs)
+                .unwrap()
+                .contains("\n\t\tcolor: red;")
+        );
+        assert!(matches!(
+            CssOptions::parse(&["--indent", "wide"]),
+            Err(CssError::InvalidIndent(_))
+        ));
+        assert!(matches!(
+            CssOptions::parse(&["--pretty"]),
+            Err(CssError::UnknownOption(_))
+        ));
+    }
+
+    #[test]
+    fn test_css_minify_vendor_prefixes() {
+        let css = ".a { -webkit-transition: all 1s; transition: all 1s }";
+        let modern = CssOptions::parse(&["--targets", "chrome", ">=", "130"]).unwrap();
+        assert_eq!(css_minify(css, &modern).unwrap(), ".a{transition:all 1s}");
+
+        let kept = CssOptions::parse(&["--no-vendor-prefix-removal", "--targets", "chrome >= 130"])
+            .unwrap();
+        assert_eq!(
+            css_minify(css, &kept).unwrap(),
+            ".a{-webkit-transition:all 1s;transition:all 1s}"
+        );
+    }
+
+    #[test]
+    fn test_css_flatten() {
+        let css = ".nav { color: lab(50% 20 -30); a { &:hover { color: red; } } @media (width > 600px) { padding: 0; } }";
+        assert_eq!(
+            css_flatten(css).unwrap(),
+            ".nav {\n  color: lab(50% 20 -30);\n}\n\n\
+             .nav a:hover {\n  color: red;\n}\n\n\
+             @media (width > 600px) {\n  .nav {\n    padding: 0;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_css_vars_lists_definitions_and_usages() {
+        let result = css_vars(STYLESHEET, None).unwrap();
+        assert_eq!(
+            result,
+            "Defined:\n  \
+             --brand    #0af  (:root)\n  \
+             --gap      4px  (:root)\n  \
+             --pad      calc(var(--gap) * 2)  (:root)\n  \
+             --gap      2px  (.compact)\n\
+             \nUsed:\n  \
+             --gap      1×\n  \
+             --brand    1×\n  \
+             --pad      1×\n  \
+             --missing  1×  (undefined)"
+        );
+    }
+
+    #[test]
+    fn test_css_extract_vars_shows_usage_sites() {
+        let result = css_extract_vars(STYLESHEET).unwrap();
+        assert_eq!(
+            result,
+            "--brand: #0af (:root)\n  \
+               used in .card { color }\n\
+             --gap: 4px (:root), 2px (.compact)\n  \
+               used in :root { --pad }\n\
+             --pad: calc(var(--gap) * 2) (:root)\n  \
+               used in .card { padding }\n\
+             --missing: undefined\n  \
+               used in .card { margin }"
+        );
+    }
+
+    #[test]
+    fn test_css_extract_colors_counts() {
+        let css = "a { color: #00AAFF; border: 1px solid red; }
+b { background: linear-gradient(red, rgb(0 170 255)); fill: currentColor; }
+@media (prefers-color-scheme: dark) { a { --fg: #123456; } }";
+        assert_eq!(
+            css_extract_colors(css).unwrap(),
+            "#0af     2×\nred      2×\n#123456  1×"
+        );
+        assert_eq!(
+            css_extract_colors("a { top: 0 }").unwrap(),
+            "No colors found"
+        );
+    }
+
+    #[test]
+    fn test_css_vars_inline_resolves_static_values() {
+        let result = css_vars(STYLESHEET, Some("inline")).unwrap();
+        assert!(result.contains("color: #0af;"));
+        assert!(result.contains("margin: 1px;"));
+        // --gap is overridden in .compact, so --pad can't be resolved fully
+        assert!(result.contains("padding: calc(var(--gap) * 2);"));
+        assert!(css_vars(STYLESHEET, Some("outline")).is_err());
+    }
+}
@@ -0,0 +1,468 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use super::url::url_encode_with;
+
+/// Options that take a value, other than the ones handled by name below.
+/// Anything else is recorded as a flag.
+const VALUE_OPTIONS: &[&str] = &[
+    "--connect-timeout",
+    "--max-time",
+    "-m",
+    "--output",
+    "-o",
+    "--proxy",
+    "-x",
+    "--resolve",
+    "--retry",
+    "--cacert",
+    "--cert",
+    "-E",
+    "--key",
+];
+
+#[derive(Debug)]
+pub enum CurlError {
+    NotCurl,
+    UnterminatedQuote,
+    MissingValue(String),
+    MissingUrl,
+    InvalidJson(String),
+}
+
+impl fmt::Display for CurlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotCurl => {
+                write!(f, "Expected a command starting with `curl`")
+            }
+            Self::UnterminatedQuote => {
+                write!(f, "Unterminated quote in command")
+            }
+            Self::MissingValue(option) => {
+                write!(f, "Missing value for {}", option)
+            }
+            Self::MissingUrl => {
+                write!(f, "No URL in request")
+            }
+            Self::InvalidJson(msg) => {
+                write!(f, "Invalid JSON - {}", msg)
+            }
+        }
+    }
+}
+
+/// Splits a shell command into words the way a POSIX shell would for the
+/// quoting a browser's "Copy as cURL" produces: single and double quotes,
+/// bash `$'...'` strings, backslash escapes and line continuations.
+fn shell_words(command: &str) -> Result<Vec<String>, CurlError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(CurlError::UnterminatedQuote),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => word.push('\n'),
+                            Some('t') => word.push('\t'),
+                            Some('r') => word.push('\r'),
+                            Some(c) => word.push(c),
+                            None => return Err(CurlError::UnterminatedQuote),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(CurlError::UnterminatedQuote),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        // Inside double quotes a backslash only escapes these
+                        Some('\\') if matches!(chars.peek(), Some('"' | '\\' | '$' | '`')) => {
+                            word.extend(chars.next());
+                        }
+                        Some('\\') if chars.peek() == Some(&'\n') => {
+                            chars.next();
+                        }
+                        Some(c) => word.push(c),
+                        None => return Err(CurlError::UnterminatedQuote),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(c) => {
+                    in_word = true;
+                    word.push(c);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+fn add_header(headers: &mut Map<String, Value>, header: &str) {
+    let (name, value) = header.split_once(':').unwrap_or((header, ""));
+    let (name, value) = (name.trim().to_string(), Value::from(value.trim()));
+    match headers.get_mut(&name) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            headers.insert(name, value);
+        }
+    }
+}
+
+fn has_header(headers: &Map<String, Value>, name: &str) -> bool {
+    headers.keys().any(|key| key.eq_ignore_ascii_case(name))
+}
+
+/// Encodes a `--data-urlencode` argument: `name=value` encodes the value,
+/// anything else is encoded whole.
+fn data_urlencode(data: &str) -> String {
+    let encode = |text: &str| url_encode_with(text, Some("form")).unwrap_or_default();
+    match data.split_once('=') {
+        Some((name, value)) if !name.is_empty() => format!("{}={}", name, encode(value)),
+        Some((_, value)) => encode(value),
+        None => encode(data),
+    }
+}
+
+/// Converts a `curl` command into JSON with its method, URL, headers and
+/// body. A JSON body is parsed into `json`; other bodies stay in `body`.
+pub fn curl_parse(buffer: &str) -> Result<String, CurlError> {
+    let words = shell_words(buffer.trim())?;
+    let mut args = words.iter().map(String::as_str);
+    if args.next() != Some("curl") {
+        return Err(CurlError::NotCurl);
+    }
+
+    let mut method = None;
+    let mut url = None;
+    let mut headers = Map::new();
+    let mut data = Vec::new();
+    let mut form = Vec::new();
+    let mut user = None;
+    let mut options = Vec::new();
+    let mut get = false;
+    let mut head = false;
+
+    // Expand bundled short flags like `-sSL`, keeping `-XPOST` whole
+    let mut expanded = Vec::new();
+    for arg in args {
+        let bundled = arg.len() > 2
+            && arg.starts_with('-')
+            && !arg.starts_with("--")
+            && arg[1..].chars().all(|c| "sSLkvigIGf".contains(c));
+        if bundled {
+            expanded.extend(arg[1..].chars().map(|c| format!("-{}", c)));
+        } else {
+            expanded.push(arg.to_string());
+        }
+    }
+
+    let mut args = expanded.into_iter();
+    while let Some(arg) = args.next() {
+        // Short options may have their value attached, as in `-XPOST`
+        let (option, attached) = match arg.as_str() {
+            a if a.len() > 2
+                && a.starts_with('-')
+                && !a.starts_with("--")
+                && a.is_char_boundary(2) =>
+            {
+                (a[..2].to_string(), Some(a[2..].to_string()))
+            }
+            a => match a.split_once('=') {
+                Some((option, value)) if a.starts_with("--") => {
+                    (option.to_string(), Some(value.to_string()))
+                }
+                _ => (a.to_string(), None),
+            },
+        };
+        let mut value = || {
+            attached
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| CurlError::MissingValue(option.clone()))
+        };
+        match option.as_str() {
+            "-X" | "--request" => method = Some(value()?),
+            "-H" | "--header" => add_header(&mut headers, &value()?),
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => {
+                data.push(value()?)
+            }
+            "--data-urlencode" => data.push(data_urlencode(&value()?)),
+            "--json" => {
+                data.push(value()?);
+                if !has_header(&headers, "Content-Type") {
+                    add_header(&mut headers, "Content-Type: application/json");
+                }
+                if !has_header(&headers, "Accept") {
+                    add_header(&mut headers, "Accept: application/json");
+                }
+            }
+            "-F" | "--form" | "--form-string" => form.push(Value::from(value()?)),
+            "-u" | "--user" => user = Some(value()?),
+            "-b" | "--cookie" => add_header(&mut headers, &format!("Cookie: {}", value()?)),
+            "-A" | "--user-agent" => add_header(&mut headers, &format!("User-Agent: {}", value()?)),
+            "-e" | "--referer" => add_header(&mut headers, &format!("Referer: {}", value()?)),
+            "--url" => url = Some(value()?),
+            "-G" | "--get" => get = true,
+            "-I" | "--head" => head = true,
+            o if VALUE_OPTIONS.contains(&o) => {
+                let value = value()?;
+                options.push(Value::from(format!("{} {}", o, value)));
+            }
+            o if o.starts_with('-') => {
+                options.push(Value::from(o.trim_start_matches('-')));
+            }
+            _ => url = Some(arg),
+        }
+    }
+
+    let mut url = url.ok_or(CurlError::MissingUrl)?;
+    let body = (!data.is_empty()).then(|| data.join("&"));
+    let body = match body {
+        // `-G` moves the data into the query string
+        Some(body) if get => {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&body);
+            None
+        }
+        body => body,
+    };
+    let method = method.unwrap_or_else(|| {
+        match (head, body.is_some() || !form.is_empty()) {
+            (true, _) => "HEAD",
+            (false, true) => "POST",
+            (false, false) => "GET",
+        }
+        .to_string()
+    });
+
+    let mut request = Map::new();
+    request.insert("method".to_string(), Value::from(method));
+    request.insert("url".to_string(), Value::from(url));
+    if !headers.is_empty() {
+        request.insert("headers".to_string(), Value::Object(headers));
+    }
+    if let Some(body) = body {
+        match serde_json::from_str::<Value>(&body) {
+            Ok(json) if body.trim_start().starts_with(['{', '[']) => {
+                request.insert("json".to_string(), json);
+            }
+            _ => {
+                request.insert("body".to_string(), Value::from(body));
+            }
+        }
+    }
+    if !form.is_empty() {
+        request.insert("form".to_string(), Value::Array(form));
+    }
+    if let Some(user) = user {
+        request.insert("user".to_string(), Value::from(user));
+    }
+    if !options.is_empty() {
+        request.insert("options".to_string(), Value::Array(options));
+    }
+    serde_json::to_string_pretty(&Value::Object(request))
+        .map_err(|e| CurlError::InvalidJson(e.to_string()))
+}
+
+/// Quotes a word for a POSIX shell, leaving simple words bare.
+fn shell_quote(word: &str) -> String {
+    let is_plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if is_plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Builds a `curl` command from the JSON `/curl-parse` produces. Only `url`
+/// is required.
+pub fn curl_generate(buffer: &str) -> Result<String, CurlError> {
+    let request: Value =
+        serde_json::from_str(buffer).map_err(|e| CurlError::InvalidJson(e.to_string()))?;
+    let url = request
+        .get("url")
+        .and_then(Value::as_str)
+        .ok_or(CurlError::MissingUrl)?;
+
+    let mut args = Vec::new();
+    let body = match (request.get("json"), request.get("body")) {
+        (Some(json), _) => Some(json.to_string()),
+        (None, Some(body)) => Some(text(body)),
+        (None, None) => None,
+    };
+    let form = request.get("form").and_then(Value::as_array);
+    let has_body = body.is_some() || form.is_some_and(|form| !form.is_empty());
+    let implied = if has_body { "POST" } else { "GET" };
+    if let Some(method) = request.get("method").and_then(Value::as_str)
+        && !method.eq_ignore_ascii_case(implied)
+    {
+        args.push(format!("-X {}", shell_quote(&method.to_uppercase())));
+    }
+    args.push(shell_quote(url));
+
+    let mut headers = request
+        .get("headers")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    if request.get("json").is_some() && !has_header(&headers, "Content-Type") {
+        headers.insert("Content-Type".to_string(), Value::from("application/json"));
+    }
+    for (name, values) in &headers {
+        let values = match values {
+            Value::Array(values) => values.clone(),
+            value => vec![value.clone()],
+        };
+        for value in values {
+            args.push(format!(
+                "-H {}",
+                shell_quote(&format!("{}: {}", name, text(&value)))
+            ));
+        }
+    }
+    if let Some(user) = request.get("user") {
+        args.push(format!("-u {}", shell_quote(&text(user))));
+    }
+    if let Some(body) = body {
+        args.push(format!("--data-raw {}", shell_quote(&body)));
+    }
+    for field in form.into_iter().flatten() {
+        args.push(format!("-F {}", shell_quote(&text(field))));
+    }
+    for option in request
+        .get("options")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let option = text(option);
+        match option.split_once(' ') {
+            Some((name, value)) if name.starts_with('-') => {
+                args.push(format!("{} {}", name, shell_quote(value)));
+            }
+            _ if option.len() == 1 => args.push(format!("-{}", option)),
+            _ => args.push(format!("--{}", option)),
+        }
+    }
+
+    Ok(format!("curl {}", args.join(" \\\n  ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_words() {
+        let words = shell_words("curl 'a b' \"c \\\"d\\\"\" $'e\\'f\\n' g\\ h \\\n  i").unwrap();
+        assert_eq!(words, ["curl", "a b", "c \"d\"", "e'f\n", "g h", "i"]);
+        assert!(shell_words("curl 'oops").is_err());
+    }
+
+    #[test]
+    fn test_curl_parse_browser_copy() {
+        let command = "curl 'https://api.example.com/users?page=2' \\\n  \
+                       -H 'accept: application/json' \\\n  \
+                       -H 'authorization: Bearer abc' \\\n  \
+                       --data-raw '{\"name\":\"Jo\"}' \\\n  \
+                       --compressed -sSL";
+        let request: Value = serde_json::from_str(&curl_parse(command).unwrap()).unwrap();
+        assert_eq!(
+            request,
+            serde_json::json!({
+                "method": "POST",
+                "url": "https://api.example.com/users?page=2",
+                "headers": {
+                    "accept": "application/json",
+                    "authorization": "Bearer abc",
+                },
+                "json": {"name": "Jo"},
+                "options": ["compressed", "s", "S", "L"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_curl_parse_get_and_errors() {
+        let request: Value = serde_json::from_str(
+            &curl_parse("curl -G https://x.test/s --data-urlencode 'q=a b'").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(request["method"], "GET");
+        assert_eq!(request["url"], "https://x.test/s?q=a+b");
+        assert!(matches!(curl_parse("wget x"), Err(CurlError::NotCurl)));
+        assert!(matches!(
+            curl_parse("curl -H"),
+            Err(CurlError::MissingValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_curl_generate_roundtrip() {
+        let request = r#"{"method": "PUT", "url": "https://x.test/a", "headers": {"X-Id": "it's"}, "json": {"a": 1}}"#;
+        let command = curl_generate(request).unwrap();
+        assert_eq!(
+            command,
+            "curl -X PUT \\\n  \
+             https://x.test/a \\\n  \
+             -H 'Content-Type: application/json' \\\n  \
+             -H 'X-Id: it'\\''s' \\\n  \
+             --data-raw '{\"a\":1}'"
+        );
+        let parsed: Value = serde_json::from_str(&curl_parse(&command).unwrap()).unwrap();
+        assert_eq!(parsed["method"], "PUT");
+        assert_eq!(parsed["headers"]["X-Id"], "it's");
+        assert_eq!(parsed["json"], serde_json::json!({"a": 1}));
+    }
+}
@@ -0,0 +1,334 @@
+use std::fmt;
+
+use base64::{Engine as _, engine::general_purpose};
+
+use super::base64::normalize_base64;
+use super::units::format_bytes;
+use crate::detect::is_binary;
+
+/// MIME types by file extension, for files whose contents don't say.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("avif", "image/avif"),
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("mjs", "text/javascript"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("otf", "font/otf"),
+    ("svg", "image/svg+xml"),
+    ("ttf", "font/ttf"),
+    ("txt", "text/plain"),
+    ("wasm", "application/wasm"),
+    ("webm", "video/webm"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("xml", "application/xml"),
+];
+
+#[derive(Debug)]
+pub enum DataUriError {
+    NotADataUri,
+    MissingComma,
+    InvalidBase64,
+}
+
+impl fmt::Display for DataUriError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotADataUri => {
+                write!(f, "Expected a data: URI")
+            }
+            Self::MissingComma => {
+                write!(f, "A data: URI needs a ',' before its payload")
+            }
+            Self::InvalidBase64 => {
+                write!(f, "The payload is not valid base64")
+            }
+        }
+    }
+}
+
+/// Image formats recognised from their headers, with their pixel size.
+#[derive(Debug, PartialEq)]
+pub struct Image {
+    pub format: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn u16_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn u24_le(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+}
+
+fn u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn i32_le(bytes: &[u8], at: usize) -> Option<i32> {
+    Some(i32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// The size comes from the first start-of-frame segment, which follows any
+/// number of metadata segments.
+fn jpeg_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+    loop {
+        if *bytes.get(at)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(at + 1)?;
+        match marker {
+            // Fill bytes before a marker
+            0xFF => at += 1,
+            0x01 | 0xD0..=0xD9 => at += 2,
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return Some((u16_be(bytes, at + 7)?, u16_be(bytes, at + 5)?));
+            }
+            _ => at += 2 + u16_be(bytes, at + 2)? as usize,
+        }
+    }
+}
+
+fn webp_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        b"VP8 " if bytes.get(23..26)? == [0x9D, 0x01, 0x2A] => {
+            Some((u16_le(bytes, 26)? & 0x3FFF, u16_le(bytes, 28)? & 0x3FFF))
+        }
+        b"VP8L" if *bytes.get(20)? == 0x2F => {
+            let b = bytes.get(21..25)?;
+            let width = 1 + (b[0] as u32 | (b[1] as u32 & 0x3F) << 8);
+            let height = 1 + (b[1] as u32 >> 6 | (b[2] as u32) << 2 | (b[3] as u32 & 0x0F) << 10);
+            Some((width, height))
+        }
+        b"VP8X" => Some((1 + u24_le(bytes, 24)?, 1 + u24_le(bytes, 27)?)),
+        _ => None,
+    }
+}
+
+/// Reads the format and dimensions from an image's header. Only the first
+/// few hundred bytes matter, except for JPEGs with large metadata.
+pub fn image_info(bytes: &[u8]) -> Option<Image> {
+    let (format, (width, height)) = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        ("PNG", (u32_be(bytes, 16)?, u32_be(bytes, 20)?))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        ("GIF", (u16_le(bytes, 6)?, u16_le(bytes, 8)?))
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        ("JPEG", jpeg_size(bytes)?)
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12)? == b"WEBP" {
+        ("WebP", webp_size(bytes)?)
+    } else if bytes.starts_with(b"BM") {
+        // A negative height means the rows are stored top-down
+        let height = i32_le(bytes, 22)?.unsigned_abs();
+        ("BMP", (i32_le(bytes, 18)?.unsigned_abs(), height))
+    } else if bytes.starts_with(&[0, 0, 1, 0]) && bytes.len() >= 8 {
+        // An icon's first image; 0 stands for 256
+        let side = |byte: u8| if byte == 0 { 256 } else { byte as u32 };
+        ("ICO", (side(bytes[6]), side(bytes[7])))
+    } else {
+        return None;
+    };
+    Some(Image {
+        format,
+        width,
+        height,
+    })
+}
+
+/// The MIME type of a file, from its contents where they identify it and
+/// its extension otherwise. Unknown text is `text/plain`.
+pub fn guess_mime(file_name: &str, bytes: &[u8]) -> &'static str {
+    if let Some(image) = image_info(bytes) {
+        return match image.format {
+            "PNG" => "image/png",
+            "GIF" => "image/gif",
+            "JPEG" => "image/jpeg",
+            "WebP" => "image/webp",
+            "BMP" => "image/bmp",
+            _ => "image/x-icon",
+        };
+    }
+    if bytes.starts_with(b"%PDF-") {
+        return "application/pdf";
+    }
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+    match EXTENSIONS.iter().find(|(known, _)| *known == extension) {
+        Some((_, mime)) => mime,
+        None if is_binary(bytes) => "application/octet-stream",
+        None => "text/plain",
+    }
+}
+
+/// A parsed `data:` URI.
+#[derive(Debug)]
+pub struct DataUri {
+    /// The media type without parameters, `text/plain` when omitted
+    pub mime: String,
+    pub base64: bool,
+    pub payload: Vec<u8>,
+}
+
+impl DataUri {
+    /// Parses a `data:` URI, also accepting one wrapped in quotes or a CSS
+    /// `url(...)` as copied from a stylesheet.
+    pub fn parse(text: &str) -> Result<Self, DataUriError> {
+        let mut uri = text.trim();
+        if let Some(inner) = uri.strip_prefix("url(").and_then(|u| u.strip_suffix(')')) {
+            uri = inner.trim();
+        }
+        uri = uri.trim_matches(['"', '\'']);
+        let rest = match uri.get(..5) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("data:") => &uri[5..],
+            _ => return Err(DataUriError::NotADataUri),
+        };
+
+        let (meta, data) = rest.split_once(',').ok_or(DataUriError::MissingComma)?;
+        let mut params = meta.split(';').map(str::trim);
+        let mime = match params.next() {
+            Some(mime) if !mime.is_empty() => mime.to_ascii_lowercase(),
+            _ => "text/plain".to_string(),
+        };
+        let base64 = params.any(|param| param.eq_ignore_ascii_case("base64"));
+
+        let data = urlencoding::decode_binary(data.trim().as_bytes());
+        let payload = match base64 {
+            true => {
+                let text = std::str::from_utf8(&data).map_err(|_| DataUriError::InvalidBase64)?;
+                general_purpose::STANDARD
+                    .decode(normalize_base64(text))
+                    .map_err(|_| DataUriError::InvalidBase64)?
+            }
+            false => data.into_owned(),
+        };
+        Ok(Self {
+            mime,
+            base64,
+            payload,
+        })
+    }
+
+    pub fn image(&self) -> Option<Image> {
+        image_info(&self.payload)
+    }
+}
+
+impl fmt::Display for DataUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mime)?;
+        if let Some(image) = self.image() {
+            write!(f, ", {} × {} {}", image.width, image.height, image.format)?;
+        }
+        write!(f, ", {}", format_bytes(self.payload.len() as f64))
+    }
+}
+
+/// Describes a `data:` URI: its MIME type, image dimensions when the
+/// payload is an image, and payload size.
+pub fn data_uri_decode(buffer: &str) -> Result<String, DataUriError> {
+    DataUri::parse(buffer).map(|uri| uri.to_string())
+}
+
+/// Builds a base64 `data:` URI for embedding a file in CSS or HTML.
+pub fn to_data_uri(bytes: &[u8], mime: &str) -> String {
+    format!(
+        "data:{};base64,{}",
+        mime,
+        general_purpose::STANDARD.encode(bytes)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn test_data_uri_decode() {
+        let png = format!("data:image/png;base64,{}", PNG);
+        assert_eq!(data_uri_decode(&png).unwrap(), "image/png, 1 × 1 PNG, 70 B");
+        assert_eq!(
+            data_uri_decode(&format!("url(\"{}\")", png)).unwrap(),
+            "image/png, 1 × 1 PNG, 70 B"
+        );
+
+        let svg = DataUri::parse("data:image/svg+xml;charset=utf-8,%3Csvg%2F%3E").unwrap();
+        assert_eq!(svg.mime, "image/svg+xml");
+        assert_eq!(svg.payload, b"<svg/>");
+        assert_eq!(svg.image(), None);
+
+        let plain = DataUri::parse("data:,Hello%2C%20World").unwrap();
+        assert_eq!(plain.mime, "text/plain");
+        assert_eq!(plain.payload, b"Hello, World");
+
+        assert!(matches!(
+            data_uri_decode("https://example.com"),
+            Err(DataUriError::NotADataUri)
+        ));
+        assert!(matches!(
+            data_uri_decode("data:text/plain"),
+            Err(DataUriError::MissingComma)
+        ));
+        assert!(matches!(
+            data_uri_decode("data:;base64,***"),
+            Err(DataUriError::InvalidBase64)
+        ));
+    }
+
+    #[test]
+    fn test_image_info() {
+        let gif = general_purpose::STANDARD
+            .decode("R0lGODlhAwACAIAAAP///wAAACH5BAEAAAAALAAAAAADAAIAAAICRAEAOw==")
+            .unwrap();
+        assert_eq!(
+            image_info(&gif),
+            Some(Image {
+                format: "GIF",
+                width: 3,
+                height: 2
+            })
+        );
+
+        // An APP0 segment before the frame header
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00,
+            0x20, 0x00, 0x40,
+        ];
+        let image = image_info(&jpeg).unwrap();
+        assert_eq!((image.format, image.width, image.height), ("JPEG", 64, 32));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+        webp.extend_from_slice(&[0x7F, 0x02, 0x00, 0xDF, 0x01, 0x00]);
+        let image = image_info(&webp).unwrap();
+        assert_eq!((image.width, image.height), (640, 480));
+
+        assert_eq!(image_info(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_to_data_uri() {
+        let png = general_purpose::STANDARD.decode(PNG).unwrap();
+        let uri = to_data_uri(&png, guess_mime("pixel.bin", &png));
+        assert_eq!(uri, format!("data:image/png;base64,{}", PNG));
+        assert_eq!(DataUri::parse(&uri).unwrap().payload, png);
+
+        assert_eq!(guess_mime("icon.SVG", b"<svg/>"), "image/svg+xml");
+        assert_eq!(guess_mime("notes", b"plain"), "text/plain");
+        assert_eq!(guess_mime("blob", &[0, 1, 2]), "application/octet-stream");
+    }
+}
@@ -0,0 +1,178 @@
+//! Just enough DER and PEM reading and writing for keys. Certificates go
+//! through x509-parser.
+
+use std::fmt;
+
+use base64::{Engine as _, engine::general_purpose};
+
+use super::base64::normalize_base64;
+
+pub(super) const TAG_BIT_STRING: u8 = 0x03;
+pub(super) const TAG_OCTET_STRING: u8 = 0x04;
+pub(super) const TAG_OID: u8 = 0x06;
+pub(super) const TAG_SEQUENCE: u8 = 0x30;
+
+#[derive(Debug)]
+pub struct DerError(pub usize);
+
+impl fmt::Display for DerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Malformed DER at offset {:#x}", self.0)
+    }
+}
+
+/// One tag-length-value element.
+#[derive(Clone, Copy)]
+pub(super) struct Tlv<'a> {
+    pub tag: u8,
+    pub content: &'a [u8],
+    /// The whole element including its header, e.g. for fingerprints
+    pub raw: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, DerError> {
+        DerReader::new(bytes).next()
+    }
+
+    /// The children of a constructed element such as a SEQUENCE.
+    pub fn children(&self) -> Result<Vec<Tlv<'a>>, DerError> {
+        let mut reader = DerReader::new(self.content);
+        let mut children = Vec::new();
+        while !reader.is_empty() {
+            children.push(reader.next()?);
+        }
+        Ok(children)
+    }
+}
+
+pub(super) struct DerReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, DerError> {
+        let byte = *self.bytes.get(self.pos).ok_or(DerError(self.pos))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    pub fn next(&mut self) -> Result<Tlv<'a>, DerError> {
+        let start = self.pos;
+        let tag = self.byte()?;
+        let first = self.byte()?;
+        let len = if first & 0x80 == 0 {
+            usize::from(first)
+        } else {
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > 4 {
+                return Err(DerError(start));
+            }
+            let mut len = 0usize;
+            for _ in 0..count {
+                len = (len << 8) | usize::from(self.byte()?);
+            }
+            len
+        };
+        let content_start = self.pos;
+        let end = content_start
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(DerError(start))?;
+        self.pos = end;
+        Ok(Tlv {
+            tag,
+            content: &self.bytes[content_start..end],
+            raw: &self.bytes[start..end],
+        })
+    }
+}
+
+/// Encodes one element, e.g. to rebuild a `SubjectPublicKeyInfo`.
+pub(super) fn encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut der = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        der.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        der.push(0x80 | bytes.len() as u8);
+        der.extend(bytes);
+    }
+    der.extend_from_slice(content);
+    der
+}
+
+/// Colon-separated uppercase hex, as fingerprints and serials are shown.
+pub(super) fn colon_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Significant bits of an unsigned big-endian INTEGER, e.g. an RSA modulus.
+pub(super) fn integer_bits(content: &[u8]) -> usize {
+    let trimmed: &[u8] = match content.iter().position(|b| *b != 0) {
+        Some(start) => &content[start..],
+        None => return 0,
+    };
+    trimmed.len() * 8 - trimmed[0].leading_zeros() as usize
+}
+
+/// The `-----BEGIN <label>-----` blocks in `text` with their decoded bytes.
+pub(super) fn pem_blocks(text: &str) -> Vec<(String, Vec<u8>)> {
+    let mut blocks = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("-----BEGIN ") {
+        let after = &rest[start + 11..];
+        let Some(label_end) = after.find("-----") else {
+            break;
+        };
+        let label = after[..label_end].to_string();
+        let body = &after[label_end + 5..];
+        let end_marker = format!("-----END {}-----", label);
+        let Some(body_end) = body.find(&end_marker) else {
+            break;
+        };
+        // Skip RFC 1421 headers like `Proc-Type:` in encrypted keys
+        let base64: String = body[..body_end]
+            .lines()
+            .filter(|line| !line.contains(':'))
+            .collect();
+        if let Ok(bytes) = general_purpose::STANDARD.decode(normalize_base64(&base64)) {
+            blocks.push((label, bytes));
+        }
+        rest = &body[body_end + end_marker.len()..];
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_rejects_truncated() {
+        assert!(Tlv::parse(&[0x30, 0x05, 0x02, 0x01]).is_err());
+        let tlv = Tlv::parse(&[0x30, 0x03, 0x02, 0x01, 0x07]).unwrap();
+        assert_eq!(tlv.children().unwrap()[0].content, [0x07]);
+        let long = encode(TAG_OCTET_STRING, &[0; 300]);
+        assert_eq!(long[..4], [0x04, 0x82, 0x01, 0x2c]);
+        assert_eq!(Tlv::parse(&long).unwrap().content.len(), 300);
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use super::json::strip_jsonc;
+
+/// Separator for nested keys, as Docker and ASP.NET configuration use.
+const NESTING: &str = "__";
+
+#[derive(Debug)]
+pub enum EnvError {
+    InvalidLine(usize),
+    UnterminatedQuote(usize),
+    InvalidJson(String),
+    NotAnObject,
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidLine(line) => {
+                write!(f, "Line {} is not a KEY=VALUE pair", line)
+            }
+            Self::UnterminatedQuote(line) => {
+                write!(f, "Quoted value starting on line {} is never closed", line)
+            }
+            Self::InvalidJson(msg) => {
+                write!(f, "Invalid JSON - {}", msg)
+            }
+            Self::NotAnObject => {
+                write!(f, "Expected a JSON object")
+            }
+        }
+    }
+}
+
+fn is_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Reads a quoted value starting just after the opening quote. Returns the
+/// value and the rest of the input after the closing quote.
+fn quoted(rest: &str, quote: char, line: usize) -> Result<(String, &str), EnvError> {
+    let mut value = String::new();
+    let mut chars = rest.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => return Ok((value, &rest[i + 1..])),
+            // Single quotes are literal, double quotes take the usual escapes
+            '\\' if quote == '"' => match chars.next().map(|(_, c)| c) {
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('t') => value.push('\t'),
+                Some(c @ ('"' | '\\' | '$' | '`')) => value.push(c),
+                Some(c) => {
+                    value.push('\\');
+                    value.push(c);
+                }
+                None => value.push('\\'),
+            },
+            c => value.push(c),
+        }
+    }
+    Err(EnvError::UnterminatedQuote(line))
+}
+
+/// Parses a `.env` file into a JSON object of strings. Handles `export`
+/// prefixes, comments, single and double quotes (including values that
+/// span lines) and inline comments after unquoted values. `${VAR}`
+/// references are kept as written rather than expanded.
+pub fn env_to_json(buffer: &str) -> Result<String, EnvError> {
+    let mut map = Map::new();
+    let mut rest = buffer;
+    let mut line = 1;
+    while !rest.is_empty() {
+        let (current, next_line) = rest.split_once('\n').unwrap_or((rest, ""));
+        let trimmed = current.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            rest = next_line;
+            line += 1;
+            continue;
+        }
+
+        let eq = current.find('=').ok_or(EnvError::InvalidLine(line))?;
+        let key = current[..eq].trim();
+        let key = key.strip_prefix("export ").unwrap_or(key).trim_start();
+        if !is_key(key) {
+            return Err(EnvError::InvalidLine(line));
+        }
+        let value = current[eq + 1..].trim_start();
+        let start = current.len() - value.len();
+
+        let value = match value.chars().next() {
+            // A quoted value may run on past this line
+            Some(quote @ ('"' | '\'')) => {
+                let (value, after) = quoted(&rest[start + 1..], quote, line)?;
+                line += rest[..rest.len() - after.len()].matches('\n').count();
+                let (tail, next) = after.split_once('\n').unwrap_or((after, ""));
+                let tail = tail.trim();
+                if !tail.is_empty() && !tail.starts_with('#') {
+                    return Err(EnvError::InvalidLine(line));
+                }
+                rest = next;
+                value
+            }
+            _ => {
+                rest = next_line;
+                let end = value
+                    .find(" #")
+                    .or(value.find("\t#"))
+                    .unwrap_or(value.len());
+                value[..end].trim_end().to_string()
+            }
+        };
+        line += 1;
+        map.insert(key.to_string(), Value::String(value));
+    }
+    serde_json::to_string_pretty(&map).map_err(|e| EnvError::InvalidJson(e.to_string()))
+}
+
+fn flatten(prefix: &str, value: &Value, pairs: &mut Vec<(String, String)>) {
+    let key = |child: &str| match prefix {
+        "" => child.to_string(),
+        prefix => format!("{}{}{}", prefix, NESTING, child),
+    };
+    match value {
+        Value::Object(map) => {
+            for (child, value) in map {
+                flatten(&key(child), value, pairs);
+            }
+        }
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten(&key(&i.to_string()), value, pairs);
+            }
+        }
+        Value::String(text) => pairs.push((prefix.to_string(), text.clone())),
+        Value::Null => pairs.push((prefix.to_string(), String::new())),
+        other => pairs.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+/// Quotes a value only when it needs it: single quotes where the value has
+/// no single quote or newline, so `$` stays literal, and double quotes with
+/// escapes otherwise.
+fn quote_value(value: &str) -> String {
+    let bare = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-./:@%+,".contains(c));
+    if bare {
+        return value.to_string();
+    }
+    if !value.contains(['\'', '\n', '\r']) {
+        return format!("'{}'", value);
+    }
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '"' | '\\' | '$' | '`' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Writes a JSON object as `.env` lines. Nested objects and arrays flatten
+/// to `PARENT__CHILD` keys, and characters a key can't hold become `_`.
+pub fn json_to_env(buffer: &str) -> Result<String, EnvError> {
+    let value: Value = serde_json::from_str(&strip_jsonc(buffer))
+        .map_err(|e| EnvError::InvalidJson(e.to_string()))?;
+    if !value.is_object() {
+        return Err(EnvError::NotAnObject);
+    }
+    let mut pairs = Vec::new();
+    flatten("", &value, &mut pairs);
+    Ok(pairs
+        .into_iter()
+        .map(|(key, value)| {
+            let key: String = key
+                .chars()
+                .map(|c| match c.is_ascii_alphanumeric() || c == '_' {
+                    true => c,
+                    false => '_',
+                })
+                .collect();
+            format!("{}={}", key, quote_value(&value))
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_to_json() {
+        let env = r#"# Database
+export DB_HOST=localhost
+DB_PORT = 5432 # default
+PASSWORD="p@ss \"word\"\n$HOME"
+LITERAL='no $expansion \n here'
+EMPTY=
+CERT="-----BEGIN-----
+abc
+-----END-----"
+URL=https://example.com/#anchor
+"#;
+        let json: Value = serde_json::from_str(&env_to_json(env).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "DB_HOST": "localhost",
+                "DB_PORT": "5432",
+                "PASSWORD": "p@ss \"word\"\n$HOME",
+                "LITERAL": "no $expansion \\n here",
+                "EMPTY": "",
+                "CERT": "-----BEGIN-----\nabc\n-----END-----",
+                "URL": "https://example.com/#anchor",
+            })
+        );
+
+        assert!(matches!(
+            env_to_json("A=1\nnot a pair"),
+            Err(EnvError::InvalidLine(2))
+        ));
+        assert!(matches!(
+            env_to_json("A=1\nB=\"open\nstill open"),
+            Err(EnvError::UnterminatedQuote(2))
+        ));
+        assert!(matches!(
+            env_to_json("A=\"x\"\nB='1' trailing"),
+            Err(EnvError::InvalidLine(2))
+        ));
+    }
+
+    #[test]
+    fn test_json_to_env() {
+        let json = r#"{"name": "app", "debug": true, "db": {"host": "db", "ports": [5432, 5433]},
+            "greeting": "hello world", "quote": "it's $5\nnow", "none": null}"#;
+        assert_eq!(
+            json_to_env(json).unwrap(),
+            "db__host=db\ndb__ports__0=5432\ndb__ports__1=5433\ndebug=true\n\
+             greeting='hello world'\nname=app\nnone=\nquote=\"it's \\$5\\nnow\""
+        );
+        assert!(matches!(json_to_env("[1]"), Err(EnvError::NotAnObject)));
+    }
+
+    #[test]
+    fn test_env_json_roundtrip() {
+        let env = "A='x y'\nB=\"line\\none \\\"q\\\" \\$x\"\nC=plain";
+        let json = env_to_json(env).unwrap();
+        assert_eq!(json_to_env(&json).unwrap(), env);
+    }
+}
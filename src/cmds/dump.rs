@@ -0,0 +1,172 @@
+use std::fmt;
+
+use base64::{Engine as _, engine::general_purpose};
+
+use super::base64::normalize_base64;
+
+/// The most a `*` run may expand a dump to, so a bogus offset can't ask
+/// for terabytes
+const MAX_FILLED_LEN: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum DumpError {
+    Empty,
+    Unrecognized,
+}
+
+impl fmt::Display for DumpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => {
+                write!(f, "No bytes found in the dump")
+            }
+            Self::Unrecognized => {
+                write!(
+                    f,
+                    "Not a byte dump - expected hex, xxd or hexdump -C output, or base64"
+                )
+            }
+        }
+    }
+}
+
+fn parse_hex(digits: &str) -> Option<Vec<u8>> {
+    if !digits.len().is_multiple_of(2) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Plain hex, optionally with `0x`/`\x` prefixes and space, comma or colon
+/// separators (`de ad`, `0xde, 0xad`, `\xde\xad`, `de:ad`).
+fn parse_plain_hex(text: &str) -> Option<Vec<u8>> {
+    let digits: String = text
+        .replace("0x", "")
+        .replace("0X", "")
+        .replace("\\x", "")
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',' && *c != ':')
+        .collect();
+    parse_hex(&digits)
+}
+
+/// Splits an `xxd` (`00000010: 7573 7461  ustar`) or `hexdump -C`
+/// (`00000010  75 73 74 61  |ustar|`) line into its offset and hex bytes.
+/// A `*` line has no offset and stands for repeats of the line before it.
+fn parse_dump_line(line: &str) -> Option<(usize, Vec<u8>)> {
+    let (offset, rest) = match line.split_once(": ") {
+        Some((offset, rest)) => (offset, rest.split("  ").next().unwrap_or("")),
+        None => {
+            let (offset, rest) = line.split_once(' ').unwrap_or((line, ""));
+            (offset, rest.split('|').next().unwrap_or(""))
+        }
+    };
+    let offset = usize::from_str_radix(offset.trim(), 16).ok()?;
+    let digits: String = rest.chars().filter(|c| !c.is_whitespace()).collect();
+    Some((offset, parse_hex(&digits)?))
+}
+
+fn parse_offset_dump(text: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut previous: Vec<u8> = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line == "*" {
+            continue;
+        }
+        let (offset, line_bytes) = parse_dump_line(line)?;
+        if offset > bytes.len() && offset > MAX_FILLED_LEN {
+            return None;
+        }
+        // Fill in lines collapsed by `*`; the bare offset on the last line
+        // of `hexdump -C` output marks where they end
+        while bytes.len() < offset && !previous.is_empty() {
+            let missing = (offset - bytes.len()).min(previous.len());
+            bytes.extend_from_slice(&previous[..missing]);
+        }
+        if bytes.len() != offset {
+            return None;
+        }
+        bytes.extend_from_slice(&line_bytes);
+        if !line_bytes.is_empty() {
+            previous = line_bytes;
+        }
+    }
+    Some(bytes)
+}
+
+/// Text stays text; anything else comes back as hex, which the other byte
+/// commands read.
+pub(super) fn bytes_to_text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|err| {
+        err.into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    })
+}
+
+/// Reads bytes back from a hex dump (plain, `xxd` or `hexdump -C`) or
+/// base64, so binary formats can be inspected from text in the buffer.
+pub fn parse_dump(buffer: &str) -> Result<Vec<u8>, DumpError> {
+    let text = buffer.trim();
+    if text.is_empty() {
+        return Err(DumpError::Empty);
+    }
+    let bytes = parse_plain_hex(text)
+        .or_else(|| parse_offset_dump(text))
+        .or_else(|| {
+            general_purpose::STANDARD
+                .decode(normalize_base64(text))
+                .ok()
+        })
+        .ok_or(DumpError::Unrecognized)?;
+    if bytes.is_empty() {
+        return Err(DumpError::Empty);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dump_plain_hex() {
+        assert_eq!(parse_dump("de ad be ef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(parse_dump("0xde, 0xAD").unwrap(), [0xde, 0xad]);
+        assert_eq!(parse_dump("\\xde\\xad").unwrap(), [0xde, 0xad]);
+    }
+
+    #[test]
+    fn test_parse_dump_xxd_and_hexdump() {
+        let xxd = "00000000: 6869 2074 6865 7265  hi there\n\
+                   00000008: 0a                        .";
+        assert_eq!(parse_dump(xxd).unwrap(), b"hi there\n");
+
+        let hexdump = "00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+                       *\n\
+                       00000030  41 42                                             |AB|\n\
+                       00000032";
+        let bytes = parse_dump(hexdump).unwrap();
+        assert_eq!(bytes.len(), 0x32);
+        assert_eq!(&bytes[0x30..], b"AB");
+    }
+
+    #[test]
+    fn test_parse_dump_rejects_huge_offsets() {
+        let dump = "00000000  41 41  |AA|\n*\nfffffffffff0  41  |A|";
+        assert!(parse_dump(dump).is_err());
+        let dump = "00000000  41 41  |AA|\n*\n01000002";
+        assert!(parse_dump(dump).is_err());
+    }
+
+    #[test]
+    fn test_parse_dump_base64_and_invalid() {
+        assert_eq!(parse_dump("aGkgdGhlcmUK").unwrap(), b"hi there\n");
+        assert!(parse_dump("not a dump!").is_err());
+        assert!(parse_dump("  ").is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// GitHub and Slack shortcodes, one `name emoji` per line. Where an emoji
+/// has several names the first one listed is the one `/emoji-encode` uses.
+const SHORTCODES: &str = include_str!("data/emoji_shortcodes.txt");
+
+const VARIATION_SELECTOR: char = '\u{FE0F}';
+
+struct Table {
+    by_name: HashMap<&'static str, &'static str>,
+    /// Emoji keyed by their first character, longest sequence first so
+    /// ZWJ sequences and flags win over their parts.
+    by_first_char: HashMap<char, Vec<(String, &'static str)>>,
+}
+
+fn table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut by_name = HashMap::new();
+        let mut by_first_char: HashMap<char, Vec<(String, &'static str)>> = HashMap::new();
+        let mut add = |emoji: String, name| {
+            let Some(first) = emoji.chars().next() else {
+                return;
+            };
+            let candidates = by_first_char.entry(first).or_default();
+            if !candidates.iter().any(|(known, _)| *known == emoji) {
+                candidates.push((emoji, name));
+            }
+        };
+        for (name, emoji) in SHORTCODES.lines().filter_map(|line| line.split_once(' ')) {
+            by_name.insert(name, emoji);
+            add(emoji.to_string(), name);
+            // Text often drops the variation selector; match it either way,
+            // but leave ©, ® and friends alone when they're written as text
+            let bare: String = emoji.chars().filter(|c| *c != VARIATION_SELECTOR).collect();
+            if bare.chars().count() > 1 || bare.chars().all(|c| c >= '\u{2100}') {
+                add(bare, name);
+            }
+        }
+        for candidates in by_first_char.values_mut() {
+            candidates.sort_by_key(|(emoji, _)| std::cmp::Reverse(emoji.len()));
+        }
+        Table {
+            by_name,
+            by_first_char,
+        }
+    })
+}
+
+/// Replaces emoji with their `:shortcode:`, for text headed to Slack or
+/// GitHub. Emoji without a shortcode in the table are kept.
+pub fn emoji_encode(buffer: &str) -> String {
+    let table = table();
+    let mut result = String::with_capacity(buffer.len());
+    let mut rest = buffer;
+    while let Some(c) = rest.chars().next() {
+        let found = table.by_first_char.get(&c).and_then(|candidates| {
+            candidates
+                .iter()
+                .find(|(emoji, _)| rest.starts_with(emoji.as_str()))
+        });
+        match found {
+            Some((emoji, name)) => {
+                result.push(':');
+                result.push_str(name);
+                result.push(':');
+                rest = &rest[emoji.len()..];
+                // A trailing variation selector belongs to the emoji
+                rest = rest.strip_prefix(VARIATION_SELECTOR).unwrap_or(rest);
+            }
+            None => {
+                result.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    result
+}
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-')
+}
+
+/// Replaces `:shortcode:` with the emoji, for plain terminals and editors.
+/// Unknown shortcodes and colons that aren't part of one, as in `12:30`,
+/// are left as they are.
+pub fn emoji_decode(buffer: &str) -> String {
+    let table = table();
+    let mut result = String::with_capacity(buffer.len());
+    let mut rest = buffer;
+    while let Some(colon) = rest.find(':') {
+        result.push_str(&rest[..colon]);
+        let after = &rest[colon + 1..];
+        let end = after.find(|c| !is_shortcode_char(c)).unwrap_or(after.len());
+        match table.by_name.get(&after[..end]) {
+            Some(emoji) if after[end..].starts_with(':') => {
+                result.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            // Resume at the next colon, which may open a shortcode itself
+            _ => {
+                result.push(':');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emoji_encode() {
+        assert_eq!(emoji_encode("Ship it 🚀 😀"), "Ship it :rocket: :grinning:");
+        assert_eq!(emoji_encode("❤️ and ❤"), ":heart: and :heart:");
+        assert_eq!(emoji_encode("🏳️‍🌈 🇯🇵"), ":rainbow_flag: :jp:");
+        assert_eq!(emoji_encode("👍🏽"), ":+1::skin-tone-4:");
+        assert_eq!(emoji_encode("© 2024 #1"), "© 2024 #1");
+        assert_eq!(emoji_encode("🫠"), "🫠");
+    }
+
+    #[test]
+    fn test_emoji_decode() {
+        assert_eq!(emoji_decode(":rocket: launch :tada:"), "🚀 launch 🎉");
+        assert_eq!(emoji_decode(":thumbsup: :+1:"), "👍 👍");
+        assert_eq!(
+            emoji_decode("at 12:30:45 :not_an_emoji:"),
+            "at 12:30:45 :not_an_emoji:"
+        );
+        assert_eq!(emoji_decode("time::fire:"), "time:🔥");
+        assert_eq!(emoji_decode(":heart:"), "❤️");
+    }
+
+    #[test]
+    fn test_emoji_roundtrip() {
+        let text = "Deployed ✅ 🔥 🇺🇸 🏴‍☠️ #️⃣";
+        assert_eq!(emoji_decode(&emoji_encode(text)), text);
+    }
+}
@@ -0,0 +1,274 @@
+use uuid::Builder;
+
+use super::time::DateTime;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Ben", "Chloe", "Diego", "Elena", "Farah", "Grace", "Hiro", "Ines",
+    "James", "Kai", "Leila", "Marco", "Nina", "Omar", "Priya", "Quinn", "Rosa", "Sam", "Tariq",
+    "Uma", "Victor", "Wen", "Yara", "Zoe",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Adeyemi", "Becker", "Chen", "Dubois", "Evans", "Fischer", "Garcia", "Hopper", "Ivanova",
+    "Johnson", "Kim", "Lovelace", "Martin", "Nakamura", "Okafor", "Patel", "Rossi", "Silva",
+    "Turing", "Usman", "Varga", "Williams", "Yilmaz", "Zhang",
+];
+
+const CITIES: &[(&str, &str)] = &[
+    ("Amsterdam", "Netherlands"),
+    ("Austin", "United States"),
+    ("Berlin", "Germany"),
+    ("Buenos Aires", "Argentina"),
+    ("Cape Town", "South Africa"),
+    ("Lagos", "Nigeria"),
+    ("Lisbon", "Portugal"),
+    ("London", "United Kingdom"),
+    ("Melbourne", "Australia"),
+    ("Montreal", "Canada"),
+    ("Mumbai", "India"),
+    ("Osaka", "Japan"),
+    ("Seoul", "South Korea"),
+    ("Stockholm", "Sweden"),
+];
+
+const STREETS: &[&str] = &[
+    "Oak", "Maple", "Station", "Market", "Church", "Mill", "Park", "River", "High", "Elm",
+];
+
+const STREET_KINDS: &[&str] = &["Street", "Road", "Avenue", "Lane", "Way"];
+
+const COMPANY_WORDS: &[&str] = &[
+    "Acme",
+    "Globex",
+    "Initech",
+    "Umbrella",
+    "Stark",
+    "Wayne",
+    "Hooli",
+    "Vandelay",
+    "Cyberdyne",
+];
+
+const COMPANY_SUFFIXES: &[&str] = &["Inc", "Ltd", "Labs", "Group", "Systems"];
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net", "test.dev"];
+
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "minim",
+    "veniam",
+    "quis",
+    "nostrud",
+    "exercitation",
+    "ullamco",
+    "laboris",
+    "nisi",
+];
+
+/// Start and end of the range generated timestamps fall in, 2015 to 2026.
+const EPOCH_RANGE: (i64, i64) = (1_420_070_400, 1_767_225_599);
+
+/// Plausible-looking fake values from a seeded generator, so the same seed
+/// always produces the same data. SplitMix64 is plenty for test fixtures
+/// and keeps the output stable across dependency upgrades.
+pub struct Faker {
+    state: u64,
+}
+
+impl Faker {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A whole number in `low..=high`.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        if high <= low {
+            return low;
+        }
+        let span = (high - low) as u64 + 1;
+        low + (self.next_u64() % span) as i64
+    }
+
+    pub fn float(&mut self, low: f64, high: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        low + unit * (high - low)
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len() as i64 - 1) as usize]
+    }
+
+    pub fn first_name(&mut self) -> String {
+        self.pick(FIRST_NAMES).to_string()
+    }
+
+    pub fn last_name(&mut self) -> String {
+        self.pick(LAST_NAMES).to_string()
+    }
+
+    pub fn full_name(&mut self) -> String {
+        format!("{} {}", self.first_name(), self.last_name())
+    }
+
+    pub fn username(&mut self) -> String {
+        format!("{}{}", self.first_name().to_lowercase(), self.range(1, 999))
+    }
+
+    pub fn email(&mut self) -> String {
+        let domain = self.pick(DOMAINS);
+        format!(
+            "{}.{}@{}",
+            self.first_name().to_lowercase(),
+            self.last_name().to_lowercase(),
+            domain
+        )
+    }
+
+    pub fn city(&mut self) -> String {
+        self.pick(CITIES).0.to_string()
+    }
+
+    pub fn country(&mut self) -> String {
+        self.pick(CITIES).1.to_string()
+    }
+
+    pub fn street_address(&mut self) -> String {
+        format!(
+            "{} {} {}",
+            self.range(1, 250),
+            self.pick(STREETS),
+            self.pick(STREET_KINDS)
+        )
+    }
+
+    pub fn postal_code(&mut self) -> String {
+        format!("{:05}", self.range(1000, 99999))
+    }
+
+    pub fn phone(&mut self) -> String {
+        format!(
+            "+1-555-{:03}-{:04}",
+            self.range(100, 999),
+            self.range(0, 9999)
+        )
+    }
+
+    pub fn company(&mut self) -> String {
+        format!(
+            "{} {}",
+            self.pick(COMPANY_WORDS),
+            self.pick(COMPANY_SUFFIXES)
+        )
+    }
+
+    pub fn url(&mut self) -> String {
+        format!("https://{}/{}", self.pick(DOMAINS), self.word())
+    }
+
+    pub fn word(&mut self) -> String {
+        self.pick(WORDS).to_string()
+    }
+
+    /// A capitalised sentence of `min..=max` words ending in a full stop.
+    pub fn sentence(&mut self, min: i64, max: i64) -> String {
+        let count = self.range(min, max);
+        let words: Vec<String> = (0..count).map(|_| self.word()).collect();
+        let mut sentence = words.join(" ");
+        if let Some(first) = sentence.get(..1) {
+            sentence.replace_range(..1, &first.to_uppercase());
+        }
+        sentence.push('.');
+        sentence
+    }
+
+    /// A version 4 UUID built from the generator rather than the OS.
+    pub fn uuid(&mut self) -> String {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+        Builder::from_random_bytes(bytes).into_uuid().to_string()
+    }
+
+    pub fn date_time(&mut self) -> String {
+        DateTime::from_unix(self.range(EPOCH_RANGE.0, EPOCH_RANGE.1)).to_string()
+    }
+
+    pub fn date(&mut self) -> String {
+        self.date_time()[..10].to_string()
+    }
+
+    pub fn ipv4(&mut self) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            self.range(1, 223),
+            self.range(0, 255),
+            self.range(0, 255),
+            self.range(1, 254)
+        )
+    }
+
+    pub fn hex_color(&mut self) -> String {
+        format!("#{:06x}", self.range(0, 0xFF_FFFF))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faker_is_reproducible() {
+        let values = |seed| {
+            let mut faker = Faker::new(seed);
+            (
+                faker.email(),
+                faker.uuid(),
+                faker.date_time(),
+                faker.range(1, 6),
+            )
+        };
+        assert_eq!(values(42), values(42));
+        assert_ne!(values(42), values(43));
+
+        let mut faker = Faker::new(7);
+        for _ in 0..100 {
+            assert!((1..=6).contains(&faker.range(1, 6)));
+            let value = faker.float(-1.0, 1.0);
+            assert!((-1.0..1.0).contains(&value));
+        }
+        assert_eq!(faker.uuid().as_bytes()[14], b'4');
+        assert!(faker.sentence(3, 3).ends_with('.'));
+        assert_eq!(faker.date().len(), 10);
+    }
+}
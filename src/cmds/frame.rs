@@ -0,0 +1,94 @@
+use std::fmt;
+
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug)]
+pub enum BoxError {
+    UnknownStyle(String),
+}
+
+impl fmt::Display for BoxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownStyle(name) => {
+                write!(
+                    f,
+                    "unknown box style '{}' - expected single, double, rounded, heavy, or ascii",
+                    name
+                )
+            }
+        }
+    }
+}
+
+/// Corners and edges of a frame: top left, top right, bottom left, bottom
+/// right, horizontal and vertical.
+struct Style([char; 6]);
+
+impl Style {
+    fn parse(name: &str) -> Result<Self, BoxError> {
+        let chars = match name.to_lowercase().as_str() {
+            "single" | "light" => ['┌', '┐', '└', '┘', '─', '│'],
+            "double" => ['╔', '╗', '╚', '╝', '═', '║'],
+            "rounded" | "round" => ['╭', '╮', '╰', '╯', '─', '│'],
+            "heavy" | "bold" => ['┏', '┓', '┗', '┛', '━', '┃'],
+            // For places that mangle anything beyond ASCII
+            "ascii" => ['+', '+', '+', '+', '-', '|'],
+            _ => return Err(BoxError::UnknownStyle(name.to_string())),
+        };
+        Ok(Self(chars))
+    }
+}
+
+/// Draws a frame around the buffer, one space inside each edge. Lines are
+/// padded to the widest by display width, so CJK text and emoji line up,
+/// and tabs expand to four spaces.
+pub fn draw_box(buffer: &str, style: Option<&str>) -> Result<String, BoxError> {
+    let Style(
+        [
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        ],
+    ) = Style::parse(style.unwrap_or("single"))?;
+    let lines: Vec<String> = buffer
+        .trim_end_matches(['\n', '\r'])
+        .lines()
+        .map(|line| line.replace('\t', "    ").trim_end().to_string())
+        .collect();
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+    let edge = horizontal.to_string().repeat(width + 2);
+
+    let mut framed = vec![format!("{}{}{}", top_left, edge, top_right)];
+    for line in &lines {
+        let padding = " ".repeat(width - line.width());
+        framed.push(format!("{} {}{} {}", vertical, line, padding, vertical));
+    }
+    framed.push(format!("{}{}{}", bottom_left, edge, bottom_right));
+    Ok(framed.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_box() {
+        assert_eq!(
+            draw_box("hello\nworld!\n", None).unwrap(),
+            "┌────────┐\n│ hello  │\n│ world! │\n└────────┘"
+        );
+        assert_eq!(
+            draw_box("hi", Some("ascii")).unwrap(),
+            "+----+\n| hi |\n+----+"
+        );
+        assert_eq!(
+            draw_box("中文\nab", Some("rounded")).unwrap(),
+            "╭──────╮\n│ 中文 │\n│ ab   │\n╰──────╯"
+        );
+        assert!(draw_box("x", Some("dotted")).is_err());
+    }
+}
@@ -0,0 +1,455 @@
+use std::fmt;
+use std::io::{self, Read};
+
+use sha2::{Digest, Sha256};
+
+#[derive(Debug)]
+pub enum HashError {
+    MissingDigest,
+    InvalidDigest(String),
+    WrongLength(HashAlgorithm, usize),
+    UnknownAlgorithm(String),
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingDigest => {
+                write!(f, "Expected the digest to compare against")
+            }
+            Self::InvalidDigest(digest) => {
+                write!(f, "'{}' is not a hex digest", digest)
+            }
+            Self::WrongLength(algorithm, len) => {
+                write!(
+                    f,
+                    "A {} digest has {} hex digits, not {}",
+                    algorithm.name(),
+                    algorithm.hex_len(),
+                    len
+                )
+            }
+            Self::UnknownAlgorithm(name) => {
+                write!(
+                    f,
+                    "Unknown hash algorithm '{}' - expected md5, sha1, or sha256",
+                    name
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+        }
+    }
+
+    fn hex_len(self) -> usize {
+        match self {
+            Self::Md5 => 32,
+            Self::Sha1 => 40,
+            Self::Sha256 => 64,
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, HashError> {
+        match name.to_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(Self::Md5),
+            "sha1" => Ok(Self::Sha1),
+            "sha256" => Ok(Self::Sha256),
+            _ => Err(HashError::UnknownAlgorithm(name.to_string())),
+        }
+    }
+
+    pub fn hex(self, bytes: &[u8]) -> String {
+        let mut hasher = Hasher::new(self);
+        hasher.update(bytes);
+        hasher.finish_hex()
+    }
+}
+
+pub fn sha256_hex(buffer: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(buffer.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// MD5 (RFC 1321) compression of one 64-byte block. Broken for security,
+/// but still what many download pages publish, so it's here for checking
+/// those and nothing else.
+fn md5_block(state: &mut [u32; 5], block: &[u8]) {
+    const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+    // The integer part of 2^32 × |sin(i + 1)|
+    const CONSTANTS: [u32; 64] = [
+        0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613,
+        0xfd469501, 0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193,
+        0xa679438e, 0x49b40821, 0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d,
+        0x02441453, 0xd8a1e681, 0xe7d3fbc8, 0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed,
+        0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a, 0xfffa3942, 0x8771f681, 0x6d9d6122,
+        0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70, 0x289b7ec6, 0xeaa127fa,
+        0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665, 0xf4292244,
+        0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+        0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb,
+        0xeb86d391,
+    ];
+
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    let [mut a, mut b, mut c, mut d, _] = *state;
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let rotated = a
+            .wrapping_add(f)
+            .wrapping_add(CONSTANTS[i])
+            .wrapping_add(words[g])
+            .rotate_left(SHIFTS[i / 16 * 4 + i % 4]);
+        (a, b, c, d) = (d, b.wrapping_add(rotated), b, c);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+/// SHA-1 (RFC 3174) compression of one 64-byte block, likewise only for
+/// checking published checksums.
+fn sha1_block(state: &mut [u32; 5], block: &[u8]) {
+    let mut words = [0u32; 80];
+    for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for i in 16..80 {
+        words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, word) in words.iter().enumerate() {
+        let (f, k) = match i / 20 {
+            0 => ((b & c) | (!b & d), 0x5a82_7999),
+            1 => (b ^ c ^ d, 0x6ed9_eba1),
+            2 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+            _ => (b ^ c ^ d, 0xca62_c1d6),
+        };
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(*word);
+        (a, b, c, d, e) = (temp, a, b.rotate_left(30), c, d);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+/// MD5 or SHA-1 fed a chunk at a time. Both use the same Merkle–Damgård
+/// framing and differ in the compression function and byte order.
+struct BlockHasher {
+    md5: bool,
+    state: [u32; 5],
+    pending: Vec<u8>,
+    len: u64,
+}
+
+impl BlockHasher {
+    fn new(md5: bool) -> Self {
+        Self {
+            md5,
+            state: [
+                0x6745_2301,
+                0xefcd_ab89,
+                0x98ba_dcfe,
+                0x1032_5476,
+                0xc3d2_e1f0,
+            ],
+            pending: Vec::with_capacity(64),
+            len: 0,
+        }
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        match self.md5 {
+            true => md5_block(&mut self.state, block),
+            false => sha1_block(&mut self.state, block),
+        }
+    }
+
+    fn update(&mut self, mut bytes: &[u8]) {
+        self.len = self.len.wrapping_add(bytes.len() as u64);
+        if !self.pending.is_empty() {
+            let take = (64 - self.pending.len()).min(bytes.len());
+            self.pending.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+            if self.pending.len() < 64 {
+                return;
+            }
+            let block = std::mem::take(&mut self.pending);
+            self.compress(&block);
+        }
+        let mut blocks = bytes.chunks_exact(64);
+        for block in &mut blocks {
+            self.compress(block);
+        }
+        self.pending.extend_from_slice(blocks.remainder());
+    }
+
+    /// Pads with a one bit, zeros to 56 bytes mod 64, then the length in
+    /// bits, and returns the digest.
+    fn finish(mut self) -> Vec<u8> {
+        let bits = self.len.wrapping_mul(8);
+        let mut tail = std::mem::take(&mut self.pending);
+        tail.push(0x80);
+        while tail.len() % 64 != 56 {
+            tail.push(0);
+        }
+        match self.md5 {
+            true => tail.extend_from_slice(&bits.to_le_bytes()),
+            false => tail.extend_from_slice(&bits.to_be_bytes()),
+        }
+        for block in tail.chunks(64) {
+            self.compress(block);
+        }
+        match self.md5 {
+            true => self.state[..4]
+                .iter()
+                .flat_map(|w| w.to_le_bytes())
+                .collect(),
+            false => self.state.iter().flat_map(|w| w.to_be_bytes()).collect(),
+        }
+    }
+}
+
+/// A digest computed incrementally, so files can be hashed without being
+/// read into memory.
+pub struct Hasher(HasherKind);
+
+enum HasherKind {
+    Block(BlockHasher),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self(match algorithm {
+            HashAlgorithm::Md5 => HasherKind::Block(BlockHasher::new(true)),
+            HashAlgorithm::Sha1 => HasherKind::Block(BlockHasher::new(false)),
+            HashAlgorithm::Sha256 => HasherKind::Sha256(Sha256::new()),
+        })
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        match &mut self.0 {
+            HasherKind::Block(hasher) => hasher.update(bytes),
+            HasherKind::Sha256(hasher) => hasher.update(bytes),
+        }
+    }
+
+    pub fn finish_hex(self) -> String {
+        let digest = match self.0 {
+            HasherKind::Block(hasher) => hasher.finish(),
+            HasherKind::Sha256(hasher) => hasher.finalize().to_vec(),
+        };
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Hashes everything `reader` yields, 64 KiB at a time.
+pub fn hash_reader(mut reader: impl Read, algorithm: HashAlgorithm) -> io::Result<String> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut chunk = vec![0; 64 * 1024];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(hasher.finish_hex()),
+            Ok(read) => hasher.update(&chunk[..read]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The outcome of comparing the buffer's digest with an expected one.
+pub struct Verification {
+    pub algorithm: HashAlgorithm,
+    pub matches: bool,
+    pub actual: String,
+    pub expected: String,
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.matches {
+            true => write!(f, "MATCH - {} {}", self.algorithm.name(), self.actual),
+            false => write!(
+                f,
+                "MISMATCH - {} is {}, expected {}",
+                self.algorithm.name(),
+                self.actual,
+                self.expected
+            ),
+        }
+    }
+}
+
+/// Pulls the digest out of what was pasted: a bare digest, one with an
+/// `sha256:` style prefix, a line of `sha256sum` output, or hex split up
+/// by spaces or colons.
+fn normalize_digest(expected: &str, algorithm: HashAlgorithm) -> Result<String, HashError> {
+    let expected = expected.trim();
+    let expected = match expected.split_once([':', '=']) {
+        Some((prefix, rest)) if prefix.chars().any(|c| !c.is_ascii_hexdigit()) => rest,
+        _ => expected,
+    };
+    let first = expected.split_whitespace().next().unwrap_or_default();
+    let digest: String = match first.len() == algorithm.hex_len() {
+        true => first.to_string(),
+        false => expected
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != ':')
+            .collect(),
+    };
+    if digest.is_empty() {
+        return Err(HashError::MissingDigest);
+    }
+    if !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(HashError::InvalidDigest(expected.to_string()));
+    }
+    if digest.len() != algorithm.hex_len() {
+        return Err(HashError::WrongLength(algorithm, digest.len()));
+    }
+    Ok(digest.to_lowercase())
+}
+
+/// Hashes the buffer and compares the digest with `expected`, ignoring case.
+pub fn verify_digest(
+    buffer: &str,
+    algorithm: HashAlgorithm,
+    expected: Option<&str>,
+) -> Result<Verification, HashError> {
+    let expected = normalize_digest(expected.ok_or(HashError::MissingDigest)?, algorithm)?;
+    let actual = algorithm.hex(buffer.as_bytes());
+    Ok(Verification {
+        algorithm,
+        matches: actual == expected,
+        actual,
+        expected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+
+    #[test]
+    fn test_digests() {
+        assert_eq!(
+            HashAlgorithm::Md5.hex(b""),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            HashAlgorithm::Md5.hex(FOX),
+            "9e107d9d372bb6826bd81d3542a419d6"
+        );
+        assert_eq!(
+            HashAlgorithm::Sha1.hex(b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            HashAlgorithm::Sha1.hex(FOX),
+            "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+        );
+        // Messages spanning several blocks
+        let long = vec![b'a'; 1000];
+        assert_eq!(
+            HashAlgorithm::Md5.hex(&long),
+            "cabe45dcc9ae5b66ba86600cca6b8ba8"
+        );
+        assert_eq!(
+            HashAlgorithm::Sha1.hex(&long),
+            "291e9a6c66994949b57ba5e650361e98fc36b1ba"
+        );
+        assert_eq!(HashAlgorithm::Sha256.hex(b"abc"), sha256_hex("abc"));
+    }
+
+    #[test]
+    fn test_hash_reader_matches_one_shot() {
+        // Uneven reads exercise blocks split across chunks
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        for algorithm in [
+            HashAlgorithm::Md5,
+            HashAlgorithm::Sha1,
+            HashAlgorithm::Sha256,
+        ] {
+            let streamed = hash_reader(io::Cursor::new(&data), algorithm).unwrap();
+            assert_eq!(streamed, algorithm.hex(&data));
+
+            let mut hasher = Hasher::new(algorithm);
+            for piece in data.chunks(37) {
+                hasher.update(piece);
+            }
+            assert_eq!(hasher.finish_hex(), streamed);
+        }
+        assert_eq!(
+            HashAlgorithm::parse("SHA-256").unwrap(),
+            HashAlgorithm::Sha256
+        );
+        assert!(HashAlgorithm::parse("crc32").is_err());
+    }
+
+    #[test]
+    fn test_verify_digest() {
+        let sha256 = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        let verified = verify_digest("abc", HashAlgorithm::Sha256, Some(sha256)).unwrap();
+        assert!(verified.matches);
+        assert!(verified.to_string().starts_with("MATCH - SHA-256 ba7816bf"));
+
+        let line = format!("sha256:{}  abc.txt", sha256.to_lowercase());
+        assert!(
+            verify_digest("abc", HashAlgorithm::Sha256, Some(&line))
+                .unwrap()
+                .matches
+        );
+        let spaced = "90 01 50 98 3c d2 4f b0 d6 96 3f 7d 28 e1 7f 72";
+        assert!(
+            verify_digest("abc", HashAlgorithm::Md5, Some(spaced))
+                .unwrap()
+                .matches
+        );
+
+        let mismatch = verify_digest("abd", HashAlgorithm::Sha256, Some(sha256)).unwrap();
+        assert!(!mismatch.matches);
+        assert!(mismatch.to_string().starts_with("MISMATCH - SHA-256 is "));
+
+        assert!(matches!(
+            verify_digest("abc", HashAlgorithm::Sha1, Some("abc123")),
+            Err(HashError::WrongLength(HashAlgorithm::Sha1, 6))
+        ));
+        assert!(matches!(
+            verify_digest("abc", HashAlgorithm::Md5, Some("not-hex")),
+            Err(HashError::InvalidDigest(_))
+        ));
+        assert!(matches!(
+            verify_digest("abc", HashAlgorithm::Md5, None),
+            Err(HashError::MissingDigest)
+        ));
+    }
+}
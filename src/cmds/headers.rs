@@ -0,0 +1,347 @@
+use std::fmt;
+
+/// Header names whose conventional spelling isn't plain Train-Case.
+const SPECIAL_CASES: &[&str] = &[
+    "Content-MD5",
+    "DNT",
+    "ETag",
+    "Sec-CH-UA",
+    "Sec-CH-UA-Mobile",
+    "Sec-CH-UA-Platform",
+    "SourceMap",
+    "TE",
+    "WWW-Authenticate",
+    "X-DNS-Prefetch-Control",
+    "X-UA-Compatible",
+    "X-XSS-Protection",
+];
+
+/// HSTS max-age below which browsers forget the policy before most
+/// preload lists accept it (six months).
+const HSTS_MIN_MAX_AGE: u64 = 15_768_000;
+
+#[derive(Debug)]
+pub enum HeaderError {
+    NoHeaders,
+    InvalidLine(usize),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoHeaders => {
+                write!(f, "No headers found")
+            }
+            Self::InvalidLine(line) => {
+                write!(f, "Expected `Name: value` on line {}", line)
+            }
+        }
+    }
+}
+
+struct Header {
+    name: String,
+    value: String,
+}
+
+impl Header {
+    fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+/// Canonical spelling for a header name, e.g. `content-type` →
+/// `Content-Type`. HTTP/2 pseudo-headers stay lowercase.
+fn normalize_name(name: &str) -> String {
+    if name.starts_with(':') {
+        return name.to_lowercase();
+    }
+    if let Some(special) = SPECIAL_CASES
+        .iter()
+        .find(|special| special.eq_ignore_ascii_case(name))
+    {
+        return special.to_string();
+    }
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+fn is_start_line(line: &str) -> bool {
+    line.starts_with("HTTP/")
+        || line
+            .split_whitespace()
+            .nth(2)
+            .is_some_and(|v| v.starts_with("HTTP/"))
+}
+
+/// Picks the lines to analyse. In `curl -v` output the response headers
+/// (`< `) win over the request headers (`> `); `*` info lines and body
+/// markers are dropped.
+fn header_lines(buffer: &str) -> Vec<(usize, &str)> {
+    let numbered = buffer.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let is_verbose = buffer
+        .lines()
+        .any(|line| line.starts_with("< ") || line.starts_with("> "));
+    if !is_verbose {
+        return numbered.collect();
+    }
+    let has_response = buffer.lines().any(|line| line.starts_with("< "));
+    let prefix = if has_response { '<' } else { '>' };
+    numbered
+        .filter_map(|(number, line)| {
+            let rest = line.strip_prefix(prefix)?;
+            Some((number, rest.strip_prefix(' ').unwrap_or(rest)))
+        })
+        .collect()
+}
+
+fn parse_headers(buffer: &str) -> Result<(Option<String>, Vec<Header>), HeaderError> {
+    let mut start_line = None;
+    let mut headers: Vec<Header> = Vec::new();
+    for (number, line) in header_lines(buffer) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if start_line.is_none() && headers.is_empty() && is_start_line(line) {
+            start_line = Some(line.trim().to_string());
+            continue;
+        }
+        // Obsolete line folding continues the previous value
+        if line.starts_with([' ', '\t'])
+            && let Some(last) = headers.last_mut()
+        {
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+            continue;
+        }
+        // Pseudo-headers such as `:status: 200` start with their own colon
+        let split_from = usize::from(line.starts_with(':'));
+        let Some(colon) = line[split_from..].find(':') else {
+            return Err(HeaderError::InvalidLine(number));
+        };
+        let (name, value) = line.split_at(split_from + colon);
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(HeaderError::InvalidLine(number));
+        }
+        headers.push(Header {
+            name: normalize_name(name),
+            value: value[1..].trim().to_string(),
+        });
+    }
+    if headers.is_empty() {
+        return Err(HeaderError::NoHeaders);
+    }
+    Ok((start_line, headers))
+}
+
+fn hsts_summary(value: &str) -> String {
+    let max_age = value.split(';').find_map(|directive| {
+        let (key, age) = directive.split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case("max-age") {
+            return None;
+        }
+        age.trim().trim_matches('"').parse::<u64>().ok()
+    });
+    match max_age {
+        None => format!("{} (no valid max-age)", value),
+        Some(0) => format!("{} (max-age=0 clears the policy)", value),
+        Some(age) if age < HSTS_MIN_MAX_AGE => format!("{} (max-age under 6 months)", value),
+        Some(_) => value.to_string(),
+    }
+}
+
+fn csp_summary(value: &str, report_only: bool) -> String {
+    let mut notes: Vec<&str> = ["'unsafe-inline'", "'unsafe-eval'"]
+        .into_iter()
+        .filter(|keyword| value.contains(keyword))
+        .collect();
+    if report_only {
+        notes.push("report-only");
+    }
+    if notes.is_empty() {
+        value.to_string()
+    } else {
+        format!("{} ({})", value, notes.join(", "))
+    }
+}
+
+fn cors_summary(headers: &[Header]) -> Option<String> {
+    let cors: Vec<&Header> = headers
+        .iter()
+        .filter(|header| header.name.starts_with("Access-Control-"))
+        .collect();
+    let origin = cors.iter().find(|h| h.is("Access-Control-Allow-Origin"));
+    let credentials = cors
+        .iter()
+        .any(|h| h.is("Access-Control-Allow-Credentials") && h.value.eq_ignore_ascii_case("true"));
+    let summary = match origin {
+        Some(origin) if origin.value == "*" && credentials => {
+            "any origin with credentials (browsers reject this)".to_string()
+        }
+        Some(origin) if origin.value == "*" => "any origin".to_string(),
+        Some(origin) if credentials => format!("{} with credentials", origin.value),
+        Some(origin) => origin.value.clone(),
+        None if cors.is_empty() => return None,
+        None => "no Access-Control-Allow-Origin".to_string(),
+    };
+    Some(summary)
+}
+
+/// Normalizes name casing, sorts the headers, then summarizes HSTS, CSP
+/// and CORS and lists headers sent more than once. Accepts a plain header
+/// block, a devtools copy (including HTTP/2 pseudo-headers) or `curl -v`
+/// output.
+pub fn headers_format(buffer: &str) -> Result<String, HeaderError> {
+    let (start_line, mut headers) = parse_headers(buffer)?;
+    let is_response = start_line
+        .as_deref()
+        .is_some_and(|line| line.starts_with("HTTP/"))
+        || headers.iter().any(|header| header.is(":status"));
+
+    // Stable sort keeps repeated headers in their original order
+    headers.sort_by_key(|header| (!header.name.starts_with(':'), header.name.to_lowercase()));
+
+    let mut output = String::new();
+    if let Some(line) = &start_line {
+        output.push_str(line);
+        output.push_str("\n\n");
+    }
+    for header in &headers {
+        output.push_str(&format!("{}: {}\n", header.name, header.value));
+    }
+
+    let find = |name: &str| headers.iter().find(|header| header.is(name));
+    let mut security = Vec::new();
+    match find("Strict-Transport-Security") {
+        Some(hsts) => security.push(("HSTS", hsts_summary(&hsts.value))),
+        None if is_response => security.push(("HSTS", "missing".to_string())),
+        None => {}
+    }
+    match (
+        find("Content-Security-Policy"),
+        find("Content-Security-Policy-Report-Only"),
+    ) {
+        (Some(csp), _) => security.push(("CSP", csp_summary(&csp.value, false))),
+        (None, Some(csp)) => security.push(("CSP", csp_summary(&csp.value, true))),
+        (None, None) if is_response => security.push(("CSP", "missing".to_string())),
+        (None, None) => {}
+    }
+    if let Some(cors) = cors_summary(&headers) {
+        security.push(("CORS", cors));
+    }
+    if !security.is_empty() {
+        output.push_str("\nSecurity:\n");
+        for (label, summary) in security {
+            output.push_str(&format!("  {:<5} {}\n", label, summary));
+        }
+    }
+
+    let mut duplicates: Vec<(&str, usize)> = Vec::new();
+    for header in &headers {
+        // Set-Cookie is the one header that is meant to repeat
+        if header.is("Set-Cookie") {
+            continue;
+        }
+        match duplicates.last_mut() {
+            Some((name, count)) if name.eq_ignore_ascii_case(&header.name) => *count += 1,
+            _ => duplicates.push((&header.name, 1)),
+        }
+    }
+    duplicates.retain(|(_, count)| *count > 1);
+    if !duplicates.is_empty() {
+        output.push_str("\nDuplicates:\n");
+        for (name, count) in duplicates {
+            output.push_str(&format!("  {} ({}×)\n", name, count));
+        }
+    }
+
+    Ok(output.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_format_response() {
+        let block = "HTTP/1.1 200 OK\n\
+                     content-type: text/html\n\
+                     strict-transport-security: max-age=300\n\
+                     access-control-allow-origin: *\n\
+                     access-control-allow-credentials: true\n\
+                     set-cookie: a=1\n\
+                     set-cookie: b=2\n\
+                     cache-control: no-cache\n\
+                     Cache-Control: private\n\
+                     www-authenticate: Basic";
+        assert_eq!(
+            headers_format(block).unwrap(),
+            "HTTP/1.1 200 OK\n\
+             \n\
+             Access-Control-Allow-Credentials: true\n\
+             Access-Control-Allow-Origin: *\n\
+             Cache-Control: no-cache\n\
+             Cache-Control: private\n\
+             Content-Type: text/html\n\
+             Set-Cookie: a=1\n\
+             Set-Cookie: b=2\n\
+             Strict-Transport-Security: max-age=300\n\
+             WWW-Authenticate: Basic\n\
+             \n\
+             Security:\n  \
+               HSTS  max-age=300 (max-age under 6 months)\n  \
+               CSP   missing\n  \
+               CORS  any origin with credentials (browsers reject this)\n\
+             \n\
+             Duplicates:\n  \
+               Cache-Control (2×)"
+        );
+    }
+
+    #[test]
+    fn test_headers_format_curl_verbose_and_pseudo_headers() {
+        let dump = "* Connected to x.test\n\
+                    > GET / HTTP/2\n\
+                    > host: x.test\n\
+                    < HTTP/2 200\n\
+                    < content-security-policy: script-src 'unsafe-inline'\n\
+                    < etag: \"abc\"\n\
+                    <\n\
+                    { [5 bytes data]";
+        let result = headers_format(dump).unwrap();
+        assert!(result.starts_with("HTTP/2 200\n\nContent-Security-Policy: script-src"));
+        assert!(result.contains("ETag: \"abc\""));
+        assert!(!result.contains("Host"));
+        assert!(result.contains("CSP   script-src 'unsafe-inline' ('unsafe-inline')"));
+
+        let devtools = ":method: GET\n:path: /\naccept: */*";
+        assert_eq!(
+            headers_format(devtools).unwrap(),
+            ":method: GET\n:path: /\nAccept: */*"
+        );
+    }
+
+    #[test]
+    fn test_headers_format_errors() {
+        assert!(matches!(
+            headers_format("\n\n"),
+            Err(HeaderError::NoHeaders)
+        ));
+        assert!(matches!(
+            headers_format("Accept: */*\nnot a header"),
+            Err(HeaderError::InvalidLine(2))
+        ));
+    }
+}
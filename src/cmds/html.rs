@@ -0,0 +1,402 @@
+use std::fmt;
+
+use super::json::json_format;
+
+#[derive(Debug)]
+pub enum HtmlError {
+    NoMetadata,
+    NoRobots,
+}
+
+impl fmt::Display for HtmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoMetadata => {
+                write!(f, "No head metadata found - expected an HTML document")
+            }
+            Self::NoRobots => {
+                write!(
+                    f,
+                    "No robots meta tags found - crawlers default to index, follow"
+                )
+            }
+        }
+    }
+}
+
+/// Meta names holding crawler directives.
+const ROBOTS_NAMES: &[&str] = &["robots", "googlebot", "googlebot-news", "bingbot", "slurp"];
+
+/// A start tag with lowercased name and attribute names.
+struct Tag {
+    name: String,
+    attrs: Vec<(String, String)>,
+    /// Raw text up to the closing tag, for `<title>` and `<script>`
+    text: Option<String>,
+}
+
+impl Tag {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Resolves the named and numeric character references that show up in
+/// titles and meta content.
+pub(super) fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let ch = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let code = match entity.strip_prefix('#')? {
+                    hex if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16),
+                    decimal => decimal.parse(),
+                };
+                char::from_u32(code.ok()?)
+            }
+        });
+        match (ch, entity) {
+            (Some(ch), Some(entity)) => {
+                decoded.push(ch);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Collapses runs of whitespace, as browsers do when showing a title.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub(super) fn parse_attrs(source: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = source.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_lowercase();
+        rest = rest[name_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    (&body[..end], &body[(end + 1).min(body.len())..])
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        } else if name.is_empty() {
+            // A stray `/`, as in `<meta ... />`
+            rest = &rest[1.min(rest.len())..];
+        }
+        if !name.is_empty() {
+            attrs.push((name, value));
+        }
+        rest = rest.trim_start();
+    }
+    attrs
+}
+
+/// Finds the end of a tag's `>`, skipping any inside quoted attribute values.
+pub(super) fn tag_end(source: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in source.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Scans start tags in document order. Comments are skipped, and the text
+/// of `<title>` and `<script>` is captured rather than scanned.
+fn scan_tags(html: &str) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &html[start + 1..];
+        if rest.starts_with("!--") {
+            pos = rest
+                .find("-->")
+                .map_or(html.len(), |end| start + 1 + end + 3);
+            continue;
+        }
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            pos = start + 1;
+            continue;
+        }
+        let Some(end) = tag_end(rest) else {
+            break;
+        };
+        let source = &rest[..end];
+        let name_end = source
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(source.len());
+        let name = source[..name_end].to_lowercase();
+        let attrs = parse_attrs(&source[name_end..]);
+        pos = start + 1 + end + 1;
+
+        let text = if name == "title" || name == "script" {
+            let closing = format!("</{}", name);
+            let body = &html[pos..];
+            let body_end = body
+                .to_ascii_lowercase()
+                .find(&closing)
+                .unwrap_or(body.len());
+            pos += body_end;
+            Some(body[..body_end].to_string())
+        } else {
+            None
+        };
+        tags.push(Tag { name, attrs, text });
+    }
+    tags
+}
+
+fn json_ld_text(tag: &Tag) -> Option<&str> {
+    let is_json_ld = tag.name == "script"
+        && tag
+            .attr("type")
+            .is_some_and(|kind| kind.eq_ignore_ascii_case("application/ld+json"));
+    tag.text.as_deref().filter(|_| is_json_ld).map(str::trim)
+}
+
+/// The contents of every `<script type="application/ld+json">` block.
+pub(super) fn json_ld_blocks(html: &str) -> Vec<String> {
+    scan_tags(html)
+        .iter()
+        .filter_map(json_ld_text)
+        .map(str::to_string)
+        .collect()
+}
+
+fn push_field(report: &mut Vec<String>, label: &str, value: Option<String>) {
+    if let Some(value) = value.filter(|value| !value.is_empty()) {
+        report.push(format!("{:<13}{}", format!("{}:", label), value));
+    }
+}
+
+fn push_group(report: &mut Vec<String>, heading: &str, properties: &[(String, String)]) {
+    if properties.is_empty() {
+        return;
+    }
+    let width = properties
+        .iter()
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or(0);
+    report.push(String::new());
+    report.push(heading.to_string());
+    for (key, value) in properties {
+        report.push(format!("  {:<width$}  {}", key, value));
+    }
+}
+
+/// Pulls the metadata that search engines and link previews read out of an
+/// HTML document: title, description, canonical URL, robots directives,
+/// OpenGraph and Twitter card properties, and formatted JSON-LD blocks.
+pub fn html_head(buffer: &str) -> Result<String, HtmlError> {
+    let tags = scan_tags(buffer);
+    let mut title = None;
+    let mut description = None;
+    let mut canonical = None;
+    let mut robots = None;
+    let mut open_graph = Vec::new();
+    let mut twitter = Vec::new();
+    let mut json_ld = Vec::new();
+
+    for tag in &tags {
+        match tag.name.as_str() {
+            "title" if title.is_none() => {
+                title = tag
+                    .text
+                    .as_deref()
+                    .map(decode_entities)
+                    .map(|t| collapse_whitespace(&t));
+            }
+            "link" => {
+                let is_canonical = tag.attr("rel").is_some_and(|rel| {
+                    rel.split_whitespace()
+                        .any(|rel| rel.eq_ignore_ascii_case("canonical"))
+                });
+                if is_canonical && canonical.is_none() {
+                    canonical = tag.attr("href").map(str::to_string);
+                }
+            }
+            "meta" => {
+                let content = tag.attr("content").unwrap_or("").to_string();
+                // OpenGraph uses `property`, but `name` is common in the wild
+                let key = tag
+                    .attr("property")
+                    .or_else(|| tag.attr("name"))
+                    .unwrap_or("")
+                    .to_lowercase();
+                match key.as_str() {
+                    "description" if description.is_none() => description = Some(content),
+                    "robots" if robots.is_none() => robots = Some(content),
+                    key if key.starts_with("og:") => open_graph.push((key.to_string(), content)),
+                    key if key.starts_with("twitter:") => twitter.push((key.to_string(), content)),
+                    _ => {}
+                }
+            }
+            "script" => {
+                if let Some(text) = json_ld_text(tag) {
+                    json_ld.push(
+                        json_format(text).unwrap_or_else(|e| format!("{} (invalid: {})", text, e)),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut report = Vec::new();
+    push_field(&mut report, "Title", title);
+    push_field(&mut report, "Description", description);
+    push_field(&mut report, "Canonical", canonical);
+    push_field(&mut report, "Robots", robots);
+    push_group(&mut report, "OpenGraph", &open_graph);
+    push_group(&mut report, "Twitter", &twitter);
+    for block in json_ld {
+        report.push(String::new());
+        report.push("JSON-LD".to_string());
+        report.push(block);
+    }
+
+    if report.is_empty() {
+        return Err(HtmlError::NoMetadata);
+    }
+    // Drop the blank line before the first section when there are no fields
+    if report[0].is_empty() {
+        report.remove(0);
+    }
+    Ok(report.join("\n"))
+}
+
+/// Lists crawler directives from `robots`-style meta tags, one bot per
+/// line, with whether the page may be indexed and its links followed.
+pub fn robots_meta(buffer: &str) -> Result<String, HtmlError> {
+    let mut lines = Vec::new();
+    for tag in scan_tags(buffer).iter().filter(|tag| tag.name == "meta") {
+        let Some(name) = tag.attr("name").map(str::to_lowercase) else {
+            continue;
+        };
+        if !ROBOTS_NAMES.contains(&name.as_str()) {
+            continue;
+        }
+        let directives: Vec<String> = tag
+            .attr("content")
+            .unwrap_or("")
+            .split(',')
+            .map(|directive| directive.trim().to_lowercase())
+            .filter(|directive| !directive.is_empty())
+            .collect();
+        let has = |directive: &str| directives.iter().any(|d| d == directive || d == "none");
+        lines.push(format!(
+            "{:<15}{}\n{:<15}index: {}, follow: {}",
+            format!("{}:", name),
+            directives.join(", "),
+            "",
+            if has("noindex") { "no" } else { "yes" },
+            if has("nofollow") { "no" } else { "yes" }
+        ));
+    }
+    if lines.is_empty() {
+        return Err(HtmlError::NoRobots);
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!doctype html>
+<html><head>
+  <!-- <title>Not this</title> -->
+  <title>
+    Caf&#233; &amp; Bar
+  </title>
+  <meta name="description" content="Best &quot;coffee&quot; in town">
+  <link rel="canonical" href="https://example.com/cafe">
+  <meta name=robots content="noindex, follow">
+  <meta property="og:title" content='Café > Bar' />
+  <meta property="og:image" content="https://example.com/a.png">
+  <meta name="twitter:card" content="summary">
+  <script type="application/ld+json">{"@type":"Cafe","name":"Café"}</script>
+  <script>var x = "<meta name='description' content='no'>";</script>
+</head></html>"#;
+
+    #[test]
+    fn test_html_head() {
+        assert_eq!(
+            html_head(PAGE).unwrap(),
+            "Title:       Café & Bar\n\
+             Description: Best \"coffee\" in town\n\
+             Canonical:   https://example.com/cafe\n\
+             Robots:      noindex, follow\n\
+             \n\
+             OpenGraph\n  \
+             og:title  Café > Bar\n  \
+             og:image  https://example.com/a.png\n\
+             \n\
+             Twitter\n  \
+             twitter:card  summary\n\
+             \n\
+             JSON-LD\n\
+             {\n  \"@type\": \"Cafe\",\n  \"name\": \"Café\"\n}"
+        );
+        assert!(html_head("just text").is_err());
+    }
+
+    #[test]
+    fn test_robots_meta() {
+        let html = r#"<meta name="robots" content="noindex, nofollow"><meta name="googlebot" content="none">"#;
+        assert_eq!(
+            robots_meta(html).unwrap(),
+            "robots:        noindex, nofollow\n               index: no, follow: no\n\
+             googlebot:     none\n               index: no, follow: no"
+        );
+        assert!(
+            robots_meta(PAGE)
+                .unwrap()
+                .contains("index: no, follow: yes")
+        );
+        assert!(robots_meta("<p>hi</p>").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+use uuid::{Uuid, Variant};
+
+use super::time::DateTime;
+
+/// 100ns intervals between the Gregorian epoch (1582-10-15) used by v1 and
+/// v6 UUIDs and the Unix epoch.
+const GREGORIAN_OFFSET: u64 = 0x01B2_1DD2_1381_4000;
+
+#[derive(Debug)]
+pub enum IdError {
+    InvalidUuid(String),
+}
+
+impl fmt::Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidUuid(msg) => {
+                write!(f, "Invalid UUID - {}", msg)
+            }
+        }
+    }
+}
+
+fn version_name(version: usize) -> &'static str {
+    match version {
+        1 => "time-based",
+        2 => "DCE security",
+        3 => "MD5 name-based",
+        4 => "random",
+        5 => "SHA-1 name-based",
+        6 => "reordered time-based",
+        7 => "Unix time-based",
+        8 => "custom",
+        _ => "unknown",
+    }
+}
+
+fn variant_name(variant: Variant) -> &'static str {
+    match variant {
+        Variant::NCS => "NCS (reserved)",
+        Variant::RFC4122 => "RFC 9562",
+        Variant::Microsoft => "Microsoft (reserved)",
+        _ => "future (reserved)",
+    }
+}
+
+/// The embedded creation time of v1, v6 and v7 UUIDs.
+fn embedded_time(uuid: &Uuid) -> Option<String> {
+    let value = uuid.as_u128();
+    let gregorian_ticks = |ticks: u64| {
+        let secs = (ticks.saturating_sub(GREGORIAN_OFFSET) / 10_000_000) as i64;
+        DateTime::from_unix(secs).to_string()
+    };
+    match uuid.get_version_num() {
+        1 => {
+            let time_low = (value >> 96) as u64 & 0xFFFF_FFFF;
+            let time_mid = (value >> 80) as u64 & 0xFFFF;
+            let time_high = (value >> 64) as u64 & 0x0FFF;
+            Some(gregorian_ticks(time_high << 48 | time_mid << 32 | time_low))
+        }
+        6 => {
+            let time_high = (value >> 80) as u64 & 0xFFFF_FFFF_FFFF;
+            let time_low = (value >> 64) as u64 & 0x0FFF;
+            Some(gregorian_ticks(time_high << 12 | time_low))
+        }
+        7 => {
+            let millis = (value >> 80) as i64;
+            Some(format!(
+                "{} ({} ms)",
+                DateTime::from_unix(millis.div_euclid(1000)),
+                millis
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Describes a UUID's version, variant and, for time-based versions, when it
+/// was created.
+pub fn uuid_inspect(buffer: &str) -> Result<String, IdError> {
+    let uuid = Uuid::parse_str(buffer.trim()).map_err(|e| IdError::InvalidUuid(e.to_string()))?;
+
+    let mut lines = vec![format!("UUID:     {}", uuid.hyphenated())];
+    if uuid.is_nil() {
+        lines.push("Version:  nil".to_string());
+    } else if uuid.is_max() {
+        lines.push("Version:  max".to_string());
+    } else {
+        let version = uuid.get_version_num();
+        lines.push(format!("Version:  {} ({})", version, version_name(version)));
+        lines.push(format!("Variant:  {}", variant_name(uuid.get_variant())));
+        if let Some(time) = embedded_time(&uuid) {
+            lines.push(format!("Created:  {}", time));
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uuid_inspect_v4() {
+        let result = uuid_inspect("550E8400-E29B-41D4-A716-446655440000\n").unwrap();
+        assert_eq!(
+            result,
+            "UUID:     550e8400-e29b-41d4-a716-446655440000\n\
+             Version:  4 (random)\n\
+             Variant:  RFC 9562"
+        );
+    }
+
+    #[test]
+    fn test_uuid_inspect_time_based() {
+        // Examples from RFC 9562 appendix A, all at 2022-02-22 19:22:22 UTC
+        let v1 = uuid_inspect("C232AB00-9414-11EC-B3C8-9F6BDECED846").unwrap();
+        assert!(v1.ends_with("Created:  2022-02-22T19:22:22Z"));
+        let v6 = uuid_inspect("1EC9414C-232A-6B00-B3C8-9F6BDECED846").unwrap();
+        assert!(v6.ends_with("Created:  2022-02-22T19:22:22Z"));
+        let v7 = uuid_inspect("017F22E2-79B0-7CC3-98C4-DC0C0C07398F").unwrap();
+        assert!(v7.ends_with("Created:  2022-02-22T19:22:22Z (1645557742000 ms)"));
+    }
+
+    #[test]
+    fn test_uuid_inspect_invalid() {
+        assert!(uuid_inspect("not-a-uuid").is_err());
+        assert!(
+            uuid_inspect("00000000-0000-0000-0000-000000000000")
+                .unwrap()
+                .ends_with("nil")
+        );
+    }
+}
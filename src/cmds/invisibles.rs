@@ -0,0 +1,136 @@
+/// Zero-width and formatting characters, shown by name. ZWJ and ZWNJ are
+/// legitimate in emoji sequences and Persian or Indic text, so
+/// `/strip-invisibles` keeps them.
+const NAMED: &[(char, &str)] = &[
+    ('\u{00AD}', "SHY"),
+    ('\u{034F}', "CGJ"),
+    ('\u{061C}', "ALM"),
+    ('\u{180E}', "MVS"),
+    ('\u{200B}', "ZWSP"),
+    ('\u{200C}', "ZWNJ"),
+    ('\u{200D}', "ZWJ"),
+    ('\u{200E}', "LRM"),
+    ('\u{200F}', "RLM"),
+    ('\u{202A}', "LRE"),
+    ('\u{202B}', "RLE"),
+    ('\u{202C}', "PDF"),
+    ('\u{202D}', "LRO"),
+    ('\u{202E}', "RLO"),
+    ('\u{2060}', "WJ"),
+    ('\u{2066}', "LRI"),
+    ('\u{2067}', "RLI"),
+    ('\u{2068}', "FSI"),
+    ('\u{2069}', "PDI"),
+    ('\u{FEFF}', "BOM"),
+];
+
+const KEPT: &[char] = &['\u{200C}', '\u{200D}'];
+
+fn is_non_breaking_space(c: char) -> bool {
+    matches!(c, '\u{00A0}' | '\u{2007}' | '\u{202F}')
+}
+
+/// Spaces other than the ASCII one, like em spaces and the ideographic space.
+fn is_unusual_space(c: char) -> bool {
+    c.is_whitespace() && !matches!(c, ' ' | '\t' | '\n' | '\r') && !is_non_breaking_space(c)
+}
+
+/// How a character looks once revealed, or `None` for ordinary text.
+fn marker(c: char) -> Option<String> {
+    let marker = match c {
+        ' ' => "·".to_string(),
+        '\t' => "→".to_string(),
+        '\r' => "␍".to_string(),
+        c if is_non_breaking_space(c) => "⍽".to_string(),
+        c => match NAMED.iter().find(|(named, _)| *named == c) {
+            Some((_, name)) => format!("⟨{}⟩", name),
+            None if is_unusual_space(c) || (c.is_control() && c != '\n') => {
+                format!("⟨U+{:04X}⟩", c as u32)
+            }
+            None => return None,
+        },
+    };
+    Some(marker)
+}
+
+/// Replaces invisible characters with visible markers and returns the
+/// byte ranges of those markers, for highlighting. Line breaks stay as
+/// they are, with a `␍` before any CRLF.
+pub fn reveal_invisibles(buffer: &str) -> (String, Vec<(usize, usize)>) {
+    let mut shown = String::with_capacity(buffer.len());
+    let mut ranges = Vec::new();
+    for c in buffer.chars() {
+        match marker(c) {
+            Some(marker) => {
+                ranges.push((shown.len(), shown.len() + marker.len()));
+                shown.push_str(&marker);
+            }
+            None => shown.push(c),
+        }
+    }
+    (shown, ranges)
+}
+
+pub fn show_invisibles(buffer: &str) -> String {
+    reveal_invisibles(buffer).0
+}
+
+/// How many characters in the buffer are invisible and not plain spaces,
+/// tabs or line endings - the ones worth pointing out.
+pub fn count_invisibles(buffer: &str) -> usize {
+    buffer
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\t' | '\r') && marker(*c).is_some())
+        .count()
+}
+
+/// Removes zero-width, bidirectional and other control characters that
+/// make equal-looking strings differ, and turns non-breaking and unusual
+/// spaces into plain ones.
+pub fn strip_invisibles(buffer: &str) -> String {
+    buffer
+        .chars()
+        .filter_map(|c| {
+            if is_non_breaking_space(c) || is_unusual_space(c) {
+                Some(' ')
+            } else if KEPT.contains(&c) || matches!(c, '\t' | '\n' | '\r') {
+                Some(c)
+            } else if c.is_control() || NAMED.iter().any(|(named, _)| *named == c) {
+                None
+            } else {
+                Some(c)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_show_invisibles() {
+        let (shown, ranges) = reveal_invisibles("\u{FEFF}a b\tc\u{00A0}d\u{200B}e\r\n");
+        assert_eq!(shown, "⟨BOM⟩a·b→c⍽d⟨ZWSP⟩e␍\n");
+        assert_eq!(ranges.len(), 6);
+        let (start, end) = ranges[0];
+        assert_eq!(&shown[start..end], "⟨BOM⟩");
+        let (start, end) = ranges[5];
+        assert_eq!(&shown[start..end], "␍");
+
+        assert_eq!(show_invisibles("x\u{2003}y\u{0007}"), "x⟨U+2003⟩y⟨U+0007⟩");
+        assert_eq!(show_invisibles("plain\ntext"), "plain\ntext");
+        assert_eq!(count_invisibles("a b\u{200B}\u{202E}\r\n"), 2);
+    }
+
+    #[test]
+    fn test_strip_invisibles() {
+        assert_eq!(
+            strip_invisibles("\u{FEFF}pass\u{200B}word\u{202E}\u{00A0}ok\u{2003}\t\n"),
+            "password ok \t\n"
+        );
+        // The family emoji is held together by ZWJs
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(strip_invisibles(family), family);
+    }
+}
@@ -1,11 +1,16 @@
+use std::collections::HashMap;
 use std::fmt;
 
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 #[derive(Debug)]
 pub enum JsonError {
     ParseError(String),
     FormatError(()),
+    MissingKeyMapping,
+    InvalidKeyMapping,
+    InvalidLine(usize, String),
+    NotAnArray,
 }
 
 impl fmt::Display for JsonError {
@@ -17,6 +22,21 @@ impl fmt::Display for JsonError {
             Self::FormatError(..) => {
                 write!(f, "Failed to format JSON")
             }
+            Self::MissingKeyMapping => {
+                write!(
+                    f,
+                    "Missing key mapping - put it after the payload or pass it as an argument"
+                )
+            }
+            Self::InvalidKeyMapping => {
+                write!(f, "Key mapping must be an object of short key to key")
+            }
+            Self::InvalidLine(line, msg) => {
+                write!(f, "Invalid JSON on line {} - {}", line, msg)
+            }
+            Self::NotAnArray => {
+                write!(f, "Expected a JSON array")
+            }
         }
     }
 }
@@ -27,12 +47,299 @@ impl From<serde_json::Error> for JsonError {
     }
 }
 
+/// Blanks out `//` and `/* */` comments and trailing commas, as found in
+/// tsconfig.json and VS Code settings. Replacing rather than removing keeps
+/// the line and column of any parse error pointing at the original text.
+pub(super) fn strip_jsonc(buffer: &str) -> String {
+    let mut bytes = buffer.as_bytes().to_vec();
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'\\', _) if in_string => i += 1,
+            (b'"', _) => in_string = !in_string,
+            (b'/', Some(b'/')) if !in_string => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    bytes[i] = b' ';
+                    i += 1;
+                }
+                continue;
+            }
+            (b'/', Some(b'*')) if !in_string => {
+                let end = buffer[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                for byte in &mut bytes[i..end] {
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    in_string = false;
+    let mut last_comma = None;
+    i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => {
+                in_string = !in_string;
+                last_comma = None;
+            }
+            b',' if !in_string => last_comma = Some(i),
+            b'}' | b']' if !in_string => {
+                if let Some(comma) = last_comma.take() {
+                    bytes[comma] = b' ';
+                }
+            }
+            byte if !byte.is_ascii_whitespace() => last_comma = None,
+            _ => {}
+        }
+        i += 1;
+    }
+    // Only ASCII bytes were overwritten, so the text is still UTF-8
+    String::from_utf8(bytes).unwrap_or_else(|_| buffer.to_string())
+}
+
+/// Pretty-prints JSON, accepting comments and trailing commas (which are
+/// dropped from the output).
 pub fn json_format(buffer: &str) -> Result<String, JsonError> {
-    let json_value: Value = serde_json::from_str(buffer)?;
+    let json_value: Value = serde_json::from_str(&strip_jsonc(buffer))?;
     serde_json::to_string_pretty(&json_value).map_err(|_| JsonError::FormatError(()))
 }
 
 pub fn json_minify(buffer: &str) -> Result<String, JsonError> {
-    let json_value: Value = serde_json::from_str(buffer)?;
+    let json_value: Value = serde_json::from_str(&strip_jsonc(buffer))?;
     serde_json::to_string(&json_value).map_err(|_| JsonError::FormatError(()))
 }
+
+/// Parses one JSON value per non-blank line.
+fn parse_lines(buffer: &str) -> Result<Vec<Value>, JsonError> {
+    buffer
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|err| JsonError::InvalidLine(index + 1, err.to_string()))
+        })
+        .collect()
+}
+
+/// Pretty-prints each JSON Lines record, with a `---` divider between
+/// records.
+pub fn ndjson_format(buffer: &str) -> Result<String, JsonError> {
+    let records = parse_lines(buffer)?
+        .iter()
+        .map(serde_json::to_string_pretty)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| JsonError::FormatError(()))?;
+    Ok(records.join("\n---\n"))
+}
+
+pub fn ndjson_to_array(buffer: &str) -> Result<String, JsonError> {
+    let records = Value::Array(parse_lines(buffer)?);
+    serde_json::to_string_pretty(&records).map_err(|_| JsonError::FormatError(()))
+}
+
+pub fn array_to_ndjson(buffer: &str) -> Result<String, JsonError> {
+    let Value::Array(records) = serde_json::from_str(&strip_jsonc(buffer))? else {
+        return Err(JsonError::NotAnArray);
+    };
+    let lines = records
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| JsonError::FormatError(()))?;
+    Ok(lines.join("\n"))
+}
+
+/// Short keys in order: `a`..`z`, then `aa`, `ab`, ...
+fn short_key(mut index: usize) -> String {
+    let mut key = Vec::new();
+    loop {
+        key.push(b'a' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    key.reverse();
+    String::from_utf8(key).unwrap_or_default()
+}
+
+fn count_keys(value: &Value, counts: &mut Vec<(String, usize)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                match counts.iter_mut().find(|(existing, _)| existing == key) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((key.clone(), 1)),
+                }
+                count_keys(child, counts);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| count_keys(item, counts)),
+        _ => {}
+    }
+}
+
+fn rename_keys(value: Value, names: &HashMap<String, String>) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, child)| {
+                    let key = names.get(&key).cloned().unwrap_or(key);
+                    (key, rename_keys(child, names))
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| rename_keys(item, names))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Replaces every object key with a short one, the most frequent keys
+/// getting the shortest, and emits the minified payload followed by the
+/// short-key mapping that `/expand-json-keys` reverses.
+pub fn minify_json_keys(buffer: &str) -> Result<String, JsonError> {
+    let value: Value = serde_json::from_str(buffer)?;
+    let mut counts = Vec::new();
+    count_keys(&value, &mut counts);
+    // Stable, so ties keep the order keys first appear in
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let mut names = HashMap::new();
+    let mut mapping = Map::new();
+    for (index, (key, _)) in counts.into_iter().enumerate() {
+        let short = short_key(index);
+        mapping.insert(short.clone(), Value::String(key.clone()));
+        names.insert(key, short);
+    }
+
+    let payload = rename_keys(value, &names);
+    let payload = serde_json::to_string(&payload).map_err(|_| JsonError::FormatError(()))?;
+    let mapping = serde_json::to_string(&mapping).map_err(|_| JsonError::FormatError(()))?;
+    Ok(format!("{}\n\n{}", payload, mapping))
+}
+
+/// Restores the original keys of a `/minify-json-keys` payload. The mapping
+/// is read from a second JSON value in the buffer, as `/minify-json-keys`
+/// emits it, or else from the argument.
+pub fn expand_json_keys(buffer: &str, mapping: Option<&str>) -> Result<String, JsonError> {
+    let mut values = serde_json::Deserializer::from_str(buffer).into_iter::<Value>();
+    let payload = values.next().ok_or(JsonError::MissingKeyMapping)??;
+    let mapping = match values.next() {
+        Some(mapping) => mapping?,
+        None => serde_json::from_str(mapping.ok_or(JsonError::MissingKeyMapping)?)?,
+    };
+
+    let Value::Object(mapping) = mapping else {
+        return Err(JsonError::InvalidKeyMapping);
+    };
+    let names = mapping
+        .into_iter()
+        .map(|(short, key)| match key {
+            Value::String(key) => Ok((short, key)),
+            _ => Err(JsonError::InvalidKeyMapping),
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    serde_json::to_string_pretty(&rename_keys(payload, &names))
+        .map_err(|_| JsonError::FormatError(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_format_accepts_jsonc() {
+        let tsconfig = r#"{
+  // Emit for older runtimes
+  "compilerOptions": {
+    "target": "es2017", /* was es5 */
+    "paths": {"@/*": ["src/*",],},
+  },
+  "include": ["src", "https://example.com/a//b"],
+}"#;
+        assert_eq!(
+            json_minify(tsconfig).unwrap(),
+            r#"{"compilerOptions":{"paths":{"@/*":["src/*"]},"target":"es2017"},"include":["src","https://example.com/a//b"]}"#
+        );
+        assert_eq!(
+            strip_jsonc("[\"a,\\\",]\", /* x\ny */ 1,]"),
+            "[\"a,\\\",]\",     \n     1 ]"
+        );
+        let err = json_format("{\n  // note\n  \"a\": }").unwrap_err();
+        assert!(err.to_string().contains("line 3"));
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let log = "{\"level\":\"info\",\"msg\":\"up\"}\n\n{\"level\":\"warn\"}\n";
+        assert_eq!(
+            ndjson_format(log).unwrap(),
+            "{\n  \"level\": \"info\",\n  \"msg\": \"up\"\n}\n---\n{\n  \"level\": \"warn\"\n}"
+        );
+        let array = ndjson_to_array(log).unwrap();
+        assert!(array.starts_with("[\n  {\n    \"level\": \"info\""));
+        assert_eq!(
+            array_to_ndjson(&array).unwrap(),
+            "{\"level\":\"info\",\"msg\":\"up\"}\n{\"level\":\"warn\"}"
+        );
+        assert!(matches!(
+            ndjson_format("{}\n{oops}"),
+            Err(JsonError::InvalidLine(2, _))
+        ));
+        assert!(matches!(array_to_ndjson("{}"), Err(JsonError::NotAnArray)));
+    }
+
+    #[test]
+    fn test_short_keys() {
+        assert_eq!(short_key(0), "a");
+        assert_eq!(short_key(25), "z");
+        assert_eq!(short_key(26), "aa");
+        assert_eq!(short_key(27 * 26), "aaa");
+    }
+
+    #[test]
+    fn test_minify_json_keys_by_frequency() {
+        let payload = r#"{"readings": [{"sensor_id": 1, "temperature": 21.5}, {"sensor_id": 2, "temperature": 19}]}"#;
+        let result = minify_json_keys(payload).unwrap();
+        assert_eq!(
+            result,
+            "{\"c\":[{\"a\":1,\"b\":21.5},{\"a\":2,\"b\":19}]}\n\n\
+             {\"a\":\"sensor_id\",\"b\":\"temperature\",\"c\":\"readings\"}"
+        );
+
+        let expanded: Value =
+            serde_json::from_str(&expand_json_keys(&result, None).unwrap()).unwrap();
+        assert_eq!(expanded, serde_json::from_str::<Value>(payload).unwrap());
+    }
+
+    #[test]
+    fn test_expand_json_keys_mapping_argument() {
+        let result = expand_json_keys(r#"{"a": {"b": true}}"#, Some(r#"{"a":"user"}"#)).unwrap();
+        assert_eq!(result, "{\n  \"user\": {\n    \"b\": true\n  }\n}");
+        assert!(matches!(
+            expand_json_keys(r#"{"a": 1}"#, None),
+            Err(JsonError::MissingKeyMapping)
+        ));
+        assert!(matches!(
+            expand_json_keys(r#"{"a": 1}"#, Some("[1]")),
+            Err(JsonError::InvalidKeyMapping)
+        ));
+    }
+}
@@ -0,0 +1,516 @@
+use std::fmt;
+
+use serde_json::{Value, json};
+
+use super::json::strip_jsonc;
+
+#[derive(Debug)]
+pub enum PatchError {
+    ParseError(String),
+    InvalidPrevious(String),
+    NothingToCompare,
+    UnknownOption(String),
+    MissingPatch,
+    InvalidPatch(String),
+    OperationFailed(usize, String),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ParseError(msg) => {
+                write!(f, "Invalid JSON - {}", msg)
+            }
+            Self::InvalidPrevious(msg) => {
+                write!(f, "Previous buffer is not valid JSON - {}", msg)
+            }
+            Self::NothingToCompare => {
+                write!(
+                    f,
+                    "Nothing to compare - put two JSON documents in the buffer or change it first"
+                )
+            }
+            Self::UnknownOption(option) => {
+                write!(f, "Unknown option '{}' - expected --patch", option)
+            }
+            Self::MissingPatch => {
+                write!(
+                    f,
+                    "Missing patch - put it after the document or pass it as an argument"
+                )
+            }
+            Self::InvalidPatch(msg) => {
+                write!(f, "Invalid patch - {}", msg)
+            }
+            Self::OperationFailed(index, msg) => {
+                write!(f, "Patch operation {} failed - {}", index, msg)
+            }
+        }
+    }
+}
+
+impl From<serde_json::Error> for PatchError {
+    fn from(err: serde_json::Error) -> PatchError {
+        PatchError::ParseError(err.to_string())
+    }
+}
+
+/// One RFC 6902 operation. Only the three `/json-diff` emits.
+enum Op {
+    Add(String, Value),
+    Remove(String, Value),
+    Replace(String, Value, Value),
+}
+
+/// Escapes a key for use in a JSON Pointer (RFC 6901).
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, ops: &mut Vec<Op>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, value) in old {
+                let path = format!("{}/{}", path, pointer_token(key));
+                match new.get(key) {
+                    Some(other) => diff_values(&path, value, other, ops),
+                    None => ops.push(Op::Remove(path, value.clone())),
+                }
+            }
+            for (key, value) in new {
+                if !old.contains_key(key) {
+                    let path = format!("{}/{}", path, pointer_token(key));
+                    ops.push(Op::Add(path, value.clone()));
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            let common = old.len().min(new.len());
+            for i in 0..common {
+                diff_values(&format!("{}/{}", path, i), &old[i], &new[i], ops);
+            }
+            // Highest index first, so each removal leaves earlier paths valid
+            for i in (common..old.len()).rev() {
+                ops.push(Op::Remove(format!("{}/{}", path, i), old[i].clone()));
+            }
+            for (i, value) in new.iter().enumerate().skip(common) {
+                ops.push(Op::Add(format!("{}/{}", path, i), value.clone()));
+            }
+        }
+        _ if old != new => ops.push(Op::Replace(path.to_string(), old.clone(), new.clone())),
+        _ => {}
+    }
+}
+
+fn format_patch(ops: &[Op]) -> String {
+    if ops.is_empty() {
+        return "[]".to_string();
+    }
+    let lines: Vec<String> = ops
+        .iter()
+        .map(|op| {
+            let op = match op {
+                Op::Add(path, value) => json!({"op": "add", "path": path, "value": value}),
+                Op::Remove(path, _) => json!({"op": "remove", "path": path}),
+                Op::Replace(path, _, value) => {
+                    json!({"op": "replace", "path": path, "value": value})
+                }
+            };
+            format!("  {}", op)
+        })
+        .collect();
+    format!("[\n{}\n]", lines.join(",\n"))
+}
+
+fn format_report(ops: &[Op]) -> String {
+    if ops.is_empty() {
+        return "No differences".to_string();
+    }
+    let root = |path: &str| match path {
+        "" => "(root)".to_string(),
+        path => path.to_string(),
+    };
+    let count = |matches: fn(&Op) -> bool| ops.iter().filter(|op| matches(op)).count();
+    let mut lines: Vec<String> = ops
+        .iter()
+        .map(|op| match op {
+            Op::Add(path, value) => format!("+ {}: {}", root(path), value),
+            Op::Remove(path, value) => format!("- {}: {}", root(path), value),
+            Op::Replace(path, old, new) => format!("~ {}: {} → {}", root(path), old, new),
+        })
+        .collect();
+    lines.push(String::new());
+    lines.push(format!(
+        "{} added, {} removed, {} changed",
+        count(|op| matches!(op, Op::Add(..))),
+        count(|op| matches!(op, Op::Remove(..))),
+        count(|op| matches!(op, Op::Replace(..)))
+    ));
+    lines.join("\n")
+}
+
+/// Structurally compares two JSON documents: the two in the buffer, one
+/// after the other, or else `previous` against the buffer. Prints an
+/// added/removed/changed report, or an RFC 6902 patch with `--patch`.
+/// Array elements are compared by position.
+pub fn json_diff(
+    buffer: &str,
+    previous: Option<&str>,
+    option: Option<&str>,
+) -> Result<String, PatchError> {
+    let patch = match option {
+        None | Some("--report") => false,
+        Some("--patch") => true,
+        Some(other) => return Err(PatchError::UnknownOption(other.to_string())),
+    };
+
+    let stripped = strip_jsonc(buffer);
+    let mut documents = serde_json::Deserializer::from_str(&stripped).into_iter::<Value>();
+    let first = documents.next().ok_or(PatchError::NothingToCompare)??;
+    let (old, new) = match documents.next() {
+        Some(second) => (first, second?),
+        None => {
+            let previous = previous.ok_or(PatchError::NothingToCompare)?;
+            let old = serde_json::from_str(&strip_jsonc(previous))
+                .map_err(|e| PatchError::InvalidPrevious(e.to_string()))?;
+            (old, first)
+        }
+    };
+
+    let mut ops = Vec::new();
+    diff_values("", &old, &new, &mut ops);
+    Ok(match patch {
+        true => format_patch(&ops),
+        false => format_report(&ops),
+    })
+}
+
+/// Splits a JSON Pointer into unescaped reference tokens.
+fn parse_pointer(path: &str) -> Result<Vec<String>, String> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = path.strip_prefix('/') else {
+        return Err(format!(
+            "'{}' is not a JSON Pointer - it must start with '/'",
+            path
+        ));
+    };
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// An array index token; `-` means one past the end, which only `add` takes.
+fn array_index(token: &str, len: usize, allow_end: bool) -> Result<usize, String> {
+    let index = match token {
+        "-" if allow_end => len,
+        token if token == "0" || !token.starts_with('0') => token
+            .parse()
+            .map_err(|_| format!("'{}' is not an array index", token))?,
+        token => return Err(format!("'{}' is not an array index", token)),
+    };
+    let max = if allow_end {
+        len
+    } else {
+        len.saturating_sub(1)
+    };
+    if index > max || (!allow_end && len == 0) {
+        return Err(format!("index {} is out of bounds", index));
+    }
+    Ok(index)
+}
+
+fn resolve<'a>(doc: &'a mut Value, tokens: &[String], path: &str) -> Result<&'a mut Value, String> {
+    let mut current = doc;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(items) => {
+                let index = array_index(token, items.len(), false)?;
+                items.get_mut(index)
+            }
+            _ => None,
+        }
+        .ok_or_else(|| format!("path '{}' does not exist", path))?;
+    }
+    Ok(current)
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let tokens = parse_pointer(path)?;
+    let Some((last, parent)) = tokens.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match resolve(doc, parent, path)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Value::Array(items) => {
+            let index = array_index(last, items.len(), true)?;
+            items.insert(index, value);
+        }
+        _ => return Err(format!("the parent of '{}' is not a container", path)),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value, String> {
+    let tokens = parse_pointer(path)?;
+    let Some((last, parent)) = tokens.split_last() else {
+        return Err("cannot remove the whole document".to_string());
+    };
+    let missing = || format!("path '{}' does not exist", path);
+    match resolve(doc, parent, path)? {
+        Value::Object(map) => map.remove(last).ok_or_else(missing),
+        Value::Array(items) => {
+            let index = array_index(last, items.len(), false)?;
+            Ok(items.remove(index))
+        }
+        _ => Err(missing()),
+    }
+}
+
+fn member<'a>(op: &'a Value, name: &str) -> Result<&'a Value, String> {
+    op.get(name).ok_or_else(|| format!("missing \"{}\"", name))
+}
+
+fn member_str<'a>(op: &'a Value, name: &str) -> Result<&'a str, String> {
+    member(op, name)?
+        .as_str()
+        .ok_or_else(|| format!("\"{}\" must be a string", name))
+}
+
+fn apply_operation(doc: &mut Value, op: &Value) -> Result<(), String> {
+    let path = member_str(op, "path")?;
+    match member_str(op, "op")? {
+        "add" => add(doc, path, member(op, "value")?.clone()),
+        "remove" => remove(doc, path).map(|_| ()),
+        "replace" => {
+            let tokens = parse_pointer(path)?;
+            *resolve(doc, &tokens, path)? = member(op, "value")?.clone();
+            Ok(())
+        }
+        "move" => {
+            let from = member_str(op, "from")?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(format!("cannot move '{}' into its own child", from));
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        "copy" => {
+            let from = member_str(op, "from")?;
+            let tokens = parse_pointer(from)?;
+            let value = resolve(doc, &tokens, from)?.clone();
+            add(doc, path, value)
+        }
+        "test" => {
+            let tokens = parse_pointer(path)?;
+            let actual = resolve(doc, &tokens, path)?;
+            let expected = member(op, "value")?;
+            match actual == expected {
+                true => Ok(()),
+                false => Err(format!(
+                    "test at '{}' expected {} but found {}",
+                    path, expected, actual
+                )),
+            }
+        }
+        other => Err(format!("unknown op '{}'", other)),
+    }
+}
+
+/// Reads the document and the patch that follows it in the buffer, or the
+/// patch from the argument when the buffer only holds the document.
+fn document_and_patch(buffer: &str, patch: Option<&str>) -> Result<(Value, Value), PatchError> {
+    let stripped = strip_jsonc(buffer);
+    let mut values = serde_json::Deserializer::from_str(&stripped).into_iter::<Value>();
+    let document = values.next().ok_or(PatchError::MissingPatch)??;
+    let patch = match values.next() {
+        Some(patch) => patch?,
+        None => serde_json::from_str(&strip_jsonc(patch.ok_or(PatchError::MissingPatch)?))
+            .map_err(|e| PatchError::InvalidPatch(e.to_string()))?,
+    };
+    Ok((document, patch))
+}
+
+/// Applies an RFC 6902 patch. Operations run in order and the first one to
+/// fail aborts the whole patch, as the RFC requires.
+pub fn json_patch(buffer: &str, patch: Option<&str>) -> Result<String, PatchError> {
+    let (mut document, patch) = document_and_patch(buffer, patch)?;
+    let Value::Array(operations) = patch else {
+        return Err(PatchError::InvalidPatch(
+            "expected an array of operations".to_string(),
+        ));
+    };
+    for (index, op) in operations.iter().enumerate() {
+        apply_operation(&mut document, op)
+            .map_err(|msg| PatchError::OperationFailed(index + 1, msg))?;
+    }
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+fn merge(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    if let Value::Object(map) = target {
+        for (key, value) in patch {
+            match value {
+                Value::Null => {
+                    map.remove(&key);
+                }
+                value => merge(map.entry(key).or_insert(Value::Null), value),
+            }
+        }
+    }
+}
+
+/// Applies an RFC 7386 merge patch: objects merge recursively, `null`
+/// deletes a member and anything else replaces it outright.
+pub fn json_merge_patch(buffer: &str, patch: Option<&str>) -> Result<String, PatchError> {
+    let (mut document, patch) = document_and_patch(buffer, patch)?;
+    merge(&mut document, patch);
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_diff_report() {
+        let buffer = r#"{"name": "ada", "tags": ["a", "b"], "old": true}
+{"name": "Ada", "tags": ["a", "b", "c"], "a/b": 1}"#;
+        assert_eq!(
+            json_diff(buffer, None, None).unwrap(),
+            "~ /name: \"ada\" → \"Ada\"\n- /old: true\n+ /tags/2: \"c\"\n+ /a~1b: 1\n\n2 added, 1 removed, 1 changed"
+        );
+        assert_eq!(
+            json_diff("[1, 2]", Some("[1, 2]"), None).unwrap(),
+            "No differences"
+        );
+        assert_eq!(
+            json_diff("2", Some("1"), None).unwrap(),
+            "~ (root): 1 → 2\n\n0 added, 0 removed, 1 changed"
+        );
+    }
+
+    #[test]
+    fn test_json_diff_patch() {
+        let patch = json_diff(
+            r#"{"list": [1]}"#,
+            Some(r#"{"list": [1, 2, 3], "x": {"y": 1}}"#),
+            Some("--patch"),
+        )
+        .unwrap();
+        assert_eq!(
+            patch,
+            "[\n  {\"op\":\"remove\",\"path\":\"/list/2\"},\n  {\"op\":\"remove\",\"path\":\"/list/1\"},\n  {\"op\":\"remove\",\"path\":\"/x\"}\n]"
+        );
+        assert_eq!(json_diff("{}", Some("{}"), Some("--patch")).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_json_diff_errors() {
+        assert!(matches!(
+            json_diff("{}", None, None),
+            Err(PatchError::NothingToCompare)
+        ));
+        assert!(matches!(
+            json_diff("{}", Some("plain text"), None),
+            Err(PatchError::InvalidPrevious(_))
+        ));
+        assert!(matches!(
+            json_diff("{} {}", None, Some("--yaml")),
+            Err(PatchError::UnknownOption(_))
+        ));
+    }
+
+    #[test]
+    fn test_json_patch() {
+        let document = r#"{"a": {"b": [1, 2]}, "c": "x"}"#;
+        let patch = r#"[
+            {"op": "test", "path": "/c", "value": "x"},
+            {"op": "add", "path": "/a/b/-", "value": 3},
+            {"op": "add", "path": "/a/b/0", "value": 0},
+            {"op": "remove", "path": "/c"},
+            {"op": "copy", "from": "/a/b", "path": "/d"},
+            {"op": "move", "from": "/a/b", "path": "/e"},
+            {"op": "replace", "path": "/a", "value": null}
+        ]"#;
+        let expected = serde_json::json!({"a": null, "d": [0, 1, 2, 3], "e": [0, 1, 2, 3]});
+        let output = json_patch(document, Some(patch)).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&output).unwrap(), expected);
+        // The patch can also follow the document in the buffer
+        let output = json_patch(&format!("{}\n{}", document, patch), None).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&output).unwrap(), expected);
+
+        let diff = json_diff(
+            r#"{"a": [1, 2, 3], "b": 1}"#,
+            Some(document),
+            Some("--patch"),
+        )
+        .unwrap();
+        let output = json_patch(document, Some(&diff)).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&output).unwrap(),
+            serde_json::json!({"a": [1, 2, 3], "b": 1})
+        );
+    }
+
+    #[test]
+    fn test_json_patch_errors() {
+        let failed = |patch| match json_patch(r#"{"a": [1]}"#, Some(patch)) {
+            Err(PatchError::OperationFailed(index, msg)) => format!("{}: {}", index, msg),
+            other => panic!("expected a failed operation, got {:?}", other),
+        };
+        assert_eq!(
+            failed(
+                r#"[{"op": "test", "path": "/a/0", "value": 1}, {"op": "test", "path": "/a/0", "value": 2}]"#
+            ),
+            "2: test at '/a/0' expected 2 but found 1"
+        );
+        assert_eq!(
+            failed(r#"[{"op": "remove", "path": "/b"}]"#),
+            "1: path '/b' does not exist"
+        );
+        assert_eq!(
+            failed(r#"[{"op": "add", "path": "/a/5", "value": 1}]"#),
+            "1: index 5 is out of bounds"
+        );
+        assert_eq!(
+            failed(r#"[{"op": "move", "from": "/a", "path": "/a/0"}]"#),
+            "1: cannot move '/a' into its own child"
+        );
+        assert!(matches!(
+            json_patch("{}", None),
+            Err(PatchError::MissingPatch)
+        ));
+        assert!(matches!(
+            json_patch("{}", Some("{}")),
+            Err(PatchError::InvalidPatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_json_merge_patch() {
+        let output = json_merge_patch(
+            r#"{"title": "Hi", "author": {"name": "Ada", "email": "a@x"}, "tags": ["a"]}"#,
+            Some(r#"{"author": {"email": null}, "tags": ["b"], "draft": {"v": 1}}"#),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&output).unwrap(),
+            serde_json::json!({"title": "Hi", "author": {"name": "Ada"}, "tags": ["b"], "draft": {"v": 1}})
+        );
+        assert_eq!(json_merge_patch("[1] \"x\"", None).unwrap(), "\"x\"");
+    }
+}
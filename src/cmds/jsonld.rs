@@ -0,0 +1,254 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use super::html::json_ld_blocks;
+
+/// Properties Google's rich results need for common schema.org types. Each
+/// inner list is a set of alternatives, any one of which is enough.
+const REQUIRED_PROPERTIES: &[(&[&str], &[&[&str]])] = &[
+    (
+        &["Article", "NewsArticle", "BlogPosting"],
+        &[&["headline"], &["author"], &["datePublished"]],
+    ),
+    (
+        &["Product"],
+        &[&["name"], &["offers", "review", "aggregateRating"]],
+    ),
+    (&["Offer"], &[&["price"], &["priceCurrency"]]),
+    (
+        &["AggregateRating"],
+        &[&["ratingValue"], &["ratingCount", "reviewCount"]],
+    ),
+    (&["Review"], &[&["author"], &["reviewRating"]]),
+    (&["Organization", "Corporation"], &[&["name"], &["url"]]),
+    (
+        &["LocalBusiness", "Restaurant", "Store"],
+        &[&["name"], &["address"]],
+    ),
+    (&["Person"], &[&["name"]]),
+    (&["WebSite"], &[&["name"], &["url"]]),
+    (&["BreadcrumbList"], &[&["itemListElement"]]),
+    (&["ListItem"], &[&["position"]]),
+    (&["Event"], &[&["name"], &["startDate"], &["location"]]),
+    (&["Recipe"], &[&["name"], &["image"]]),
+    (&["FAQPage"], &[&["mainEntity"]]),
+    (&["Question"], &[&["name"], &["acceptedAnswer"]]),
+    (&["Answer"], &[&["text"]]),
+    (
+        &["VideoObject"],
+        &[&["name"], &["thumbnailUrl"], &["uploadDate"]],
+    ),
+    (
+        &["JobPosting"],
+        &[
+            &["title"],
+            &["datePosted"],
+            &["description"],
+            &["hiringOrganization"],
+        ],
+    ),
+];
+
+#[derive(Debug)]
+pub enum JsonLdError {
+    NoBlocks,
+    InvalidJson(usize, String),
+}
+
+impl fmt::Display for JsonLdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoBlocks => {
+                write!(
+                    f,
+                    "No JSON-LD found - expected JSON or HTML with application/ld+json scripts"
+                )
+            }
+            Self::InvalidJson(block, msg) => {
+                write!(f, "JSON-LD block {} is invalid JSON - {}", block, msg)
+            }
+        }
+    }
+}
+
+fn is_schema_org(context: &Value) -> bool {
+    match context {
+        Value::String(url) => url.contains("schema.org"),
+        Value::Array(items) => items.iter().any(is_schema_org),
+        Value::Object(map) => map.get("@vocab").is_some_and(|vocab| {
+            vocab
+                .as_str()
+                .is_some_and(|vocab| vocab.contains("schema.org"))
+        }),
+        _ => false,
+    }
+}
+
+fn types(node: &serde_json::Map<String, Value>) -> Vec<String> {
+    let strip = |name: &str| {
+        // Full IRIs like https://schema.org/Article name the same type
+        name.rsplit(['/', ':']).next().unwrap_or(name).to_string()
+    };
+    match node.get("@type") {
+        Some(Value::String(name)) => vec![strip(name)],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).map(strip).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Checks one node and everything nested under it, appending a line per
+/// typed node to `checks`.
+fn check_node(value: &Value, path: &str, checks: &mut Vec<String>) {
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check_node(item, &format!("{}[{}]", path, i), checks);
+            }
+        }
+        Value::Object(node) => {
+            let node_types = types(node);
+            let location = if path.is_empty() {
+                String::new()
+            } else {
+                format!(" at {}", path)
+            };
+            // Bare references like {"@id": "..."} point at nodes checked elsewhere
+            let is_reference = node.keys().all(|key| key == "@id");
+            if node_types.is_empty() && path.is_empty() && !node.contains_key("@graph") {
+                checks.push("(top level): missing @type".to_string());
+            }
+            for node_type in &node_types {
+                let required = REQUIRED_PROPERTIES
+                    .iter()
+                    .find(|(names, _)| names.contains(&node_type.as_str()))
+                    .map(|(_, required)| *required);
+                let line = match required {
+                    None => format!("{}{}: not checked (unknown type)", node_type, location),
+                    Some(required) => {
+                        let missing: Vec<String> = required
+                            .iter()
+                            .filter(|alternatives| {
+                                !alternatives
+                                    .iter()
+                                    .any(|property| node.contains_key(*property))
+                            })
+                            .map(|alternatives| alternatives.join(" or "))
+                            .collect();
+                        if missing.is_empty() {
+                            format!("{}{}: ok", node_type, location)
+                        } else {
+                            format!("{}{}: missing {}", node_type, location, missing.join(", "))
+                        }
+                    }
+                };
+                if !is_reference {
+                    checks.push(line);
+                }
+            }
+            for (key, child) in node {
+                if key.starts_with('@') && key != "@graph" {
+                    continue;
+                }
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                check_node(child, &child_path, checks);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_block(value: &Value) -> Vec<String> {
+    let mut checks = Vec::new();
+    let roots: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    for root in &roots {
+        match root.get("@context") {
+            None => checks.push("(top level): missing @context".to_string()),
+            Some(context) if !is_schema_org(context) => {
+                checks.push("(top level): @context doesn't reference schema.org".to_string());
+            }
+            Some(_) => {}
+        }
+    }
+    check_node(value, "", &mut checks);
+    checks
+}
+
+/// Pretty-prints JSON-LD, taken from HTML `<script>` blocks or given as
+/// JSON, and checks each typed node for the properties schema.org rich
+/// results require.
+pub fn json_ld(buffer: &str) -> Result<String, JsonLdError> {
+    let trimmed = buffer.trim();
+    let blocks = if trimmed.starts_with(['{', '[']) {
+        vec![trimmed.to_string()]
+    } else {
+        json_ld_blocks(buffer)
+    };
+    if blocks.is_empty() {
+        return Err(JsonLdError::NoBlocks);
+    }
+
+    let mut sections = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        let value: Value = serde_json::from_str(block)
+            .map_err(|e| JsonLdError::InvalidJson(i + 1, e.to_string()))?;
+        let checks = check_block(&value);
+        let mut section = serde_json::to_string_pretty(&value).unwrap_or_default();
+        section.push_str("\n\nChecks:");
+        for check in checks {
+            section.push_str(&format!("\n  {}", check));
+        }
+        sections.push(section);
+    }
+    Ok(sections.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_ld_flags_missing_properties() {
+        let html = r#"<script type="application/ld+json">
+            {"@context": "https://schema.org", "@type": "Article", "headline": "Hi",
+             "publisher": {"@type": "Organization", "name": "Acme"}}
+        </script>"#;
+        let result = json_ld(html).unwrap();
+        assert!(result.ends_with(
+            "Checks:\n  \
+             Article: missing author, datePublished\n  \
+             Organization at publisher: missing url"
+        ));
+    }
+
+    #[test]
+    fn test_json_ld_graph_and_alternatives() {
+        let raw = r#"{"@context": {"@vocab": "https://schema.org/"}, "@graph": [
+            {"@type": "Product", "name": "Mug", "aggregateRating":
+                {"@type": "AggregateRating", "ratingValue": 4.5, "reviewCount": 12}},
+            {"@type": "Thing"}
+        ]}"#;
+        let result = json_ld(raw).unwrap();
+        assert!(result.ends_with(
+            "Checks:\n  \
+             Product at @graph[0]: ok\n  \
+             AggregateRating at @graph[0].aggregateRating: ok\n  \
+             Thing at @graph[1]: not checked (unknown type)"
+        ));
+    }
+
+    #[test]
+    fn test_json_ld_context_and_errors() {
+        let result = json_ld(r#"{"@type": "Person", "name": "Ada"}"#).unwrap();
+        assert!(result.contains("(top level): missing @context\n  Person: ok"));
+        assert!(json_ld("<p>no scripts</p>").is_err());
+        assert!(json_ld("{oops").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+
+use base64::{Engine as _, engine::general_purpose};
+use serde_json::{Map, Value};
+
+use super::time::DateTime;
+
+/// Registered claims holding NumericDate values.
+const TIME_CLAIMS: &[&str] = &["exp", "iat", "nbf"];
+
+#[derive(Debug)]
+pub enum JwtError {
+    InvalidFormat,
+    InvalidSegment(&'static str),
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => {
+                write!(f, "Not a JWT - expected header.payload.signature")
+            }
+            Self::InvalidSegment(segment) => {
+                write!(f, "JWT {} is not base64url-encoded JSON", segment)
+            }
+        }
+    }
+}
+
+fn decode_segment(segment: &str, name: &'static str) -> Result<Value, JwtError> {
+    let bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(segment.trim_end_matches('='))
+        .map_err(|_| JwtError::InvalidSegment(name))?;
+    serde_json::from_slice(&bytes).map_err(|_| JwtError::InvalidSegment(name))
+}
+
+/// Decodes the header and payload of a JWT into one JSON document. The
+/// signature is shown but not verified, and `exp`/`iat`/`nbf` are also
+/// rendered as UTC times.
+pub fn jwt_decode(buffer: &str) -> Result<String, JwtError> {
+    let token = buffer.trim();
+    let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+    let segments: Vec<&str> = token.split('.').collect();
+    let [header, payload, signature] = segments[..] else {
+        return Err(JwtError::InvalidFormat);
+    };
+
+    let header = decode_segment(header, "header")?;
+    let payload = decode_segment(payload, "payload")?;
+
+    let times: Map<String, Value> = TIME_CLAIMS
+        .iter()
+        .filter_map(|claim| {
+            let secs = payload.get(*claim)?.as_i64()?;
+            Some((
+                claim.to_string(),
+                Value::String(DateTime::from_unix(secs).to_string()),
+            ))
+        })
+        .collect();
+
+    let mut decoded = Map::new();
+    decoded.insert("header".to_string(), header);
+    decoded.insert("payload".to_string(), payload);
+    decoded.insert(
+        "signature".to_string(),
+        Value::String(signature.to_string()),
+    );
+    if !times.is_empty() {
+        decoded.insert("times".to_string(), Value::Object(times));
+    }
+
+    // Serializing a Value can't fail
+    Ok(serde_json::to_string_pretty(&Value::Object(decoded)).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jwt_decode() {
+        // {"alg":"HS256","typ":"JWT"}.{"sub":"1234567890","iat":1516239022}
+        let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+                     eyJzdWIiOiIxMjM0NTY3ODkwIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+                     SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+        let decoded: Value = serde_json::from_str(&jwt_decode(token).unwrap()).unwrap();
+        assert_eq!(decoded["header"]["alg"], "HS256");
+        assert_eq!(decoded["payload"]["sub"], "1234567890");
+        assert_eq!(decoded["times"]["iat"], "2018-01-18T01:30:22Z");
+        assert!(jwt_decode(&format!("Bearer {}", token)).is_ok());
+    }
+
+    #[test]
+    fn test_jwt_decode_errors() {
+        assert!(matches!(jwt_decode("abc"), Err(JwtError::InvalidFormat)));
+        assert!(matches!(
+            jwt_decode("eyJhbGciOiJIUzI1NiJ9.!!!.sig"),
+            Err(JwtError::InvalidSegment("payload"))
+        ));
+    }
+}
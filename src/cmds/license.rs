@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use std::fmt;
+
+struct License {
+    id: &'static str,
+    text: &'static str,
+}
+
+const LICENSES: &[License] = &[
+    License {
+        id: "0BSD",
+        text: include_str!("licenses/0BSD.txt"),
+    },
+    License {
+        id: "Apache-2.0",
+        text: include_str!("licenses/Apache-2.0.txt"),
+    },
+    License {
+        id: "BSD-2-Clause",
+        text: include_str!("licenses/BSD-2-Clause.txt"),
+    },
+    License {
+        id: "BSD-3-Clause",
+        text: include_str!("licenses/BSD-3-Clause.txt"),
+    },
+    License {
+        id: "BSL-1.0",
+        text: include_str!("licenses/BSL-1.0.txt"),
+    },
+    License {
+        id: "ISC",
+        text: include_str!("licenses/ISC.txt"),
+    },
+    License {
+        id: "MIT",
+        text: include_str!("licenses/MIT.txt"),
+    },
+    License {
+        id: "MPL-2.0",
+        text: include_str!("licenses/MPL-2.0.txt"),
+    },
+    License {
+        id: "Unlicense",
+        text: include_str!("licenses/Unlicense.txt"),
+    },
+    License {
+        id: "Zlib",
+        text: include_str!("licenses/Zlib.txt"),
+    },
+];
+
+/// Matches below this score are not reported as candidates.
+const MIN_CONFIDENCE: f64 = 0.3;
+
+#[derive(Debug)]
+pub enum LicenseError {
+    MissingId,
+    UnknownLicense(String),
+}
+
+impl fmt::Display for LicenseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingId => {
+                write!(f, "Missing SPDX id - expected one of {}", license_ids())
+            }
+            Self::UnknownLicense(id) => {
+                write!(
+                    f,
+                    "Unknown license '{}' - expected one of {}",
+                    id,
+                    license_ids()
+                )
+            }
+        }
+    }
+}
+
+fn license_ids() -> String {
+    LICENSES
+        .iter()
+        .map(|license| license.id)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Word bigrams of the lowercased text, ignoring punctuation and the
+/// `<year>`/`<copyright holders>` placeholders in the templates.
+fn shingles(text: &str) -> HashSet<(String, String)> {
+    let without_placeholders = text
+        .replace("<year>", " ")
+        .replace("<copyright holders>", " ");
+    let words: Vec<String> = without_placeholders
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect();
+
+    words
+        .windows(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect()
+}
+
+/// Scores the buffer against every embedded license and returns the SPDX ids
+/// with their confidence (Dice coefficient over word bigrams), best first.
+fn score_licenses(buffer: &str) -> Vec<(&'static str, f64)> {
+    let input = shingles(buffer);
+    let mut scores: Vec<(&'static str, f64)> = LICENSES
+        .iter()
+        .map(|license| {
+            let template = shingles(license.text);
+            let shared = input.intersection(&template).count();
+            let total = input.len() + template.len();
+            let score = if total == 0 {
+                0.0
+            } else {
+                2.0 * shared as f64 / total as f64
+            };
+            (license.id, score)
+        })
+        .collect();
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores
+}
+
+pub fn license_detect(buffer: &str) -> String {
+    let scores = score_licenses(buffer);
+    let candidates: Vec<&(&str, f64)> = scores
+        .iter()
+        .filter(|(_, score)| *score >= MIN_CONFIDENCE)
+        .take(3)
+        .collect();
+
+    let Some((best_id, best_score)) = candidates.first() else {
+        let (closest_id, closest_score) = scores[0];
+        return format!(
+            "No known license matched (closest: {} at {:.1}%)",
+            closest_id,
+            closest_score * 100.0
+        );
+    };
+
+    let mut report = format!(
+        "Best match: {} ({:.1}% confidence)\n",
+        best_id,
+        best_score * 100.0
+    );
+    for (id, score) in candidates {
+        report.push_str(&format!("\n  {:<14} {:>5.1}%", id, score * 100.0));
+    }
+    report
+}
+
+pub fn license_text(id: Option<&str>) -> Result<String, LicenseError> {
+    let id = id.ok_or(LicenseError::MissingId)?;
+    LICENSES
+        .iter()
+        .find(|license| license.id.eq_ignore_ascii_case(id))
+        .map(|license| license.text.to_string())
+        .ok_or_else(|| LicenseError::UnknownLicense(id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_detect_mit_with_holder() {
+        let text = license_text(Some("MIT"))
+            .unwrap()
+            .replace("<year>", "2024")
+            .replace("<copyright holders>", "Jane Doe");
+        let result = license_detect(&text);
+        assert!(result.starts_with("Best match: MIT ("));
+    }
+
+    #[test]
+    fn test_license_detect_distinguishes_bsd_variants() {
+        let text = license_text(Some("BSD-2-Clause")).unwrap();
+        let scores = score_licenses(&text);
+        assert_eq!(scores[0].0, "BSD-2-Clause");
+        assert_eq!(scores[1].0, "BSD-3-Clause");
+    }
+
+    #[test]
+    fn test_license_detect_no_match() {
+        let result = license_detect("fn main() { println!(\"hello\"); }");
+        assert!(result.starts_with("No known license matched"));
+    }
+
+    #[test]
+    fn test_license_text_case_insensitive() {
+        assert!(license_text(Some("apache-2.0")).is_ok());
+        assert!(license_text(Some("GPL-9.0")).is_err());
+        assert!(license_text(None).is_err());
+    }
+}
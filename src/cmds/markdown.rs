@@ -0,0 +1,1452 @@
+use super::html::{decode_entities, parse_attrs, tag_end};
+
+/// Elements with no closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content never makes it into the Markdown.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "button", "select",
+    "textarea",
+];
+
+/// Elements that start a new block, and so close an open `<p>`.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+enum Node {
+    Text(String),
+    Element(Element),
+}
+
+struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str, attrs: Vec<(String, String)>) -> Self {
+        Self {
+            name: name.to_string(),
+            attrs,
+            children: Vec::new(),
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+}
+
+fn is_block(node: &Node) -> bool {
+    matches!(node, Node::Element(element) if BLOCK_ELEMENTS.contains(&element.name.as_str()))
+}
+
+/// The tags an opening tag closes implicitly, and the tags that stop the
+/// search, so a new `<li>` ends the previous item but not an outer list's.
+fn implied_end(name: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    match name {
+        "li" => Some((&["li"], &["ul", "ol"])),
+        "dt" | "dd" => Some((&["dt", "dd"], &["dl"])),
+        "tr" => Some((&["tr", "td", "th"], &["table", "thead", "tbody", "tfoot"])),
+        "td" | "th" => Some((&["td", "th"], &["tr", "table"])),
+        "thead" | "tbody" | "tfoot" => {
+            Some((&["thead", "tbody", "tfoot", "tr", "td", "th"], &["table"]))
+        }
+        _ => None,
+    }
+}
+
+fn close_top(stack: &mut Vec<Element>) {
+    if stack.len() > 1 {
+        let element = stack.pop().expect("stack has a root");
+        let parent = stack.last_mut().expect("stack has a root");
+        parent.children.push(Node::Element(element));
+    }
+}
+
+/// Builds a loose element tree the way browsers forgive HTML: unclosed
+/// paragraphs and list items end where the next one starts, and stray
+/// closing tags are ignored.
+fn parse_html(html: &str) -> Element {
+    let mut stack = vec![Element::new("", Vec::new())];
+    let mut pos = 0;
+    while pos < html.len() {
+        let Some(offset) = html[pos..].find('<') else {
+            let text = &html[pos..];
+            push_text(&mut stack, text);
+            break;
+        };
+        push_text(&mut stack, &html[pos..pos + offset]);
+        let start = pos + offset;
+        let rest = &html[start + 1..];
+
+        if rest.starts_with("!--") {
+            pos = rest
+                .find("-->")
+                .map_or(html.len(), |end| start + 1 + end + 3);
+            continue;
+        }
+        if rest.starts_with(['!', '?']) {
+            pos = rest.find('>').map_or(html.len(), |end| start + 1 + end + 1);
+            continue;
+        }
+        let closing = rest.starts_with('/');
+        let source = rest.strip_prefix('/').unwrap_or(rest);
+        if !source.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            push_text(&mut stack, "<");
+            pos = start + 1;
+            continue;
+        }
+        let Some(end) = tag_end(source) else {
+            push_text(&mut stack, &html[start..]);
+            break;
+        };
+        let tag = &source[..end];
+        pos = start + 1 + usize::from(closing) + end + 1;
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_lowercase();
+
+        if closing {
+            if let Some(open) = stack.iter().rposition(|element| element.name == name) {
+                while stack.len() > open.max(1) {
+                    close_top(&mut stack);
+                }
+            }
+            continue;
+        }
+
+        if SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            let body = html[pos..].to_ascii_lowercase();
+            pos = match body.find(&format!("</{}", name)) {
+                Some(close) => {
+                    let after = pos + close;
+                    html[after..]
+                        .find('>')
+                        .map_or(html.len(), |end| after + end + 1)
+                }
+                None => html.len(),
+            };
+            continue;
+        }
+
+        if let Some((ends, scope)) = implied_end(&name) {
+            let open = stack
+                .iter()
+                .rposition(|element| ends.contains(&element.name.as_str()));
+            let boundary = stack
+                .iter()
+                .rposition(|element| scope.contains(&element.name.as_str()));
+            if let Some(open) = open.filter(|open| boundary.is_none_or(|b| *open > b)) {
+                while stack.len() > open {
+                    close_top(&mut stack);
+                }
+            }
+        }
+        if BLOCK_ELEMENTS.contains(&name.as_str()) && stack.last().is_some_and(|e| e.name == "p") {
+            close_top(&mut stack);
+        }
+
+        let element = Element::new(&name, parse_attrs(&tag[name_end..]));
+        if VOID_ELEMENTS.contains(&name.as_str()) || tag.ends_with('/') {
+            let parent = stack.last_mut().expect("stack has a root");
+            parent.children.push(Node::Element(element));
+        } else {
+            stack.push(element);
+        }
+    }
+    while stack.len() > 1 {
+        close_top(&mut stack);
+    }
+    stack.pop().expect("stack has a root")
+}
+
+fn push_text(stack: &mut [Element], text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let parent = stack.last_mut().expect("stack has a root");
+    let text = decode_entities(text);
+    match parent.children.last_mut() {
+        Some(Node::Text(previous)) => previous.push_str(&text),
+        _ => parent.children.push(Node::Text(text)),
+    }
+}
+
+fn text_content(nodes: &[Node], text: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(content) => text.push_str(content),
+            Node::Element(element) if element.name == "br" => text.push('\n'),
+            Node::Element(element) => text_content(&element.children, text),
+        }
+    }
+}
+
+/// Escapes the characters that would otherwise start Markdown syntax.
+/// Underscores inside words, as in `snake_case`, are left alone.
+fn escape_markdown(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut escaped = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let in_word = |offset: Option<usize>| {
+            offset
+                .and_then(|i| chars.get(i))
+                .is_some_and(|c| c.is_alphanumeric())
+        };
+        let needs_escape = match c {
+            '\\' | '*' | '`' | '[' | ']' | '<' => true,
+            '_' => !(in_word(i.checked_sub(1)) && in_word(Some(i + 1))),
+            _ => false,
+        };
+        if needs_escape {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Wraps inline content in a delimiter, keeping surrounding spaces outside
+/// it since `** bold**` isn't emphasis.
+fn wrap(inner: &str, delimiter: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.to_string();
+    }
+    let lead = if inner.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trail = if inner.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", lead, delimiter, trimmed, delimiter, trail)
+}
+
+fn inline_code(code: &str) -> String {
+    let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut longest = 0;
+    let mut run = 0;
+    for c in code.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest + 1);
+    if longest > 0 {
+        format!("{} {} {}", fence, code, fence)
+    } else {
+        format!("{}{}{}", fence, code, fence)
+    }
+}
+
+fn link_destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url)
+    } else {
+        url.to_string()
+    }
+}
+
+fn link_title(element: &Element) -> String {
+    match element.attr("title") {
+        Some(title) if !title.is_empty() => format!(" \"{}\"", title.replace('"', "\\\"")),
+        _ => String::new(),
+    }
+}
+
+fn render_inline(nodes: &[Node]) -> String {
+    let mut markdown = String::new();
+    for node in nodes {
+        let element = match node {
+            Node::Text(text) => {
+                let collapsed: String = text
+                    .split(|c: char| c.is_ascii_whitespace())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                markdown.push_str(&escape_markdown(&collapsed));
+                continue;
+            }
+            Node::Element(element) => element,
+        };
+        let inner = || render_inline(&element.children);
+        let rendered = match element.name.as_str() {
+            "strong" | "b" => wrap(&inner(), "**"),
+            "em" | "i" | "cite" => wrap(&inner(), "*"),
+            "del" | "s" | "strike" => wrap(&inner(), "~~"),
+            "code" | "kbd" | "samp" | "tt" => {
+                let mut code = String::new();
+                text_content(&element.children, &mut code);
+                inline_code(&code)
+            }
+            "br" => "\\\n".to_string(),
+            "img" => {
+                let alt = element.attr("alt").unwrap_or_default();
+                let src = element.attr("src").unwrap_or_default();
+                format!(
+                    "![{}]({}{})",
+                    escape_markdown(alt),
+                    link_destination(src),
+                    link_title(element)
+                )
+            }
+            "a" => {
+                let text = inner();
+                match element.attr("href") {
+                    Some(href) if !href.is_empty() && !href.starts_with("javascript:") => {
+                        let label = text.trim();
+                        if label == escape_markdown(href) && href.contains("://") {
+                            format!("<{}>", href)
+                        } else {
+                            format!(
+                                "[{}]({}{})",
+                                label,
+                                link_destination(href),
+                                link_title(element)
+                            )
+                        }
+                    }
+                    _ => text,
+                }
+            }
+            _ => inner(),
+        };
+        markdown.push_str(&rendered);
+    }
+    markdown
+}
+
+/// Tidies a paragraph's inline Markdown: single spaces, and no spaces at
+/// the start or end of a line.
+fn tidy_paragraph(markdown: &str) -> String {
+    markdown
+        .split('\n')
+        .map(|line| {
+            line.split(' ')
+                .filter(|w| !w.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches(|c: char| c == '\n' || c == ' ')
+        .trim_end_matches('\\')
+        .to_string()
+}
+
+fn render_blocks(nodes: &[Node], blocks: &mut Vec<String>) {
+    let mut inline = Vec::new();
+    let flush = |inline: &mut Vec<&Node>, blocks: &mut Vec<String>| {
+        let markdown: String = inline
+            .drain(..)
+            .map(|node| render_inline(std::slice::from_ref(node)))
+            .collect();
+        let paragraph = tidy_paragraph(&markdown);
+        if !paragraph.is_empty() {
+            blocks.push(paragraph);
+        }
+    };
+    for node in nodes {
+        match node {
+            Node::Element(element) if is_block(node) => {
+                flush(&mut inline, blocks);
+                render_block(element, blocks);
+            }
+            node => inline.push(node),
+        }
+    }
+    flush(&mut inline, blocks);
+}
+
+fn indent_lines(text: &str, first: &str, rest: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| match (i, line.is_empty()) {
+            (0, _) => format!("{}{}", first, line),
+            (_, true) => String::new(),
+            _ => format!("{}{}", rest, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_list(list: &Element, blocks: &mut Vec<String>) {
+    let ordered = list.name == "ol";
+    let start: usize = list
+        .attr("start")
+        .and_then(|start| start.parse().ok())
+        .unwrap_or(1);
+    let items: Vec<&Element> = list.elements().filter(|e| e.name == "li").collect();
+    let loose = items
+        .iter()
+        .any(|item| item.elements().any(|child| child.name == "p"));
+
+    let mut rendered = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let mut content = Vec::new();
+        render_blocks(&item.children, &mut content);
+        let content = content.join(if loose { "\n\n" } else { "\n" });
+        let marker = match ordered {
+            true => format!("{}. ", start + i),
+            false => "- ".to_string(),
+        };
+        let indent = " ".repeat(marker.len());
+        rendered.push(
+            indent_lines(&content, &marker, &indent)
+                .trim_end()
+                .to_string(),
+        );
+    }
+    if !rendered.is_empty() {
+        blocks.push(rendered.join(if loose { "\n\n" } else { "\n" }));
+    }
+}
+
+fn render_pre(pre: &Element, blocks: &mut Vec<String>) {
+    let code = pre.elements().find(|e| e.name == "code");
+    let language = [code.and_then(|c| c.attr("class")), pre.attr("class")]
+        .into_iter()
+        .flatten()
+        .flat_map(str::split_whitespace)
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default();
+    let mut text = String::new();
+    text_content(&pre.children, &mut text);
+    // Browsers drop a newline straight after `<pre>`
+    let text = text.strip_prefix('\n').unwrap_or(&text).trim_end();
+
+    let mut fence = "```".to_string();
+    while text.contains(fence.as_str()) {
+        fence.push('`');
+    }
+    blocks.push(format!("{}{}\n{}\n{}", fence, language, text, fence));
+}
+
+fn render_table(table: &Element, blocks: &mut Vec<String>) {
+    let rows: Vec<&Element> = table
+        .elements()
+        .flat_map(|child| match child.name.as_str() {
+            "thead" | "tbody" | "tfoot" => child.elements().collect(),
+            _ => vec![child],
+        })
+        .filter(|row| row.name == "tr")
+        .collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            row.elements()
+                .filter(|cell| cell.name == "td" || cell.name == "th")
+                .map(|cell| {
+                    tidy_paragraph(&render_inline(&cell.children))
+                        .replace("\\\n", " ")
+                        .replace('|', "\\|")
+                })
+                .collect()
+        })
+        .filter(|row: &Vec<String>| !row.is_empty())
+        .collect();
+    let Some(columns) = cells.iter().map(Vec::len).max() else {
+        return;
+    };
+    let line = |row: &[String]| {
+        let padded: Vec<&str> = (0..columns)
+            .map(|i| row.get(i).map_or("", String::as_str))
+            .collect();
+        format!("| {} |", padded.join(" | "))
+    };
+    let mut table = vec![line(&cells[0])];
+    table.push(format!("|{}|", vec![" --- "; columns].join("|")));
+    table.extend(cells[1..].iter().map(|row| line(row)));
+    blocks.push(table.join("\n"));
+}
+
+fn render_block(element: &Element, blocks: &mut Vec<String>) {
+    match element.name.as_str() {
+        heading @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+            let level = heading[1..].parse().unwrap_or(1);
+            let text = tidy_paragraph(&render_inline(&element.children)).replace("\\\n", " ");
+            if !text.is_empty() {
+                blocks.push(format!("{} {}", "#".repeat(level), text));
+            }
+        }
+        "ul" | "ol" => render_list(element, blocks),
+        "pre" => render_pre(element, blocks),
+        "table" => render_table(element, blocks),
+        "hr" => blocks.push("---".to_string()),
+        "blockquote" => {
+            let mut inner = Vec::new();
+            render_blocks(&element.children, &mut inner);
+            let quoted = inner
+                .join("\n\n")
+                .lines()
+                .map(|line| match line {
+                    "" => ">".to_string(),
+                    line => format!("> {}", line),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            if !quoted.is_empty() {
+                blocks.push(quoted);
+            }
+        }
+        _ => render_blocks(&element.children, blocks),
+    }
+}
+
+/// Converts HTML to CommonMark with GitHub tables and strikethrough.
+/// Scripts, styles and the document head are dropped, and elements with no
+/// Markdown equivalent keep just their text.
+pub fn html_to_markdown(buffer: &str) -> String {
+    let root = parse_html(buffer);
+    let mut blocks = Vec::new();
+    render_blocks(&root.children, &mut blocks);
+    blocks.join("\n\n")
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn is_entity(text: &str) -> bool {
+    let Some(end) = text.find(';').filter(|end| (2..=10).contains(end)) else {
+        return false;
+    };
+    let name = &text[1..end];
+    match name.strip_prefix('#') {
+        Some(number) => number
+            .strip_prefix(['x', 'X'])
+            .map_or(number.chars().all(|c| c.is_ascii_digit()), |hex| {
+                hex.chars().all(|c| c.is_ascii_hexdigit())
+            }),
+        None => name.chars().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
+fn is_inline_tag(text: &str) -> bool {
+    let inner = text.strip_prefix("</").or_else(|| text.strip_prefix('<'));
+    inner.is_some_and(|inner| inner.starts_with(|c: char| c.is_ascii_alphabetic()))
+        && !text[1..text.len() - 1].contains('<')
+}
+
+/// Finds a closing run of exactly `count` backticks.
+fn find_backticks(text: &str, count: usize) -> Option<usize> {
+    let mut i = 0;
+    while let Some(offset) = text[i..].find('`') {
+        let start = i + offset;
+        let run = text[start..].chars().take_while(|c| *c == '`').count();
+        if run == count {
+            return Some(start);
+        }
+        i = start + run;
+    }
+    None
+}
+
+/// Finds the closing delimiter run for emphasis opened with `count` of
+/// `marker`: not preceded by whitespace, and for `_` not followed by a
+/// letter.
+fn find_emphasis_close(text: &str, marker: char, count: usize) -> Option<usize> {
+    let mut i = 0;
+    while let Some(offset) = text[i..].find(marker) {
+        let start = i + offset;
+        if text[..start].ends_with('\\') {
+            i = start + 1;
+            continue;
+        }
+        let run = text[start..].chars().take_while(|c| *c == marker).count();
+        let before = text[..start].chars().next_back();
+        let after = text[start + run..].chars().next();
+        let closes = start > 0
+            && before.is_some_and(|c| !c.is_whitespace())
+            && (marker == '*' || !after.is_some_and(char::is_alphanumeric));
+        if closes && (run == count || (count == 3 && run > 3)) {
+            return Some(start);
+        }
+        i = start + run;
+    }
+    None
+}
+
+struct Link<'a> {
+    label: &'a str,
+    destination: &'a str,
+    title: Option<&'a str>,
+    len: usize,
+}
+
+/// Parses `[label](destination "title")` at the start of `text`.
+fn parse_link(text: &str) -> Option<Link<'_>> {
+    let mut depth = 0;
+    let mut label_end = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    label_end = Some(i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let label_end = label_end?;
+    let rest = text[label_end + 1..].strip_prefix('(')?;
+    let body = rest.trim_start();
+    let (destination, after) = match body.strip_prefix('<') {
+        Some(angled) => {
+            let end = angled.find('>')?;
+            (&angled[..end], &angled[end + 1..])
+        }
+        None => {
+            let mut depth = 0;
+            let end = body
+                .char_indices()
+                .find(|(_, c)| match c {
+                    '(' => {
+                        depth += 1;
+                        false
+                    }
+                    ')' if depth == 0 => true,
+                    ')' => {
+                        depth -= 1;
+                        false
+                    }
+                    c => c.is_whitespace(),
+                })
+                .map_or(body.len(), |(i, _)| i);
+            (&body[..end], &body[end..])
+        }
+    };
+    let after_trimmed = after.trim_start();
+    let (title, after) = match after_trimmed.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let end = after_trimmed[1..].find(quote)?;
+            (Some(&after_trimmed[1..end + 1]), &after_trimmed[end + 2..])
+        }
+        _ => (None, after_trimmed),
+    };
+    let after = after.trim_start().strip_prefix(')')?;
+    Some(Link {
+        label: &text[1..label_end],
+        destination,
+        title,
+        len: text.len() - after.len(),
+    })
+}
+
+fn plain_text(markdown: &str) -> String {
+    markdown
+        .chars()
+        .filter(|c| !matches!(c, '*' | '_' | '`' | '[' | ']' | '\\'))
+        .collect()
+}
+
+fn render_markdown_inline(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let consumed = text.len() - rest.len();
+        let previous = text[..consumed].chars().next_back();
+        match c {
+            '\\' => match rest[1..].chars().next() {
+                Some('\n') => {
+                    html.push_str("<br />\n");
+                    rest = &rest[2..];
+                }
+                Some(next) if next.is_ascii_punctuation() => {
+                    html.push_str(&escape_html(&next.to_string()));
+                    rest = &rest[2..];
+                }
+                _ => {
+                    html.push('\\');
+                    rest = &rest[1..];
+                }
+            },
+            '`' => {
+                let run = rest.chars().take_while(|c| *c == '`').count();
+                let after = &rest[run..];
+                match find_backticks(after, run) {
+                    Some(end) => {
+                        let code = after[..end].replace('\n', " ");
+                        let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                            Some(inner) if !inner.trim().is_empty() => inner.to_string(),
+                            _ => code,
+                        };
+                        html.push_str(&format!("<code>{}</code>", escape_html(&code)));
+                        rest = &after[end + run..];
+                    }
+                    None => {
+                        html.push_str(&rest[..run]);
+                        rest = after;
+                    }
+                }
+            }
+            '!' if rest[1..].starts_with('[') => match parse_link(&rest[1..]) {
+                Some(link) => {
+                    html.push_str(&format!(
+                        "<img src=\"{}\" alt=\"{}\"",
+                        escape_html(link.destination),
+                        escape_html(&plain_text(link.label))
+                    ));
+                    if let Some(title) = link.title {
+                        html.push_str(&format!(" title=\"{}\"", escape_html(title)));
+                    }
+                    html.push_str(" />");
+                    rest = &rest[1 + link.len..];
+                }
+                None => {
+                    html.push('!');
+                    rest = &rest[1..];
+                }
+            },
+            '[' => match parse_link(rest) {
+                Some(link) => {
+                    html.push_str(&format!("<a href=\"{}\"", escape_html(link.destination)));
+                    if let Some(title) = link.title {
+                        html.push_str(&format!(" title=\"{}\"", escape_html(title)));
+                    }
+                    html.push_str(&format!(">{}</a>", render_markdown_inline(link.label)));
+                    rest = &rest[link.len..];
+                }
+                None => {
+                    html.push('[');
+                    rest = &rest[1..];
+                }
+            },
+            '<' => {
+                let tag = rest.find('>').map(|end| &rest[..end + 1]);
+                let inner = tag.map(|tag| &tag[1..tag.len() - 1]);
+                match (tag, inner) {
+                    (Some(tag), Some(inner))
+                        if !inner.contains(char::is_whitespace)
+                            && (inner.contains("://") || inner.contains('@')) =>
+                    {
+                        let href = match inner.contains("://") {
+                            true => inner.to_string(),
+                            false => format!("mailto:{}", inner),
+                        };
+                        html.push_str(&format!(
+                            "<a href=\"{}\">{}</a>",
+                            escape_html(&href),
+                            escape_html(inner)
+                        ));
+                        rest = &rest[tag.len()..];
+                    }
+                    // Inline HTML passes through, as CommonMark allows
+                    (Some(tag), _) if is_inline_tag(tag) => {
+                        html.push_str(tag);
+                        rest = &rest[tag.len()..];
+                    }
+                    _ => {
+                        html.push_str("&lt;");
+                        rest = &rest[1..];
+                    }
+                }
+            }
+            '*' | '_' => {
+                let run = rest.chars().take_while(|x| *x == c).count();
+                let after = &rest[run..];
+                let opens = after.starts_with(|n: char| !n.is_whitespace())
+                    && (c == '*' || !previous.is_some_and(char::is_alphanumeric));
+                let count = run.min(3);
+                let close = opens
+                    .then(|| find_emphasis_close(after, c, count))
+                    .flatten();
+                match close {
+                    Some(end) => {
+                        // Extra opening markers beyond three stay literal
+                        html.push_str(&rest[..run - count]);
+                        let inner = render_markdown_inline(&after[..end]);
+                        let (open, close) = match count {
+                            1 => ("<em>", "</em>"),
+                            2 => ("<strong>", "</strong>"),
+                            _ => ("<em><strong>", "</strong></em>"),
+                        };
+                        html.push_str(&format!("{}{}{}", open, inner, close));
+                        rest = &after[end + count..];
+                    }
+                    None => {
+                        html.push_str(&rest[..run]);
+                        rest = after;
+                    }
+                }
+            }
+            '~' if rest.starts_with("~~") => {
+                let after = &rest[2..];
+                match after.find("~~").filter(|end| *end > 0) {
+                    Some(end) => {
+                        let inner = render_markdown_inline(&after[..end]);
+                        html.push_str(&format!("<del>{}</del>", inner));
+                        rest = &after[end + 2..];
+                    }
+                    None => {
+                        html.push_str("~~");
+                        rest = after;
+                    }
+                }
+            }
+            '\n' => {
+                let hard_break = html.ends_with("  ");
+                html.truncate(html.trim_end_matches(' ').len());
+                html.push_str(if hard_break { "<br />\n" } else { "\n" });
+                rest = &rest[1..];
+            }
+            '&' if is_entity(rest) => {
+                html.push('&');
+                rest = &rest[1..];
+            }
+            c => {
+                html.push_str(&escape_html(&c.to_string()));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    html
+}
+
+/// Leading spaces, counting a tab as four.
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| matches!(c, ' ' | '\t'))
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Removes up to `columns` columns of indentation.
+fn dedent(line: &str, columns: usize) -> &str {
+    let mut width = 0;
+    for (i, c) in line.char_indices() {
+        if width >= columns || !matches!(c, ' ' | '\t') {
+            return &line[i..];
+        }
+        width += if c == '\t' { 4 } else { 1 };
+    }
+    ""
+}
+
+/// The rest of a list item's first line after its marker.
+fn after_marker(line: &str, content_indent: usize) -> &str {
+    let mut width = 0;
+    for (i, c) in line.char_indices() {
+        if width >= content_indent {
+            return &line[i..];
+        }
+        width += if c == '\t' { 4 } else { 1 };
+    }
+    ""
+}
+
+fn is_thematic_break(line: &str) -> bool {
+    let trimmed = line.trim();
+    let Some(marker) = trimmed
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '-' | '*' | '_'))
+    else {
+        return false;
+    };
+    trimmed.chars().all(|c| c == marker || c == ' ')
+        && trimmed.chars().filter(|c| *c == marker).count() >= 3
+}
+
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    let rest = &trimmed[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let text = rest.trim();
+    // A closing run of #s only counts after a space
+    let without_closing = text.trim_end_matches('#');
+    let text = match without_closing.is_empty() || without_closing.ends_with(' ') {
+        true => without_closing.trim_end(),
+        false => text,
+    };
+    Some((level, text))
+}
+
+/// The fence character and length of a code fence opener or closer.
+fn code_fence(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|c| *c == marker).count();
+    let info = trimmed[len..].trim();
+    (len >= 3 && !(marker == '`' && info.contains('`'))).then_some((marker, len, info))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ListKind {
+    Bullet(char),
+    Ordered(char),
+}
+
+struct ListMarker {
+    kind: ListKind,
+    start: usize,
+    /// Columns from the start of the line to the item's content
+    content_indent: usize,
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+    let indent = indentation(line);
+    if indent >= 4 {
+        return None;
+    }
+    let trimmed = line.trim_start();
+    let (kind, start, marker_len) = match trimmed.chars().next()? {
+        bullet @ ('-' | '*' | '+') => (ListKind::Bullet(bullet), 1, 1),
+        '0'..='9' => {
+            let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+            let delimiter = trimmed[digits..].chars().next()?;
+            if digits > 9 || !matches!(delimiter, '.' | ')') {
+                return None;
+            }
+            let start = trimmed[..digits].parse().ok()?;
+            (ListKind::Ordered(delimiter), start, digits + 1)
+        }
+        _ => return None,
+    };
+    let after = &trimmed[marker_len..];
+    if !after.is_empty() && !after.starts_with([' ', '\t']) {
+        return None;
+    }
+    let spaces = indentation(after);
+    let spaces = if (1..=4).contains(&spaces) { spaces } else { 1 };
+    Some(ListMarker {
+        kind,
+        start,
+        content_indent: indent + marker_len + spaces,
+    })
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let trimmed = match trimmed.strip_suffix('|') {
+        Some(inner) if !inner.ends_with('\\') => inner,
+        _ => trimmed,
+    };
+    let mut cells = vec![String::new()];
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cells.last_mut().expect("cells start non-empty").push('|');
+                chars.next();
+            }
+            '|' => cells.push(String::new()),
+            c => cells.last_mut().expect("cells start non-empty").push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+fn table_alignments(line: &str) -> Option<Vec<Option<&'static str>>> {
+    if !line.contains('-') || !line.contains(['|', ':']) && !line.trim().starts_with('-') {
+        return None;
+    }
+    table_cells(line)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Some("center"),
+                (true, false) => Some("left"),
+                (false, true) => Some("right"),
+                (false, false) => None,
+            })
+        })
+        .collect()
+}
+
+fn starts_html_block(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("<!--") {
+        return true;
+    }
+    let Some(tag) = trimmed
+        .strip_prefix("</")
+        .or_else(|| trimmed.strip_prefix('<'))
+    else {
+        return false;
+    };
+    let name: String = tag
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase();
+    BLOCK_ELEMENTS.contains(&name.as_str()) && tag[name.len()..].starts_with([' ', '>', '/'])
+}
+
+fn flush_paragraph(paragraph: &mut Vec<&str>, tight: bool, html: &mut String) {
+    if paragraph.is_empty() {
+        return;
+    }
+    let text = paragraph.join("\n");
+    let content = render_markdown_inline(text.trim_end());
+    paragraph.clear();
+    if tight {
+        html.push_str(&content);
+        html.push('\n');
+    } else {
+        html.push_str(&format!("<p>{}</p>\n", content));
+    }
+}
+
+/// Renders Markdown block structure. In a tight list item, paragraphs are
+/// written without `<p>` tags.
+fn render_markdown_blocks(lines: &[&str], tight: bool, html: &mut String) {
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line.trim().is_empty() {
+            flush_paragraph(&mut paragraph, tight, html);
+            i += 1;
+            continue;
+        }
+
+        // Setext headings underline the paragraph above them
+        if !paragraph.is_empty() && indentation(line) < 4 {
+            let underline = line.trim();
+            let level = match underline.chars().next() {
+                Some('=') if underline.chars().all(|c| c == '=') => Some(1),
+                Some('-') if underline.chars().all(|c| c == '-') => Some(2),
+                _ => None,
+            };
+            if let Some(level) = level {
+                let text = paragraph.join("\n");
+                paragraph.clear();
+                let content = render_markdown_inline(text.trim());
+                html.push_str(&format!("<h{}>{}</h{}>\n", level, content, level));
+                i += 1;
+                continue;
+            }
+        }
+
+        if indentation(line) >= 4 && paragraph.is_empty() {
+            let mut code = Vec::new();
+            while i < lines.len() && (lines[i].trim().is_empty() || indentation(lines[i]) >= 4) {
+                code.push(dedent(lines[i], 4));
+                i += 1;
+            }
+            while code.last().is_some_and(|line| line.trim().is_empty()) {
+                code.pop();
+            }
+            let code = escape_html(&code.join("\n"));
+            html.push_str(&format!("<pre><code>{}\n</code></pre>\n", code));
+            continue;
+        }
+
+        if let Some((marker, len, info)) = code_fence(line) {
+            flush_paragraph(&mut paragraph, tight, html);
+            let indent = indentation(line);
+            let mut code = Vec::new();
+            i += 1;
+            while i < lines.len() {
+                let closes = code_fence(lines[i]).is_some_and(|(closing, closing_len, info)| {
+                    closing == marker && closing_len >= len && info.is_empty()
+                });
+                if closes {
+                    i += 1;
+                    break;
+                }
+                code.push(dedent(lines[i], indent));
+                i += 1;
+            }
+            let language = info.split_whitespace().next();
+            let class = language
+                .map(|language| format!(" class=\"language-{}\"", escape_html(language)))
+                .unwrap_or_default();
+            let mut code = escape_html(&code.join("\n"));
+            if !code.is_empty() {
+                code.push('\n');
+            }
+            html.push_str(&format!("<pre><code{}>{}</code></pre>\n", class, code));
+            continue;
+        }
+
+        if let Some((level, text)) = atx_heading(line) {
+            flush_paragraph(&mut paragraph, tight, html);
+            let content = render_markdown_inline(text);
+            html.push_str(&format!("<h{}>{}</h{}>\n", level, content, level));
+            i += 1;
+            continue;
+        }
+
+        if is_thematic_break(line) {
+            flush_paragraph(&mut paragraph, tight, html);
+            html.push_str("<hr />\n");
+            i += 1;
+            continue;
+        }
+
+        if line.trim_start().starts_with('>') {
+            flush_paragraph(&mut paragraph, tight, html);
+            let mut quoted = Vec::new();
+            while i < lines.len() {
+                let Some(inner) = lines[i].trim_start().strip_prefix('>') else {
+                    break;
+                };
+                quoted.push(inner.strip_prefix(' ').unwrap_or(inner));
+                i += 1;
+            }
+            html.push_str("<blockquote>\n");
+            render_markdown_blocks(&quoted, false, html);
+            html.push_str("</blockquote>\n");
+            continue;
+        }
+
+        if let Some(marker) = list_marker(line) {
+            flush_paragraph(&mut paragraph, tight, html);
+            i = render_list_items(lines, i, marker, html);
+            continue;
+        }
+
+        let alignments = lines.get(i + 1).and_then(|next| table_alignments(next));
+        if let Some(alignments) = alignments.filter(|_| paragraph.is_empty() && line.contains('|'))
+        {
+            let header = table_cells(line);
+            if header.len() == alignments.len() {
+                i = render_markdown_table(lines, i, &header, &alignments, html);
+                continue;
+            }
+        }
+
+        if paragraph.is_empty() && starts_html_block(line) {
+            while i < lines.len() && !lines[i].trim().is_empty() {
+                html.push_str(lines[i]);
+                html.push('\n');
+                i += 1;
+            }
+            continue;
+        }
+
+        paragraph.push(line.trim_start());
+        i += 1;
+    }
+    flush_paragraph(&mut paragraph, tight, html);
+}
+
+/// Renders the list starting at `lines[start]` and returns the index of
+/// the first line after it.
+fn render_list_items(lines: &[&str], start: usize, first: ListMarker, html: &mut String) -> usize {
+    let mut items: Vec<Vec<&str>> = Vec::new();
+    let mut loose = false;
+    let mut i = start;
+    let (kind, first_number) = (first.kind, first.start);
+    let mut marker = Some(first);
+
+    while let Some(current) = marker.take() {
+        let mut item = vec![after_marker(lines[i], current.content_indent)];
+        i += 1;
+        let mut blank = false;
+        while i < lines.len() {
+            let line = lines[i];
+            if line.trim().is_empty() {
+                blank = true;
+                item.push("");
+                i += 1;
+                continue;
+            }
+            if indentation(line) >= current.content_indent {
+                if blank {
+                    loose = true;
+                }
+                blank = false;
+                item.push(dedent(line, current.content_indent));
+                i += 1;
+                continue;
+            }
+            if let Some(next) = list_marker(line).filter(|next| next.kind == kind) {
+                if blank {
+                    loose = true;
+                }
+                marker = Some(next);
+                break;
+            }
+            // A lazy continuation of the item's paragraph
+            let starts_block = list_marker(line).is_some()
+                || is_thematic_break(line)
+                || atx_heading(line).is_some()
+                || code_fence(line).is_some()
+                || line.trim_start().starts_with('>');
+            if !blank && !starts_block {
+                item.push(line.trim_start());
+                i += 1;
+                continue;
+            }
+            break;
+        }
+        while item.last().is_some_and(|line| line.is_empty()) {
+            item.pop();
+        }
+        items.push(item);
+    }
+
+    let (open, close) = match kind {
+        ListKind::Ordered(_) if first_number != 1 => {
+            (format!("<ol start=\"{}\">\n", first_number), "</ol>\n")
+        }
+        ListKind::Ordered(_) => ("<ol>\n".to_string(), "</ol>\n"),
+        ListKind::Bullet(_) => ("<ul>\n".to_string(), "</ul>\n"),
+    };
+    html.push_str(&open);
+    for item in items {
+        let mut content = String::new();
+        render_markdown_blocks(&item, !loose, &mut content);
+        let content = content.trim_end();
+        let last_line = content.lines().next_back().unwrap_or_default();
+        if loose || starts_html_block(content) {
+            html.push_str(&format!("<li>\n{}\n</li>\n", content));
+        } else if starts_html_block(last_line) {
+            html.push_str(&format!("<li>{}\n</li>\n", content));
+        } else {
+            html.push_str(&format!("<li>{}</li>\n", content));
+        }
+    }
+    html.push_str(close);
+    i
+}
+
+fn render_markdown_table(
+    lines: &[&str],
+    start: usize,
+    header: &[String],
+    alignments: &[Option<&str>],
+    html: &mut String,
+) -> usize {
+    let cell = |tag: &str, i: usize, text: &str| {
+        let align = alignments
+            .get(i)
+            .copied()
+            .flatten()
+            .map(|align| format!(" align=\"{}\"", align))
+            .unwrap_or_default();
+        format!(
+            "<{}{}>{}</{}>\n",
+            tag,
+            align,
+            render_markdown_inline(text),
+            tag
+        )
+    };
+    html.push_str("<table>\n<thead>\n<tr>\n");
+    for (i, text) in header.iter().enumerate() {
+        html.push_str(&cell("th", i, text));
+    }
+    html.push_str("</tr>\n</thead>\n");
+
+    let mut i = start + 2;
+    let mut body = String::new();
+    while i < lines.len() && !lines[i].trim().is_empty() && lines[i].contains('|') {
+        let cells = table_cells(lines[i]);
+        body.push_str("<tr>\n");
+        for column in 0..header.len() {
+            let text = cells.get(column).map_or("", String::as_str);
+            body.push_str(&cell("td", column, text));
+        }
+        body.push_str("</tr>\n");
+        i += 1;
+    }
+    if !body.is_empty() {
+        html.push_str(&format!("<tbody>\n{}</tbody>\n", body));
+    }
+    html.push_str("</table>\n");
+    i
+}
+
+/// Converts CommonMark to HTML, with GitHub's tables and strikethrough.
+/// Inline and block HTML pass through unchanged.
+pub fn markdown_to_html(buffer: &str) -> String {
+    let lines: Vec<&str> = buffer.lines().collect();
+    let mut html = String::new();
+    render_markdown_blocks(&lines, false, &mut html);
+    html.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = r#"<html><head><title>Docs</title><style>p { color: red }</style></head>
+<body>
+  <h1>Getting <em>started</em></h1>
+  <p>Install with <code>cargo install pomp</code> and read the
+     <a href="https://example.com/docs" title="Docs">full guide</a>.<br>Then <b>enjoy</b>!</p>
+  <ul>
+    <li>First
+    <li>Second with <strong>bold</strong>
+      <ol start="3"><li>Nested</li><li>Items</li></ol>
+    </li>
+  </ul>
+  <pre><code class="language-rust">fn main() {
+    println!("&lt;hi&gt;");
+}
+</code></pre>
+  <blockquote><p>Quoted *text*</p></blockquote>
+  <table>
+    <thead><tr><th>Name</th><th>Value</th></tr></thead>
+    <tbody><tr><td>a|b</td><td><img src="x.png" alt="X"></td></tr></tbody>
+  </table>
+  <hr>
+  <script>alert(1)</script>
+</body></html>"#;
+        assert_eq!(
+            html_to_markdown(html),
+            "# Getting *started*\n\n\
+             Install with `cargo install pomp` and read the \
+             [full guide](https://example.com/docs \"Docs\").\\\nThen **enjoy**!\n\n\
+             - First\n- Second with **bold**\n  3. Nested\n  4. Items\n\n\
+             ```rust\nfn main() {\n    println!(\"<hi>\");\n}\n```\n\n\
+             > Quoted \\*text\\*\n\n\
+             | Name | Value |\n| --- | --- |\n| a\\|b | ![X](x.png) |\n\n\
+             ---"
+        );
+    }
+
+    #[test]
+    fn test_html_to_markdown_inline_edge_cases() {
+        assert_eq!(
+            html_to_markdown("<p>a <b> spaced </b> b, snake_case and _under_</p>"),
+            "a **spaced** b, snake_case and \\_under\\_"
+        );
+        assert_eq!(
+            html_to_markdown("<a href=\"https://x.dev\">https://x.dev</a> <code>a`b</code>"),
+            "<https://x.dev> `` a`b ``"
+        );
+        assert_eq!(html_to_markdown("plain &amp; simple"), "plain & simple");
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        let markdown = "# Title #\n\nSome *em*, **strong**, ~~gone~~ and `code <b>`.\n\
+                        A [link](https://example.com \"Home\") and ![img](a.png).  \n\
+                        Next line with a_b_c & <https://auto.link>.\n\n\
+                        - one\n- two\n  - nested\n\n\
+                        3. three\n4. four\n\n\
+                        > quoted\n> > deeper\n\n\
+                        ```rust\nlet x = 1 < 2;\n```\n\n\
+                        | Left | Right |\n|:-----|------:|\n| a \\| b | 2 |\n\n\
+                        ***\n\nSetext\n======";
+        assert_eq!(
+            markdown_to_html(markdown),
+            "<h1>Title</h1>\n\
+             <p>Some <em>em</em>, <strong>strong</strong>, <del>gone</del> and <code>code &lt;b&gt;</code>.\n\
+             A <a href=\"https://example.com\" title=\"Home\">link</a> and \
+             <img src=\"a.png\" alt=\"img\" />.<br />\n\
+             Next line with a_b_c &amp; <a href=\"https://auto.link\">https://auto.link</a>.</p>\n\
+             <ul>\n<li>one</li>\n<li>two\n<ul>\n<li>nested</li>\n</ul>\n</li>\n</ul>\n\
+             <ol start=\"3\">\n<li>three</li>\n<li>four</li>\n</ol>\n\
+             <blockquote>\n<p>quoted</p>\n<blockquote>\n<p>deeper</p>\n</blockquote>\n</blockquote>\n\
+             <pre><code class=\"language-rust\">let x = 1 &lt; 2;\n</code></pre>\n\
+             <table>\n<thead>\n<tr>\n<th align=\"left\">Left</th>\n<th align=\"right\">Right</th>\n\
+             </tr>\n</thead>\n<tbody>\n<tr>\n<td align=\"left\">a | b</td>\n\
+             <td align=\"right\">2</td>\n</tr>\n</tbody>\n</table>\n\
+             <hr />\n\
+             <h1>Setext</h1>"
+        );
+    }
+
+    #[test]
+    fn test_markdown_to_html_loose_list_and_html() {
+        assert_eq!(
+            markdown_to_html("- a\n\n- b\n\n<div class=\"note\">\n*raw*\n</div>"),
+            "<ul>\n<li>\n<p>a</p>\n</li>\n<li>\n<p>b</p>\n</li>\n</ul>\n\
+             <div class=\"note\">\n*raw*\n</div>"
+        );
+        assert_eq!(
+            markdown_to_html("***both*** \\*lit\\*"),
+            "<p><em><strong>both</strong></em> *lit*</p>"
+        );
+    }
+
+    #[test]
+    fn test_markdown_html_roundtrip() {
+        let markdown = "## Notes\n\nUse **bold** and *em* with [links](https://x.dev).\n\n\
+                        - one\n- two\n\n```sh\necho hi\n```";
+        assert_eq!(html_to_markdown(&markdown_to_html(markdown)), markdown);
+    }
+}
@@ -0,0 +1,580 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value};
+
+use super::fake::Faker;
+use super::json::strip_jsonc;
+
+const MAX_COUNT: usize = 1000;
+
+/// How deep `$ref`s may nest before a recursive schema is cut off.
+const MAX_DEPTH: usize = 8;
+
+/// Keywords that mark an object as a JSON Schema rather than a sample.
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$ref",
+    "$defs",
+    "definitions",
+    "title",
+    "description",
+    "type",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "prefixItems",
+    "minItems",
+    "maxItems",
+    "enum",
+    "const",
+    "examples",
+    "default",
+    "format",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "oneOf",
+    "anyOf",
+    "allOf",
+];
+
+#[derive(Debug)]
+pub enum MockError {
+    InvalidJson(String),
+    InvalidCount(String),
+    InvalidSeed(String),
+    UnknownOption(String),
+    UnresolvedRef(String),
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidJson(msg) => {
+                write!(f, "Invalid JSON - {}", msg)
+            }
+            Self::InvalidCount(count) => {
+                write!(
+                    f,
+                    "Invalid count '{}' - expected a number from 1 to {}",
+                    count, MAX_COUNT
+                )
+            }
+            Self::InvalidSeed(seed) => {
+                write!(f, "Invalid seed '{}' - expected a whole number", seed)
+            }
+            Self::UnknownOption(option) => {
+                write!(
+                    f,
+                    "Unknown option '{}' - expected a count or --seed <n>",
+                    option
+                )
+            }
+            Self::UnresolvedRef(reference) => {
+                write!(
+                    f,
+                    "Cannot resolve $ref '{}' - only local refs are supported",
+                    reference
+                )
+            }
+        }
+    }
+}
+
+fn is_schema(value: &Value) -> bool {
+    let Value::Object(map) = value else {
+        return false;
+    };
+    let marker = [
+        "type",
+        "properties",
+        "items",
+        "$ref",
+        "enum",
+        "oneOf",
+        "anyOf",
+        "allOf",
+    ];
+    map.contains_key("$schema")
+        || (map
+            .keys()
+            .all(|key| SCHEMA_KEYWORDS.contains(&key.as_str()))
+            && marker.iter().any(|key| map.contains_key(*key)))
+}
+
+struct Mock<'a> {
+    faker: Faker,
+    root: &'a Value,
+}
+
+impl Mock<'_> {
+    /// Picks a generator from a property name like `email` or `created_at`.
+    fn hinted_string(&mut self, hint: &str) -> String {
+        let hint = hint.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|word| hint.contains(word));
+        let faker = &mut self.faker;
+        if has(&["email"]) {
+            faker.email()
+        } else if has(&["uuid", "guid"]) || hint == "id" || hint.ends_with("_id") {
+            faker.uuid()
+        } else if has(&["username", "login", "handle"]) {
+            faker.username()
+        } else if has(&["first"]) {
+            faker.first_name()
+        } else if has(&["last", "surname"]) {
+            faker.last_name()
+        } else if has(&["company", "organization", "employer"]) {
+            faker.company()
+        } else if has(&["name", "author"]) {
+            faker.full_name()
+        } else if has(&["city", "town"]) {
+            faker.city()
+        } else if has(&["country"]) {
+            faker.country()
+        } else if has(&["address", "street"]) {
+            faker.street_address()
+        } else if has(&["zip", "postal"]) {
+            faker.postal_code()
+        } else if has(&["phone", "mobile", "tel"]) {
+            faker.phone()
+        } else if has(&["url", "website", "link", "href", "avatar", "image"]) {
+            faker.url()
+        } else if has(&["birth", "date", "day"]) && !has(&["update"]) {
+            faker.date()
+        } else if has(&["_at", "time", "updated", "created", "timestamp"]) {
+            faker.date_time()
+        } else if has(&["color", "colour"]) {
+            faker.hex_color()
+        } else if hint == "ip" || has(&["ip_address", "ipaddress"]) {
+            faker.ipv4()
+        } else if has(&["title", "subject", "summary", "headline"]) {
+            faker.sentence(2, 5)
+        } else if has(&[
+            "description",
+            "bio",
+            "body",
+            "text",
+            "comment",
+            "message",
+            "note",
+        ]) {
+            faker.sentence(6, 14)
+        } else {
+            faker.word()
+        }
+    }
+
+    fn hinted_range(hint: &str) -> Option<(f64, f64)> {
+        let hint = hint.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|word| hint.contains(word));
+        if has(&["age"]) {
+            Some((18.0, 80.0))
+        } else if has(&["lat"]) {
+            Some((-90.0, 90.0))
+        } else if has(&["lon", "lng"]) {
+            Some((-180.0, 180.0))
+        } else if has(&["year"]) {
+            Some((1970.0, 2026.0))
+        } else if has(&["rating", "stars"]) {
+            Some((1.0, 5.0))
+        } else if has(&["percent", "score"]) {
+            Some((0.0, 100.0))
+        } else if has(&["price", "amount", "total", "cost", "balance"]) {
+            Some((1.0, 1000.0))
+        } else if has(&["count", "quantity", "qty"]) {
+            Some((0.0, 100.0))
+        } else {
+            None
+        }
+    }
+
+    fn integer(&mut self, low: f64, high: f64) -> Value {
+        Value::from(self.faker.range(low.ceil() as i64, high.floor() as i64))
+    }
+
+    /// Rounded to two decimal places, like most prices and measurements.
+    fn number(&mut self, low: f64, high: f64) -> Value {
+        let value = (self.faker.float(low, high) * 100.0).round() / 100.0;
+        Value::from(value.clamp(low, high))
+    }
+
+    fn schema_string(&mut self, schema: &Map<String, Value>, hint: &str) -> String {
+        let faker = &mut self.faker;
+        let mut value = match schema.get("format").and_then(Value::as_str) {
+            Some("email" | "idn-email") => faker.email(),
+            Some("uri" | "url" | "iri") => faker.url(),
+            Some("date") => faker.date(),
+            Some("date-time") => faker.date_time(),
+            Some("time") => faker.date_time()[11..].to_string(),
+            Some("uuid") => faker.uuid(),
+            Some("ipv4") => faker.ipv4(),
+            Some("hostname" | "idn-hostname") => format!("{}.example.com", faker.word()),
+            _ => self.hinted_string(hint),
+        };
+        let length = |key| schema.get(key).and_then(Value::as_u64).map(|n| n as usize);
+        if let Some(min) = length("minLength") {
+            while value.chars().count() < min {
+                value.push(' ');
+                value.push_str(&self.faker.word());
+            }
+        }
+        if let Some(max) = length("maxLength") {
+            value = value.chars().take(max).collect();
+        }
+        value
+    }
+
+    fn bounds(schema: &Map<String, Value>, hint: &str) -> (f64, f64) {
+        let get = |key| schema.get(key).and_then(Value::as_f64);
+        let (default_low, default_high) = Self::hinted_range(hint).unwrap_or((1.0, 1000.0));
+        let low = get("minimum")
+            .or(get("exclusiveMinimum").map(|n| n + 1.0))
+            .unwrap_or(default_low);
+        let high = get("maximum")
+            .or(get("exclusiveMaximum").map(|n| n - 1.0))
+            .unwrap_or(default_high.max(low));
+        (low, high.max(low))
+    }
+
+    /// A schema made of every `allOf` branch, so their properties combine.
+    fn merge_all_of(&self, branches: &[Value]) -> Result<Value, MockError> {
+        let mut merged = Map::new();
+        for branch in branches {
+            let Some(branch) = self.resolve(branch)?.as_object() else {
+                continue;
+            };
+            for (key, value) in branch {
+                match (merged.get_mut(key), value) {
+                    (Some(Value::Object(existing)), Value::Object(value)) => {
+                        existing.extend(value.clone());
+                    }
+                    (Some(Value::Array(existing)), Value::Array(value)) => {
+                        existing.extend(value.iter().cloned());
+                    }
+                    _ => {
+                        merged.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        Ok(Value::Object(merged))
+    }
+
+    /// Follows a local `$ref` like `#/$defs/address`.
+    fn resolve<'s>(&'s self, schema: &'s Value) -> Result<&'s Value, MockError> {
+        let Some(reference) = schema.get("$ref").and_then(Value::as_str) else {
+            return Ok(schema);
+        };
+        reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.root.pointer(pointer))
+            .ok_or_else(|| MockError::UnresolvedRef(reference.to_string()))
+    }
+
+    fn schema_value(
+        &mut self,
+        schema: &Value,
+        hint: &str,
+        depth: usize,
+    ) -> Result<Value, MockError> {
+        let Value::Object(map) = schema else {
+            // `true` allows anything and `false` nothing
+            return Ok(match schema {
+                Value::Bool(true) => Value::String(self.hinted_string(hint)),
+                _ => Value::Null,
+            });
+        };
+        if map.contains_key("$ref") {
+            if depth >= MAX_DEPTH {
+                return Ok(Value::Null);
+            }
+            let target = self.resolve(schema)?.clone();
+            return self.schema_value(&target, hint, depth + 1);
+        }
+        if let Some(value) = map.get("const") {
+            return Ok(value.clone());
+        }
+        for key in ["enum", "examples"] {
+            if let Some(Value::Array(values)) = map.get(key)
+                && !values.is_empty()
+            {
+                return Ok(self.faker.pick(values).clone());
+            }
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(branches)) = map.get(key)
+                && !branches.is_empty()
+            {
+                let branch = self.faker.pick(branches).clone();
+                return self.schema_value(&branch, hint, depth);
+            }
+        }
+        if let Some(Value::Array(branches)) = map.get("allOf") {
+            let merged = self.merge_all_of(branches)?;
+            return self.schema_value(&merged, hint, depth);
+        }
+
+        let kind = match map.get("type") {
+            Some(Value::String(kind)) => kind.as_str(),
+            Some(Value::Array(kinds)) => kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|kind| *kind != "null")
+                .unwrap_or("null"),
+            _ if map.contains_key("properties") => "object",
+            _ if map.contains_key("items") => "array",
+            _ => "string",
+        };
+        Ok(match kind {
+            "object" => {
+                let required: Vec<&str> = map
+                    .get("required")
+                    .and_then(Value::as_array)
+                    .map(|keys| keys.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+                let mut object = Map::new();
+                if let Some(Value::Object(properties)) = map.get("properties") {
+                    for (key, property) in properties {
+                        // Optional properties show up three times in four
+                        if required.contains(&key.as_str()) || self.faker.range(0, 3) > 0 {
+                            object.insert(key.clone(), self.schema_value(property, key, depth)?);
+                        }
+                    }
+                }
+                Value::Object(object)
+            }
+            "array" => {
+                let get = |key| map.get(key).and_then(Value::as_i64);
+                let min = get("minItems").unwrap_or(1);
+                let max = get("maxItems").unwrap_or(min.max(1) + 2);
+                let count = self.faker.range(min, max.max(min));
+                let prefix = map.get("prefixItems").and_then(Value::as_array);
+                let items = map.get("items").cloned().unwrap_or(Value::Bool(true));
+                let mut values = Vec::new();
+                for i in 0..count as usize {
+                    let item = match (prefix.and_then(|prefix| prefix.get(i)), &items) {
+                        (Some(item), _) => item,
+                        // Draft 4-7 tuple form
+                        (None, Value::Array(tuple)) => match tuple.get(i) {
+                            Some(item) => item,
+                            None => break,
+                        },
+                        (None, item) => item,
+                    };
+                    let item = item.clone();
+                    values.push(self.schema_value(&item, hint, depth)?);
+                }
+                Value::Array(values)
+            }
+            "integer" => {
+                let (low, high) = Self::bounds(map, hint);
+                self.integer(low, high)
+            }
+            "number" => {
+                let (low, high) = Self::bounds(map, hint);
+                self.number(low, high)
+            }
+            "boolean" => Value::Bool(self.faker.bool()),
+            "null" => Value::Null,
+            _ => Value::String(self.schema_string(map, hint)),
+        })
+    }
+
+    /// Another value shaped like `sample`, keeping recognisable string
+    /// formats and number magnitudes.
+    fn sample_value(&mut self, sample: &Value, hint: &str) -> Value {
+        match sample {
+            Value::Null => Value::Null,
+            Value::Bool(_) => Value::Bool(self.faker.bool()),
+            Value::Number(number) => {
+                let sample = number.as_f64().unwrap_or_default();
+                let limit = (sample.abs() * 2.0).max(10.0);
+                // Keep the sign, and keep positive counts and ids above zero
+                let low = match sample {
+                    sample if sample < 0.0 => -limit,
+                    sample if sample >= 1.0 => 1.0,
+                    _ => 0.0,
+                };
+                let (low, high) = Self::hinted_range(hint).unwrap_or((low, limit));
+                match number.is_f64() {
+                    true => self.number(low, high),
+                    false => self.integer(low, high),
+                }
+            }
+            Value::String(text) => {
+                let faker = &mut self.faker;
+                let bytes = text.as_bytes();
+                let is_date = bytes.len() >= 10
+                    && bytes[4] == b'-'
+                    && bytes[7] == b'-'
+                    && text[..4].chars().all(|c| c.is_ascii_digit());
+                Value::String(if text.contains('@') && !text.contains(' ') {
+                    faker.email()
+                } else if uuid::Uuid::parse_str(text).is_ok() {
+                    faker.uuid()
+                } else if is_date && text.len() == 10 {
+                    faker.date()
+                } else if is_date && text.as_bytes()[10] == b'T' {
+                    faker.date_time()
+                } else if text.starts_with("http://") || text.starts_with("https://") {
+                    faker.url()
+                } else if text.starts_with('#') && text.len() == 7 {
+                    faker.hex_color()
+                } else {
+                    self.hinted_string(hint)
+                })
+            }
+            Value::Array(items) if items.is_empty() => Value::Array(Vec::new()),
+            Value::Array(items) => {
+                let count = self.faker.range(1, items.len().max(3) as i64);
+                let values = (0..count)
+                    .map(|_| {
+                        let item = self.faker.pick(items);
+                        self.sample_value(item, hint)
+                    })
+                    .collect();
+                Value::Array(values)
+            }
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), self.sample_value(value, key)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Generates fake instances of the JSON Schema or sample object in the
+/// buffer: `/json-mock [count] [--seed <n>]`. A single instance is printed
+/// as is, several as an array. Without a seed every run differs.
+pub fn json_mock(buffer: &str, args: &[&str]) -> Result<String, MockError> {
+    let mut count = 1;
+    let mut seed = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--seed" => {
+                let value = args.next().copied().unwrap_or_default();
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| MockError::InvalidSeed(value.to_string()))?,
+                );
+            }
+            arg if arg.starts_with(|c: char| c.is_ascii_digit()) => {
+                count = arg
+                    .parse()
+                    .ok()
+                    .filter(|count| (1..=MAX_COUNT).contains(count))
+                    .ok_or_else(|| MockError::InvalidCount(arg.to_string()))?;
+            }
+            arg => return Err(MockError::UnknownOption(arg.to_string())),
+        }
+    }
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+
+    let input: Value = serde_json::from_str(&strip_jsonc(buffer))
+        .map_err(|e| MockError::InvalidJson(e.to_string()))?;
+    let schema = is_schema(&input);
+    let mut mock = Mock {
+        faker: Faker::new(seed),
+        root: &input,
+    };
+    let mut instances = Vec::with_capacity(count);
+    for _ in 0..count {
+        instances.push(match schema {
+            true => mock.schema_value(&input, "", 0)?,
+            false => mock.sample_value(&input, ""),
+        });
+    }
+    let output = match count {
+        1 => instances.remove(0),
+        _ => Value::Array(instances),
+    };
+    serde_json::to_string_pretty(&output).map_err(|e| MockError::InvalidJson(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r##"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "required": ["id", "email", "age", "role", "tags", "address"],
+        "properties": {
+            "id": {"type": "string", "format": "uuid"},
+            "email": {"type": "string"},
+            "age": {"type": "integer", "minimum": 18, "maximum": 30},
+            "role": {"enum": ["admin", "user"]},
+            "tags": {"type": "array", "items": {"type": "string"}, "minItems": 2, "maxItems": 2},
+            "address": {"$ref": "#/$defs/address"}
+        },
+        "$defs": {
+            "address": {
+                "type": "object",
+                "required": ["city"],
+                "properties": {"city": {"type": "string"}}
+            }
+        }
+    }"##;
+
+    #[test]
+    fn test_json_mock_schema() {
+        let output = json_mock(SCHEMA, &["3", "--seed", "1"]).unwrap();
+        assert_eq!(output, json_mock(SCHEMA, &["3", "--seed", "1"]).unwrap());
+        let Value::Array(users) = serde_json::from_str(&output).unwrap() else {
+            panic!("expected an array of instances");
+        };
+        assert_eq!(users.len(), 3);
+        for user in users {
+            assert!(uuid::Uuid::parse_str(user["id"].as_str().unwrap()).is_ok());
+            assert!(user["email"].as_str().unwrap().contains('@'));
+            assert!((18..=30).contains(&user["age"].as_i64().unwrap()));
+            assert!(["admin", "user"].contains(&user["role"].as_str().unwrap()));
+            assert_eq!(user["tags"].as_array().unwrap().len(), 2);
+            assert!(user["address"]["city"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_json_mock_sample() {
+        let sample = r#"{"name": "Ada", "createdAt": "2024-01-02T03:04:05Z", "score": 1.5, "ok": true, "tags": ["x"]}"#;
+        let output = json_mock(sample, &["--seed", "9"]).unwrap();
+        let user: Value = serde_json::from_str(&output).unwrap();
+        assert!(user["name"].as_str().unwrap().contains(' '));
+        assert!(user["createdAt"].as_str().unwrap().ends_with('Z'));
+        assert!(user["score"].is_f64());
+        assert!(user["ok"].is_boolean());
+        assert!(!user["tags"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_json_mock_errors() {
+        assert!(matches!(
+            json_mock("{}", &["0"]),
+            Err(MockError::InvalidCount(_))
+        ));
+        assert!(matches!(
+            json_mock("{}", &["--seed", "abc"]),
+            Err(MockError::InvalidSeed(_))
+        ));
+        assert!(matches!(
+            json_mock(r##"{"$ref": "other.json#/x"}"##, &[]),
+            Err(MockError::UnresolvedRef(_))
+        ));
+    }
+}
@@ -1,11 +1,107 @@
+mod archive;
 mod base64;
+mod binary_formats;
+mod browsers;
+mod changelog;
+mod cipher;
+mod codegen;
+mod compress;
+mod crypto;
 mod css;
+mod curl;
+mod data_uri;
+mod der;
+mod dotenv;
+mod dump;
+mod emoji;
+mod fake;
+mod frame;
+mod hash;
+mod headers;
+mod html;
+mod ids;
+mod invisibles;
 mod json;
+mod json_patch;
+mod jsonld;
+mod jwt;
+mod keys;
+mod license;
+mod markdown;
+mod mock;
+mod output;
+mod registry;
+mod scaffold;
+mod secrets;
+mod slug;
+mod spell;
+mod tailwind;
+mod template;
+mod time;
 mod unicode;
+mod units;
 mod url;
+mod x509;
 
-pub use base64::{base64_decode, base64_encode};
-pub use css::{css_format, css_minify};
-pub use json::{json_format, json_minify};
-pub use unicode::{unicode_escape, unicode_unescape};
-pub use url::{url_decode, url_encode};
+#[cfg(all(test, feature = "extended-tests"))]
+mod roundtrip_tests;
+#[cfg(all(test, feature = "extended-tests"))]
+mod snapshot_tests;
+
+pub use archive::{cpio_inspect, tar_header};
+pub use base64::{base64_decode, base64_encode, base64_encode_with, base64_split};
+pub use binary_formats::{
+    cbor_to_json, flatbuffer_inspect, msgpack_to_json, protobuf_decode, thrift_decode,
+};
+pub use changelog::changelog_format;
+pub use cipher::{caesar, rot13};
+pub use codegen::{json_to_go, json_to_rust, json_to_typescript};
+pub use compress::{deflate, gunzip, gzip, inflate, zstd_compress, zstd_decompress};
+pub use crypto::{CryptoError, decrypt, decrypt_text, encrypt, encrypt_text};
+pub use css::{
+    CssOptions, css_compile, css_extract_colors, css_extract_vars, css_flatten, css_format,
+    css_minify, css_vars,
+};
+pub use curl::{curl_generate, curl_parse};
+pub use data_uri::{DataUri, data_uri_decode, guess_mime, image_info, to_data_uri};
+pub use dotenv::{env_to_json, json_to_env};
+pub use dump::parse_dump;
+pub use emoji::{emoji_decode, emoji_encode};
+pub use frame::draw_box;
+pub use hash::{HashAlgorithm, Hasher, hash_reader, sha256_hex, verify_digest};
+pub use headers::headers_format;
+pub use html::{html_head, robots_meta};
+pub use ids::uuid_inspect;
+pub use invisibles::{count_invisibles, reveal_invisibles, show_invisibles, strip_invisibles};
+pub use json::{
+    array_to_ndjson, expand_json_keys, json_format, json_minify, minify_json_keys, ndjson_format,
+    ndjson_to_array,
+};
+pub use json_patch::{json_diff, json_merge_patch, json_patch};
+pub use jsonld::json_ld;
+pub use jwt::jwt_decode;
+pub use keys::{key_inspect, pem_to_der};
+pub use license::{license_detect, license_text};
+pub use markdown::{html_to_markdown, markdown_to_html};
+pub use mock::json_mock;
+pub use output::CommandOutput;
+pub use registry::{TRANSFORMS, Transform, TransformError, find_transform, transform};
+pub use scaffold::{editorconfig, gitignore};
+pub use secrets::{SecretError, password, random_string};
+pub use slug::{sanitize_filename, slugify};
+pub use spell::{Misspelling, spellcheck, spellfix};
+pub use tailwind::tailwind_sort;
+pub use template::{
+    TemplateError, fill_template, interpolate, interpolate_command, is_var_name, placeholders,
+    render_template, template_values,
+};
+pub use time::DateTime;
+pub use unicode::{
+    EscapeDialect, unicode_escape, unicode_escape_with, unicode_inspect, unicode_unescape,
+};
+pub use units::{bytes_human, convert_units, format_bytes, human_bytes};
+pub use url::{
+    UrlMode, idna_email, json_to_query, punycode_decode, punycode_encode, query_to_json,
+    url_decode, url_decode_with, url_encode, url_encode_with, url_normalize, url_parse,
+};
+pub use x509::cert_decode;
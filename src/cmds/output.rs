@@ -0,0 +1,56 @@
+use crate::detect::{ContentType, detect, suggest};
+
+/// What a transform produced: the text, its content type and the commands
+/// worth suggesting after it.
+#[derive(Debug)]
+pub struct CommandOutput {
+    pub text: String,
+    pub content_type: ContentType,
+    /// Commands worth running next, best first
+    pub suggestions: Vec<&'static str>,
+}
+
+impl CommandOutput {
+    /// Output that could be anything, like decoded base64, so its type is
+    /// detected.
+    pub fn new(text: String) -> Self {
+        let content_type = detect(&text);
+        Self::typed(text, content_type)
+    }
+
+    /// Output of a type the transform already knows, skipping detection.
+    pub fn typed(text: String, content_type: ContentType) -> Self {
+        let suggestions = suggest(&text, content_type).into_iter().collect();
+        Self {
+            text,
+            content_type,
+            suggestions,
+        }
+    }
+}
+
+impl From<String> for CommandOutput {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_output_tags_json() {
+        let output = CommandOutput::new("{\"a\": [1, 2]}".to_string());
+        assert_eq!(output.content_type, ContentType::Json);
+        assert_eq!(output.suggestions, ["/json-format"]);
+
+        let output = CommandOutput::new("plain words".to_string());
+        assert_eq!(output.content_type, ContentType::Text);
+        assert!(output.suggestions.is_empty());
+
+        // A known type isn't second-guessed: hex-looking text stays text
+        let output = CommandOutput::typed("deadbeef".to_string(), ContentType::Text);
+        assert!(output.suggestions.is_empty());
+    }
+}
@@ -0,0 +1,878 @@
+use std::fmt;
+
+use super::{
+    CssOptions, HashAlgorithm, array_to_ndjson, base64_decode, base64_encode_with, base64_split,
+    bytes_human, caesar, cbor_to_json, cert_decode, changelog_format, convert_units, cpio_inspect,
+    css_compile, css_extract_colors, css_extract_vars, css_flatten, css_format, css_minify,
+    css_vars, curl_generate, curl_parse, data_uri_decode, decrypt_text, deflate, draw_box,
+    editorconfig, emoji_decode, emoji_encode, encrypt_text, env_to_json, expand_json_keys,
+    flatbuffer_inspect, gitignore, gunzip, gzip, headers_format, html_head, html_to_markdown,
+    human_bytes, idna_email, inflate, json_diff, json_format, json_ld, json_merge_patch,
+    json_minify, json_mock, json_patch, json_to_env, json_to_go, json_to_query, json_to_rust,
+    json_to_typescript, jwt_decode, key_inspect, license_detect, license_text, markdown_to_html,
+    minify_json_keys, msgpack_to_json, ndjson_format, ndjson_to_array, password, pem_to_der,
+    protobuf_decode, punycode_decode, punycode_encode, query_to_json, random_string,
+    render_template, robots_meta, rot13, sanitize_filename, sha256_hex, show_invisibles, slugify,
+    strip_invisibles, tailwind_sort, tar_header, thrift_decode, unicode_escape_with,
+    unicode_inspect, unicode_unescape, url_decode_with, url_encode_with, url_normalize, url_parse,
+    uuid_inspect, verify_digest, zstd_compress, zstd_decompress,
+};
+use crate::cmds::CommandOutput;
+use crate::detect::ContentType;
+
+#[derive(Debug)]
+pub enum TransformError {
+    UnknownTransform(String),
+    EmptyInput,
+    Failed(String),
+}
+
+impl fmt::Display for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownTransform(name) => {
+                write!(f, "Unknown command '{}'", name)
+            }
+            Self::EmptyInput => {
+                write!(f, "Buffer is empty")
+            }
+            Self::Failed(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransformError {}
+
+impl TransformError {
+    /// The 1-based line and column the error points at, for parsers that
+    /// report one as `line N column M`.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        let Self::Failed(msg) = self else {
+            return None;
+        };
+        msg.match_indices("line ").find_map(|(start, _)| {
+            let rest = &msg[start + "line ".len()..];
+            let (line, rest) = rest.split_once(|c: char| !c.is_ascii_digit())?;
+            let column = rest
+                .trim_start_matches(',')
+                .trim_start()
+                .strip_prefix("column ")?;
+            let column: String = column.chars().take_while(char::is_ascii_digit).collect();
+            Some((line.parse().ok()?, column.parse().ok()?))
+        })
+    }
+}
+
+fn failed(err: impl fmt::Display) -> TransformError {
+    TransformError::Failed(err.to_string())
+}
+
+// Output types for transforms that always produce the same kind of text,
+// so it doesn't have to be detected afterwards
+
+fn json(text: String) -> CommandOutput {
+    CommandOutput::typed(text, ContentType::Json)
+}
+
+fn json_lines(text: String) -> CommandOutput {
+    CommandOutput::typed(text, ContentType::JsonLines)
+}
+
+fn css(text: String) -> CommandOutput {
+    CommandOutput::typed(text, ContentType::Css)
+}
+
+fn base64(text: String) -> CommandOutput {
+    CommandOutput::typed(text, ContentType::Base64)
+}
+
+fn plain(text: String) -> CommandOutput {
+    CommandOutput::typed(text, ContentType::Text)
+}
+
+/// Checks the input against the digest given as arguments. A mismatch is
+/// an error so scripts piping through `pomp` see a failing exit status.
+fn verify(
+    input: &str,
+    algorithm: HashAlgorithm,
+    args: &[&str],
+) -> Result<CommandOutput, TransformError> {
+    let expected = args.join(" ");
+    let verification = verify_digest(
+        input,
+        algorithm,
+        Some(expected.as_str()).filter(|e| !e.is_empty()),
+    )
+    .map_err(failed)?;
+    match verification.matches {
+        true => Ok(plain(verification.to_string())),
+        false => Err(failed(verification)),
+    }
+}
+
+/// A pure text-to-text command. The TUI runs these on its buffer, and
+/// library users can run them through [`transform`].
+pub struct Transform {
+    /// Command name including the leading slash, e.g. `/json-format`
+    pub name: &'static str,
+    pub help: &'static str,
+    /// Whether the transform reads its input; generators like `/uuid` don't
+    pub needs_input: bool,
+    run: fn(&str, &[&str]) -> Result<CommandOutput, TransformError>,
+}
+
+impl Transform {
+    pub fn apply(&self, input: &str, args: &[&str]) -> Result<CommandOutput, TransformError> {
+        if self.needs_input && input.is_empty() {
+            return Err(TransformError::EmptyInput);
+        }
+        let mut output = (self.run)(input, args)?;
+        // Running the same command again is never a useful suggestion
+        output.suggestions.retain(|command| *command != self.name);
+        Ok(output)
+    }
+}
+
+pub const TRANSFORMS: &[Transform] = &[
+    Transform {
+        name: "/array-to-ndjson",
+        help: "Convert a JSON array into JSON Lines, one compact record per line",
+        needs_input: true,
+        run: |input, _| array_to_ndjson(input).map(json_lines).map_err(failed),
+    },
+    Transform {
+        name: "/base64-decode",
+        help: "Decode base64, ignoring line breaks; binary comes out as hex",
+        needs_input: true,
+        run: |input, _| {
+            base64_decode(input.trim())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/base64-encode",
+        help: "Encode text as base64; --mime wraps at 76 columns, --wrap=<width> at any width",
+        needs_input: true,
+        run: |input, args| base64_encode_with(input, args).map(base64).map_err(failed),
+    },
+    Transform {
+        name: "/base64-split",
+        help: "Re-wrap base64 text into lines of 76 (or the given number of) columns",
+        needs_input: true,
+        run: |input, args| {
+            base64_split(input, args.first().copied())
+                .map(base64)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/box",
+        help: "Frame the text in a box: single (default), double, rounded, heavy or ascii",
+        needs_input: true,
+        run: |input, args| {
+            draw_box(input, args.first().copied())
+                .map(plain)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/bytes-human",
+        help: "Format a byte count as a human-readable size (1.46 MiB)",
+        needs_input: true,
+        run: |input, _| bytes_human(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/caesar",
+        help: "Shift letters by N places (e.g. /caesar 3 or -3); with no shift, list all 25",
+        needs_input: true,
+        run: |input, args| {
+            caesar(input, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/cbor-to-json",
+        help: "Decode CBOR from a hex or base64 dump into JSON",
+        needs_input: true,
+        run: |input, _| cbor_to_json(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/cert-decode",
+        help: "Show subject, issuer, SANs, validity and fingerprint of a PEM certificate or CSR",
+        needs_input: true,
+        run: |input, _| cert_decode(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/changelog-format",
+        help: "Group conventional commits into a Markdown changelog",
+        needs_input: true,
+        run: |input, args| {
+            changelog_format(input, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/convert",
+        help: "Convert units, e.g. /convert ms s or /convert celsius fahrenheit",
+        needs_input: true,
+        run: |input, args| {
+            convert_units(input, args.first().copied(), args.get(1).copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/cpio-inspect",
+        help: "List the members of a cpio archive from a hex or base64 dump",
+        needs_input: true,
+        run: |input, _| cpio_inspect(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/css-compile",
+        help: "Lower nesting, add prefixes and transpile for --targets \"last 2 versions\"",
+        needs_input: true,
+        run: |input, args| css_compile(input, args).map(css).map_err(failed),
+    },
+    Transform {
+        name: "/css-extract-colors",
+        help: "List every color used in the CSS with how often it appears",
+        needs_input: true,
+        run: |input, _| {
+            css_extract_colors(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/css-extract-vars",
+        help: "List custom properties with their values and the declarations that use them",
+        needs_input: true,
+        run: |input, _| {
+            css_extract_vars(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/css-flatten",
+        help: "Expand nested CSS rules into flat selectors for older toolchains",
+        needs_input: true,
+        run: |input, _| css_flatten(input).map(css).map_err(failed),
+    },
+    Transform {
+        name: "/css-format",
+        help: "Format CSS code; --indent <n|tab>",
+        needs_input: true,
+        run: |input, args| {
+            css_format(input, &CssOptions::parse(args).map_err(failed)?)
+                .map(css)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/css-minify",
+        help: "Minify CSS code; --targets <query> drops unneeded prefixes unless --no-vendor-prefix-removal",
+        needs_input: true,
+        run: |input, args| {
+            css_minify(input, &CssOptions::parse(args).map_err(failed)?)
+                .map(css)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/css-vars",
+        help: "List custom properties defined and used; `inline` substitutes resolvable var()s",
+        needs_input: true,
+        run: |input, args| {
+            css_vars(input, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/curl-generate",
+        help: "Build a curl command from a JSON request (method, url, headers, body)",
+        needs_input: true,
+        run: |input, _| curl_generate(input).map(plain).map_err(failed),
+    },
+    Transform {
+        name: "/curl-parse",
+        help: "Convert a curl command into JSON with its method, URL, headers and body",
+        needs_input: true,
+        run: |input, _| curl_parse(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/cuid",
+        help: "Generate a CUID (Collision-resistant Unique ID)",
+        needs_input: false,
+        run: |_, _| Ok(plain(cuid::cuid2())),
+    },
+    Transform {
+        name: "/data-uri-decode",
+        help: "Show a data: URI's MIME type, image dimensions and size; /save writes its payload",
+        needs_input: true,
+        run: |input, _| {
+            data_uri_decode(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/decrypt",
+        help: "Decrypt base64 from /encrypt with the passphrase (prompted if omitted)",
+        needs_input: true,
+        run: |input, args| {
+            decrypt_text(input, &args.join(" "))
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/deflate",
+        help: "Compress with zlib deflate (HTTP Content-Encoding: deflate) to base64; optional level 0-9",
+        needs_input: true,
+        run: |input, args| {
+            deflate(input, args.first().copied())
+                .map(base64)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/editorconfig",
+        help: "Generate a starter .editorconfig (e.g. indent=2 rust)",
+        needs_input: false,
+        run: |_, args| editorconfig(args).map(plain).map_err(failed),
+    },
+    Transform {
+        name: "/emoji-decode",
+        help: "Replace :shortcode: with emoji, e.g. :rocket: to 🚀",
+        needs_input: true,
+        run: |input, _| Ok(CommandOutput::new(emoji_decode(input))),
+    },
+    Transform {
+        name: "/emoji-encode",
+        help: "Replace emoji with their Slack/GitHub :shortcode:",
+        needs_input: true,
+        run: |input, _| Ok(CommandOutput::new(emoji_encode(input))),
+    },
+    Transform {
+        name: "/encrypt",
+        help: "Encrypt with a passphrase (XChaCha20-Poly1305, Argon2id) and output base64",
+        needs_input: true,
+        run: |input, args| {
+            encrypt_text(input, &args.join(" "))
+                .map(base64)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/env-to-json",
+        help: "Convert a .env file of KEY=VALUE lines to a JSON object",
+        needs_input: true,
+        run: |input, _| env_to_json(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/expand-json-keys",
+        help: "Restore keys shortened by /minify-json-keys, using the mapping after the payload",
+        needs_input: true,
+        run: |input, args| {
+            expand_json_keys(input, args.first().copied())
+                .map(json)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/flatbuffer-inspect",
+        help: "Dump FlatBuffers tables from a hex or base64 dump, without a schema",
+        needs_input: true,
+        run: |input, _| {
+            flatbuffer_inspect(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/gitignore",
+        help: "Generate a .gitignore for stacks (e.g. rust,node,macos)",
+        needs_input: false,
+        run: |_, args| gitignore(&args.join(",")).map(plain).map_err(failed),
+    },
+    Transform {
+        name: "/gunzip",
+        help: "Decompress gzip data given as base64 or a hex dump",
+        needs_input: true,
+        run: |input, _| gunzip(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/gzip",
+        help: "Compress with gzip to base64; optional level 0-9",
+        needs_input: true,
+        run: |input, args| {
+            gzip(input, args.first().copied())
+                .map(base64)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/headers-format",
+        help: "Normalize and sort a raw header block; summarize HSTS, CSP, CORS and duplicates",
+        needs_input: true,
+        run: |input, _| {
+            headers_format(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/html-head",
+        help: "Extract title, description, canonical, OpenGraph, Twitter cards and JSON-LD from HTML",
+        needs_input: true,
+        run: |input, _| html_head(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/html-to-markdown",
+        help: "Convert HTML to Markdown, dropping scripts, styles and the head",
+        needs_input: true,
+        run: |input, _| Ok(plain(html_to_markdown(input))),
+    },
+    Transform {
+        name: "/human-bytes",
+        help: "Parse a human-readable size (1.46 MiB) into bytes",
+        needs_input: true,
+        run: |input, _| human_bytes(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/idna-email",
+        help: "Convert email domains to punycode (IDNA), one address per line",
+        needs_input: true,
+        run: |input, _| idna_email(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/inflate",
+        help: "Decompress zlib or raw deflate data given as base64 or a hex dump",
+        needs_input: true,
+        run: |input, _| inflate(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/json-diff",
+        help: "Compare two JSON documents, or the buffer against its previous state; --patch for RFC 6902",
+        needs_input: true,
+        run: |input, args| {
+            json_diff(input, None, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/json-format",
+        help: "Format JSON with indentation; comments and trailing commas are accepted",
+        needs_input: true,
+        run: |input, _| json_format(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/json-ld",
+        help: "Pretty-print JSON-LD (raw or from HTML) and check schema.org required properties",
+        needs_input: true,
+        run: |input, _| json_ld(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/json-merge-patch",
+        help: "Apply an RFC 7386 merge patch, given after the document or as an argument",
+        needs_input: true,
+        run: |input, args| {
+            let patch = args.join(" ");
+            json_merge_patch(
+                input,
+                Some(patch.as_str()).filter(|patch| !patch.is_empty()),
+            )
+            .map(json)
+            .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/json-minify",
+        help: "Minify JSON by removing whitespace and any comments",
+        needs_input: true,
+        run: |input, _| json_minify(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/json-mock",
+        help: "Generate fake data from a JSON Schema or sample: /json-mock [count] [--seed n]",
+        needs_input: true,
+        run: |input, args| json_mock(input, args).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/json-patch",
+        help: "Apply an RFC 6902 JSON Patch, given after the document or as an argument",
+        needs_input: true,
+        run: |input, args| {
+            let patch = args.join(" ");
+            json_patch(
+                input,
+                Some(patch.as_str()).filter(|patch| !patch.is_empty()),
+            )
+            .map(json)
+            .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/json-to-env",
+        help: "Convert a JSON object to .env lines, quoting values and flattening nested keys with __",
+        needs_input: true,
+        run: |input, _| json_to_env(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/json-to-go",
+        help: "Generate Go structs with json tags from a JSON sample; optional root type name",
+        needs_input: true,
+        run: |input, args| {
+            json_to_go(input, args.first().copied())
+                .map(plain)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/json-to-query",
+        help: "Serialize a JSON object as a URL query string",
+        needs_input: true,
+        run: |input, _| json_to_query(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/json-to-rust",
+        help: "Generate serde structs from a JSON sample; optional root type name",
+        needs_input: true,
+        run: |input, args| {
+            json_to_rust(input, args.first().copied())
+                .map(plain)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/json-to-typescript",
+        help: "Generate TypeScript interfaces from a JSON sample; optional root type name",
+        needs_input: true,
+        run: |input, args| {
+            json_to_typescript(input, args.first().copied())
+                .map(plain)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/jwt-decode",
+        help: "Decode a JWT's header and payload (the signature is not verified)",
+        needs_input: true,
+        run: |input, _| jwt_decode(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/key-inspect",
+        help: "Show type, size and SHA-256 fingerprints of PEM public or private keys",
+        needs_input: true,
+        run: |input, _| key_inspect(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/license",
+        help: "Insert the canonical text of a license by SPDX id",
+        needs_input: false,
+        run: |_, args| {
+            license_text(args.first().copied())
+                .map(plain)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/license-detect",
+        help: "Detect which open-source license the buffer matches",
+        needs_input: true,
+        run: |input, _| Ok(plain(license_detect(input))),
+    },
+    Transform {
+        name: "/markdown-to-html",
+        help: "Convert Markdown (with GitHub tables and strikethrough) to HTML",
+        needs_input: true,
+        run: |input, _| Ok(plain(markdown_to_html(input))),
+    },
+    Transform {
+        name: "/minify-json-keys",
+        help: "Shorten JSON keys for compact payloads and emit the key mapping",
+        needs_input: true,
+        run: |input, _| minify_json_keys(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/msgpack-to-json",
+        help: "Decode MessagePack from a hex or base64 dump into JSON",
+        needs_input: true,
+        run: |input, _| msgpack_to_json(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/ndjson-format",
+        help: "Pretty-print each line of JSON Lines (NDJSON) with dividers between records",
+        needs_input: true,
+        run: |input, _| ndjson_format(input).map(json_lines).map_err(failed),
+    },
+    Transform {
+        name: "/ndjson-to-array",
+        help: "Collect JSON Lines (NDJSON) records into one JSON array",
+        needs_input: true,
+        run: |input, _| ndjson_to_array(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/password",
+        help: "Generate a password with letters, digits and symbols: /password [length]",
+        needs_input: false,
+        run: |_, args| password(args).map(plain).map_err(failed),
+    },
+    Transform {
+        name: "/pem-to-der",
+        help: "Extract the DER bytes of PEM blocks as base64 (or `hex`)",
+        needs_input: true,
+        run: |input, args| {
+            pem_to_der(input, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/protobuf-decode",
+        help: "Dump protobuf wire format from a hex or base64 dump, without a schema",
+        needs_input: true,
+        run: |input, _| {
+            protobuf_decode(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/punycode-decode",
+        help: "Show xn-- domains (or the hosts of URLs and emails) in Unicode",
+        needs_input: true,
+        run: |input, _| {
+            punycode_decode(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/punycode-encode",
+        help: "Convert internationalized domains, URLs or emails to their xn-- form",
+        needs_input: true,
+        run: |input, _| {
+            punycode_encode(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/query-to-json",
+        help: "Parse a query string (or URL) into JSON; repeated keys and key[] become arrays",
+        needs_input: true,
+        run: |input, _| query_to_json(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/random-string",
+        help: "Generate a random string: /random-string [length] [alnum|hex|base64url|symbols]",
+        needs_input: false,
+        run: |_, args| random_string(args).map(plain).map_err(failed),
+    },
+    Transform {
+        name: "/robots-meta",
+        help: "Show robots meta directives from HTML and whether the page is indexable",
+        needs_input: true,
+        run: |input, _| robots_meta(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/rot13",
+        help: "Rotate letters by 13 places (applying it twice restores the text)",
+        needs_input: true,
+        run: |input, _| Ok(CommandOutput::new(rot13(input))),
+    },
+    Transform {
+        name: "/sanitize-filename",
+        help: "Remove characters that aren't allowed in file names on Windows, macOS or Linux",
+        needs_input: true,
+        run: |input, _| Ok(plain(sanitize_filename(input))),
+    },
+    Transform {
+        name: "/sha-256",
+        help: "Generate SHA-256 hash of buffer contents",
+        needs_input: true,
+        run: |input, _| Ok(CommandOutput::typed(sha256_hex(input), ContentType::Hex)),
+    },
+    Transform {
+        name: "/show-invisibles",
+        help: "Reveal spaces, tabs, NBSPs, zero-width characters and BOMs as visible symbols",
+        needs_input: true,
+        run: |input, _| Ok(CommandOutput::new(show_invisibles(input))),
+    },
+    Transform {
+        name: "/slugify",
+        help: "Turn each line into a URL-safe slug, transliterating accents; optional separator",
+        needs_input: true,
+        run: |input, args| Ok(plain(slugify(input, args.first().copied()))),
+    },
+    Transform {
+        name: "/strip-invisibles",
+        help: "Remove zero-width, bidi and control characters and normalise unusual spaces",
+        needs_input: true,
+        run: |input, _| Ok(CommandOutput::new(strip_invisibles(input))),
+    },
+    Transform {
+        name: "/tailwind-sort",
+        help: "Sort Tailwind classes in the recommended order and drop duplicates",
+        needs_input: true,
+        run: |input, _| Ok(CommandOutput::new(tailwind_sort(input))),
+    },
+    Transform {
+        name: "/tar-header",
+        help: "Decode tar headers from a hex or base64 dump: names, modes, sizes, offsets",
+        needs_input: true,
+        run: |input, _| tar_header(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/template",
+        help: "Fill {{placeholder}} tokens: /template name=value ...; prompts for each in the TUI",
+        needs_input: true,
+        run: |input, args| {
+            render_template(input, args)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/thrift-decode",
+        help: "Dump Thrift compact protocol from a hex or base64 dump, without an IDL",
+        needs_input: true,
+        run: |input, _| thrift_decode(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/unicode-escape",
+        help: "Encode non-ASCII characters as unicode escapes for json, rust, python, css or html",
+        needs_input: true,
+        run: |input, args| {
+            unicode_escape_with(input, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/unicode-inspect",
+        help: "List each character with its code point, UTF-8 bytes, category and name",
+        needs_input: true,
+        run: |input, _| Ok(plain(unicode_inspect(input))),
+    },
+    Transform {
+        name: "/unicode-unescape",
+        help: "Decode unicode escape sequences (\\uXXXX)",
+        needs_input: true,
+        run: |input, _| {
+            unicode_unescape(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/url-decode",
+        help: "Decode URL-encoded text; form also reads + as a space",
+        needs_input: true,
+        run: |input, args| {
+            url_decode_with(input, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/url-encode",
+        help: "Encode text for URLs as a component, path, query, url (keeping separators) or form",
+        needs_input: true,
+        run: |input, args| {
+            url_encode_with(input, args.first().copied())
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+    Transform {
+        name: "/url-normalize",
+        help: "Canonicalize URLs: lowercase, punycode host, no default port, sorted query",
+        needs_input: true,
+        run: |input, _| url_normalize(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/url-parse",
+        help: "Break a URL into scheme, host, port, path, decoded query parameters and fragment",
+        needs_input: true,
+        run: |input, _| url_parse(input).map(json).map_err(failed),
+    },
+    Transform {
+        name: "/uuid",
+        help: "Generate a UUID v4",
+        needs_input: false,
+        run: |_, _| {
+            Ok(CommandOutput::typed(
+                uuid::Uuid::new_v4().to_string(),
+                ContentType::Uuid,
+            ))
+        },
+    },
+    Transform {
+        name: "/uuid-inspect",
+        help: "Show a UUID's version, variant and embedded timestamp",
+        needs_input: true,
+        run: |input, _| uuid_inspect(input).map(CommandOutput::new).map_err(failed),
+    },
+    Transform {
+        name: "/verify-md5",
+        help: "Check the buffer's MD5 against an expected digest",
+        needs_input: true,
+        run: |input, args| verify(input, HashAlgorithm::Md5, args),
+    },
+    Transform {
+        name: "/verify-sha1",
+        help: "Check the buffer's SHA-1 against an expected digest",
+        needs_input: true,
+        run: |input, args| verify(input, HashAlgorithm::Sha1, args),
+    },
+    Transform {
+        name: "/verify-sha256",
+        help: "Check the buffer's SHA-256 against an expected digest",
+        needs_input: true,
+        run: |input, args| verify(input, HashAlgorithm::Sha256, args),
+    },
+    Transform {
+        name: "/zstd-compress",
+        help: "Compress with zstd to base64",
+        needs_input: true,
+        run: |input, _| Ok(base64(zstd_compress(input))),
+    },
+    Transform {
+        name: "/zstd-decompress",
+        help: "Decompress zstd data given as base64 or a hex dump",
+        needs_input: true,
+        run: |input, _| {
+            zstd_decompress(input)
+                .map(CommandOutput::new)
+                .map_err(failed)
+        },
+    },
+];
+
+/// Looks up a transform by name, with or without the leading slash.
+pub fn find_transform(name: &str) -> Option<&'static Transform> {
+    let name = name.trim_start_matches('/');
+    TRANSFORMS
+        .iter()
+        .find(|transform| &transform.name[1..] == name)
+}
+
+/// Runs the named transform, e.g. `transform("json-format", input, &[])`,
+/// and returns just its text. [`Transform::apply`] also returns the detected
+/// content type and suggestions.
+pub fn transform(name: &str, input: &str, args: &[&str]) -> Result<String, TransformError> {
+    find_transform(name)
+        .ok_or_else(|| TransformError::UnknownTransform(name.to_string()))?
+        .apply(input, args)
+        .map(|output| output.text)
+}
@@ -0,0 +1,43 @@
+//! Property tests checking that each decoder inverts its encoder.
+
+use proptest::prelude::*;
+
+use super::{
+    base64_decode, base64_encode, unicode_escape, unicode_escape_with, unicode_unescape,
+    url_decode, url_decode_with, url_encode, url_encode_with,
+};
+
+proptest! {
+    #[test]
+    fn base64_roundtrip(input in any::<String>()) {
+        prop_assert_eq!(base64_decode(&base64_encode(&input)).unwrap(), input);
+    }
+
+    #[test]
+    fn url_roundtrip(input in any::<String>()) {
+        prop_assert_eq!(url_decode(&url_encode(&input)).unwrap(), input);
+    }
+
+    #[test]
+    fn url_form_roundtrip(input in any::<String>()) {
+        let encoded = url_encode_with(&input, Some("form")).unwrap();
+        prop_assert_eq!(url_decode_with(&encoded, Some("form")).unwrap(), input);
+    }
+
+    // Backslashes are passed through unescaped, so they can't round-trip
+    #[test]
+    fn unicode_roundtrip(input in "[^\\\\]*") {
+        prop_assert_eq!(unicode_unescape(&unicode_escape(&input)).unwrap(), input);
+    }
+
+    #[test]
+    fn unicode_rust_roundtrip(input in "[^\\\\]*") {
+        let escaped = unicode_escape_with(&input, Some("rust")).unwrap();
+        prop_assert_eq!(unicode_unescape(&escaped).unwrap(), input);
+    }
+
+    #[test]
+    fn unicode_escape_is_ascii(input in any::<String>()) {
+        prop_assert!(unicode_escape(&input).is_ascii());
+    }
+}
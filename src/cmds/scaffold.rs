@@ -0,0 +1,231 @@
+use std::fmt;
+
+const GITIGNORE_TEMPLATES: &[(&str, &str)] = &[
+    ("go", include_str!("templates/gitignore/go.gitignore")),
+    ("java", include_str!("templates/gitignore/java.gitignore")),
+    (
+        "jetbrains",
+        include_str!("templates/gitignore/jetbrains.gitignore"),
+    ),
+    ("linux", include_str!("templates/gitignore/linux.gitignore")),
+    ("macos", include_str!("templates/gitignore/macos.gitignore")),
+    ("node", include_str!("templates/gitignore/node.gitignore")),
+    (
+        "python",
+        include_str!("templates/gitignore/python.gitignore"),
+    ),
+    ("rust", include_str!("templates/gitignore/rust.gitignore")),
+    (
+        "vscode",
+        include_str!("templates/gitignore/vscode.gitignore"),
+    ),
+    (
+        "windows",
+        include_str!("templates/gitignore/windows.gitignore"),
+    ),
+];
+
+/// Per-language `.editorconfig` sections added when a stack is named.
+const EDITORCONFIG_SECTIONS: &[(&str, &str)] = &[
+    ("go", "[*.go]\nindent_style = tab\n"),
+    ("makefile", "[Makefile]\nindent_style = tab\n"),
+    ("markdown", "[*.md]\ntrim_trailing_whitespace = false\n"),
+    ("node", "[*.{js,jsx,ts,tsx,json}]\nindent_size = 2\n"),
+    ("python", "[*.py]\nindent_size = 4\n"),
+    ("rust", "[*.rs]\nindent_size = 4\n"),
+    ("yaml", "[*.{yml,yaml}]\nindent_size = 2\n"),
+];
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    MissingStacks,
+    UnknownStack(String, String),
+    InvalidOption(String),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingStacks => {
+                write!(
+                    f,
+                    "Missing stacks - expected a comma-separated list of {}",
+                    names(GITIGNORE_TEMPLATES)
+                )
+            }
+            Self::UnknownStack(stack, expected) => {
+                write!(
+                    f,
+                    "Unknown stack '{}' - expected one of {}",
+                    stack, expected
+                )
+            }
+            Self::InvalidOption(option) => {
+                write!(f, "Invalid option '{}'", option)
+            }
+        }
+    }
+}
+
+fn names(templates: &[(&str, &str)]) -> String {
+    templates
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn find_template(templates: &[(&str, &'static str)], name: &str) -> Option<&'static str> {
+    templates
+        .iter()
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name))
+        .map(|(_, template)| *template)
+}
+
+/// Concatenates the embedded templates for each stack in a list like
+/// `rust,node,macos`.
+pub fn gitignore(stacks: &str) -> Result<String, ScaffoldError> {
+    let stacks: Vec<&str> = stacks
+        .split([',', ' '])
+        .map(|stack| stack.trim())
+        .filter(|stack| !stack.is_empty())
+        .collect();
+
+    if stacks.is_empty() {
+        return Err(ScaffoldError::MissingStacks);
+    }
+
+    let sections = stacks
+        .iter()
+        .map(|stack| {
+            find_template(GITIGNORE_TEMPLATES, stack).ok_or_else(|| {
+                ScaffoldError::UnknownStack(stack.to_string(), names(GITIGNORE_TEMPLATES))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(sections.join("\n"))
+}
+
+/// Builds a starter `.editorconfig`. Arguments are either `key=value`
+/// overrides for the `[*]` section (with `indent=2`/`indent=tab` and
+/// `eol=crlf` shorthands) or stack names that add language sections.
+pub fn editorconfig(args: &[&str]) -> Result<String, ScaffoldError> {
+    let mut properties: Vec<(String, String)> = [
+        ("charset", "utf-8"),
+        ("end_of_line", "lf"),
+        ("indent_style", "space"),
+        ("indent_size", "4"),
+        ("insert_final_newline", "true"),
+        ("trim_trailing_whitespace", "true"),
+    ]
+    .iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    let mut sections = Vec::new();
+
+    let mut set = |key: &str, value: &str| match properties
+        .iter_mut()
+        .find(|(existing, _)| existing == key)
+    {
+        Some(property) => property.1 = value.to_string(),
+        None => properties.push((key.to_string(), value.to_string())),
+    };
+
+    for arg in args {
+        let Some((key, value)) = arg.split_once('=') else {
+            let section = find_template(EDITORCONFIG_SECTIONS, arg).ok_or_else(|| {
+                ScaffoldError::UnknownStack(arg.to_string(), names(EDITORCONFIG_SECTIONS))
+            })?;
+            sections.push(section);
+            continue;
+        };
+
+        if key.is_empty() || value.is_empty() {
+            return Err(ScaffoldError::InvalidOption(arg.to_string()));
+        }
+
+        match key {
+            "indent" if value == "tab" => set("indent_style", "tab"),
+            "indent" if value.chars().all(|c| c.is_ascii_digit()) => {
+                set("indent_style", "space");
+                set("indent_size", value);
+            }
+            "indent" => return Err(ScaffoldError::InvalidOption(arg.to_string())),
+            "eol" => set("end_of_line", value),
+            _ => set(key, value),
+        }
+    }
+
+    let mut config = String::from("root = true\n\n[*]\n");
+    for (key, value) in &properties {
+        config.push_str(&format!("{} = {}\n", key, value));
+    }
+    for section in sections {
+        config.push('\n');
+        config.push_str(section);
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_each_stack() {
+        for (name, template) in GITIGNORE_TEMPLATES {
+            assert_eq!(gitignore(name).unwrap(), *template);
+        }
+        let combined = gitignore("Rust, node macos").unwrap();
+        let rust = combined.find("# Rust").unwrap();
+        let node = combined.find("# Node").unwrap();
+        let macos = combined.find("# macOS").unwrap();
+        assert!(rust < node && node < macos);
+    }
+
+    #[test]
+    fn test_gitignore_unknown_and_missing() {
+        assert!(matches!(
+            gitignore("rust,cobol"),
+            Err(ScaffoldError::UnknownStack(stack, _)) if stack == "cobol"
+        ));
+        assert!(matches!(
+            gitignore(" , "),
+            Err(ScaffoldError::MissingStacks)
+        ));
+    }
+
+    #[test]
+    fn test_editorconfig_sections_and_overrides() {
+        let config = editorconfig(&[]).unwrap();
+        assert!(config.starts_with("root = true\n\n[*]\ncharset = utf-8\n"));
+        assert!(config.contains("indent_size = 4\n"));
+
+        let config = editorconfig(&["go", "indent=2", "eol=crlf", "max_line_length=100"]).unwrap();
+        assert!(config.contains("indent_size = 2\n"));
+        assert!(config.contains("end_of_line = crlf\n"));
+        assert!(config.contains("max_line_length = 100\n"));
+        assert!(config.ends_with("\n[*.go]\nindent_style = tab\n"));
+
+        let config = editorconfig(&["indent=tab"]).unwrap();
+        assert!(config.contains("indent_style = tab\n"));
+    }
+
+    #[test]
+    fn test_editorconfig_errors() {
+        assert!(matches!(
+            editorconfig(&["cobol"]),
+            Err(ScaffoldError::UnknownStack(stack, _)) if stack == "cobol"
+        ));
+        assert!(matches!(
+            editorconfig(&["indent=wide"]),
+            Err(ScaffoldError::InvalidOption(_))
+        ));
+        assert!(matches!(
+            editorconfig(&["=2"]),
+            Err(ScaffoldError::InvalidOption(_))
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+/// Symbols that survive shells, URLs and config files without quoting
+const SYMBOLS: &str = "!#%+-.:=?@^_~";
+const HEX: &str = "0123456789abcdef";
+
+const MAX_LENGTH: usize = 4096;
+const DEFAULT_STRING_LENGTH: usize = 32;
+const DEFAULT_PASSWORD_LENGTH: usize = 20;
+/// One character from each class
+const MIN_PASSWORD_LENGTH: usize = 4;
+
+#[derive(Debug)]
+pub enum SecretError {
+    /// The argument and the shortest length allowed
+    InvalidLength(String, usize),
+    UnknownCharset(String),
+}
+
+impl fmt::Display for SecretError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidLength(arg, min) => {
+                write!(
+                    f,
+                    "Invalid length '{}', expected {} to {}",
+                    arg, min, MAX_LENGTH
+                )
+            }
+            Self::UnknownCharset(name) => {
+                write!(
+                    f,
+                    "Unknown character set '{}', expected alnum, hex, base64url or symbols",
+                    name
+                )
+            }
+        }
+    }
+}
+
+/// A uniformly random index below `bound`, rejecting the values that would
+/// bias it towards the start.
+fn random_below(bound: usize) -> usize {
+    let bound = bound as u32;
+    let zone = u32::MAX - u32::MAX % bound;
+    loop {
+        let value = OsRng.next_u32();
+        if value < zone {
+            return (value % bound) as usize;
+        }
+    }
+}
+
+fn pick(charset: &[char]) -> char {
+    charset[random_below(charset.len())]
+}
+
+fn parse_length(arg: Option<&str>, default: usize, min: usize) -> Result<usize, SecretError> {
+    let Some(arg) = arg else {
+        return Ok(default);
+    };
+    arg.parse()
+        .ok()
+        .filter(|length| (min..=MAX_LENGTH).contains(length))
+        .ok_or_else(|| SecretError::InvalidLength(arg.to_string(), min))
+}
+
+fn charset(name: &str) -> Result<String, SecretError> {
+    Ok(match name {
+        "alnum" => [LOWER, UPPER, DIGITS].concat(),
+        "hex" => HEX.to_string(),
+        "base64url" => [UPPER, LOWER, DIGITS, "-_"].concat(),
+        "symbols" => [LOWER, UPPER, DIGITS, SYMBOLS].concat(),
+        _ => return Err(SecretError::UnknownCharset(name.to_string())),
+    })
+}
+
+/// `/random-string [length] [alnum|hex|base64url|symbols]`, from the OS's
+/// secure random source. The arguments can come in either order.
+pub fn random_string(args: &[&str]) -> Result<String, SecretError> {
+    let mut length = DEFAULT_STRING_LENGTH;
+    let mut chars = charset("alnum")?;
+    for arg in args {
+        match arg.starts_with(|c: char| c.is_ascii_digit()) {
+            true => length = parse_length(Some(arg), DEFAULT_STRING_LENGTH, 1)?,
+            false => chars = charset(arg)?,
+        }
+    }
+    let chars: Vec<char> = chars.chars().collect();
+    Ok((0..length).map(|_| pick(&chars)).collect())
+}
+
+/// `/password [length]`: at least one lowercase letter, uppercase letter,
+/// digit and symbol, in random positions.
+pub fn password(args: &[&str]) -> Result<String, SecretError> {
+    let length = parse_length(
+        args.first().copied(),
+        DEFAULT_PASSWORD_LENGTH,
+        MIN_PASSWORD_LENGTH,
+    )?;
+    let classes: Vec<Vec<char>> = [LOWER, UPPER, DIGITS, SYMBOLS]
+        .iter()
+        .map(|class| class.chars().collect())
+        .collect();
+    let all: Vec<char> = classes.concat();
+
+    let mut password: Vec<char> = classes.iter().map(|class| pick(class)).collect();
+    password.extend((MIN_PASSWORD_LENGTH..length).map(|_| pick(&all)));
+    // Fisher-Yates, so the guaranteed characters aren't always first
+    for i in (1..password.len()).rev() {
+        password.swap(i, random_below(i + 1));
+    }
+    Ok(password.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_string() {
+        let value = random_string(&[]).unwrap();
+        assert_eq!(value.len(), DEFAULT_STRING_LENGTH);
+        assert!(value.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        let value = random_string(&["hex", "64"]).unwrap();
+        assert_eq!(value.len(), 64);
+        assert!(value.chars().all(|c| HEX.contains(c)));
+        assert_ne!(value, random_string(&["64", "hex"]).unwrap());
+
+        assert!(matches!(
+            random_string(&["0"]),
+            Err(SecretError::InvalidLength(_, 1))
+        ));
+        assert!(matches!(
+            random_string(&["emoji"]),
+            Err(SecretError::UnknownCharset(_))
+        ));
+    }
+
+    #[test]
+    fn test_password_has_every_class() {
+        for _ in 0..50 {
+            let value = password(&["4"]).unwrap();
+            assert_eq!(value.chars().count(), 4);
+            for class in [LOWER, UPPER, DIGITS, SYMBOLS] {
+                assert!(value.chars().any(|c| class.contains(c)), "{}", value);
+            }
+        }
+        assert_eq!(password(&[]).unwrap().len(), DEFAULT_PASSWORD_LENGTH);
+        assert!(password(&["3"]).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use icu_normalizer::DecomposingNormalizerBorrowed;
+
+/// Letters that don't decompose into an ASCII base plus accents.
+const TRANSLITERATIONS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('æ', "ae"),
+    ('œ', "oe"),
+    ('ø', "o"),
+    ('đ', "d"),
+    ('ð', "d"),
+    ('ł', "l"),
+    ('þ', "th"),
+    ('ı', "i"),
+    ('ħ', "h"),
+    ('ŋ', "ng"),
+    // Cyrillic, following the common Russian/Ukrainian romanisation
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('ґ', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('є', "ye"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('и', "i"),
+    ('і', "i"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ъ', ""),
+    ('ы', "y"),
+    ('ь', ""),
+    ('э', "e"),
+    ('ю', "yu"),
+    ('я', "ya"),
+    // Greek
+    ('α', "a"),
+    ('β', "v"),
+    ('γ', "g"),
+    ('δ', "d"),
+    ('ε', "e"),
+    ('ζ', "z"),
+    ('η', "i"),
+    ('θ', "th"),
+    ('ι', "i"),
+    ('κ', "k"),
+    ('λ', "l"),
+    ('μ', "m"),
+    ('ν', "n"),
+    ('ξ', "x"),
+    ('ο', "o"),
+    ('π', "p"),
+    ('ρ', "r"),
+    ('σ', "s"),
+    ('ς', "s"),
+    ('τ', "t"),
+    ('υ', "y"),
+    ('φ', "f"),
+    ('χ', "ch"),
+    ('ψ', "ps"),
+    ('ω', "o"),
+];
+
+/// Characters Windows forbids in file names; `/` is also the only one Unix
+/// forbids, and macOS Finder shows `:` as `/`.
+const FILENAME_RESERVED: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension.
+const WINDOWS_DEVICES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Most file systems cap a name at 255 bytes.
+const MAX_FILENAME_BYTES: usize = 255;
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+fn slugify_line(line: &str, separator: &str) -> String {
+    let lowered = line.to_lowercase();
+    let decomposed = DecomposingNormalizerBorrowed::new_nfd().normalize(&lowered);
+    let mut spaced = String::new();
+    for c in decomposed.chars() {
+        if c.is_ascii_alphanumeric() {
+            spaced.push(c);
+        } else if is_combining_mark(c) || matches!(c, '\'' | '’') {
+            // Accents go and apostrophes join, so "don't" becomes "dont"
+        } else if let Some((_, ascii)) = TRANSLITERATIONS.iter().find(|(from, _)| *from == c) {
+            spaced.push_str(ascii);
+        } else if c == '&' {
+            spaced.push_str(" and ");
+        } else {
+            spaced.push(' ');
+        }
+    }
+    spaced
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Lowercase ASCII slugs, one per line, with accents stripped and Cyrillic
+/// and Greek romanised. Words are joined by `-` unless another separator
+/// is given.
+pub fn slugify(buffer: &str, separator: Option<&str>) -> String {
+    let separator = separator.unwrap_or("-");
+    buffer
+        .lines()
+        .map(|line| slugify_line(line, separator))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn sanitize_line(line: &str) -> String {
+    let name: String = line
+        .chars()
+        .filter(|c| !c.is_control() && !FILENAME_RESERVED.contains(c))
+        .collect();
+    // Windows drops trailing dots and spaces, which can collide two names
+    let mut name = name.trim().trim_end_matches(['.', ' ']).to_string();
+
+    let stem = name.split('.').next().unwrap_or_default();
+    if WINDOWS_DEVICES.contains(&stem.trim_end().to_lowercase().as_str()) {
+        name.insert(0, '_');
+    }
+    if name.is_empty() || name.chars().all(|c| c == '.') {
+        return "untitled".to_string();
+    }
+
+    if name.len() > MAX_FILENAME_BYTES {
+        // Keep a short extension and cut the stem on a character boundary
+        let extension = match name.rfind('.') {
+            Some(dot) if dot > 0 && name.len() - dot <= 16 => name[dot..].to_string(),
+            _ => String::new(),
+        };
+        let mut end = MAX_FILENAME_BYTES - extension.len();
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name = format!("{}{}", name[..end].trim_end_matches(['.', ' ']), extension);
+    }
+    name
+}
+
+/// Removes characters that are invalid in file names on Windows, macOS or
+/// Linux, one name per line, and fixes up names Windows would reject or
+/// silently alter.
+pub fn sanitize_filename(buffer: &str) -> String {
+    buffer
+        .lines()
+        .map(sanitize_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("Crème Brûlée: A How-To Guide!", None),
+            "creme-brulee-a-how-to-guide"
+        );
+        assert_eq!(
+            slugify("Straße & Ærø — don't", None),
+            "strasse-and-aero-dont"
+        );
+        assert_eq!(
+            slugify("Привет, мир\nΚαλημέρα", None),
+            "privet-mir\nkalimera"
+        );
+        assert_eq!(slugify("  Hello   World  ", Some("_")), "hello_world");
+        assert_eq!(slugify("日本", None), "");
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(
+            sanitize_filename("report: Q1/Q2 <final>?.pdf"),
+            "report Q1Q2 final.pdf"
+        );
+        assert_eq!(sanitize_filename("notes.txt. . "), "notes.txt");
+        assert_eq!(sanitize_filename("CON.txt\nnul"), "_CON.txt\n_nul");
+        assert_eq!(sanitize_filename("???\n.."), "untitled\nuntitled");
+        assert_eq!(sanitize_filename("tab\there"), "tabhere");
+
+        let long = format!("{}.tar.gz", "é".repeat(200));
+        let sanitized = sanitize_filename(&long);
+        assert!(sanitized.len() <= MAX_FILENAME_BYTES);
+        assert!(sanitized.ends_with("é.gz"));
+    }
+}
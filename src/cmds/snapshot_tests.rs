@@ -0,0 +1,40 @@
+//! Snapshot tests pinning formatter output. Review changes with
+//! `cargo insta review` after running the `extended-tests` suite.
+
+use insta::assert_snapshot;
+
+use super::{CssOptions, css_format, css_minify, json_format, json_minify};
+
+const JSON_SAMPLE: &str =
+    r#"{"name":"pomp","tags":["tui","data"],"nested":{"empty":[],"n":1.5,"ok":true,"none":null}}"#;
+
+const CSS_SAMPLE: &str = "
+    .card { color: #ff0000; margin: 0px 0px 0px 0px; }
+    .card:hover, .card:focus { background: rgba(0, 0, 0, 0.5) }
+    @media (max-width: 600px) { .card { display: none } }
+";
+
+#[test]
+fn test_json_format() {
+    assert_snapshot!(json_format(JSON_SAMPLE).unwrap());
+}
+
+#[test]
+fn test_json_minify() {
+    assert_snapshot!(json_minify(&json_format(JSON_SAMPLE).unwrap()).unwrap());
+}
+
+#[test]
+fn test_json_format_error() {
+    assert_snapshot!(json_format("{\"a\": }").unwrap_err().to_string());
+}
+
+#[test]
+fn test_css_format() {
+    assert_snapshot!(css_format(CSS_SAMPLE, &CssOptions::default()).unwrap());
+}
+
+#[test]
+fn test_css_minify() {
+    assert_snapshot!(css_minify(CSS_SAMPLE, &CssOptions::default()).unwrap());
+}
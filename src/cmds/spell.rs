@@ -0,0 +1,129 @@
+use unicase::UniCase;
+
+/// A word found in the misspelling dictionary, located by byte offsets into
+/// the checked text.
+#[derive(Debug, Clone)]
+pub struct Misspelling {
+    pub word: String,
+    pub start: usize,
+    pub end: usize,
+    pub suggestions: Vec<String>,
+}
+
+/// Tokens that look like code, paths, or addresses are skipped entirely so
+/// identifiers don't get "corrected".
+fn is_code_like(token: &str) -> bool {
+    token.contains("://")
+        || token.contains('@')
+        || token.contains('_')
+        || token.contains('/')
+        || token.contains('\\')
+        || token.contains("::")
+}
+
+/// Keeps the capitalization of the original word when applying a suggestion.
+fn match_case(original: &str, suggestion: &str) -> String {
+    if original.len() > 1 && original.chars().all(|c| c.is_uppercase()) {
+        return suggestion.to_uppercase();
+    }
+    let mut chars = suggestion.chars();
+    match (original.chars().next(), chars.next()) {
+        (Some(first), Some(suggested_first)) if first.is_uppercase() => {
+            suggested_first.to_uppercase().chain(chars).collect()
+        }
+        _ => suggestion.to_string(),
+    }
+}
+
+pub fn spellcheck(buffer: &str) -> Vec<Misspelling> {
+    let mut misspellings = Vec::new();
+    let mut token_start = None;
+
+    // Walk whitespace-separated tokens, then words within each token
+    for (index, ch) in buffer.char_indices().chain([(buffer.len(), ' ')]) {
+        if !ch.is_whitespace() {
+            token_start.get_or_insert(index);
+            continue;
+        }
+        let Some(start) = token_start.take() else {
+            continue;
+        };
+        let token = &buffer[start..index];
+        if is_code_like(token) {
+            continue;
+        }
+
+        let mut word_start = None;
+        for (offset, ch) in token.char_indices().chain([(token.len(), ' ')]) {
+            let is_word_char = ch.is_alphabetic() || (ch == '\'' && word_start.is_some());
+            if is_word_char {
+                word_start.get_or_insert(offset);
+                continue;
+            }
+            let Some(word_offset) = word_start.take() else {
+                continue;
+            };
+            let word = token[word_offset..offset].trim_end_matches('\'');
+            if word.chars().count() < 2 {
+                continue;
+            }
+            if let Some(corrections) = typos_dict::WORD.find(&UniCase::new(word)) {
+                let word_start = start + word_offset;
+                misspellings.push(Misspelling {
+                    word: word.to_string(),
+                    start: word_start,
+                    end: word_start + word.len(),
+                    suggestions: corrections
+                        .iter()
+                        .map(|correction| match_case(word, correction))
+                        .collect(),
+                });
+            }
+        }
+    }
+
+    misspellings
+}
+
+/// Replaces misspellings with their first suggestion. With `only`, just the
+/// misspelling at that (zero-based) index is fixed.
+pub fn spellfix(buffer: &str, misspellings: &[Misspelling], only: Option<usize>) -> String {
+    let mut fixed = buffer.to_string();
+    // Apply from the end so earlier byte offsets stay valid
+    for (index, misspelling) in misspellings.iter().enumerate().rev() {
+        if only.is_some_and(|only| only != index) {
+            continue;
+        }
+        if let Some(suggestion) = misspelling.suggestions.first() {
+            fixed.replace_range(misspelling.start..misspelling.end, suggestion);
+        }
+    }
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spellcheck_finds_and_fixes() {
+        let text = "We recieve the mesage at https://example.com/recieve";
+        let misspellings = spellcheck(text);
+        let words: Vec<&str> = misspellings.iter().map(|m| m.word.as_str()).collect();
+        assert_eq!(words, vec!["recieve", "mesage"]);
+        assert_eq!(
+            spellfix(text, &misspellings, None),
+            "We receive the message at https://example.com/recieve"
+        );
+        assert_eq!(
+            spellfix(text, &misspellings, Some(1)),
+            "We recieve the message at https://example.com/recieve"
+        );
+    }
+
+    #[test]
+    fn test_spellfix_keeps_capitalization() {
+        let misspellings = spellcheck("Recieve");
+        assert_eq!(spellfix("Recieve", &misspellings, None), "Receive");
+    }
+}
@@ -0,0 +1,710 @@
+/// Property groups in the order Tailwind emits their utilities, which is
+/// the order its Prettier plugin sorts classes by.
+const PROPERTY_ORDER: &[&str] = &[
+    "container",
+    "sr-only",
+    "pointer-events",
+    "visibility",
+    "position",
+    "inset",
+    "isolation",
+    "z-index",
+    "order",
+    "grid-column",
+    "grid-row",
+    "float",
+    "clear",
+    "margin",
+    "box-sizing",
+    "line-clamp",
+    "display",
+    "aspect-ratio",
+    "size",
+    "height",
+    "max-height",
+    "min-height",
+    "width",
+    "min-width",
+    "max-width",
+    "flex",
+    "flex-shrink",
+    "flex-grow",
+    "flex-basis",
+    "table-layout",
+    "caption-side",
+    "border-collapse",
+    "border-spacing",
+    "transform-origin",
+    "translate",
+    "rotate",
+    "skew",
+    "scale",
+    "transform",
+    "animation",
+    "cursor",
+    "touch-action",
+    "user-select",
+    "resize",
+    "scroll-snap-type",
+    "scroll-snap-align",
+    "scroll-snap-stop",
+    "scroll-margin",
+    "scroll-padding",
+    "list-style-position",
+    "list-style-type",
+    "list-style-image",
+    "appearance",
+    "columns",
+    "break",
+    "grid-auto-columns",
+    "grid-auto-flow",
+    "grid-auto-rows",
+    "grid-template-columns",
+    "grid-template-rows",
+    "flex-direction",
+    "flex-wrap",
+    "place-content",
+    "place-items",
+    "align-content",
+    "align-items",
+    "justify-content",
+    "justify-items",
+    "gap",
+    "space",
+    "divide-width",
+    "divide-style",
+    "divide-color",
+    "divide-opacity",
+    "place-self",
+    "align-self",
+    "justify-self",
+    "overflow",
+    "overscroll",
+    "scroll-behavior",
+    "text-overflow",
+    "hyphens",
+    "whitespace",
+    "text-wrap",
+    "word-break",
+    "border-radius",
+    "border-width",
+    "border-style",
+    "border-color",
+    "border-opacity",
+    "background-color",
+    "background-opacity",
+    "background-image",
+    "gradient-stops",
+    "box-decoration-break",
+    "background-size",
+    "background-attachment",
+    "background-clip",
+    "background-position",
+    "background-repeat",
+    "background-origin",
+    "fill",
+    "stroke",
+    "stroke-width",
+    "object-fit",
+    "object-position",
+    "padding",
+    "text-align",
+    "text-indent",
+    "vertical-align",
+    "font-family",
+    "font-size",
+    "font-weight",
+    "text-transform",
+    "font-style",
+    "font-variant-numeric",
+    "line-height",
+    "letter-spacing",
+    "text-color",
+    "text-opacity",
+    "text-decoration-line",
+    "text-decoration-color",
+    "text-decoration-style",
+    "text-decoration-thickness",
+    "text-underline-offset",
+    "font-smoothing",
+    "placeholder-color",
+    "caret-color",
+    "accent-color",
+    "opacity",
+    "background-blend-mode",
+    "mix-blend-mode",
+    "box-shadow",
+    "box-shadow-color",
+    "outline-style",
+    "outline-width",
+    "outline-offset",
+    "outline-color",
+    "ring-width",
+    "ring-color",
+    "ring-opacity",
+    "ring-offset-width",
+    "ring-offset-color",
+    "filter",
+    "backdrop-filter",
+    "transition-property",
+    "transition-delay",
+    "transition-duration",
+    "transition-timing-function",
+    "will-change",
+    "content",
+];
+
+/// Utilities whose group follows from the prefix alone. The longest
+/// matching prefix wins, so `scroll-m` beats `scroll`.
+const PREFIXES: &[(&str, &str)] = &[
+    ("pointer-events", "pointer-events"),
+    ("isolation", "isolation"),
+    ("inset", "inset"),
+    ("top", "inset"),
+    ("right", "inset"),
+    ("bottom", "inset"),
+    ("left", "inset"),
+    ("start", "inset"),
+    ("end", "inset"),
+    ("z", "z-index"),
+    ("order", "order"),
+    ("col", "grid-column"),
+    ("row", "grid-row"),
+    ("float", "float"),
+    ("clear", "clear"),
+    ("line-clamp", "line-clamp"),
+    ("aspect", "aspect-ratio"),
+    ("size", "size"),
+    ("max-h", "max-height"),
+    ("min-h", "min-height"),
+    ("h", "height"),
+    ("min-w", "min-width"),
+    ("max-w", "max-width"),
+    ("w", "width"),
+    ("shrink", "flex-shrink"),
+    ("grow", "flex-grow"),
+    ("basis", "flex-basis"),
+    ("table", "table-layout"),
+    ("caption", "caption-side"),
+    ("border-spacing", "border-spacing"),
+    ("origin", "transform-origin"),
+    ("translate", "translate"),
+    ("rotate", "rotate"),
+    ("skew", "skew"),
+    ("scale", "scale"),
+    ("animate", "animation"),
+    ("cursor", "cursor"),
+    ("touch", "touch-action"),
+    ("select", "user-select"),
+    ("resize", "resize"),
+    ("scroll-m", "scroll-margin"),
+    ("scroll-mx", "scroll-margin"),
+    ("scroll-my", "scroll-margin"),
+    ("scroll-mt", "scroll-margin"),
+    ("scroll-mr", "scroll-margin"),
+    ("scroll-mb", "scroll-margin"),
+    ("scroll-ml", "scroll-margin"),
+    ("scroll-p", "scroll-padding"),
+    ("scroll-px", "scroll-padding"),
+    ("scroll-py", "scroll-padding"),
+    ("scroll-pt", "scroll-padding"),
+    ("scroll-pr", "scroll-padding"),
+    ("scroll-pb", "scroll-padding"),
+    ("scroll-pl", "scroll-padding"),
+    ("scroll", "scroll-behavior"),
+    ("list-image", "list-style-image"),
+    ("appearance", "appearance"),
+    ("columns", "columns"),
+    ("auto-cols", "grid-auto-columns"),
+    ("grid-flow", "grid-auto-flow"),
+    ("auto-rows", "grid-auto-rows"),
+    ("grid-cols", "grid-template-columns"),
+    ("grid-rows", "grid-template-rows"),
+    ("place-content", "place-content"),
+    ("place-items", "place-items"),
+    ("place-self", "place-self"),
+    ("items", "align-items"),
+    ("justify-items", "justify-items"),
+    ("justify-self", "justify-self"),
+    ("justify", "justify-content"),
+    ("self", "align-self"),
+    ("gap", "gap"),
+    ("space", "space"),
+    ("overflow", "overflow"),
+    ("overscroll", "overscroll"),
+    ("hyphens", "hyphens"),
+    ("whitespace", "whitespace"),
+    ("rounded", "border-radius"),
+    ("from", "gradient-stops"),
+    ("via", "gradient-stops"),
+    ("to", "gradient-stops"),
+    ("box-decoration", "box-decoration-break"),
+    ("indent", "text-indent"),
+    ("align", "vertical-align"),
+    ("leading", "line-height"),
+    ("tracking", "letter-spacing"),
+    ("underline-offset", "text-underline-offset"),
+    ("placeholder", "placeholder-color"),
+    ("caret", "caret-color"),
+    ("accent", "accent-color"),
+    ("opacity", "opacity"),
+    ("mix-blend", "mix-blend-mode"),
+    ("blur", "filter"),
+    ("brightness", "filter"),
+    ("contrast", "filter"),
+    ("drop-shadow", "filter"),
+    ("grayscale", "filter"),
+    ("hue-rotate", "filter"),
+    ("invert", "filter"),
+    ("saturate", "filter"),
+    ("sepia", "filter"),
+    ("backdrop", "backdrop-filter"),
+    ("transition", "transition-property"),
+    ("delay", "transition-delay"),
+    ("duration", "transition-duration"),
+    ("ease", "transition-timing-function"),
+    ("will-change", "will-change"),
+];
+
+/// Variants in the order Tailwind registers them. A class with variants
+/// sorts after every class without any.
+const VARIANT_ORDER: &[&str] = &[
+    "first-letter",
+    "first-line",
+    "marker",
+    "selection",
+    "file",
+    "placeholder",
+    "backdrop",
+    "before",
+    "after",
+    "first",
+    "last",
+    "only",
+    "odd",
+    "even",
+    "first-of-type",
+    "last-of-type",
+    "only-of-type",
+    "visited",
+    "target",
+    "open",
+    "default",
+    "checked",
+    "indeterminate",
+    "placeholder-shown",
+    "autofill",
+    "optional",
+    "required",
+    "valid",
+    "invalid",
+    "in-range",
+    "out-of-range",
+    "read-only",
+    "empty",
+    "focus-within",
+    "hover",
+    "focus",
+    "focus-visible",
+    "active",
+    "enabled",
+    "disabled",
+    "group",
+    "peer",
+    "aria",
+    "data",
+    "ltr",
+    "rtl",
+    "motion-safe",
+    "motion-reduce",
+    "dark",
+    "print",
+    "sm",
+    "md",
+    "lg",
+    "xl",
+    "2xl",
+    "portrait",
+    "landscape",
+];
+
+const DISPLAY: &[&str] = &[
+    "block",
+    "inline-block",
+    "inline",
+    "flex",
+    "inline-flex",
+    "table",
+    "inline-table",
+    "table-caption",
+    "table-cell",
+    "table-column",
+    "table-column-group",
+    "table-footer-group",
+    "table-header-group",
+    "table-row-group",
+    "table-row",
+    "flow-root",
+    "grid",
+    "inline-grid",
+    "contents",
+    "list-item",
+    "hidden",
+];
+
+const FONT_WEIGHTS: &[&str] = &[
+    "thin",
+    "extralight",
+    "light",
+    "normal",
+    "medium",
+    "semibold",
+    "bold",
+    "extrabold",
+    "black",
+];
+
+const FONT_SIZES: &[&str] = &[
+    "xs", "sm", "base", "lg", "xl", "2xl", "3xl", "4xl", "5xl", "6xl", "7xl", "8xl", "9xl",
+];
+
+const LINE_STYLES: &[&str] = &["solid", "dashed", "dotted", "double", "hidden", "none"];
+
+const SHADOW_SIZES: &[&str] = &["sm", "md", "lg", "xl", "2xl", "inner", "none"];
+
+/// Whether an arbitrary value or theme key reads as a length rather than
+/// a color, e.g. `2`, `[3px]` or `px`.
+fn is_length(value: &str) -> bool {
+    let value = value.trim_start_matches('[');
+    value.is_empty() || value == "px" || value.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Matches `prefix` or `prefix-<value>`, returning the value.
+fn value_after<'a>(utility: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = utility.strip_prefix(prefix)?;
+    if rest.is_empty() {
+        Some(rest)
+    } else {
+        rest.strip_prefix('-')
+    }
+}
+
+fn spacing_group(utility: &str) -> Option<&'static str> {
+    let (head, _) = utility.split_once('-')?;
+    match head {
+        "m" | "mx" | "my" | "mt" | "mr" | "mb" | "ml" | "ms" | "me" => Some("margin"),
+        "p" | "px" | "py" | "pt" | "pr" | "pb" | "pl" | "ps" | "pe" => Some("padding"),
+        _ => None,
+    }
+}
+
+/// The property group of a utility with its variants, `!` and negative
+/// sign removed, or `None` for classes Tailwind doesn't generate.
+fn property_group(utility: &str) -> Option<&'static str> {
+    let group = match utility {
+        "container" => "container",
+        "sr-only" | "not-sr-only" => "sr-only",
+        "visible" | "invisible" | "collapse" => "visibility",
+        "static" | "fixed" | "absolute" | "relative" | "sticky" => "position",
+        "isolate" => "isolation",
+        "box-border" | "box-content" => "box-sizing",
+        "transform" | "transform-cpu" | "transform-gpu" | "transform-none" => "transform",
+        "truncate" | "text-ellipsis" | "text-clip" => "text-overflow",
+        "text-wrap" | "text-nowrap" | "text-balance" | "text-pretty" => "text-wrap",
+        "uppercase" | "lowercase" | "capitalize" | "normal-case" => "text-transform",
+        "italic" | "not-italic" => "font-style",
+        "ordinal" | "slashed-zero" | "lining-nums" | "oldstyle-nums" | "proportional-nums"
+        | "tabular-nums" | "diagonal-fractions" | "stacked-fractions" | "normal-nums" => {
+            "font-variant-numeric"
+        }
+        "underline" | "overline" | "line-through" | "no-underline" => "text-decoration-line",
+        "antialiased" | "subpixel-antialiased" => "font-smoothing",
+        "border-collapse" | "border-separate" => "border-collapse",
+        "flex-row" | "flex-row-reverse" | "flex-col" | "flex-col-reverse" => "flex-direction",
+        "flex-wrap" | "flex-wrap-reverse" | "flex-nowrap" => "flex-wrap",
+        "filter" | "filter-none" => "filter",
+        "outline" | "outline-none" => "outline-style",
+        "shadow" => "box-shadow",
+        "ring" | "ring-inset" => "ring-width",
+        "border" => "border-width",
+        _ if DISPLAY.contains(&utility) => "display",
+        _ => return prefixed_group(utility),
+    };
+    Some(group)
+}
+
+fn prefixed_group(utility: &str) -> Option<&'static str> {
+    if let Some(group) = spacing_group(utility) {
+        return Some(group);
+    }
+    if let Some(value) = value_after(utility, "text") {
+        return Some(match value {
+            "left" | "center" | "right" | "justify" | "start" | "end" => "text-align",
+            _ if FONT_SIZES.contains(&value) || is_length(value) => "font-size",
+            _ if value.starts_with("opacity") => "text-opacity",
+            _ => "text-color",
+        });
+    }
+    if let Some(value) = value_after(utility, "font") {
+        return Some(
+            if FONT_WEIGHTS.contains(&value)
+                || value
+                    .trim_start_matches('[')
+                    .starts_with(|c: char| c.is_ascii_digit())
+            {
+                "font-weight"
+            } else {
+                "font-family"
+            },
+        );
+    }
+    if let Some(value) = value_after(utility, "bg") {
+        return Some(match value {
+            "fixed" | "local" | "scroll" => "background-attachment",
+            "auto" | "cover" | "contain" => "background-size",
+            "none" => "background-image",
+            "bottom" | "center" | "left" | "left-bottom" | "left-top" | "right"
+            | "right-bottom" | "right-top" | "top" => "background-position",
+            _ if value.starts_with("gradient") => "background-image",
+            _ if value.starts_with("repeat") || value == "no-repeat" => "background-repeat",
+            _ if value.starts_with("clip") => "background-clip",
+            _ if value.starts_with("origin") => "background-origin",
+            _ if value.starts_with("blend") => "background-blend-mode",
+            _ if value.starts_with("opacity") => "background-opacity",
+            _ => "background-color",
+        });
+    }
+    if let Some(value) = value_after(utility, "border") {
+        // Side-specific forms like `border-t-2` or `border-x-red-500`
+        let value = value
+            .split_once('-')
+            .filter(|(side, _)| matches!(*side, "x" | "y" | "t" | "r" | "b" | "l" | "s" | "e"))
+            .map_or(value, |(_, rest)| rest);
+        let value = match value {
+            "x" | "y" | "t" | "r" | "b" | "l" | "s" | "e" => "",
+            value => value,
+        };
+        return Some(match value {
+            _ if LINE_STYLES.contains(&value) => "border-style",
+            _ if value.starts_with("opacity") => "border-opacity",
+            _ if is_length(value) => "border-width",
+            _ => "border-color",
+        });
+    }
+    if let Some(value) = value_after(utility, "divide") {
+        return Some(match value {
+            _ if value.starts_with('x') || value.starts_with('y') => "divide-width",
+            _ if LINE_STYLES.contains(&value) => "divide-style",
+            _ if value.starts_with("opacity") => "divide-opacity",
+            _ => "divide-color",
+        });
+    }
+    if let Some(value) = value_after(utility, "shadow") {
+        return Some(if SHADOW_SIZES.contains(&value) || value.starts_with('[') {
+            "box-shadow"
+        } else {
+            "box-shadow-color"
+        });
+    }
+    if let Some(value) = value_after(utility, "ring-offset") {
+        return Some(if is_length(value) {
+            "ring-offset-width"
+        } else {
+            "ring-offset-color"
+        });
+    }
+    if let Some(value) = value_after(utility, "ring") {
+        return Some(match value {
+            _ if value.starts_with("opacity") => "ring-opacity",
+            _ if is_length(value) => "ring-width",
+            _ => "ring-color",
+        });
+    }
+    if let Some(value) = value_after(utility, "outline") {
+        return Some(match value {
+            _ if value.starts_with("offset") => "outline-offset",
+            "dashed" | "dotted" | "double" => "outline-style",
+            _ if is_length(value) => "outline-width",
+            _ => "outline-color",
+        });
+    }
+    if let Some(value) = value_after(utility, "decoration") {
+        return Some(match value {
+            "clone" | "slice" => "box-decoration-break",
+            "solid" | "double" | "dotted" | "dashed" | "wavy" => "text-decoration-style",
+            "auto" | "from-font" => "text-decoration-thickness",
+            _ if is_length(value) => "text-decoration-thickness",
+            _ => "text-decoration-color",
+        });
+    }
+    if let Some(value) = value_after(utility, "stroke") {
+        return Some(if is_length(value) {
+            "stroke-width"
+        } else {
+            "stroke"
+        });
+    }
+    if let Some(value) = value_after(utility, "object") {
+        return Some(match value {
+            "contain" | "cover" | "fill" | "none" | "scale-down" => "object-fit",
+            _ => "object-position",
+        });
+    }
+    if let Some(value) = value_after(utility, "list") {
+        return Some(match value {
+            "inside" | "outside" => "list-style-position",
+            _ if value.starts_with("image") => "list-style-image",
+            _ => "list-style-type",
+        });
+    }
+    if let Some(value) = value_after(utility, "snap") {
+        return Some(match value {
+            "start" | "end" | "center" | "align-none" => "scroll-snap-align",
+            "normal" | "always" => "scroll-snap-stop",
+            _ => "scroll-snap-type",
+        });
+    }
+    if let Some(value) = value_after(utility, "break") {
+        return Some(match value {
+            "normal" | "words" | "all" | "keep" => "word-break",
+            _ => "break",
+        });
+    }
+    if let Some(value) = value_after(utility, "content") {
+        return Some(if value == "none" || value.starts_with('[') {
+            "content"
+        } else {
+            "align-content"
+        });
+    }
+    if value_after(utility, "flex").is_some() {
+        return Some("flex");
+    }
+    if value_after(utility, "fill").is_some() {
+        return Some("fill");
+    }
+    PREFIXES
+        .iter()
+        .filter(|(prefix, _)| value_after(utility, prefix).is_some())
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, group)| *group)
+}
+
+/// Splits `md:hover:!-mt-2` into its variants and the bare utility,
+/// leaving colons inside arbitrary values like `bg-[url(a:b)]` alone.
+fn split_variants(class: &str) -> (Vec<&str>, &str) {
+    let mut variants = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in class.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 => {
+                variants.push(&class[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let utility = class[start..]
+        .trim_start_matches('!')
+        .trim_start_matches('-');
+    (variants, utility)
+}
+
+fn variant_rank(variant: &str) -> usize {
+    let name = variant
+        .split_once(['-', '/', '['])
+        .filter(|(head, _)| matches!(*head, "group" | "peer" | "aria" | "data"))
+        .map_or(variant, |(head, _)| head);
+    VARIANT_ORDER
+        .iter()
+        .position(|known| *known == name)
+        .unwrap_or(VARIANT_ORDER.len())
+}
+
+/// Sort key: unknown classes first in their original order, then by
+/// variants (none first) and property group.
+fn sort_key(class: &str) -> Option<(Vec<usize>, usize)> {
+    let (variants, utility) = split_variants(class);
+    let group = property_group(utility)?;
+    let rank = PROPERTY_ORDER
+        .iter()
+        .position(|known| *known == group)
+        .unwrap_or(PROPERTY_ORDER.len());
+    let mut variants: Vec<usize> = variants.into_iter().map(variant_rank).collect();
+    variants.sort_unstable();
+    Some((variants, rank))
+}
+
+fn sort_classes(classes: &str) -> String {
+    let mut seen: Vec<&str> = Vec::new();
+    for class in classes.split_whitespace() {
+        if !seen.contains(&class) {
+            seen.push(class);
+        }
+    }
+    // Stable, so classes in the same group keep their relative order
+    seen.sort_by_key(|class| sort_key(class));
+    seen.join(" ")
+}
+
+/// Sorts the values of `class="..."`/`className="..."` attributes when
+/// the buffer is markup, and otherwise every line as a class list.
+pub fn tailwind_sort(buffer: &str) -> String {
+    let mut output = String::with_capacity(buffer.len());
+    let mut rest = buffer;
+    let mut found = false;
+    while let Some(start) = ["class=\"", "className=\""]
+        .iter()
+        .filter_map(|attribute| rest.find(attribute).map(|at| at + attribute.len()))
+        .min()
+    {
+        let Some(len) = rest[start..].find('"') else {
+            break;
+        };
+        found = true;
+        output.push_str(&rest[..start]);
+        output.push_str(&sort_classes(&rest[start..start + len]));
+        rest = &rest[start + len..];
+    }
+    if found {
+        output.push_str(rest);
+        return output;
+    }
+    buffer
+        .lines()
+        .map(sort_classes)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tailwind_sort_class_list() {
+        assert_eq!(
+            tailwind_sort(
+                "text-white px-4 sm:px-8 py-2 sm:py-3 bg-sky-700 hover:bg-sky-800 card flex px-4"
+            ),
+            "card flex bg-sky-700 px-4 py-2 text-white hover:bg-sky-800 sm:px-8 sm:py-3"
+        );
+        assert_eq!(
+            tailwind_sort("font-bold text-sm font-mono border-red-500 border-2 rounded -mt-2"),
+            "-mt-2 rounded border-2 border-red-500 font-mono text-sm font-bold"
+        );
+    }
+
+    #[test]
+    fn test_tailwind_sort_markup() {
+        assert_eq!(
+            tailwind_sort(
+                "<div class=\"p-4 block\"><a className=\"underline focus:ring dark:text-[#fff] text-lg\">"
+            ),
+            "<div class=\"block p-4\"><a className=\"text-lg underline focus:ring dark:text-[#fff]\">"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use super::json::strip_jsonc;
+
+#[derive(Debug)]
+pub enum TemplateError {
+    NoPlaceholders,
+    InvalidAssignment(String),
+    Missing(Vec<String>),
+    InvalidJson(String),
+    NotAnObject,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoPlaceholders => {
+                write!(f, "No {{{{placeholder}}}} tokens in the template")
+            }
+            Self::InvalidAssignment(arg) => {
+                write!(f, "Expected name=value, got '{}'", arg)
+            }
+            Self::Missing(names) => {
+                write!(f, "No value for {}", names.join(", "))
+            }
+            Self::InvalidJson(msg) => {
+                write!(f, "Invalid JSON - {}", msg)
+            }
+            Self::NotAnObject => {
+                write!(f, "Expected a JSON object of values")
+            }
+        }
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Splits the template into literal text and placeholder names, in order.
+/// Braces around anything that isn't a name stay literal.
+fn tokens(template: &str) -> Vec<(&str, Option<&str>)> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    let mut literal_start = 0;
+    let mut offset = 0;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            break;
+        };
+        let name = after[..close].trim();
+        let consumed = open + 2 + close + 2;
+        if is_name(name) {
+            tokens.push((&template[literal_start..offset + open], Some(name)));
+            literal_start = offset + consumed;
+            offset += consumed;
+            rest = &after[close + 2..];
+        } else {
+            offset += open + 1;
+            rest = &rest[open + 1..];
+        }
+    }
+    tokens.push((&template[literal_start..], None));
+    tokens
+}
+
+/// The distinct placeholder names in the order they first appear.
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, name) in tokens(template) {
+        if let Some(name) = name
+            && !names.iter().any(|seen| seen == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Replaces each `{{name}}` with its value. Placeholders without a value
+/// stay in the output as `{{name}}`.
+pub fn fill_template(template: &str, values: &BTreeMap<String, String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    for (literal, name) in tokens(template) {
+        filled.push_str(literal);
+        if let Some(name) = name {
+            match values.get(name) {
+                Some(value) => filled.push_str(value),
+                None => {
+                    filled.push_str("{{");
+                    filled.push_str(name);
+                    filled.push_str("}}");
+                }
+            }
+        }
+    }
+    filled
+}
+
+fn lookup<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    if let Some(found) = value.get(name) {
+        return Some(found);
+    }
+    name.split('.').try_fold(value, |value, key| match value {
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        value => value.get(key),
+    })
+}
+
+/// Values for the template's placeholders taken from a JSON object.
+/// Dotted names like `user.id` reach into nested objects and arrays;
+/// strings are inserted as they are and anything else as JSON.
+pub fn template_values(
+    template: &str,
+    json: &str,
+) -> Result<BTreeMap<String, String>, TemplateError> {
+    let value: Value = serde_json::from_str(&strip_jsonc(json))
+        .map_err(|e| TemplateError::InvalidJson(e.to_string()))?;
+    if !value.is_object() {
+        return Err(TemplateError::NotAnObject);
+    }
+    Ok(placeholders(template)
+        .into_iter()
+        .filter_map(|name| {
+            let found = match lookup(&value, &name)? {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            Some((name, found))
+        })
+        .collect())
+}
+
+/// Fills the template from `name=value` arguments, failing if any
+/// placeholder is left without a value.
+pub fn render_template(template: &str, args: &[&str]) -> Result<String, TemplateError> {
+    let names = placeholders(template);
+    if names.is_empty() {
+        return Err(TemplateError::NoPlaceholders);
+    }
+    let mut values = BTreeMap::new();
+    for arg in args {
+        let (name, value) = arg
+            .split_once('=')
+            .filter(|(name, _)| is_name(name))
+            .ok_or_else(|| TemplateError::InvalidAssignment(arg.to_string()))?;
+        values.insert(name.to_string(), value.to_string());
+    }
+    let missing: Vec<String> = names
+        .into_iter()
+        .filter(|name| !values.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(TemplateError::Missing(missing));
+    }
+    Ok(fill_template(template, &values))
+}
+
+/// Whether `name` can be used as a `$name` variable.
+pub fn is_var_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Replaces `$name` and `${name}` with the variable's value. Names that
+/// aren't set stay as they are, so `$1` in an awk script or a stray `$`
+/// passes through.
+pub fn interpolate(text: &str, vars: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let (name, consumed) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(close) => (&braced[..close], close + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match vars.get(name) {
+            Some(value) if is_var_name(name) => {
+                out.push_str(value);
+                rest = &after[consumed..];
+            }
+            _ => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Interpolates a command line unless its command is one of `verbatim`,
+/// whose arguments must reach it as typed.
+pub fn interpolate_command(
+    line: &str,
+    vars: &BTreeMap<String, String>,
+    verbatim: &[&str],
+) -> String {
+    let command = line.split_whitespace().next().unwrap_or("");
+    match vars.is_empty() || verbatim.contains(&command) {
+        true => line.to_string(),
+        false => interpolate(line, vars),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        let template =
+            r#"{"id": {{ id }}, "name": "{{name}}", "again": {{id}}, "x": "{{not valid}}"}"#;
+        assert_eq!(placeholders(template), ["id", "name"]);
+        assert_eq!(placeholders("{{{a}}}"), ["a"]);
+        assert!(placeholders("{{ }} and {{unclosed").is_empty());
+    }
+
+    #[test]
+    fn test_render_template() {
+        let template = "SELECT * FROM {{table}} WHERE id = {{ id }} -- {{table}}";
+        assert_eq!(
+            render_template(template, &["table=users", "id=42"]).unwrap(),
+            "SELECT * FROM users WHERE id = 42 -- users"
+        );
+        assert!(matches!(
+            render_template(template, &["table=users"]),
+            Err(TemplateError::Missing(names)) if names == ["id"]
+        ));
+        assert!(matches!(
+            render_template(template, &["oops"]),
+            Err(TemplateError::InvalidAssignment(_))
+        ));
+        assert!(matches!(
+            render_template("plain", &[]),
+            Err(TemplateError::NoPlaceholders)
+        ));
+    }
+
+    #[test]
+    fn test_template_values_from_json() {
+        let template = "{{name}} {{user.id}} {{tags.1}} {{active}} {{missing}}";
+        let values = template_values(
+            template,
+            r#"{"name": "Ada", "user": {"id": 7}, "tags": ["a", "b"], "active": true}"#,
+        )
+        .unwrap();
+        assert_eq!(fill_template(template, &values), "Ada 7 b true {{missing}}");
+        assert!(matches!(
+            template_values(template, "[1]"),
+            Err(TemplateError::NotAnObject)
+        ));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let vars = BTreeMap::from([
+            ("host".to_string(), "api.example.com".to_string()),
+            ("key".to_string(), "s3cret".to_string()),
+        ]);
+        assert_eq!(
+            interpolate("/pipe curl -su $key: https://${host}/v1", &vars),
+            "/pipe curl -su s3cret: https://api.example.com/v1"
+        );
+        assert_eq!(
+            interpolate("/pipe awk '{print $1}' $hostname $ ${key", &vars),
+            "/pipe awk '{print $1}' $hostname $ ${key"
+        );
+        assert!(is_var_name("api_key2"));
+        assert!(!is_var_name("2nd") && !is_var_name("a-b") && !is_var_name(""));
+    }
+
+    #[test]
+    fn test_interpolate_command() {
+        let vars = BTreeMap::from([
+            ("HOME".to_string(), "/nowhere".to_string()),
+            ("pass".to_string(), "s3cret".to_string()),
+        ]);
+        let verbatim = ["/pipe", "/encrypt"];
+        assert_eq!(
+            interpolate_command("/pipe echo $HOME", &vars, &verbatim),
+            "/pipe echo $HOME"
+        );
+        assert_eq!(
+            interpolate_command("  /encrypt $pass", &vars, &verbatim),
+            "  /encrypt $pass"
+        );
+        assert_eq!(
+            interpolate_command("/url-encode $pass", &vars, &verbatim),
+            "/url-encode s3cret"
+        );
+    }
+}
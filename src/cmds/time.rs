@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// A UTC calendar time, converted from a Unix timestamp without pulling in
+/// a date library using the days-to-civil algorithm from Howard Hinnant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl DateTime {
+    pub fn from_unix(secs: i64) -> Self {
+        let days = secs.div_euclid(86400);
+        let seconds_of_day = secs.rem_euclid(86400) as u32;
+
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year,
+            month,
+            day,
+            hour: seconds_of_day / 3600,
+            minute: seconds_of_day % 3600 / 60,
+            second: seconds_of_day % 60,
+        }
+    }
+}
+
+/// ISO 8601, e.g. `2024-02-29T12:34:56Z`.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_unix() {
+        assert_eq!(
+            DateTime::from_unix(1_709_210_096).to_string(),
+            "2024-02-29T12:34:56Z"
+        );
+        assert_eq!(DateTime::from_unix(0).to_string(), "1970-01-01T00:00:00Z");
+        assert_eq!(DateTime::from_unix(-1).to_string(), "1969-12-31T23:59:59Z");
+    }
+}
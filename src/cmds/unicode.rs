@@ -1,9 +1,39 @@
 use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::OnceLock;
+
+use icu_properties::props::GeneralCategory;
+use icu_properties::{CodePointMapData, PropertyNamesLong, PropertyNamesShort};
+use unicode_width::UnicodeWidthStr;
+
+/// Character names from the Unicode 14 database, one `XXXX;NAME` per line,
+/// for the blocks people actually paste: Latin, Greek, Cyrillic, combining
+/// marks, punctuation, symbols, box drawing, emoji and the invisible
+/// formatting characters. Controls use their ISO 6429 names.
+const UNICODE_NAMES: &str = include_str!("data/unicode_names.txt");
+
+/// The inspector lists this many characters before summarising the rest.
+const MAX_INSPECTED: usize = 2000;
+
+const JAMO_LEAD: &[&str] = &[
+    "G", "GG", "N", "D", "DD", "R", "M", "B", "BB", "S", "SS", "", "J", "JJ", "C", "K", "T", "P",
+    "H",
+];
+const JAMO_VOWEL: &[&str] = &[
+    "A", "AE", "YA", "YAE", "EO", "E", "YEO", "YE", "O", "WA", "WAE", "OE", "YO", "U", "WEO", "WE",
+    "WI", "YU", "EU", "YI", "I",
+];
+const JAMO_TAIL: &[&str] = &[
+    "", "G", "GG", "GS", "N", "NJ", "NH", "D", "L", "LG", "LM", "LB", "LS", "LT", "LP", "LH", "M",
+    "B", "BS", "S", "SS", "NG", "J", "C", "K", "T", "P", "H",
+];
 
 #[derive(Debug)]
 pub enum UnicodeEscapeError {
     InvalidEscapeSequence(String),
     InvalidCodePoint,
+    UnknownDialect(String),
 }
 
 impl fmt::Display for UnicodeEscapeError {
@@ -15,10 +45,108 @@ impl fmt::Display for UnicodeEscapeError {
             Self::InvalidCodePoint => {
                 write!(f, "invalid unicode code point")
             }
+            Self::UnknownDialect(name) => {
+                write!(
+                    f,
+                    "unknown escape dialect '{}' - expected json, rust, python, css, or html",
+                    name
+                )
+            }
         }
     }
 }
 
+/// The escape syntax of the language the output is pasted into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EscapeDialect {
+    /// `\uXXXX`, with surrogate pairs outside the BMP (also JavaScript, Java)
+    Json,
+    /// `\u{X}`
+    Rust,
+    /// `\xXX`, `\uXXXX`, or `\UXXXXXXXX`
+    Python,
+    /// `\XXXXXX`, always six digits so a following hex digit isn't absorbed
+    Css,
+    /// `&#xX;`
+    Html,
+}
+
+impl EscapeDialect {
+    pub fn parse(name: &str) -> Result<Self, UnicodeEscapeError> {
+        match name.to_lowercase().as_str() {
+            "json" | "js" | "javascript" | "java" => Ok(Self::Json),
+            "rust" | "rs" => Ok(Self::Rust),
+            "python" | "py" => Ok(Self::Python),
+            "css" => Ok(Self::Css),
+            "html" | "xml" => Ok(Self::Html),
+            _ => Err(UnicodeEscapeError::UnknownDialect(name.to_string())),
+        }
+    }
+
+    fn escape(self, ch: char, result: &mut String) {
+        let code_point = ch as u32;
+        match self {
+            Self::Json => {
+                let mut units = [0u16; 2];
+                for unit in ch.encode_utf16(&mut units) {
+                    result.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            Self::Rust => result.push_str(&format!("\\u{{{:x}}}", code_point)),
+            Self::Python if code_point <= 0xFF => {
+                result.push_str(&format!("\\x{:02x}", code_point));
+            }
+            Self::Python if code_point <= 0xFFFF => {
+                result.push_str(&format!("\\u{:04x}", code_point));
+            }
+            Self::Python => result.push_str(&format!("\\U{:08x}", code_point)),
+            Self::Css => result.push_str(&format!("\\{:06x}", code_point)),
+            Self::Html => result.push_str(&format!("&#x{:x};", code_point)),
+        }
+    }
+}
+
+/// Reads `count` hex digits of an escape sequence.
+fn read_hex(
+    chars: &mut Peekable<Chars>,
+    prefix: &str,
+    count: usize,
+) -> Result<u32, UnicodeEscapeError> {
+    let hex_digits: String = chars.by_ref().take(count).collect();
+    let invalid =
+        || UnicodeEscapeError::InvalidEscapeSequence(format!("\\{}{}", prefix, hex_digits));
+    if hex_digits.len() != count {
+        return Err(invalid());
+    }
+    u32::from_str_radix(&hex_digits, 16).map_err(|_| invalid())
+}
+
+/// Reads the rest of a `\u` escape: `XXXX` (combining a JSON surrogate pair
+/// with the `\uXXXX` after it) or Rust's `{X}`.
+fn read_u_escape(chars: &mut Peekable<Chars>) -> Result<char, UnicodeEscapeError> {
+    if chars.next_if_eq(&'{').is_some() {
+        let hex_digits: String = chars.by_ref().take_while(|c| *c != '}').collect();
+        let code_point = u32::from_str_radix(&hex_digits, 16).map_err(|_| {
+            UnicodeEscapeError::InvalidEscapeSequence(format!("\\u{{{}}}", hex_digits))
+        })?;
+        return char::from_u32(code_point).ok_or(UnicodeEscapeError::InvalidCodePoint);
+    }
+
+    let code_point = read_hex(chars, "u", 4)?;
+    if (0xD800..0xDC00).contains(&code_point) {
+        if chars.next() != Some('\\') || chars.next() != Some('u') {
+            return Err(UnicodeEscapeError::InvalidCodePoint);
+        }
+        let low = read_hex(chars, "u", 4)?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(UnicodeEscapeError::InvalidCodePoint);
+        }
+        let combined = 0x10000 + ((code_point - 0xD800) << 10) + (low - 0xDC00);
+        return char::from_u32(combined).ok_or(UnicodeEscapeError::InvalidCodePoint);
+    }
+    char::from_u32(code_point).ok_or(UnicodeEscapeError::InvalidCodePoint)
+}
+
 pub fn unicode_unescape(buffer: &str) -> Result<String, UnicodeEscapeError> {
     let mut result = String::new();
     let mut chars = buffer.chars().peekable();
@@ -26,27 +154,12 @@ pub fn unicode_unescape(buffer: &str) -> Result<String, UnicodeEscapeError> {
     while let Some(ch) = chars.next() {
         if ch == '\\' {
             match chars.next() {
-                Some('u') => {
-                    // Collect the next 4 hex digits
-                    let hex_digits: String = chars.by_ref().take(4).collect();
-
-                    if hex_digits.len() != 4 {
-                        return Err(UnicodeEscapeError::InvalidEscapeSequence(format!(
-                            "\\u{}",
-                            hex_digits
-                        )));
-                    }
-
-                    // Parse the hex value
-                    let code_point = u32::from_str_radix(&hex_digits, 16).map_err(|_| {
-                        UnicodeEscapeError::InvalidEscapeSequence(format!("\\u{}", hex_digits))
-                    })?;
-
-                    // Convert to char
-                    let unicode_char =
-                        char::from_u32(code_point).ok_or(UnicodeEscapeError::InvalidCodePoint)?;
-
-                    result.push(unicode_char);
+                Some('u') => result.push(read_u_escape(&mut chars)?),
+                Some('U') => {
+                    let code_point = read_hex(&mut chars, "U", 8)?;
+                    result.push(
+                        char::from_u32(code_point).ok_or(UnicodeEscapeError::InvalidCodePoint)?,
+                    );
                 }
                 Some('n') => result.push('\n'),
                 Some('r') => result.push('\r'),
@@ -67,25 +180,209 @@ pub fn unicode_unescape(buffer: &str) -> Result<String, UnicodeEscapeError> {
 }
 
 pub fn unicode_escape(buffer: &str) -> String {
-    let mut result = String::new();
+    escape_with(buffer, EscapeDialect::Json)
+}
+
+/// Escapes non-ASCII and control characters for the dialect named by
+/// `dialect`, defaulting to JSON.
+pub fn unicode_escape_with(
+    buffer: &str,
+    dialect: Option<&str>,
+) -> Result<String, UnicodeEscapeError> {
+    let dialect = dialect
+        .map(EscapeDialect::parse)
+        .transpose()?
+        .unwrap_or(EscapeDialect::Json);
+    Ok(escape_with(buffer, dialect))
+}
 
+fn escape_with(buffer: &str, dialect: EscapeDialect) -> String {
+    let mut result = String::new();
     for ch in buffer.chars() {
         if ch.is_ascii() && !ch.is_control() {
             result.push(ch);
         } else {
-            // Encode as \uXXXX
-            let code_point = ch as u32;
-            if code_point <= 0xFFFF {
-                result.push_str(&format!("\\u{:04x}", code_point));
-            } else {
-                // For code points beyond BMP, we need to use surrogate pairs or \U notation
-                // For simplicity, we'll use Rust's escape_unicode
-                for escaped_ch in ch.escape_unicode() {
-                    result.push(escaped_ch);
-                }
-            }
+            dialect.escape(ch, &mut result);
         }
     }
-
     result
 }
+
+fn names() -> &'static [(u32, &'static str)] {
+    static NAMES: OnceLock<Vec<(u32, &'static str)>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        UNICODE_NAMES
+            .lines()
+            .filter_map(|line| {
+                let (code, name) = line.split_once(';')?;
+                Some((u32::from_str_radix(code, 16).ok()?, name))
+            })
+            .collect()
+    })
+}
+
+/// The character's Unicode name, or `None` outside the bundled blocks.
+/// Ideographs and Hangul syllables are named by rule rather than listed.
+fn char_name(c: char, category: GeneralCategory) -> Option<String> {
+    let code = c as u32;
+    if category == GeneralCategory::Unassigned {
+        return None;
+    }
+    match code {
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0x20000..=0x2F7FF | 0x30000..=0x323AF => {
+            Some(format!("CJK UNIFIED IDEOGRAPH-{:04X}", code))
+        }
+        0xF900..=0xFAFF | 0x2F800..=0x2FA1F => {
+            Some(format!("CJK COMPATIBILITY IDEOGRAPH-{:04X}", code))
+        }
+        0xAC00..=0xD7A3 => {
+            let index = (code - 0xAC00) as usize;
+            let (lead, vowel, tail) = (index / 588, index % 588 / 28, index % 28);
+            Some(format!(
+                "HANGUL SYLLABLE {}{}{}",
+                JAMO_LEAD[lead], JAMO_VOWEL[vowel], JAMO_TAIL[tail]
+            ))
+        }
+        _ => {
+            let names = names();
+            let found = names.binary_search_by_key(&code, |(code, _)| *code).ok()?;
+            Some(names[found].1.to_string())
+        }
+    }
+}
+
+/// How the character shows in the table: control pictures for C0
+/// controls, a dotted circle under combining marks, and nothing for other
+/// characters that don't draw.
+fn glyph(c: char, category: GeneralCategory) -> String {
+    match category {
+        GeneralCategory::Control => match c as u32 {
+            code @ 0x00..=0x20 => char::from_u32(0x2400 + code).into_iter().collect(),
+            0x7F => "␡".to_string(),
+            _ => String::new(),
+        },
+        GeneralCategory::SpaceSeparator if c == ' ' => "␠".to_string(),
+        GeneralCategory::NonspacingMark | GeneralCategory::EnclosingMark => format!("◌{}", c),
+        GeneralCategory::Format
+        | GeneralCategory::SpaceSeparator
+        | GeneralCategory::LineSeparator
+        | GeneralCategory::ParagraphSeparator
+        | GeneralCategory::PrivateUse
+        | GeneralCategory::Surrogate
+        | GeneralCategory::Unassigned => String::new(),
+        _ => c.to_string(),
+    }
+}
+
+/// Lists each character in the buffer with its code point, UTF-8 bytes,
+/// general category and name, one row per character.
+pub fn unicode_inspect(buffer: &str) -> String {
+    let categories = CodePointMapData::<GeneralCategory>::new();
+    let short_names = PropertyNamesShort::<GeneralCategory>::new();
+    let long_names = PropertyNamesLong::<GeneralCategory>::new();
+
+    let rows: Vec<_> = buffer
+        .chars()
+        .take(MAX_INSPECTED)
+        .map(|c| {
+            let category = categories.get(c);
+            let mut utf8 = [0; 4];
+            let bytes: Vec<_> = c
+                .encode_utf8(&mut utf8)
+                .bytes()
+                .map(|b| format!("{:02X}", b))
+                .collect();
+            let category_name = format!(
+                "{} {}",
+                short_names.get(category).unwrap_or("?"),
+                long_names.get(category).unwrap_or("?").replace('_', " ")
+            );
+            (
+                glyph(c, category),
+                format!("U+{:04X}", c as u32),
+                bytes.join(" "),
+                category_name,
+                char_name(c, category).unwrap_or_else(|| "-".to_string()),
+            )
+        })
+        .collect();
+
+    let bytes_width = rows.iter().map(|row| row.2.len()).max().unwrap_or(0).max(5);
+    let category_width = rows.iter().map(|row| row.3.len()).max().unwrap_or(0).max(8);
+    let mut report = format!(
+        "CHAR  {:<10}  {:<bytes_width$}  {:<category_width$}  NAME",
+        "CODE POINT", "UTF-8", "CATEGORY"
+    );
+    for (glyph, code_point, bytes, category, name) in &rows {
+        // Pad by display width so wide characters and emoji stay aligned
+        let padding = 4usize.saturating_sub(glyph.width());
+        report.push_str(&format!(
+            "\n{}{}  {:<10}  {:<bytes_width$}  {:<category_width$}  {}",
+            glyph,
+            " ".repeat(padding),
+            code_point,
+            bytes,
+            category,
+            name
+        ));
+    }
+    let remaining = buffer.chars().count().saturating_sub(MAX_INSPECTED);
+    if remaining > 0 {
+        report.push_str(&format!("\n… and {} more characters", remaining));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_escape_dialects() {
+        let input = "é😀";
+        let escaped = |dialect| unicode_escape_with(input, Some(dialect)).unwrap();
+        assert_eq!(escaped("json"), "\\u00e9\\ud83d\\ude00");
+        assert_eq!(escaped("rust"), "\\u{e9}\\u{1f600}");
+        assert_eq!(escaped("python"), "\\xe9\\U0001f600");
+        assert_eq!(escaped("css"), "\\0000e9\\01f600");
+        assert_eq!(escaped("html"), "&#xe9;&#x1f600;");
+        assert!(unicode_escape_with(input, Some("cobol")).is_err());
+    }
+
+    #[test]
+    fn test_unicode_unescape_astral() {
+        assert_eq!(unicode_unescape("\\ud83d\\ude00").unwrap(), "😀");
+        assert_eq!(unicode_unescape("\\u{1f600}!").unwrap(), "😀!");
+        assert_eq!(unicode_unescape("\\U0001f600").unwrap(), "😀");
+        assert!(unicode_unescape("\\ud83d").is_err());
+    }
+
+    #[test]
+    fn test_unicode_inspect() {
+        let report = unicode_inspect("Aé\u{301}€\t😀한\u{200B}");
+        let rows: Vec<&str> = report.lines().collect();
+        assert_eq!(rows.len(), 9);
+        assert!(rows[0].starts_with("CHAR  CODE POINT  UTF-8"));
+        assert!(rows[1].starts_with("A     U+0041      41 "));
+        assert!(rows[1].ends_with("Lu Uppercase Letter  LATIN CAPITAL LETTER A"));
+        assert!(rows[2].contains("C3 A9") && rows[2].ends_with("LATIN SMALL LETTER E WITH ACUTE"));
+        assert!(rows[3].starts_with("◌\u{301}") && rows[3].contains("Mn Nonspacing Mark"));
+        assert!(rows[4].contains("E2 82 AC") && rows[4].ends_with("EURO SIGN"));
+        assert!(rows[5].starts_with("␉ ") && rows[5].ends_with("CHARACTER TABULATION"));
+        assert!(rows[6].contains("F0 9F 98 80") && rows[6].ends_with("GRINNING FACE"));
+        assert!(rows[7].ends_with("HANGUL SYLLABLE HAN"));
+        assert!(rows[8].starts_with("      U+200B") && rows[8].ends_with("ZERO WIDTH SPACE"));
+
+        // Every column after the glyph lines up, wide characters included
+        let column = |row: &str| row.find("U+").map(|i| row[..i].width());
+        assert!(rows[1..].iter().all(|row| column(row) == Some(6)));
+    }
+
+    #[test]
+    fn test_unicode_inspect_names_by_rule() {
+        assert!(unicode_inspect("中").ends_with("CJK UNIFIED IDEOGRAPH-4E2D"));
+        assert!(unicode_inspect("\u{E000}").ends_with("Co Private Use  -"));
+        let long = "x".repeat(MAX_INSPECTED + 5);
+        assert!(unicode_inspect(&long).ends_with("… and 5 more characters"));
+    }
+}
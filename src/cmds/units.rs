@@ -0,0 +1,321 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum UnitError {
+    InvalidNumber(String),
+    UnknownUnit(String),
+    IncompatibleUnits(String, String),
+    MissingUnits,
+}
+
+impl fmt::Display for UnitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidNumber(value) => {
+                write!(f, "Invalid number '{}'", value)
+            }
+            Self::UnknownUnit(unit) => {
+                write!(f, "Unknown unit '{}'", unit)
+            }
+            Self::IncompatibleUnits(from, to) => {
+                write!(f, "Cannot convert {} to {}", from, to)
+            }
+            Self::MissingUnits => {
+                write!(f, "Usage: /convert <from> <to> (e.g. /convert ms s)")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dimension {
+    Time,
+    Data,
+    Length,
+    Temperature,
+}
+
+/// Linear units as (names, dimension, size in the dimension's base unit).
+/// Temperatures are handled separately since they have an offset.
+const UNITS: &[(&[&str], Dimension, f64)] = &[
+    (&["ns", "nanosecond", "nanoseconds"], Dimension::Time, 1e-9),
+    (
+        &["us", "µs", "microsecond", "microseconds"],
+        Dimension::Time,
+        1e-6,
+    ),
+    (
+        &["ms", "millisecond", "milliseconds"],
+        Dimension::Time,
+        1e-3,
+    ),
+    (&["s", "sec", "second", "seconds"], Dimension::Time, 1.0),
+    (&["min", "minute", "minutes"], Dimension::Time, 60.0),
+    (&["h", "hr", "hour", "hours"], Dimension::Time, 3600.0),
+    (&["d", "day", "days"], Dimension::Time, 86400.0),
+    (&["bit", "bits"], Dimension::Data, 0.125),
+    (&["b", "byte", "bytes"], Dimension::Data, 1.0),
+    (&["kb"], Dimension::Data, 1e3),
+    (&["mb"], Dimension::Data, 1e6),
+    (&["gb"], Dimension::Data, 1e9),
+    (&["tb"], Dimension::Data, 1e12),
+    (&["kib"], Dimension::Data, 1024.0),
+    (&["mib"], Dimension::Data, 1048576.0),
+    (&["gib"], Dimension::Data, 1073741824.0),
+    (&["tib"], Dimension::Data, 1099511627776.0),
+    (&["mm"], Dimension::Length, 1e-3),
+    (&["cm"], Dimension::Length, 1e-2),
+    (&["m"], Dimension::Length, 1.0),
+    (&["km"], Dimension::Length, 1e3),
+    (&["in", "inch", "inches"], Dimension::Length, 0.0254),
+    (&["ft", "foot", "feet"], Dimension::Length, 0.3048),
+    (&["mi", "mile", "miles"], Dimension::Length, 1609.344),
+];
+
+const BINARY_PREFIXES: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Linear(Dimension, f64),
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl Unit {
+    fn dimension(&self) -> Dimension {
+        match self {
+            Self::Linear(dimension, _) => *dimension,
+            _ => Dimension::Temperature,
+        }
+    }
+}
+
+fn parse_unit(name: &str) -> Result<Unit, UnitError> {
+    let lower = name.to_lowercase();
+    match lower.as_str() {
+        "c" | "celsius" => return Ok(Unit::Celsius),
+        "f" | "fahrenheit" => return Ok(Unit::Fahrenheit),
+        "k" | "kelvin" => return Ok(Unit::Kelvin),
+        _ => {}
+    }
+    UNITS
+        .iter()
+        .find(|(names, _, _)| names.contains(&lower.as_str()))
+        .map(|(_, dimension, factor)| Unit::Linear(*dimension, *factor))
+        .ok_or_else(|| UnitError::UnknownUnit(name.to_string()))
+}
+
+fn parse_number(value: &str) -> Result<f64, UnitError> {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .filter(|c| *c != '_' && *c != ',')
+        .collect();
+    cleaned
+        .parse::<f64>()
+        .map_err(|_| UnitError::InvalidNumber(value.trim().to_string()))
+}
+
+/// Formats a number with at most six decimals and no trailing zeros.
+fn format_number(value: f64) -> String {
+    let formatted = format!("{:.6}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Applies `convert` to every non-empty line so a column of values can be
+/// converted at once.
+fn map_lines(
+    buffer: &str,
+    convert: impl Fn(&str) -> Result<String, UnitError>,
+) -> Result<String, UnitError> {
+    buffer
+        .trim()
+        .lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                Ok(String::new())
+            } else {
+                convert(line)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// Formats a byte count with binary prefixes, e.g. 1536000 → "1.46 MiB".
+pub fn format_bytes(bytes: f64) -> String {
+    let mut value = bytes;
+    let mut prefix = 0;
+    while value.abs() >= 1024.0 && prefix < BINARY_PREFIXES.len() - 1 {
+        value /= 1024.0;
+        prefix += 1;
+    }
+    if prefix == 0 {
+        format!("{} B", format_number(value))
+    } else {
+        format!("{:.2} {}", value, BINARY_PREFIXES[prefix])
+    }
+}
+
+pub fn bytes_human(buffer: &str) -> Result<String, UnitError> {
+    map_lines(buffer, |line| parse_number(line).map(format_bytes))
+}
+
+/// Where the unit starts after a number, skipping the `e` of an exponent
+/// as in `1e3 KB`.
+fn unit_start(line: &str) -> usize {
+    let bytes = line.as_bytes();
+    let is_exponent = |i: usize| {
+        let after = match bytes.get(i + 1) {
+            Some(b'+' | b'-') => i + 2,
+            _ => i + 1,
+        };
+        i > 0
+            && (bytes[i - 1].is_ascii_digit() || bytes[i - 1] == b'.')
+            && bytes.get(after).is_some_and(u8::is_ascii_digit)
+    };
+    line.char_indices()
+        .find(|&(i, c)| c.is_alphabetic() && !(matches!(c, 'e' | 'E') && is_exponent(i)))
+        .map_or(line.len(), |(i, _)| i)
+}
+
+pub fn human_bytes(buffer: &str) -> Result<String, UnitError> {
+    map_lines(buffer, |line| {
+        let line = line.trim();
+        let split_at = unit_start(line);
+        let (number, unit) = line.split_at(split_at);
+        let value = parse_number(number)?;
+        let unit = if unit.trim().is_empty() {
+            "b"
+        } else {
+            unit.trim()
+        };
+        match parse_unit(unit)? {
+            Unit::Linear(Dimension::Data, factor) => Ok(format!("{}", (value * factor).round())),
+            _ => Err(UnitError::UnknownUnit(unit.to_string())),
+        }
+    })
+}
+
+pub fn convert_units(
+    buffer: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<String, UnitError> {
+    let (Some(from), Some(to)) = (from, to) else {
+        return Err(UnitError::MissingUnits);
+    };
+    let from_unit = parse_unit(from)?;
+    let to_unit = parse_unit(to)?;
+    if from_unit.dimension() != to_unit.dimension() {
+        return Err(UnitError::IncompatibleUnits(
+            from.to_string(),
+            to.to_string(),
+        ));
+    }
+
+    map_lines(buffer, |line| {
+        let value = parse_number(line)?;
+        let converted = match (from_unit, to_unit) {
+            (Unit::Linear(_, from_factor), Unit::Linear(_, to_factor)) => {
+                value * from_factor / to_factor
+            }
+            (from_unit, to_unit) => {
+                let kelvin = match from_unit {
+                    Unit::Celsius => value + 273.15,
+                    Unit::Fahrenheit => (value - 32.0) * 5.0 / 9.0 + 273.15,
+                    _ => value,
+                };
+                match to_unit {
+                    Unit::Celsius => kelvin - 273.15,
+                    Unit::Fahrenheit => (kelvin - 273.15) * 9.0 / 5.0 + 32.0,
+                    _ => kelvin,
+                }
+            }
+        };
+        Ok(format_number(converted))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_human() {
+        assert_eq!(bytes_human("1536000").unwrap(), "1.46 MiB");
+        assert_eq!(
+            bytes_human("512\n\n1_073_741_824").unwrap(),
+            "512 B\n\n1.00 GiB"
+        );
+        assert!(matches!(
+            bytes_human("lots"),
+            Err(UnitError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_human_bytes() {
+        assert_eq!(human_bytes("1.46 MiB").unwrap(), "1530921");
+        assert_eq!(human_bytes("2KiB\n10").unwrap(), "2048\n10");
+        assert_eq!(human_bytes("1e3 KB").unwrap(), "1000000");
+        assert_eq!(human_bytes("2.5E+2b").unwrap(), "250");
+        // An E that starts a unit isn't an exponent
+        assert!(matches!(
+            human_bytes("1 EiB"),
+            Err(UnitError::UnknownUnit(unit)) if unit == "EiB"
+        ));
+        assert!(matches!(
+            human_bytes("5 km"),
+            Err(UnitError::UnknownUnit(_))
+        ));
+        assert!(matches!(
+            human_bytes("MiB"),
+            Err(UnitError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_convert_units() {
+        assert_eq!(
+            convert_units("1500\n250", Some("ms"), Some("s")).unwrap(),
+            "1.5\n0.25"
+        );
+        assert_eq!(convert_units("100", Some("C"), Some("F")).unwrap(), "212");
+        assert_eq!(
+            convert_units("0", Some("c"), Some("kelvin")).unwrap(),
+            "273.15"
+        );
+        assert_eq!(
+            convert_units("1", Some("mi"), Some("km")).unwrap(),
+            "1.609344"
+        );
+        assert_eq!(convert_units("8", Some("bits"), Some("b")).unwrap(), "1");
+    }
+
+    #[test]
+    fn test_convert_units_errors() {
+        assert!(matches!(
+            convert_units("1", None, None),
+            Err(UnitError::MissingUnits)
+        ));
+        assert!(matches!(
+            convert_units("1", Some("ms"), Some("kg")),
+            Err(UnitError::UnknownUnit(unit)) if unit == "kg"
+        ));
+        assert!(matches!(
+            convert_units("1", Some("ms"), Some("m")),
+            Err(UnitError::IncompatibleUnits(_, _))
+        ));
+        assert!(matches!(
+            convert_units("soon", Some("ms"), Some("s")),
+            Err(UnitError::InvalidNumber(_))
+        ));
+    }
+}
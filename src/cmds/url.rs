@@ -1,26 +1,598 @@
 use std::fmt;
 
+use serde_json::{Map, Value};
+use url::{Host, Url};
+
 #[derive(Debug)]
-pub enum UrlDecodeError {
+pub enum UrlError {
     InvalidEncoding,
+    UnknownMode(String),
+    InvalidUrl(String, url::ParseError),
+    InvalidEmail(String),
+    InvalidDomain(String),
+    InvalidJson(String),
+    NotAnObject,
+    UnsupportedValue(String),
 }
 
-impl fmt::Display for UrlDecodeError {
+impl fmt::Display for UrlError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::InvalidEncoding => {
                 write!(f, "invalid URL encoding")
             }
+            Self::UnknownMode(mode) => {
+                write!(
+                    f,
+                    "unknown mode '{}' - expected component, path, query, url, or form",
+                    mode
+                )
+            }
+            Self::InvalidUrl(line, err) => {
+                write!(f, "invalid URL '{}': {}", line, err)
+            }
+            Self::InvalidEmail(line) => {
+                write!(f, "invalid email address '{}'", line)
+            }
+            Self::InvalidDomain(domain) => {
+                write!(f, "invalid domain name '{}'", domain)
+            }
+            Self::InvalidJson(msg) => {
+                write!(f, "invalid JSON - {}", msg)
+            }
+            Self::NotAnObject => {
+                write!(f, "expected a JSON object of query parameters")
+            }
+            Self::UnsupportedValue(key) => {
+                write!(
+                    f,
+                    "'{}' can't be written as a query parameter - arrays may only hold plain values",
+                    key
+                )
+            }
         }
     }
 }
 
-pub fn url_decode(buffer: &str) -> Result<String, UrlDecodeError> {
+/// What the encoded text will be used as, which decides the characters
+/// left alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UrlMode {
+    /// Everything but the RFC 3986 unreserved characters is encoded
+    Component,
+    /// One path segment: `/`, `?` and `#` are encoded, `:@!$&'()*+,;=` kept
+    Path,
+    /// A query key or value: `&`, `=`, `+` and `#` are encoded
+    Query,
+    /// A whole URL, keeping reserved separators and existing `%XX` escapes
+    Url,
+    /// `application/x-www-form-urlencoded`, with `+` for spaces
+    Form,
+}
+
+impl UrlMode {
+    pub fn parse(name: &str) -> Result<Self, UrlError> {
+        match name.to_lowercase().as_str() {
+            "component" => Ok(Self::Component),
+            "path" | "segment" => Ok(Self::Path),
+            "query" => Ok(Self::Query),
+            "url" | "full" => Ok(Self::Url),
+            "form" => Ok(Self::Form),
+            _ => Err(UrlError::UnknownMode(name.to_string())),
+        }
+    }
+
+    fn keeps(self, byte: u8) -> bool {
+        let unreserved = byte.is_ascii_alphanumeric() || b"-._~".contains(&byte);
+        match self {
+            Self::Component => unreserved,
+            Self::Path => unreserved || b"!$&'()*+,;=:@".contains(&byte),
+            Self::Query => unreserved || b"!$'()*,;:@/?".contains(&byte),
+            Self::Url => unreserved || b":/?#[]@!$&'()*+,;=".contains(&byte),
+            // The WHATWG form encoder, which also escapes `~`
+            Self::Form => byte.is_ascii_alphanumeric() || b"*-._".contains(&byte),
+        }
+    }
+}
+
+fn parse_mode(mode: Option<&str>) -> Result<UrlMode, UrlError> {
+    mode.map(UrlMode::parse)
+        .transpose()
+        .map(|mode| mode.unwrap_or(UrlMode::Component))
+}
+
+pub fn url_decode(buffer: &str) -> Result<String, UrlError> {
     urlencoding::decode(buffer)
         .map(|s| s.into_owned())
-        .map_err(|_| UrlDecodeError::InvalidEncoding)
+        .map_err(|_| UrlError::InvalidEncoding)
+}
+
+/// Decodes percent escapes, also reading `+` as a space in `form` mode.
+pub fn url_decode_with(buffer: &str, mode: Option<&str>) -> Result<String, UrlError> {
+    match parse_mode(mode)? {
+        UrlMode::Form => url_decode(&buffer.replace('+', " ")),
+        _ => url_decode(buffer),
+    }
 }
 
 pub fn url_encode(buffer: &str) -> String {
     urlencoding::encode(buffer).into_owned()
 }
+
+pub fn url_encode_with(buffer: &str, mode: Option<&str>) -> Result<String, UrlError> {
+    let mode = parse_mode(mode)?;
+    let bytes = buffer.as_bytes();
+    let mut encoded = String::with_capacity(bytes.len());
+    for (i, &byte) in bytes.iter().enumerate() {
+        let is_escape = byte == b'%'
+            && bytes
+                .get(i + 1..i + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+        if mode.keeps(byte) || (mode == UrlMode::Url && is_escape) {
+            encoded.push(byte as char);
+        } else if mode == UrlMode::Form && byte == b' ' {
+            encoded.push('+');
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    Ok(encoded)
+}
+
+/// Decodes `%XX` escapes of unreserved characters, which mean the same
+/// thing unescaped, and uppercases the rest.
+fn normalize_escapes(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut normalized = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) if UrlMode::Component.keeps(byte) => normalized.push(byte as char),
+            Some(byte) => normalized.push_str(&format!("%{:02X}", byte)),
+            None => {
+                // Only the ASCII bytes of a valid URL are ever inspected here
+                let ch = text[i..].chars().next().unwrap_or_default();
+                normalized.push(ch);
+                i += ch.len_utf8();
+                continue;
+            }
+        }
+        i += 3;
+    }
+    normalized
+}
+
+/// Canonicalizes one URL. Parsing already lowercases the scheme and host,
+/// converts the host to punycode, drops default ports and resolves `.` and
+/// `..` segments; on top of that, escapes are normalized and the query
+/// parameters are sorted by name.
+fn normalize_url(line: &str) -> Result<String, UrlError> {
+    let mut url = Url::parse(line).map_err(|e| UrlError::InvalidUrl(line.to_string(), e))?;
+
+    let path = normalize_escapes(url.path());
+    url.set_path(&path);
+
+    let query = url.query().map(|query| {
+        let mut params: Vec<String> = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(normalize_escapes)
+            .collect();
+        // Stable, so repeated keys keep their relative order
+        params.sort_by(|a, b| {
+            let key = |param: &str| param.split('=').next().map(str::to_string);
+            key(a).cmp(&key(b))
+        });
+        params.join("&")
+    });
+    url.set_query(query.as_deref().filter(|query| !query.is_empty()));
+
+    Ok(url.to_string())
+}
+
+pub fn url_normalize(buffer: &str) -> Result<String, UrlError> {
+    buffer
+        .lines()
+        .map(|line| match line.trim() {
+            "" => Ok(String::new()),
+            line => normalize_url(line),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// Converts the domain of each address to its punycode (IDNA) form, as
+/// mail servers see it. The local part is left alone since only the
+/// receiving server may interpret it.
+pub fn idna_email(buffer: &str) -> Result<String, UrlError> {
+    buffer
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            if line.is_empty() {
+                return Ok(String::new());
+            }
+            let invalid = || UrlError::InvalidEmail(line.to_string());
+            let (local, domain) = line.rsplit_once('@').ok_or_else(invalid)?;
+            if local.is_empty() {
+                return Err(invalid());
+            }
+            match Host::parse(domain) {
+                Ok(Host::Domain(domain)) => Ok(format!("{}@{}", local, domain)),
+                _ => Err(invalid()),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// Finds the host in a line holding a bare domain, an email address or a
+/// URL, returned as byte offsets so the rest of the line can be kept.
+fn host_span(line: &str) -> (usize, usize) {
+    let start = match line.find("://") {
+        Some(scheme_end) => {
+            let authority_end = line[scheme_end + 3..]
+                .find(['/', '?', '#'])
+                .map_or(line.len(), |end| scheme_end + 3 + end);
+            line[..authority_end]
+                .rfind('@')
+                .filter(|at| *at > scheme_end)
+                .map_or(scheme_end + 3, |at| at + 1)
+        }
+        None => line.rfind('@').map_or(0, |at| at + 1),
+    };
+    let end = line[start..]
+        .find(['/', ':', '?', '#'])
+        .map_or(line.len(), |end| start + end);
+    (start, end)
+}
+
+fn map_hosts(
+    buffer: &str,
+    convert: impl Fn(&str) -> Result<String, UrlError>,
+) -> Result<String, UrlError> {
+    buffer
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            if line.is_empty() {
+                return Ok(String::new());
+            }
+            let (start, end) = host_span(line);
+            let host = convert(&line[start..end])?;
+            Ok(format!("{}{}{}", &line[..start], host, &line[end..]))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// Converts internationalized domains to their ASCII `xn--` form. Each
+/// line can be a domain, an email address or a URL.
+pub fn punycode_encode(buffer: &str) -> Result<String, UrlError> {
+    map_hosts(buffer, |host| {
+        idna::domain_to_ascii(host).map_err(|_| UrlError::InvalidDomain(host.to_string()))
+    })
+}
+
+/// Shows `xn--` labels as the Unicode a browser would display them as,
+/// which reveals look-alike characters in phishing domains.
+pub fn punycode_decode(buffer: &str) -> Result<String, UrlError> {
+    map_hosts(buffer, |host| match idna::domain_to_unicode(host) {
+        (unicode, Ok(())) => Ok(unicode),
+        (_, Err(_)) => Err(UrlError::InvalidDomain(host.to_string())),
+    })
+}
+
+/// Splits `a[b][]` into `a`, `b` and an empty segment for the array push.
+/// Keys that don't follow the bracket syntax are taken literally.
+fn key_segments(key: &str) -> Vec<&str> {
+    let Some(open) = key.find('[').filter(|open| *open > 0 && key.ends_with(']')) else {
+        return vec![key];
+    };
+    let mut segments = vec![&key[..open]];
+    for segment in key[open + 1..key.len() - 1].split("][") {
+        if segment.contains(['[', ']']) {
+            return vec![key];
+        }
+        segments.push(segment);
+    }
+    segments
+}
+
+fn insert_param(target: &mut Map<String, Value>, segments: &[&str], value: String) {
+    let (key, rest) = (segments[0].to_string(), &segments[1..]);
+    match rest.first() {
+        None => match target.get_mut(&key) {
+            // Repeated keys collect into an array
+            Some(Value::Array(items)) => items.push(Value::String(value)),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, Value::String(value)]);
+            }
+            None => {
+                target.insert(key, Value::String(value));
+            }
+        },
+        Some(&"") => {
+            let entry = target
+                .entry(key)
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if !entry.is_array() {
+                let first = entry.take();
+                *entry = Value::Array(vec![first]);
+            }
+            let Value::Array(items) = entry else {
+                return;
+            };
+            if rest.len() == 1 {
+                items.push(Value::String(value));
+            } else {
+                let mut object = Map::new();
+                insert_param(&mut object, &rest[1..], value);
+                items.push(Value::Object(object));
+            }
+        }
+        Some(_) => {
+            let entry = target
+                .entry(key)
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            if let Value::Object(object) = entry {
+                insert_param(object, rest, value);
+            }
+        }
+    }
+}
+
+/// Parses a query string, or the query of a whole URL, into a JSON object.
+/// Repeated keys and `key[]` become arrays and `key[sub]` nested objects;
+/// values are decoded the way forms submit them and kept as strings.
+pub fn query_to_json(buffer: &str) -> Result<String, UrlError> {
+    let text = buffer.trim();
+    // Only strip what looks like a URL or path, since `?` may also appear
+    // unescaped in a value
+    let query = match text.split_once('?') {
+        Some((prefix, query)) if prefix.is_empty() || prefix.contains('/') => query,
+        _ => text,
+    };
+    let query = query.split('#').next().unwrap_or_default();
+
+    let mut object = Map::new();
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        let key = url_decode_with(key, Some("form"))?;
+        let value = url_decode_with(value, Some("form"))?;
+        insert_param(&mut object, &key_segments(&key), value);
+    }
+    serde_json::to_string_pretty(&Value::Object(object))
+        .map_err(|e| UrlError::InvalidJson(e.to_string()))
+}
+
+fn push_params(key: &str, value: &Value, params: &mut Vec<String>) -> Result<(), UrlError> {
+    let scalar = |value: &Value| match value {
+        Value::String(text) => Ok(url_encode_with(text, Some("query"))?),
+        Value::Null => Ok(String::new()),
+        Value::Bool(_) | Value::Number(_) => Ok(value.to_string()),
+        _ => Err(UrlError::UnsupportedValue(key.to_string())),
+    };
+    match value {
+        Value::Object(object) => {
+            for (sub, child) in object {
+                let sub = url_encode_with(sub, Some("query"))?;
+                push_params(&format!("{}[{}]", key, sub), child, params)?;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                params.push(format!("{}[]={}", key, scalar(item)?));
+            }
+        }
+        Value::Null => params.push(key.to_string()),
+        _ => params.push(format!("{}={}", key, scalar(value)?)),
+    }
+    Ok(())
+}
+
+/// Serializes a JSON object as a query string, the reverse of
+/// `/query-to-json`: arrays become `key[]=...` and objects `key[sub]=...`.
+pub fn json_to_query(buffer: &str) -> Result<String, UrlError> {
+    let value: Value =
+        serde_json::from_str(buffer).map_err(|e| UrlError::InvalidJson(e.to_string()))?;
+    let Value::Object(object) = value else {
+        return Err(UrlError::NotAnObject);
+    };
+    let mut params = Vec::new();
+    for (key, value) in &object {
+        push_params(&url_encode_with(key, Some("query"))?, value, &mut params)?;
+    }
+    Ok(params.join("&"))
+}
+
+/// Breaks a URL into its parts as JSON. Query parameters stay a list of
+/// name/value pairs so order and repeated names survive, with both decoded.
+pub fn url_parse(buffer: &str) -> Result<String, UrlError> {
+    let line = buffer.trim();
+    let url = Url::parse(line).map_err(|e| UrlError::InvalidUrl(line.to_string(), e))?;
+
+    let mut parts = Map::new();
+    parts.insert("scheme".to_string(), Value::from(url.scheme()));
+    if !url.username().is_empty() {
+        let username = url_decode(url.username())?;
+        parts.insert("username".to_string(), Value::from(username));
+    }
+    if let Some(password) = url.password() {
+        parts.insert("password".to_string(), Value::from(url_decode(password)?));
+    }
+    if let Some(host) = url.host_str() {
+        parts.insert("host".to_string(), Value::from(host));
+    }
+    if let Some(port) = url.port_or_known_default() {
+        parts.insert("port".to_string(), Value::from(port));
+    }
+    parts.insert("path".to_string(), Value::from(url.path()));
+    if let Some(segments) = url.path_segments() {
+        let segments = segments
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| url_decode(segment).map(Value::from))
+            .collect::<Result<Vec<_>, _>>()?;
+        parts.insert("segments".to_string(), Value::Array(segments));
+    }
+    if url.query().is_some() {
+        let params = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let mut param = Map::new();
+                param.insert("name".to_string(), Value::from(name.into_owned()));
+                param.insert("value".to_string(), Value::from(value.into_owned()));
+                Value::Object(param)
+            })
+            .collect();
+        parts.insert("query".to_string(), Value::Array(params));
+    }
+    if let Some(fragment) = url.fragment() {
+        parts.insert("fragment".to_string(), Value::from(url_decode(fragment)?));
+    }
+
+    serde_json::to_string_pretty(&Value::Object(parts))
+        .map_err(|e| UrlError::InvalidJson(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_encode_modes() {
+        let input = "a b/c?d=e&f#g";
+        let encoded = |mode| url_encode_with(input, Some(mode)).unwrap();
+        assert_eq!(encoded("component"), "a%20b%2Fc%3Fd%3De%26f%23g");
+        assert_eq!(encoded("path"), "a%20b%2Fc%3Fd=e&f%23g");
+        assert_eq!(encoded("query"), "a%20b/c?d%3De%26f%23g");
+        assert_eq!(encoded("url"), "a%20b/c?d=e&f#g");
+        assert_eq!(encoded("form"), "a+b%2Fc%3Fd%3De%26f%23g");
+        assert_eq!(url_encode_with(input, None).unwrap(), url_encode(input));
+        assert!(url_encode_with(input, Some("bogus")).is_err());
+    }
+
+    #[test]
+    fn test_url_encode_full_url_keeps_escapes() {
+        let url = "https://example.com/caf%C3%A9 menu?q=crème brûlée";
+        assert_eq!(
+            url_encode_with(url, Some("url")).unwrap(),
+            "https://example.com/caf%C3%A9%20menu?q=cr%C3%A8me%20br%C3%BBl%C3%A9e"
+        );
+    }
+
+    #[test]
+    fn test_url_decode_form() {
+        assert_eq!(url_decode_with("a+b%2B", Some("form")).unwrap(), "a b+");
+        assert_eq!(url_decode_with("a+b%2B", None).unwrap(), "a+b+");
+    }
+
+    #[test]
+    fn test_url_normalize() {
+        let url = "HTTPS://Bücher.Example:443/a/./b/../c%7e%2f?z=1&a=2&&m=%3d#Frag";
+        assert_eq!(
+            url_normalize(url).unwrap(),
+            "https://xn--bcher-kva.example/a/c~%2F?a=2&m=%3D&z=1#Frag"
+        );
+        assert_eq!(
+            url_normalize("http://example.com:8080?").unwrap(),
+            "http://example.com:8080/"
+        );
+        assert!(url_normalize("not a url").is_err());
+    }
+
+    #[test]
+    fn test_punycode() {
+        assert_eq!(
+            punycode_encode("Bücher.example\nhttps://jo@münchen.de:8080/a?b\nJosé@ουτοπία.δπθ")
+                .unwrap(),
+            "xn--bcher-kva.example\n\
+             https://jo@xn--mnchen-3ya.de:8080/a?b\n\
+             José@xn--kxae4bafwg.xn--pxaix"
+        );
+        // Cyrillic "а" posing as the Latin letter
+        assert_eq!(
+            punycode_decode("https://xn--pple-43d.com/login").unwrap(),
+            "https://аpple.com/login"
+        );
+        assert_eq!(punycode_decode("example.com").unwrap(), "example.com");
+        assert!(matches!(
+            punycode_decode("xn--a.com"),
+            Err(UrlError::InvalidDomain(_))
+        ));
+    }
+
+    #[test]
+    fn test_idna_email() {
+        assert_eq!(
+            idna_email("José@Bücher.example\nplain@example.com").unwrap(),
+            "José@xn--bcher-kva.example\nplain@example.com"
+        );
+        assert!(idna_email("no-at-sign").is_err());
+    }
+
+    #[test]
+    fn test_query_to_json() {
+        let json = query_to_json("https://example.com/?a=1&b=hello%20world&arr[]=x&arr[]=y&tag=a&tag=b+c&user[name]=Jo&flag#top")
+            .unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "a": "1",
+                "arr": ["x", "y"],
+                "b": "hello world",
+                "flag": "",
+                "tag": ["a", "b c"],
+                "user": {"name": "Jo"},
+            })
+        );
+        assert_eq!(key_segments("odd[key"), ["odd[key"]);
+        assert!(query_to_json("q=why?").unwrap().contains(r#""q": "why?""#));
+    }
+
+    #[test]
+    fn test_json_to_query_roundtrip() {
+        let query =
+            json_to_query(r#"{"q": "a&b c", "ids": [1, 2], "page": {"size": 10}, "none": null}"#)
+                .unwrap();
+        assert_eq!(query, "ids[]=1&ids[]=2&none&page[size]=10&q=a%26b%20c");
+        let back: Value = serde_json::from_str(&query_to_json(&query).unwrap()).unwrap();
+        assert_eq!(back["q"], "a&b c");
+        assert_eq!(back["ids"], serde_json::json!(["1", "2"]));
+        assert!(matches!(json_to_query("[1]"), Err(UrlError::NotAnObject)));
+        assert!(json_to_query(r#"{"a": [[1]]}"#).is_err());
+    }
+
+    #[test]
+    fn test_url_parse() {
+        let url = "https://user@auth.example.com/oauth/authorize?client_id=abc&redirect_uri=https%3A%2F%2Fapp.example%2Fcb&scope=read+write#state";
+        let parsed: Value = serde_json::from_str(&url_parse(url).unwrap()).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!({
+                "scheme": "https",
+                "username": "user",
+                "host": "auth.example.com",
+                "port": 443,
+                "path": "/oauth/authorize",
+                "segments": ["oauth", "authorize"],
+                "query": [
+                    {"name": "client_id", "value": "abc"},
+                    {"name": "redirect_uri", "value": "https://app.example/cb"},
+                    {"name": "scope", "value": "read write"},
+                ],
+                "fragment": "state",
+            })
+        );
+        assert!(url_parse("example.com/no-scheme").is_err());
+    }
+}
@@ -0,0 +1,414 @@
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use sha2::{Digest, Sha256};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::certification_request::X509CertificationRequest;
+use x509_parser::extensions::{GeneralName, ParsedExtension};
+use x509_parser::oid_registry::asn1_rs::{FromDer, Oid};
+use x509_parser::public_key::PublicKey;
+use x509_parser::time::ASN1Time;
+use x509_parser::x509::{AlgorithmIdentifier, SubjectPublicKeyInfo, X509Name};
+
+use super::der::{TAG_SEQUENCE, colon_hex, integer_bits, pem_blocks};
+use super::dump::parse_dump;
+
+const PEM_LABELS: &[&str] = &[
+    "CERTIFICATE",
+    "TRUSTED CERTIFICATE",
+    "X509 CERTIFICATE",
+    "CERTIFICATE REQUEST",
+    "NEW CERTIFICATE REQUEST",
+];
+
+const NAME_ATTRIBUTES: &[(&str, &str)] = &[
+    ("2.5.4.3", "CN"),
+    ("2.5.4.5", "serialNumber"),
+    ("2.5.4.6", "C"),
+    ("2.5.4.7", "L"),
+    ("2.5.4.8", "ST"),
+    ("2.5.4.9", "street"),
+    ("2.5.4.10", "O"),
+    ("2.5.4.11", "OU"),
+    ("1.2.840.113549.1.9.1", "emailAddress"),
+    ("0.9.2342.19200300.100.1.25", "DC"),
+    ("0.9.2342.19200300.100.1.1", "UID"),
+];
+
+pub(super) const ALGORITHMS: &[(&str, &str)] = &[
+    ("1.2.840.113549.1.1.1", "RSA"),
+    ("1.2.840.113549.1.1.5", "sha1WithRSAEncryption"),
+    ("1.2.840.113549.1.1.10", "RSASSA-PSS"),
+    ("1.2.840.113549.1.1.11", "sha256WithRSAEncryption"),
+    ("1.2.840.113549.1.1.12", "sha384WithRSAEncryption"),
+    ("1.2.840.113549.1.1.13", "sha512WithRSAEncryption"),
+    ("1.2.840.10045.2.1", "EC"),
+    ("1.2.840.10045.4.3.2", "ecdsa-with-SHA256"),
+    ("1.2.840.10045.4.3.3", "ecdsa-with-SHA384"),
+    ("1.2.840.10045.4.3.4", "ecdsa-with-SHA512"),
+    ("1.2.840.10040.4.1", "DSA"),
+    ("1.2.840.113549.1.5.13", "PBES2"),
+    ("1.3.101.110", "X25519"),
+    ("1.3.101.111", "X448"),
+    ("1.3.101.112", "Ed25519"),
+    ("1.3.101.113", "Ed448"),
+];
+
+const CURVES: &[(&str, &str)] = &[
+    ("1.2.840.10045.3.1.7", "P-256"),
+    ("1.3.132.0.34", "P-384"),
+    ("1.3.132.0.35", "P-521"),
+    ("1.3.132.0.10", "secp256k1"),
+];
+
+#[derive(Debug)]
+pub enum X509Error {
+    NoCertificate,
+    Malformed(String),
+}
+
+impl fmt::Display for X509Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoCertificate => {
+                write!(f, "No PEM certificate or certificate request found")
+            }
+            Self::Malformed(msg) => {
+                write!(f, "Not a valid certificate - {}", msg)
+            }
+        }
+    }
+}
+
+pub(super) fn malformed(err: impl fmt::Display) -> X509Error {
+    X509Error::Malformed(err.to_string())
+}
+
+pub(super) fn lookup(table: &[(&str, &'static str)], oid: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == oid)
+        .map(|(_, name)| *name)
+}
+
+/// The name of an OID from `table`, or its dotted form. Arcs too big for a
+/// u64 come back as the OID's hex bytes rather than a wrapped number.
+fn oid_name(table: &[(&str, &'static str)], oid: &Oid) -> String {
+    let dotted = oid.to_id_string();
+    lookup(table, &dotted).map_or(dotted, str::to_string)
+}
+
+/// The algorithm in an `AlgorithmIdentifier`, by name where known.
+pub(super) fn algorithm_name(identifier: &AlgorithmIdentifier) -> String {
+    oid_name(ALGORITHMS, &identifier.algorithm)
+}
+
+/// Describes a `SubjectPublicKeyInfo`, e.g. "RSA 2048 bits" or "EC P-256".
+pub(super) fn public_key_summary(spki: &SubjectPublicKeyInfo) -> Result<String, X509Error> {
+    let name = algorithm_name(&spki.algorithm);
+    match spki.parsed().map_err(malformed)? {
+        // x509-parser's own key_size miscounts moduli without a leading zero
+        PublicKey::RSA(key) => Ok(format!("RSA {} bits", integer_bits(key.modulus))),
+        PublicKey::EC(_) => {
+            let curve = spki
+                .algorithm
+                .parameters
+                .clone()
+                .and_then(|parameters| Oid::try_from(parameters).ok());
+            Ok(match curve {
+                Some(curve) => format!("EC {}", oid_name(CURVES, &curve)),
+                None => "EC".to_string(),
+            })
+        }
+        _ => Ok(name),
+    }
+}
+
+/// Formats a `Name` the way OpenSSL's RFC 2253 output does, most specific
+/// attribute first as it appears in the certificate.
+fn name_string(name: &X509Name) -> String {
+    name.iter_attributes()
+        .map(|attribute| {
+            format!(
+                "{}={}",
+                oid_name(NAME_ATTRIBUTES, attribute.attr_type()),
+                String::from_utf8_lossy(attribute.as_slice())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn general_name(name: &GeneralName) -> Option<String> {
+    match name {
+        GeneralName::RFC822Name(email) => Some(format!("email:{}", email)),
+        GeneralName::DNSName(dns) => Some(format!("DNS:{}", dns)),
+        GeneralName::URI(uri) => Some(format!("URI:{}", uri)),
+        GeneralName::IPAddress(octets) => match octets.len() {
+            4 => Some(format!(
+                "IP:{}",
+                Ipv4Addr::from(<[u8; 4]>::try_from(*octets).ok()?)
+            )),
+            16 => Some(format!(
+                "IP:{}",
+                Ipv6Addr::from(<[u8; 16]>::try_from(*octets).ok()?)
+            )),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Appends the extensions we know how to show. A known extension that
+/// doesn't parse makes the whole certificate invalid.
+fn describe_extension(
+    extension: &ParsedExtension,
+    lines: &mut Vec<(&str, String)>,
+) -> Result<(), X509Error> {
+    match extension {
+        ParsedExtension::SubjectAlternativeName(san) => {
+            let names: Vec<String> = san.general_names.iter().filter_map(general_name).collect();
+            lines.push(("SANs", names.join(", ")));
+        }
+        ParsedExtension::BasicConstraints(constraints) => {
+            lines.push(("CA", if constraints.ca { "yes" } else { "no" }.to_string()));
+        }
+        ParsedExtension::ParseError { error } => return Err(malformed(error)),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ`, as the validity dates are shown.
+fn time_string(time: &ASN1Time) -> String {
+    let time = time.to_datetime();
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+fn describe_certificate(der: &[u8]) -> Result<Vec<(&'static str, String)>, X509Error> {
+    let (_, certificate) = X509Certificate::from_der(der).map_err(malformed)?;
+    let tbs = &certificate.tbs_certificate;
+    let subject = name_string(&tbs.subject);
+    let issuer = name_string(&tbs.issuer);
+    let self_signed = subject == issuer;
+
+    let mut lines = vec![
+        ("Subject", subject),
+        (
+            "Issuer",
+            if self_signed {
+                format!("{} (self-signed)", issuer)
+            } else {
+                issuer
+            },
+        ),
+        ("Version", (tbs.version.0 + 1).to_string()),
+    ];
+    let serial = match tbs.raw_serial() {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        all => all,
+    };
+    lines.push(("Serial", colon_hex(serial)));
+    lines.push(("Not Before", time_string(&tbs.validity.not_before)));
+    lines.push(("Not After", time_string(&tbs.validity.not_after)));
+    lines.push(("Key", public_key_summary(&tbs.subject_pki)?));
+    lines.push((
+        "Signature",
+        algorithm_name(&certificate.signature_algorithm),
+    ));
+    for extension in tbs.extensions() {
+        describe_extension(extension.parsed_extension(), &mut lines)?;
+    }
+    lines.push(("SHA-256", colon_hex(&Sha256::digest(certificate.as_raw()))));
+    Ok(lines)
+}
+
+fn describe_request(der: &[u8]) -> Result<Vec<(&'static str, String)>, X509Error> {
+    let (_, request) = X509CertificationRequest::from_der(der).map_err(malformed)?;
+    let info = &request.certification_request_info;
+    let mut lines = vec![
+        ("Subject", name_string(&info.subject)),
+        ("Key", public_key_summary(&info.subject_pki)?),
+        ("Signature", algorithm_name(&request.signature_algorithm)),
+    ];
+    for extension in request.requested_extensions().into_iter().flatten() {
+        describe_extension(extension, &mut lines)?;
+    }
+    lines.push(("SHA-256", colon_hex(&Sha256::digest(request.as_raw()))));
+    Ok(lines)
+}
+
+fn describe(der: &[u8], is_request: bool) -> Result<String, X509Error> {
+    let (title, lines) = match is_request {
+        true => ("Certificate request", describe_request(der)?),
+        false => ("Certificate", describe_certificate(der)?),
+    };
+    let width = lines
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut output = title.to_string();
+    for (label, value) in lines {
+        output.push_str(&format!("\n  {:<width$} {}", format!("{}:", label), value));
+    }
+    Ok(output)
+}
+
+/// Decodes every PEM certificate and CSR in the buffer, or a single one
+/// given as a hex or base64 dump of its DER bytes.
+pub fn cert_decode(buffer: &str) -> Result<String, X509Error> {
+    let mut ders: Vec<(bool, Vec<u8>)> = pem_blocks(buffer)
+        .into_iter()
+        .filter(|(label, _)| PEM_LABELS.contains(&label.as_str()))
+        .map(|(label, der)| (label.ends_with("REQUEST"), der))
+        .collect();
+    if ders.is_empty() {
+        let der = parse_dump(buffer).map_err(|_| X509Error::NoCertificate)?;
+        if der.first() != Some(&TAG_SEQUENCE) {
+            return Err(X509Error::NoCertificate);
+        }
+        // A bare dump doesn't say which it is
+        let is_request = X509Certificate::from_der(&der).is_err()
+            && X509CertificationRequest::from_der(&der).is_ok();
+        ders.push((is_request, der));
+    }
+    let decoded = ders
+        .iter()
+        .map(|(is_request, der)| describe(der, *is_request))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(decoded.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EC_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIB+DCCAZ+gAwIBAgICEjQwCgYIKoZIzj0EAwIwOTEUMBIGA1UEAwwLZXhhbXBs
+ZS5jb20xFDASBgNVBAoMC0V4YW1wbGUgT3JnMQswCQYDVQQGEwJVUzAeFw0yNjEw
+MTQxMzQ4MjdaFw0zNjEwMTExMzQ4MjdaMDkxFDASBgNVBAMMC2V4YW1wbGUuY29t
+MRQwEgYDVQQKDAtFeGFtcGxlIE9yZzELMAkGA1UEBhMCVVMwWTATBgcqhkjOPQIB
+BggqhkjOPQMBBwNCAATuONktzw4gpg+zPimjr3zbM3k1P/XuJCp6sRhSOn//09xJ
+ixyNiWTsdrv7TkY9sfvyLoEsneH47NZqQ1Z5qalUo4GWMIGTMB0GA1UdDgQWBBRQ
+OpHHoFnHyVqdDrjNs/wpxxDovTAfBgNVHSMEGDAWgBRQOpHHoFnHyVqdDrjNs/wp
+xxDovTAPBgNVHRMBAf8EBTADAQH/MEAGA1UdEQQ5MDeCC2V4YW1wbGUuY29tgg93
+d3cuZXhhbXBsZS5jb22HBMAAAgGBEWFkbWluQGV4YW1wbGUuY29tMAoGCCqGSM49
+BAMCA0cAMEQCIAJZ8xWdGDNgt2zlbwoLhl5GilD24FJk4nawvyw/AVtqAiA6/9x2
+I/Lw5qUs3AmrkP3XvIcFL/Giyo+SyCbRb21CMA==
+-----END CERTIFICATE-----
+";
+
+    const EC_CSR: &str = "-----BEGIN CERTIFICATE REQUEST-----
+MIIBATCBqQIBADAaMRgwFgYDVQQDDA9jc3IuZXhhbXBsZS5jb20wWTATBgcqhkjO
+PQIBBggqhkjOPQMBBwNCAATuONktzw4gpg+zPimjr3zbM3k1P/XuJCp6sRhSOn//
+09xJixyNiWTsdrv7TkY9sfvyLoEsneH47NZqQ1Z5qalUoC0wKwYJKoZIhvcNAQkO
+MR4wHDAaBgNVHREEEzARgg9jc3IuZXhhbXBsZS5jb20wCgYIKoZIzj0EAwIDRwAw
+RAIgJUCjHSErRML2GFSi9TYnjzthTE8SukbUgweUCcmDXGoCIAqnycbmEdNba6t6
+6h5wdxP+jXBOLChP0fXLZonW3W+K
+-----END CERTIFICATE REQUEST-----
+";
+
+    #[test]
+    fn test_cert_decode_certificate() {
+        let result = cert_decode(EC_CERT).unwrap();
+        assert_eq!(
+            result,
+            "Certificate\n  \
+             Subject:    CN=example.com, O=Example Org, C=US\n  \
+             Issuer:     CN=example.com, O=Example Org, C=US (self-signed)\n  \
+             Version:    3\n  \
+             Serial:     12:34\n  \
+             Not Before: 2026-10-14T13:48:27Z\n  \
+             Not After:  2036-10-11T13:48:27Z\n  \
+             Key:        EC P-256\n  \
+             Signature:  ecdsa-with-SHA256\n  \
+             CA:         yes\n  \
+             SANs:       DNS:example.com, DNS:www.example.com, IP:192.0.2.1, email:admin@example.com\n  \
+             SHA-256:    2F:07:C7:AD:76:61:99:F9:04:87:58:C2:09:6E:77:50:A2:56:F8:CF:E4:98:58:A1:6E:AE:07:95:A6:D1:4F:71"
+        );
+    }
+
+    #[test]
+    fn test_cert_decode_request_and_chain() {
+        let result = cert_decode(&format!("{}{}", EC_CSR, EC_CERT)).unwrap();
+        let (request, certificate) = result.split_once("\n\n").unwrap();
+        assert!(request.starts_with("Certificate request\n  Subject:   CN=csr.example.com"));
+        assert!(request.contains("SANs:      DNS:csr.example.com"));
+        assert!(certificate.starts_with("Certificate\n"));
+    }
+
+    fn ec_cert_der() -> Vec<u8> {
+        pem_blocks(EC_CERT).remove(0).1
+    }
+
+    #[test]
+    fn test_cert_decode_truncated_and_corrupted() {
+        let der = ec_cert_der();
+        for len in 0..der.len() {
+            assert!(
+                describe(&der[..len], false).is_err(),
+                "truncated to {}",
+                len
+            );
+        }
+        // Mangled lengths and tags anywhere, nested SEQUENCEs included,
+        // must come back as errors or output rather than panics
+        for i in 0..der.len() {
+            for byte in [0x00, 0x7f, 0x80, 0xff] {
+                let mut corrupted = der.clone();
+                corrupted[i] = byte;
+                let _ = describe(&corrupted, false);
+                let _ = describe(&corrupted, true);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cert_decode_truncated_extension() {
+        let mut der = ec_cert_der();
+        // The SAN extension's OCTET STRING wraps a 0x37-byte SEQUENCE; claim
+        // one more byte than it has
+        let san = der
+            .windows(7)
+            .position(|w| w == [0x06, 0x03, 0x55, 0x1d, 0x11, 0x04, 0x39])
+            .unwrap();
+        assert_eq!(der[san + 8], 0x37);
+        der[san + 8] = 0x38;
+        assert!(matches!(
+            describe(&der, false),
+            Err(X509Error::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_long_oid_is_not_truncated() {
+        // An arc of 2^70, which doesn't fit in a u64
+        let mut der = vec![0x30, 0x0e, 0x06, 0x0c, 0x2a, 0x81];
+        der.extend([0x80; 9]);
+        der.push(0x00);
+        let (_, identifier) = AlgorithmIdentifier::from_der(&der).unwrap();
+        assert_eq!(
+            algorithm_name(&identifier),
+            "2a 81 80 80 80 80 80 80 80 80 80 00"
+        );
+    }
+
+    #[test]
+    fn test_cert_decode_rejects_other_input() {
+        assert!(matches!(
+            cert_decode("hello world"),
+            Err(X509Error::NoCertificate)
+        ));
+        assert!(cert_decode("30 05 02 01").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! Fallbacks for terminals that can't handle pomp's default escape
+//! sequences or box-drawing characters, such as the legacy Windows console.
+
+use std::io;
+
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use crossterm::terminal::supports_keyboard_enhancement;
+use ratatui::symbols::{border, scrollbar};
+
+use crate::config::CompatMode;
+
+pub const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+pub const ASCII_SCROLLBAR: scrollbar::Set = scrollbar::Set {
+    track: "|",
+    thumb: "#",
+    begin: "^",
+    end: "v",
+};
+
+/// How `/image-preview` draws pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Graphics {
+    /// Two pixels per cell, as the colors of a `▀` and the cell behind it
+    HalfBlocks,
+    Kitty,
+    Sixel,
+}
+
+impl Graphics {
+    /// Goes by what the terminal advertises, since querying it means
+    /// waiting on a reply that may never come. tmux and screen pass neither
+    /// protocol through by default.
+    fn from_env(var: &impl Fn(&str) -> Option<String>) -> Self {
+        if var("TMUX").is_some() || var("STY").is_some() {
+            return Self::HalfBlocks;
+        }
+        let term = var("TERM").unwrap_or_default();
+        let program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("KITTY_WINDOW_ID").is_some()
+            || matches!(term.as_str(), "xterm-kitty" | "xterm-ghostty")
+            || matches!(program.as_str(), "WezTerm" | "ghostty")
+        {
+            Self::Kitty
+        } else if term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.contains("sixel")
+            || program == "iTerm.app"
+        {
+            Self::Sixel
+        } else {
+            Self::HalfBlocks
+        }
+    }
+}
+
+/// Terminal features pomp turns on at startup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capabilities {
+    pub mouse: bool,
+    pub bracketed_paste: bool,
+    pub keyboard_enhancement: bool,
+    /// Whether box-drawing and block characters render correctly
+    pub unicode: bool,
+    pub graphics: Graphics,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+impl Capabilities {
+    pub fn full() -> Self {
+        Self {
+            mouse: true,
+            bracketed_paste: true,
+            keyboard_enhancement: true,
+            unicode: true,
+            graphics: Graphics::HalfBlocks,
+        }
+    }
+
+    pub fn legacy() -> Self {
+        Self {
+            mouse: false,
+            bracketed_paste: false,
+            keyboard_enhancement: false,
+            unicode: false,
+            graphics: Graphics::HalfBlocks,
+        }
+    }
+
+    /// Resolves the capabilities for `mode`. `POMP_COMPAT=on|off|auto` in the
+    /// environment overrides the configured mode.
+    pub fn detect(mode: CompatMode) -> Self {
+        let mode = match std::env::var("POMP_COMPAT").ok().as_deref() {
+            Some("on") => CompatMode::On,
+            Some("off") => CompatMode::Off,
+            Some("auto") => CompatMode::Auto,
+            _ => mode,
+        };
+
+        match mode {
+            CompatMode::On => Self::legacy(),
+            CompatMode::Off => Self::full(),
+            CompatMode::Auto => Self::from_env(cfg!(windows), |name| std::env::var(name).ok()),
+        }
+    }
+
+    /// Guesses support from the environment, since none of these features can
+    /// be queried reliably before enabling them.
+    fn from_env(windows: bool, var: impl Fn(&str) -> Option<String>) -> Self {
+        match var("TERM").as_deref() {
+            Some("dumb") => return Self::legacy(),
+            // The Linux VT has no mouse reporting without gpm and a limited font
+            Some("linux") => {
+                return Self {
+                    bracketed_paste: true,
+                    ..Self::legacy()
+                };
+            }
+            _ => {}
+        }
+
+        // Windows Terminal, VS Code, and ConEmu all speak VT sequences, but the
+        // classic conhost window doesn't set any of these.
+        let modern_windows = var("WT_SESSION").is_some()
+            || var("TERM_PROGRAM").is_some()
+            || var("ConEmuANSI").as_deref() == Some("ON");
+        if windows && !modern_windows {
+            // crossterm reads mouse input through the console API, so it still works
+            return Self {
+                mouse: true,
+                ..Self::legacy()
+            };
+        }
+
+        Self {
+            graphics: Graphics::from_env(&var),
+            ..Self::full()
+        }
+    }
+
+    /// Turns on the supported features. The kitty keyboard protocol is only
+    /// pushed if the terminal answers the capability query, and is marked
+    /// unavailable otherwise so `disable` won't pop it.
+    pub fn enable(&mut self) -> io::Result<()> {
+        let mut stdout = crate::term::output();
+        if self.mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
+        if self.bracketed_paste {
+            execute!(stdout, EnableBracketedPaste)?;
+        }
+        if self.keyboard_enhancement {
+            self.keyboard_enhancement = supports_keyboard_enhancement().unwrap_or(false);
+        }
+        if self.keyboard_enhancement {
+            // Disambiguation alone makes Shift/Ctrl+Enter and Super combos
+            // distinct from their plain keys
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(
+                    KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                        | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+                )
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn disable(&self) -> io::Result<()> {
+        let mut stdout = crate::term::output();
+        if self.keyboard_enhancement {
+            execute!(stdout, PopKeyboardEnhancementFlags)?;
+        }
+        if self.mouse {
+            execute!(stdout, DisableMouseCapture)?;
+        }
+        if self.bracketed_paste {
+            execute!(stdout, DisableBracketedPaste)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_from_env() {
+        let conhost = Capabilities::from_env(true, env(&[]));
+        assert!(conhost.mouse);
+        assert!(!conhost.unicode && !conhost.bracketed_paste);
+
+        let windows_terminal = Capabilities::from_env(true, env(&[("WT_SESSION", "1")]));
+        assert_eq!(windows_terminal, Capabilities::full());
+
+        let xterm = Capabilities::from_env(false, env(&[("TERM", "xterm-256color")]));
+        assert_eq!(xterm, Capabilities::full());
+
+        let dumb = Capabilities::from_env(false, env(&[("TERM", "dumb")]));
+        assert_eq!(dumb, Capabilities::legacy());
+    }
+
+    #[test]
+    fn test_graphics_from_env() {
+        let graphics = |vars| Capabilities::from_env(false, env(vars)).graphics;
+        assert_eq!(graphics(&[("TERM", "xterm-kitty")]), Graphics::Kitty);
+        assert_eq!(graphics(&[("TERM_PROGRAM", "WezTerm")]), Graphics::Kitty);
+        assert_eq!(graphics(&[("TERM", "foot")]), Graphics::Sixel);
+        assert_eq!(
+            graphics(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux-1000/default")]),
+            Graphics::HalfBlocks
+        );
+        assert_eq!(
+            graphics(&[("TERM", "xterm-256color")]),
+            Graphics::HalfBlocks
+        );
+    }
+}
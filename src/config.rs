@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::paths::config_dir;
+
+/// User settings read from `~/.config/pomp/config.toml`. Every section is
+/// optional so a missing or partial file falls back to defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: ThemeConfig,
+    pub notify: NotifyConfig,
+    pub compat: CompatConfig,
+    pub clipboard: ClipboardConfig,
+    pub share: ShareConfig,
+    /// Extra commands mapping a name to a shell command line, which gets the
+    /// buffer on stdin and whose stdout replaces it
+    pub plugins: BTreeMap<String, String>,
+    pub sandbox: SandboxConfig,
+    pub macros: BTreeMap<String, MacroConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Built-in preset to start from (`dark`, `light`, or `solarized`)
+    pub preset: Option<String>,
+    pub popup_bg: Option<String>,
+    pub popup_fg: Option<String>,
+    pub selected_bg: Option<String>,
+    pub selected_fg: Option<String>,
+    pub border: Option<String>,
+    pub error: Option<String>,
+    pub warning: Option<String>,
+    pub info: Option<String>,
+    pub success: Option<String>,
+    pub highlight: Option<String>,
+    /// One of `block`, `underline`, or `bar`
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Only commands running at least this long trigger notifications
+    pub threshold_ms: u64,
+    /// Ring the terminal bell
+    pub bell: bool,
+    /// Send an OSC 9 desktop notification through the terminal
+    pub osc: bool,
+    /// Shell command to run, with `POMP_COMMAND`, `POMP_STATUS`,
+    /// `POMP_ELAPSED_MS`, and `POMP_MESSAGE` in its environment
+    pub command: Option<String>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            threshold_ms: 2000,
+            bell: false,
+            osc: false,
+            command: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ShareConfig {
+    /// `0x0`, `gist`, or `custom`
+    pub service: String,
+    /// Endpoint for the `custom` service, which receives the buffer as a
+    /// plain-text POST body
+    pub url: Option<String>,
+    /// GitHub token for gists, falling back to `GITHUB_TOKEN`
+    pub token: Option<String>,
+    /// Create public rather than secret gists
+    pub public: bool,
+}
+
+impl Default for ShareConfig {
+    fn default() -> Self {
+        Self {
+            service: "0x0".to_string(),
+            url: None,
+            token: None,
+            public: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompatMode {
+    /// Detect legacy terminals from the environment
+    #[default]
+    Auto,
+    /// Always use ASCII borders and skip optional terminal features
+    On,
+    Off,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CompatConfig {
+    pub mode: CompatMode,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardBackend {
+    /// The system clipboard, or OSC 52 over SSH or when there is none
+    #[default]
+    Auto,
+    System,
+    /// Copy through the terminal with the OSC 52 escape sequence
+    Osc52,
+    /// Turn off copying and pasting
+    None,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    pub backend: ClipboardBackend,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxMode {
+    /// Ask before the first run of a plugin, and again whenever it changes
+    #[default]
+    Prompt,
+    /// Only run plugins listed in `allow`
+    Allowlist,
+    /// Run every plugin without asking
+    Trust,
+}
+
+/// Which external plugins may run. Rhai scripts are not affected since they
+/// have no file, network or process access.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub mode: SandboxMode,
+    /// Plugins that run without confirmation
+    pub allow: Vec<String>,
+    /// Plugins that never run
+    pub deny: Vec<String>,
+}
+
+/// A recorded macro: either just its steps, or a table that also binds it to
+/// a key like `F5` or `alt-1`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MacroConfig {
+    Steps(Vec<String>),
+    Bound {
+        steps: Vec<String>,
+        key: Option<String>,
+    },
+}
+
+impl MacroConfig {
+    pub fn steps(&self) -> &[String] {
+        match self {
+            Self::Steps(steps) | Self::Bound { steps, .. } => steps,
+        }
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::Steps(_) => None,
+            Self::Bound { key, .. } => key.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    ReadError(String),
+    ParseError(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ReadError(msg) => {
+                write!(f, "Failed to read config - {}", msg)
+            }
+            Self::ParseError(msg) => {
+                write!(f, "Invalid config - {}", msg)
+            }
+        }
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+impl Config {
+    pub fn load() -> Result<Config, ConfigError> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let contents =
+            fs::read_to_string(&path).map_err(|e| ConfigError::ReadError(e.to_string()))?;
+        toml::from_str(&contents).map_err(|e| ConfigError::ParseError(e.message().to_string()))
+    }
+}
@@ -0,0 +1,412 @@
+use std::borrow::Cow;
+
+use ropey::Rope;
+use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+
+const MAX_HISTORY: usize = 200;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditKind {
+    Insert,
+    Backspace,
+    Delete,
+    /// Pastes, kills and other bulk edits, which always get their own step
+    Other,
+}
+
+/// Undo history for the input editor. Consecutive single-character edits
+/// of the same kind at adjacent positions are coalesced into one step, so
+/// undo removes a typed word rather than a letter.
+#[derive(Default)]
+pub struct InputHistory {
+    undo_stack: Vec<(Rope, usize)>,
+    redo_stack: Vec<(Rope, usize)>,
+    last_edit: Option<(EditKind, usize)>,
+}
+
+impl InputHistory {
+    /// Records the state before an edit of `kind` made at `cursor`.
+    pub fn record(&mut self, text: &Rope, cursor: usize, kind: EditKind) {
+        let continues_group = match self.last_edit {
+            Some((EditKind::Insert, last)) => kind == EditKind::Insert && cursor == last + 1,
+            // `last` is where the previous backspace left the cursor
+            Some((EditKind::Backspace, last)) => kind == EditKind::Backspace && cursor == last,
+            Some((EditKind::Delete, last)) => kind == EditKind::Delete && cursor == last,
+            _ => false,
+        };
+
+        if !continues_group {
+            // Ropes share structure, so snapshots are cheap
+            self.undo_stack.push((text.clone(), cursor));
+            if self.undo_stack.len() > MAX_HISTORY {
+                self.undo_stack.remove(0);
+            }
+        }
+        self.redo_stack.clear();
+        // A backspace removes a whole grapheme, which can be several chars
+        let landing = match kind {
+            EditKind::Backspace => prev_grapheme(text, cursor),
+            _ => cursor,
+        };
+        self.last_edit = Some((kind, landing));
+    }
+
+    /// Ends the current group, e.g. after typing whitespace.
+    pub fn break_group(&mut self) {
+        self.last_edit = None;
+    }
+
+    pub fn undo(&mut self, text: &Rope, cursor: usize) -> Option<(Rope, usize)> {
+        let previous = self.undo_stack.pop()?;
+        self.redo_stack.push((text.clone(), cursor));
+        self.last_edit = None;
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, text: &Rope, cursor: usize) -> Option<(Rope, usize)> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push((text.clone(), cursor));
+        self.last_edit = None;
+        Some(next)
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Line and column (both zero-based, in chars) of a char position.
+pub fn line_col(text: &Rope, pos: usize) -> (usize, usize) {
+    let pos = pos.min(text.len_chars());
+    let line = text.char_to_line(pos);
+    (line, pos - text.line_to_char(line))
+}
+
+/// The char position at a line and column, clamped to the end of the line.
+pub fn pos_from_line_col(text: &Rope, target_line: usize, target_col: usize) -> usize {
+    if target_line >= text.len_lines() {
+        return text.len_chars();
+    }
+    let start = text.line_to_char(target_line);
+    start + target_col.min(line_text(text, target_line).chars().count())
+}
+
+/// The text of one line without its line break. Only the visible lines
+/// are read, so this stays cheap for a large paste.
+pub fn line_text(text: &Rope, line: usize) -> Cow<'_, str> {
+    let slice = text.line(line);
+    let end = slice.len_chars();
+    let end = match end > 0 && slice.char(end - 1) == '\n' {
+        true => end - 1,
+        false => end,
+    };
+    let end = match end > 0 && slice.char(end - 1) == '\r' {
+        true => end - 1,
+        false => end,
+    };
+    slice.slice(..end).into()
+}
+
+fn chunk_char_offset(chunk: &str, byte: usize) -> usize {
+    chunk[..byte].chars().count()
+}
+
+/// Start of the grapheme cluster before `pos`, so an emoji sequence or a
+/// letter with combining marks is stepped over as one.
+pub fn prev_grapheme(text: &Rope, pos: usize) -> usize {
+    let pos = pos.min(text.len_chars());
+    let byte = text.char_to_byte(pos);
+    let (mut chunk, mut chunk_byte, mut chunk_char, _) = text.chunk_at_byte(byte);
+    let mut cursor = GraphemeCursor::new(byte, text.len_bytes(), true);
+    loop {
+        match cursor.prev_boundary(chunk, chunk_byte) {
+            Ok(None) => return 0,
+            Ok(Some(boundary)) => {
+                return chunk_char + chunk_char_offset(chunk, boundary - chunk_byte);
+            }
+            Err(GraphemeIncomplete::PrevChunk) => {
+                (chunk, chunk_byte, chunk_char, _) = text.chunk_at_byte(chunk_byte - 1);
+            }
+            Err(GraphemeIncomplete::PreContext(at)) => {
+                let context = text.chunk_at_byte(at - 1).0;
+                cursor.provide_context(context, at - context.len());
+            }
+            Err(_) => return pos.saturating_sub(1),
+        }
+    }
+}
+
+/// End of the grapheme cluster starting at `pos`.
+pub fn next_grapheme(text: &Rope, pos: usize) -> usize {
+    let len = text.len_chars();
+    let pos = pos.min(len);
+    let byte = text.char_to_byte(pos);
+    let (mut chunk, mut chunk_byte, mut chunk_char, _) = text.chunk_at_byte(byte);
+    let mut cursor = GraphemeCursor::new(byte, text.len_bytes(), true);
+    loop {
+        match cursor.next_boundary(chunk, chunk_byte) {
+            Ok(None) => return len,
+            Ok(Some(boundary)) => {
+                return chunk_char + chunk_char_offset(chunk, boundary - chunk_byte);
+            }
+            Err(GraphemeIncomplete::NextChunk) => {
+                chunk_byte += chunk.len();
+                chunk_char += chunk.chars().count();
+                chunk = text.chunk_at_byte(chunk_byte).0;
+            }
+            Err(GraphemeIncomplete::PreContext(at)) => {
+                let context = text.chunk_at_byte(at - 1).0;
+                cursor.provide_context(context, at - context.len());
+            }
+            Err(_) => return (pos + 1).min(len),
+        }
+    }
+}
+
+/// `pos`, or the start of the grapheme cluster it falls inside.
+pub fn grapheme_start(text: &Rope, pos: usize) -> usize {
+    let len = text.len_chars();
+    if pos == 0 || pos >= len {
+        return pos.min(len);
+    }
+    prev_grapheme(text, next_grapheme(text, pos))
+}
+
+/// Start of the word before `pos`, skipping any separators in between.
+pub fn prev_word_start(text: &Rope, pos: usize) -> usize {
+    let mut pos = pos.min(text.len_chars());
+    while pos > 0 && !is_word_char(text.char(pos - 1)) {
+        pos -= 1;
+    }
+    while pos > 0 && is_word_char(text.char(pos - 1)) {
+        pos -= 1;
+    }
+    pos
+}
+
+/// End of the word after `pos`, skipping any separators in between.
+pub fn next_word_end(text: &Rope, pos: usize) -> usize {
+    let len = text.len_chars();
+    let mut pos = pos.min(len);
+    while pos < len && !is_word_char(text.char(pos)) {
+        pos += 1;
+    }
+    while pos < len && is_word_char(text.char(pos)) {
+        pos += 1;
+    }
+    pos
+}
+
+pub fn line_start(text: &Rope, pos: usize) -> usize {
+    text.line_to_char(text.char_to_line(pos))
+}
+
+/// Position of the line break ending the line containing `pos`, or the end
+/// of the text on the last line. A `\r\n` break is one grapheme, so this
+/// stops before the `\r` rather than between the two.
+pub fn line_end(text: &Rope, pos: usize) -> usize {
+    let line = text.char_to_line(pos);
+    if line + 1 >= text.len_lines() {
+        return text.len_chars();
+    }
+    let end = text.line_to_char(line + 1) - 1;
+    match end > 0 && text.char(end - 1) == '\r' {
+        true => end - 1,
+        false => end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    use super::*;
+
+    #[test]
+    fn test_word_boundaries() {
+        let text = Rope::from("https://example.com/some_path?q=1");
+        assert_eq!(prev_word_start(&text, text.len_chars()), 32);
+        assert_eq!(prev_word_start(&text, 32), 30);
+        assert_eq!(prev_word_start(&text, 29), 20);
+        assert_eq!(next_word_end(&text, 0), 5);
+        assert_eq!(next_word_end(&text, 5), 15);
+        assert_eq!(next_word_end(&text, 19), 29);
+    }
+
+    #[test]
+    fn test_line_col_round_trip() {
+        let text = Rope::from("ab\ncde\n\nf");
+        assert_eq!(line_col(&text, 5), (1, 2));
+        assert_eq!(pos_from_line_col(&text, 1, 2), 5);
+        // Columns past the end of a line clamp to it
+        assert_eq!(pos_from_line_col(&text, 0, 10), 2);
+        assert_eq!(line_col(&text, text.len_chars()), (3, 1));
+        assert_eq!(pos_from_line_col(&text, 9, 0), text.len_chars());
+
+        let crlf = Rope::from("ab\r\ncd\n");
+        assert_eq!(line_text(&crlf, 0), "ab");
+        assert_eq!(line_text(&crlf, 1), "cd");
+        assert_eq!(line_text(&crlf, 2), "");
+    }
+
+    #[test]
+    fn test_input_history_coalesces_typing() {
+        let mut history = InputHistory::default();
+        let mut text = Rope::new();
+        for (cursor, ch) in "hello world".chars().enumerate() {
+            history.record(&text, cursor, EditKind::Insert);
+            text.insert_char(cursor, ch);
+            if ch == ' ' {
+                history.break_group();
+            }
+        }
+
+        let (text, cursor) = history.undo(&text, 11).unwrap();
+        assert_eq!(text.to_string(), "hello ");
+        assert_eq!(cursor, 6);
+        let (text, _) = history.undo(&text, cursor).unwrap();
+        assert_eq!(text.to_string(), "");
+        assert!(history.undo(&text, 0).is_none());
+
+        let (text, _) = history.redo(&text, 0).unwrap();
+        assert_eq!(text.to_string(), "hello ");
+    }
+
+    #[test]
+    fn test_input_history_coalesces_backspacing_graphemes() {
+        let mut history = InputHistory::default();
+        // "e" and a combining acute accent make one grapheme of two chars
+        let mut text = Rope::from("ae\u{301}");
+        let mut cursor = 3;
+        while cursor > 0 {
+            history.record(&text, cursor, EditKind::Backspace);
+            let start = prev_grapheme(&text, cursor);
+            text.remove(start..cursor);
+            cursor = start;
+        }
+        let (text, cursor) = history.undo(&text, 0).unwrap();
+        assert_eq!(text.to_string(), "ae\u{301}");
+        assert_eq!(cursor, 3);
+        assert!(history.undo(&text, cursor).is_none());
+    }
+
+    #[test]
+    fn test_input_history_splits_on_cursor_jump() {
+        let mut history = InputHistory::default();
+        let mut text = Rope::from("ab");
+        history.record(&text, 2, EditKind::Backspace);
+        text.remove(1..2);
+        // Moving elsewhere before the next backspace starts a new step
+        history.record(&text, 0, EditKind::Delete);
+        text.remove(0..1);
+        let (text, _) = history.undo(&text, 0).unwrap();
+        assert_eq!(text.to_string(), "a");
+    }
+
+    #[test]
+    fn test_grapheme_steps() {
+        // A family emoji joined with ZWJs, then "e" with a combining acute
+        let text = Rope::from("a👨‍👩‍👧e\u{301}b");
+        let family = "👨‍👩‍👧".chars().count();
+        assert_eq!(next_grapheme(&text, 0), 1);
+        assert_eq!(next_grapheme(&text, 1), 1 + family);
+        assert_eq!(next_grapheme(&text, 1 + family), 3 + family);
+        assert_eq!(prev_grapheme(&text, 3 + family), 1 + family);
+        assert_eq!(prev_grapheme(&text, 1 + family), 1);
+        assert_eq!(prev_grapheme(&text, 0), 0);
+        assert_eq!(next_grapheme(&text, text.len_chars()), text.len_chars());
+        assert_eq!(grapheme_start(&text, 3), 1);
+        assert_eq!(grapheme_start(&text, 2 + family), 1 + family);
+
+        let crlf = Rope::from("a\r\nb");
+        assert_eq!(next_grapheme(&crlf, 1), 3);
+    }
+
+    #[test]
+    fn test_line_bounds() {
+        let text = Rope::from("first\nsecond\nthird");
+        assert_eq!(line_start(&text, 8), 6);
+        assert_eq!(line_end(&text, 8), 12);
+        assert_eq!(line_start(&text, 15), 13);
+        assert_eq!(line_end(&text, 15), 18);
+        // On the line break itself the cursor is still on that line
+        assert_eq!(line_start(&text, 5), 0);
+        assert_eq!(line_end(&text, 5), 5);
+
+        let crlf = Rope::from("ab\r\ncd\r\n");
+        assert_eq!(line_end(&crlf, 0), 2);
+        assert_eq!(next_grapheme(&crlf, 2), 4);
+        assert_eq!(line_start(&crlf, 5), 4);
+        assert_eq!(line_end(&crlf, 5), 6);
+        assert_eq!(line_end(&crlf, crlf.len_chars()), crlf.len_chars());
+    }
+
+    #[test]
+    fn test_grapheme_steps_over_flags_and_marks() {
+        // Two regional indicator pairs are two flags, a letter with two
+        // combining marks is one grapheme, as is a skin-toned emoji
+        let text = Rope::from("🇯🇵🇫🇷o\u{308}\u{332}👍🏽");
+        let mut stops = vec![0];
+        while *stops.last().unwrap() < text.len_chars() {
+            stops.push(next_grapheme(&text, *stops.last().unwrap()));
+        }
+        assert_eq!(stops, [0, 2, 4, 7, 9]);
+        let mut back = vec![text.len_chars()];
+        while *back.last().unwrap() > 0 {
+            back.push(prev_grapheme(&text, *back.last().unwrap()));
+        }
+        back.reverse();
+        assert_eq!(back, stops);
+        assert_eq!(grapheme_start(&text, 1), 0);
+        assert_eq!(grapheme_start(&text, 3), 2);
+        assert_eq!(grapheme_start(&text, 6), 4);
+        assert_eq!(grapheme_start(&text, 8), 7);
+    }
+
+    #[test]
+    fn test_grapheme_steps_across_rope_chunks() {
+        // Long enough that the rope splits into chunks, with graphemes
+        // straddling the chunk boundaries
+        let unit = "ab👨‍👩‍👧e\u{301}🇯🇵\r\n";
+        let source = unit.repeat(200);
+        let text = Rope::from(source.as_str());
+        assert!(text.chunks().count() > 1);
+
+        let expected: Vec<usize> = source
+            .grapheme_indices(true)
+            .map(|(byte, grapheme)| source[..byte + grapheme.len()].chars().count())
+            .collect();
+        let mut pos = 0;
+        for &end in &expected {
+            pos = next_grapheme(&text, pos);
+            assert_eq!(pos, end);
+        }
+        for &end in expected.iter().rev().skip(1) {
+            pos = prev_grapheme(&text, pos);
+            assert_eq!(pos, end);
+        }
+        assert_eq!(prev_grapheme(&text, pos), 0);
+    }
+
+    #[test]
+    fn test_vertical_moves_with_wide_chars() {
+        // Columns count chars, so wide CJK and emoji are one column each
+        let text = Rope::from("漢字かな\nab👨‍👩‍👧cd\nx");
+        let family = "👨‍👩‍👧".chars().count();
+        assert_eq!(line_col(&text, 2), (0, 2));
+        assert_eq!(pos_from_line_col(&text, 0, 3), 3);
+        assert_eq!(line_text(&text, 0), "漢字かな");
+
+        // Moving down from column 3 lands inside the family emoji, which
+        // the cursor snaps back to the start of
+        let inside = pos_from_line_col(&text, 1, 3);
+        assert_eq!(inside, 5 + 3);
+        assert_eq!(grapheme_start(&text, inside), 5 + 2);
+        let (line, col) = line_col(&text, 5 + 2 + family);
+        assert_eq!((line, col), (1, 2 + family));
+        // and moving up again clamps to the end of the shorter line above
+        assert_eq!(pos_from_line_col(&text, 0, col), 4);
+        // while moving down clamps to the end of the last line
+        assert_eq!(pos_from_line_col(&text, 2, col), text.len_chars());
+    }
+}
@@ -0,0 +1,188 @@
+//! Failures as data: what went wrong, in which command and where in the
+//! input, for the TUI's status bar and for callers that want more than a
+//! message to show.
+
+use std::fmt;
+
+use serde_json::{Value, json};
+
+use crate::cmds::TransformError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The command needs input and there is none
+    EmptyInput,
+    /// Missing or malformed arguments
+    Usage,
+    UnknownCommand,
+    /// The input didn't parse, usually with a position
+    InvalidInput,
+    /// Reading or writing a file, or talking to another process
+    Io,
+    /// Blocked by the sandbox policy
+    Denied,
+    /// A checksum or other comparison didn't match
+    Mismatch,
+    Failed,
+}
+
+/// How loudly an error is shown. Warnings are about how a command was
+/// used rather than something that broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl ErrorKind {
+    /// Stable name for machine-readable output.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::EmptyInput => "empty-input",
+            Self::Usage => "usage",
+            Self::UnknownCommand => "unknown-command",
+            Self::InvalidInput => "invalid-input",
+            Self::Io => "io",
+            Self::Denied => "denied",
+            Self::Mismatch => "mismatch",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::EmptyInput | Self::Usage | Self::UnknownCommand => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppError {
+    /// Command that failed, including the leading slash
+    pub command: Option<String>,
+    pub kind: ErrorKind,
+    pub message: String,
+    /// 1-based line and column in the input, the column counted in bytes
+    /// as serde_json does
+    pub position: Option<(usize, usize)>,
+}
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl fmt::Display) -> Self {
+        Self {
+            command: None,
+            kind,
+            message: message.to_string(),
+            position: None,
+        }
+    }
+
+    pub fn failed(message: impl fmt::Display) -> Self {
+        Self::new(ErrorKind::Failed, message)
+    }
+
+    /// `Usage: <usage>`, for a command given the wrong arguments.
+    pub fn usage(usage: &str) -> Self {
+        Self::new(ErrorKind::Usage, format!("Usage: {}", usage))
+    }
+
+    pub fn empty_input() -> Self {
+        Self::new(ErrorKind::EmptyInput, "Buffer is empty")
+    }
+
+    pub fn in_command(mut self, command: &str) -> Self {
+        self.command = Some(command.to_string());
+        self
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+
+    /// The error as JSON, with `line` and `column` when there's a position.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({ "kind": self.kind.label(), "message": self.message });
+        if let Some(command) = &self.command {
+            value["command"] = json!(command);
+        }
+        if let Some((line, column)) = self.position {
+            value["line"] = json!(line);
+            value["column"] = json!(column);
+        }
+        value
+    }
+}
+
+impl From<&TransformError> for AppError {
+    fn from(err: &TransformError) -> Self {
+        let position = err.position();
+        let kind = match err {
+            TransformError::UnknownTransform(_) => ErrorKind::UnknownCommand,
+            TransformError::EmptyInput => ErrorKind::EmptyInput,
+            TransformError::Failed(_) if position.is_some() => ErrorKind::InvalidInput,
+            TransformError::Failed(_) => ErrorKind::Failed,
+        };
+        Self {
+            position,
+            ..Self::new(kind, err)
+        }
+    }
+}
+
+/// The byte offset within `line` of a 1-based error column, clamped to the
+/// line and moved back to the start of the char it lands in.
+pub fn column_offset(line: &str, column: usize) -> usize {
+    line.floor_char_boundary(column.saturating_sub(1))
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_transform_error() {
+        let err = crate::transform("json-format", "{\"a\": }", &[]).unwrap_err();
+        let err = AppError::from(&err).in_command("/json-format");
+        assert_eq!(err.kind, ErrorKind::InvalidInput);
+        assert_eq!(err.position, Some((1, 7)));
+        assert_eq!(err.severity(), Severity::Error);
+        assert_eq!(
+            err.to_json(),
+            json!({
+                "kind": "invalid-input",
+                "message": "Invalid JSON - expected value at line 1 column 7",
+                "command": "/json-format",
+                "line": 1,
+                "column": 7,
+            })
+        );
+
+        let empty = AppError::from(&TransformError::EmptyInput);
+        assert_eq!(empty.severity(), Severity::Warning);
+        assert_eq!(
+            empty.to_json(),
+            json!({ "kind": "empty-input", "message": "Buffer is empty" })
+        );
+    }
+
+    #[test]
+    fn test_column_offset_counts_bytes() {
+        let input = "{\"é\": }";
+        let err = crate::transform("json-format", input, &[]).unwrap_err();
+        let (_, column) = err.position().unwrap();
+        assert_eq!(&input[column_offset(input, column)..], "}");
+        // Inside a multibyte char, at the line's end and past it
+        assert_eq!(column_offset("é", 2), 0);
+        assert_eq!(column_offset("ab", 3), 2);
+        assert_eq!(column_offset("ab", 40), 2);
+    }
+}
@@ -0,0 +1,16 @@
+//! The transforms behind pomp's slash commands, usable without the TUI.
+//!
+//! ```
+//! let formatted = pomp::transform("json-format", r#"{"a":1}"#, &[]).unwrap();
+//! assert_eq!(formatted, "{\n  \"a\": 1\n}");
+//! ```
+
+pub mod cmds;
+pub mod detect;
+pub mod editor;
+pub mod error;
+pub mod mcp;
+pub mod rpc;
+
+pub use cmds::{CommandOutput, TRANSFORMS, Transform, TransformError, transform};
+pub use error::{AppError, ErrorKind, Severity};
@@ -0,0 +1,183 @@
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use toml_edit::{Array, DocumentMut, Item, value};
+
+use crate::config::{MacroConfig, config_path};
+
+#[derive(Debug)]
+pub enum MacroError {
+    InvalidName(String),
+    NotFound(String),
+    NothingRecorded,
+    NoConfigDir,
+    SaveFailed(String),
+}
+
+impl fmt::Display for MacroError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidName(name) => {
+                write!(
+                    f,
+                    "Invalid macro name '{}' - use letters, digits, '-' and '_'",
+                    name
+                )
+            }
+            Self::NotFound(name) => {
+                write!(f, "No macro named '{}'", name)
+            }
+            Self::NothingRecorded => {
+                write!(f, "Nothing was recorded")
+            }
+            Self::NoConfigDir => {
+                write!(f, "Could not find a config directory to save macros in")
+            }
+            Self::SaveFailed(msg) => {
+                write!(f, "Failed to save macro - {}", msg)
+            }
+        }
+    }
+}
+
+/// A named sequence of submitted lines, replayed as if each were typed and
+/// entered in turn.
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<String>,
+    pub key: Option<(KeyCode, KeyModifiers)>,
+}
+
+impl Macro {
+    pub fn from_config(name: &str, config: &MacroConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            steps: config.steps().to_vec(),
+            key: config.key().and_then(parse_key),
+        }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        self.key
+            .is_some_and(|(code, modifiers)| key.code == code && key.modifiers == modifiers)
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<(), MacroError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(MacroError::InvalidName(name.to_string()))
+    }
+}
+
+/// Parses bindings like `F5`, `alt-1`, or `ctrl-t`.
+pub fn parse_key(binding: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let lower = binding.to_lowercase();
+    if let Some(number) = lower.strip_prefix('f')
+        && let Ok(number) = number.parse::<u8>()
+    {
+        return (1..=12)
+            .contains(&number)
+            .then_some((KeyCode::F(number), KeyModifiers::NONE));
+    }
+
+    let (modifier, key) = lower.split_once('-')?;
+    let modifiers = match modifier {
+        "alt" => KeyModifiers::ALT,
+        "ctrl" => KeyModifiers::CONTROL,
+        _ => return None,
+    };
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some((KeyCode::Char(c), modifiers)),
+        _ => None,
+    }
+}
+
+/// Writes the macro's steps into the `[macros]` table, keeping the rest of
+/// the file (comments included) and any existing key binding intact.
+fn update_document(contents: &str, name: &str, steps: &[String]) -> Result<String, MacroError> {
+    let mut document: DocumentMut = contents
+        .parse()
+        .map_err(|e: toml_edit::TomlError| MacroError::SaveFailed(e.message().to_string()))?;
+    let macros = document
+        .entry("macros")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| MacroError::SaveFailed("[macros] is not a table".to_string()))?;
+
+    let steps: Array = steps.iter().map(String::as_str).collect();
+    match macros.get_mut(name) {
+        Some(existing) if existing.is_table_like() => {
+            existing["steps"] = value(steps);
+        }
+        _ => {
+            macros.insert(name, Item::Value(steps.into()));
+        }
+    }
+    Ok(document.to_string())
+}
+
+pub fn save(name: &str, steps: &[String]) -> Result<(), MacroError> {
+    if steps.is_empty() {
+        return Err(MacroError::NothingRecorded);
+    }
+    let path = config_path().ok_or(MacroError::NoConfigDir)?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(MacroError::SaveFailed(e.to_string())),
+    };
+    let updated = update_document(&contents, name, steps)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| MacroError::SaveFailed(e.to_string()))?;
+    }
+    fs::write(&path, updated).map_err(|e| MacroError::SaveFailed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("F5"), Some((KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(
+            parse_key("alt-1"),
+            Some((KeyCode::Char('1'), KeyModifiers::ALT))
+        );
+        assert_eq!(parse_key("F13"), None);
+        assert_eq!(parse_key("shift-a"), None);
+    }
+
+    #[test]
+    fn test_update_document_keeps_comments() {
+        let contents = "# my settings\n[theme]\npreset = \"light\"\n";
+        let updated =
+            update_document(contents, "tidy", &steps(&["/json-format", "/copy"])).unwrap();
+        assert!(updated.starts_with("# my settings\n[theme]\npreset = \"light\"\n"));
+        assert!(updated.contains("[macros]\ntidy = [\"/json-format\", \"/copy\"]"));
+    }
+
+    #[test]
+    fn test_update_document_keeps_key_binding() {
+        let contents = "[macros.tidy]\nkey = \"F5\"\nsteps = [\"/copy\"]\n";
+        let updated = update_document(contents, "tidy", &steps(&["/json-format"])).unwrap();
+        let config: crate::config::Config = toml::from_str(&updated).unwrap();
+        let tidy = &config.macros["tidy"];
+        assert_eq!(tidy.key(), Some("F5"));
+        assert_eq!(tidy.steps(), ["/json-format"]);
+    }
+}
@@ -1,115 +1,509 @@
-mod cmds;
-
-use arboard::Clipboard;
-use cmds::{
-    base64_decode, base64_encode, css_format, css_minify, json_format, json_minify, unicode_escape,
-    unicode_unescape, url_decode, url_encode,
+mod cli;
+mod clipboard;
+mod compat;
+mod config;
+mod macros;
+mod multiplexer;
+mod notify;
+mod paths;
+mod pipeline;
+mod plugins;
+mod rdap;
+mod sandbox;
+mod scripts;
+mod share;
+mod shell;
+mod snippets;
+mod stash;
+mod term;
+mod theme;
+mod ui;
+mod usage;
+
+use base64::{Engine as _, engine::general_purpose};
+use clap::Parser;
+use cli::Cli;
+use clipboard::{ClipboardError, ClipboardProvider};
+use compat::{Capabilities, Graphics};
+use config::{Config, NotifyConfig, ShareConfig};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+use macros::{Macro, MacroError};
+use multiplexer::Multiplexer;
+use pipeline::Pipeline;
+use plugins::Plugin;
+use pomp::cmds::{
+    DataUri, HashAlgorithm, Misspelling, TRANSFORMS, Transform, count_invisibles, fill_template,
+    find_transform, format_bytes, guess_mime, hash_reader, interpolate_command, is_var_name,
+    json_diff, parse_dump, placeholders, reveal_invisibles, spellcheck, spellfix, template_values,
+    to_data_uri, verify_digest,
 };
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use pomp::detect::{ContentType, detect, is_binary, suggest};
+use pomp::editor::{self, EditKind, InputHistory};
+use pomp::error::column_offset;
+use pomp::mcp;
+use pomp::rpc::{self, Client};
+use pomp::{AppError, ErrorKind};
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame,
     layout::{Constraint, Layout},
     prelude::Rect,
-    style::{Color, Style, Stylize},
+    style::{Style, Stylize},
     symbols::border,
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Widget},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Widget,
+    },
 };
+use rdap::Query;
 use ropey::Rope;
-use sha2::{Digest, Sha256};
-use std::io::Result;
-use uuid::Uuid;
+use sandbox::{Policy, Verdict};
+use scripts::Script;
+use share::Service;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{Read, Result, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use term::Tui;
+use theme::{PRESETS, Theme};
+use ui::confirm::ConfirmDialog;
+use ui::debug::{DebugOverlay, DebugStats, MemoryStats};
+use ui::history::{HistoryEntry, HistoryList, HistoryPicker};
+use ui::image::{DEFAULT_CELL_PIXELS, ImagePreview, ImagePreviewDialog, KITTY_DELETE};
+use ui::mask;
+use ui::snippets::{SnippetList, SnippetPicker};
+use ui::statusbar::{CommandOutcome, StatusBar};
+use unicode_segmentation::UnicodeSegmentation;
+use usage::Usage;
+
+/// Which pane receives key presses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Focus {
+    Input,
+    Buffer,
+}
+
+/// A command waiting on a passphrase typed into the (masked) input.
+enum PassphraseAction {
+    Stash(String),
+    Unstash(String),
+    Transform(&'static Transform),
+}
+
+/// `/template` asking for the placeholders it has no value for, one at a
+/// time through the input.
+struct TemplatePrompt {
+    template: String,
+    values: BTreeMap<String, String>,
+    /// Names still to ask for, next one first
+    remaining: Vec<String>,
+    total: usize,
+}
+
+impl TemplatePrompt {
+    fn message(&self) -> Option<String> {
+        self.remaining.first().map(|name| {
+            format!(
+                "Value for {{{{{}}}}} ({}/{}) - Enter to continue, Esc to cancel",
+                name,
+                self.total - self.remaining.len() + 1,
+                self.total
+            )
+        })
+    }
+}
+
+/// An action waiting on the user to confirm it.
+enum PendingAction {
+    Share(Service),
+    /// First run of a plugin, by index into `App::plugins`, with its args
+    RunPlugin(usize, Vec<String>),
+    /// A file whose path was pasted, usually by dropping it on the terminal,
+    /// with its size in bytes
+    LoadFile(PathBuf, u64),
+    /// `/save` to a file that already exists
+    Overwrite(PathBuf),
+}
+
+/// Where a failed transform says the buffer went wrong.
+struct ErrorMarker {
+    /// Line index into the buffer
+    line: usize,
+    /// Byte offset into the buffer of the character at fault
+    offset: usize,
+}
+
+/// Lines kept above an error when scrolling to it.
+const ERROR_CONTEXT_LINES: usize = 3;
+/// Characters kept left of an error when wrap is off.
+const ERROR_CONTEXT_COLUMNS: usize = 40;
+
+/// Files bigger than this aren't loaded; `/hash-file` still works on them.
+const MAX_LOADED_FILE_BYTES: u64 = 64 * 1024 * 1024;
 
 pub struct App {
     exit: bool,
+    focus: Focus,
     input: Rope,
     cursor_pos: usize,
     buffer: String,
     scroll_pos: usize,
-    clipboard: Clipboard,
-    error_message: Option<String>,
+    /// Horizontal offset in characters, only used when wrap is off
+    scroll_col: usize,
+    /// Why the clipboard couldn't be opened, if it couldn't, so `/copy` and
+    /// Ctrl-V can say so instead of failing silently
+    clipboard: std::result::Result<Box<dyn ClipboardProvider>, ClipboardError>,
+    error_message: Option<AppError>,
     info_message: Option<String>,
+    /// Shown in the success color, for results like a matching checksum
+    success_message: Option<String>,
     autocomplete_index: Option<usize>,
     autocomplete_scroll: usize,
     input_scroll_line: usize,
+    input_history: InputHistory,
     undo_stack: Vec<String>,
     redo_stack: Vec<String>,
+    misspellings: Vec<Misspelling>,
+    /// Byte ranges of the markers /show-invisibles put in the buffer
+    invisibles: Vec<(usize, usize)>,
+    /// Position of the last parse error, highlighted until the next command
+    error_marker: Option<ErrorMarker>,
+    theme: Theme,
+    last_outcome: Option<CommandOutcome>,
+    /// Commands run this session, oldest first, for /history
+    history: Vec<HistoryEntry>,
+    history_picker: Option<HistoryPicker>,
+    notify: NotifyConfig,
+    share: ShareConfig,
+    pending: Option<PendingAction>,
+    plugins: Vec<Plugin>,
+    policy: Policy,
+    scripts: Vec<Script>,
+    macros: Vec<Macro>,
+    /// Name and steps of the macro being recorded
+    recording: Option<(String, Vec<String>)>,
+    last_macro: Option<String>,
+    /// `/set-var` values, substituted for `$name` in commands
+    vars: BTreeMap<String, String>,
+    usage: Usage,
+    pipeline: Pipeline,
+    /// Last clipboard contents seen while /watch is on
+    watching: Option<String>,
+    /// Command suggested for text just entered into the buffer, run by Ctrl-T
+    suggestion: Option<&'static str>,
+    passphrase_prompt: Option<PassphraseAction>,
+    template_prompt: Option<TemplatePrompt>,
+    debug: DebugStats,
+    /// Where the buffer scrollbar was last drawn, for mouse hit-testing
+    scrollbar_area: Cell<Option<Rect>>,
+    /// `/image-preview`, open until the next key press
+    image_preview: Option<ImagePreview>,
+    /// Where the preview dialog was last laid out
+    image_preview_area: Cell<Option<Rect>>,
+    /// `/snippet list`, open until a snippet is picked or it is closed
+    snippet_picker: Option<SnippetPicker>,
+    /// Terminal features in use, turned off while an editor runs
+    capabilities: Capabilities,
+    /// Open the buffer in `$EDITOR` once the current event is handled
+    edit_requested: bool,
+    /// Repaint every cell on the next frame, for when something has drawn
+    /// outside ratatui
+    needs_clear: bool,
+    dragging_scrollbar: bool,
+    line_numbers: bool,
+    wrap: bool,
+    /// Keep the buffer scrolled where it was after a command replaces it
+    keep_scroll: bool,
+    /// Copy the buffer to the clipboard after every successful transform
+    auto_copy: bool,
+    /// Add submitted text to the end of the buffer rather than replacing it
+    append: bool,
+    /// Print the buffer to stdout on exit, for `--emit` and `/emit`
+    pub emit: bool,
+    /// Draw the buffer as bullets, for `/mask` and generated secrets
+    masked: bool,
+    /// Detected buffer type, keyed by a hash of the buffer it was computed for
+    content_type_cache: RefCell<Option<(u64, ContentType)>>,
+    /// Hash of the input when it last changed, to notice edits
+    input_hash: u64,
+    /// Detected type of what's being typed, shown beside the prompt
+    input_badge: Option<ContentType>,
+    /// When to detect the input's type, once it has stopped changing
+    input_badge_due: Option<Instant>,
 }
 
 impl Default for App {
     fn default() -> Self {
+        Self::new(Config::default(), Capabilities::default())
+    }
+}
+
+impl App {
+    pub fn new(config: Config, capabilities: Capabilities) -> Self {
+        let (mut theme, theme_error) = match Theme::from_config(&config.theme) {
+            Ok(theme) => (theme, None),
+            Err(e) => (Theme::default(), Some(AppError::failed(e))),
+        };
+        theme.ascii = !capabilities.unicode;
+
+        let mut reserved = Self::builtin_commands();
+        let plugins = plugins::discover(&config.plugins, &reserved);
+        reserved.extend(plugins.iter().map(|plugin| plugin.name.as_str()));
+        let scripts = scripts::discover(&reserved);
+        let pipeline = Pipeline::new(
+            plugins
+                .iter()
+                .map(|plugin| plugin.name.as_str())
+                .chain(scripts.iter().map(|script| script.name.as_str())),
+        );
+        let clipboard = clipboard::provider(&config.clipboard);
+        let help = if capabilities.keyboard_enhancement {
+            "Press / for commands • Shift+Enter for newline".to_string()
+        } else {
+            "Press / for commands • Ctrl+J or Alt+Enter for newline".to_string()
+        };
+        let info_message = match &clipboard {
+            Ok(_) => help,
+            Err(e) => format!("{} • /copy and Ctrl-V are disabled", e),
+        };
+
         Self {
             exit: false,
+            focus: Focus::Input,
             input: Rope::new(),
             cursor_pos: 0,
             buffer: String::new(),
             scroll_pos: 0,
-            clipboard: Clipboard::new().unwrap(),
-            error_message: None,
-            info_message: Some(
-                "Press / for commands • Ctrl+J or Alt+Enter for newline".to_string(),
-            ),
+            scroll_col: 0,
+            clipboard,
+            error_message: theme_error,
+            info_message: Some(info_message),
+            success_message: None,
             autocomplete_index: None,
             autocomplete_scroll: 0,
             input_scroll_line: 0,
+            input_history: InputHistory::default(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            misspellings: Vec::new(),
+            invisibles: Vec::new(),
+            error_marker: None,
+            theme,
+            last_outcome: None,
+            history: Vec::new(),
+            history_picker: None,
+            notify: config.notify,
+            share: config.share,
+            pending: None,
+            plugins,
+            policy: Policy::load(config.sandbox),
+            scripts,
+            macros: config
+                .macros
+                .iter()
+                .map(|(name, config)| Macro::from_config(name, config))
+                .collect(),
+            recording: None,
+            last_macro: None,
+            vars: BTreeMap::new(),
+            usage: Usage::load(),
+            pipeline,
+            watching: None,
+            suggestion: None,
+            passphrase_prompt: None,
+            template_prompt: None,
+            debug: DebugStats::default(),
+            scrollbar_area: Cell::new(None),
+            image_preview: None,
+            image_preview_area: Cell::new(None),
+            snippet_picker: None,
+            capabilities,
+            edit_requested: false,
+            needs_clear: false,
+            dragging_scrollbar: false,
+            line_numbers: false,
+            wrap: false,
+            keep_scroll: false,
+            auto_copy: false,
+            append: false,
+            emit: false,
+            masked: false,
+            content_type_cache: RefCell::new(None),
+            input_hash: 0,
+            input_badge: None,
+            input_badge_due: None,
         }
     }
-}
 
-impl App {
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+    pub fn run(&mut self, terminal: &mut Tui) -> Result<()> {
         while !self.exit {
+            if std::mem::take(&mut self.needs_clear) {
+                terminal.clear()?;
+            }
+            let render_started = Instant::now();
             terminal.draw(|frame| self.draw(frame))?;
+            self.draw_image_preview()?;
+            self.debug.record_render(render_started.elapsed());
             self.handle_events()?;
+            if std::mem::take(&mut self.edit_requested) {
+                self.edit_in_editor(terminal)?;
+            }
         }
         Ok(())
     }
 
-    fn get_cursor_line_col(&self) -> (usize, usize) {
-        let text = self.input.to_string();
-        let mut line = 0;
-        let mut col = 0;
-        for (i, ch) in text.chars().enumerate() {
-            if i >= self.cursor_pos {
-                break;
-            }
-            if ch == '\n' {
-                line += 1;
-                col = 0;
-            } else {
-                col += 1;
+    /// `/edit`: hands the terminal to the editor on a temp copy of the
+    /// buffer, then loads the file back once the editor exits.
+    fn edit_in_editor(&mut self, terminal: &mut Tui) -> Result<()> {
+        let extension = match self.buffer_content_type() {
+            ContentType::Json => "json",
+            ContentType::JsonLines => "jsonl",
+            ContentType::Css => "css",
+            _ => "txt",
+        };
+        let path =
+            std::env::temp_dir().join(format!("pomp-edit-{}.{}", std::process::id(), extension));
+        // create_new won't follow a symlink someone left in the temp dir
+        let _ = std::fs::remove_file(&path);
+        let written = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(self.buffer.as_bytes()));
+        if let Err(e) = written {
+            self.error_message = Some(AppError::new(
+                ErrorKind::Io,
+                format!("Can't write {}: {}", path.display(), e),
+            ));
+            return Ok(());
+        }
+
+        let editor = shell::editor();
+        self.capabilities.disable()?;
+        term::restore();
+        let mut command = shell::edit_command(&editor, &path);
+        if term::on_stderr() {
+            // Keep the editor off the stdout that --emit is saving
+            command.stdout(std::io::stderr());
+        }
+        let status = command.status();
+        crossterm::terminal::enable_raw_mode()?;
+        crossterm::execute!(term::output(), crossterm::terminal::EnterAlternateScreen)?;
+        self.capabilities.enable()?;
+        terminal.clear()?;
+
+        let edited = status.and_then(|status| match status.success() {
+            true => std::fs::read_to_string(&path),
+            false => Err(std::io::Error::other(format!(
+                "{} exited with {}",
+                editor, status
+            ))),
+        });
+        let _ = std::fs::remove_file(&path);
+        match edited {
+            Ok(mut text) => {
+                // Editors end the last line; don't add that to a buffer without one
+                if !self.buffer.ends_with('\n') && text.ends_with('\n') {
+                    text.pop();
+                    if text.ends_with('\r') {
+                        text.pop();
+                    }
+                }
+                if text == self.buffer {
+                    self.info_message = Some("No changes".to_string());
+                } else {
+                    self.push_undo();
+                    self.buffer = text;
+                    self.info_message = Some(format!("Edited in {}", editor));
+                }
             }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
         }
-        (line, col)
+        Ok(())
     }
 
-    fn set_cursor_from_line_col(&mut self, target_line: usize, target_col: usize) {
-        let text = self.input.to_string();
-        let mut line = 0;
-        let mut col = 0;
-        let mut pos = 0;
-
-        for ch in text.chars() {
-            if line == target_line && col == target_col {
-                break;
-            }
-            if line > target_line {
-                break;
+    /// Sends the preview as kitty or sixel graphics once ratatui has drawn
+    /// the dialog around it, and again only if the layout moves.
+    fn draw_image_preview(&mut self) -> Result<()> {
+        let (Some(preview), Some(area)) = (&mut self.image_preview, self.image_preview_area.get())
+        else {
+            return Ok(());
+        };
+        let placement = preview.placement(area);
+        if self.capabilities.graphics == Graphics::HalfBlocks || preview.drawn == Some(placement) {
+            return Ok(());
+        }
+        let cell = match crossterm::terminal::window_size() {
+            Ok(size) if size.width > 0 && size.columns > 0 && size.rows > 0 => {
+                (size.width / size.columns, size.height / size.rows)
             }
-            if ch == '\n' {
-                if line == target_line {
-                    break;
-                }
-                line += 1;
-                col = 0;
-            } else {
-                col += 1;
+            _ => DEFAULT_CELL_PIXELS,
+        };
+        let escape = match self.capabilities.graphics {
+            Graphics::Kitty => format!("{}{}", KITTY_DELETE, preview.kitty(placement, cell)),
+            _ => preview.sixel(placement, cell),
+        };
+        let mut stdout = term::output();
+        crossterm::queue!(stdout, crossterm::cursor::MoveTo(placement.x, placement.y))?;
+        stdout.write_all(escape.as_bytes())?;
+        stdout.flush()?;
+        preview.drawn = Some(placement);
+        Ok(())
+    }
+
+    fn close_image_preview(&mut self) {
+        if self
+            .image_preview
+            .take()
+            .is_some_and(|preview| preview.drawn.is_some())
+        {
+            if self.capabilities.graphics == Graphics::Kitty {
+                let mut stdout = term::output();
+                let _ = stdout.write_all(KITTY_DELETE.as_bytes());
+                let _ = stdout.flush();
             }
-            pos += 1;
+            // Sixel pixels stay until their cells are redrawn
+            self.needs_clear = true;
+        }
+    }
+
+    /// `/image-preview` for PNG or JPEG data held as a data: URI, base64
+    /// or a hex dump.
+    fn preview_image(&mut self) {
+        if self.buffer.is_empty() {
+            self.error_message = Some(AppError::empty_input());
+            return;
+        }
+        let bytes = match DataUri::parse(&self.buffer) {
+            Ok(uri) => Ok(uri.payload),
+            Err(_) => parse_dump(&self.buffer),
+        };
+        let Ok(bytes) = bytes else {
+            self.error_message = Some(AppError::failed(
+                "Expected image data as a data: URI, base64 or a hex dump",
+            ));
+            return;
+        };
+        match ImagePreview::decode(&bytes) {
+            Ok(preview) => self.image_preview = Some(preview),
+            Err(e) => self.error_message = Some(AppError::failed(e)),
         }
-        self.cursor_pos = pos;
+    }
+
+    fn get_cursor_line_col(&self) -> (usize, usize) {
+        editor::line_col(&self.input, self.cursor_pos)
+    }
+
+    fn set_cursor_from_line_col(&mut self, target_line: usize, target_col: usize) {
+        let pos = editor::pos_from_line_col(&self.input, target_line, target_col);
+        self.cursor_pos = editor::grapheme_start(&self.input, pos);
     }
 
     fn adjust_input_scroll(&mut self) {
@@ -152,61 +546,163 @@ impl App {
         }
     }
 
-    fn get_available_commands() -> Vec<&'static str> {
-        vec![
-            "/base64-decode",
-            "/base64-encode",
+    /// Commands whose output is a secret, so the buffer is masked after them
+    const SECRET_OUTPUT: &[&'static str] = &["/password", "/random-string"];
+
+    /// Commands whose argument is a passphrase. /history and macros keep
+    /// just the command, so running it again prompts instead.
+    const SECRET_ARGS: &[&'static str] = &["/encrypt", "/decrypt"];
+
+    /// Commands whose arguments skip `$name` interpolation: `/pipe` hands
+    /// variables to the shell instead, and a passphrase is taken as typed.
+    const VERBATIM_ARGS: &[&'static str] = &["/pipe", "/encrypt", "/decrypt"];
+
+    /// Commands handled by the app itself rather than the transform registry
+    const APP_COMMANDS: &[(&'static str, &'static str)] = &[
+        (
             "/copy",
-            "/css-format",
-            "/css-minify",
-            "/cuid",
-            "/exit",
-            "/json-format",
-            "/json-minify",
-            "/redo",
-            "/sha-256",
-            "/undo",
-            "/unicode-unescape",
-            "/unicode-escape",
-            "/url-decode",
-            "/url-encode",
-            "/uuid",
-        ]
+            "Copy buffer to clipboard (--tmux|--screen); append `| /copy` to any command",
+        ),
+        ("/edit", "Edit the buffer in $VISUAL or $EDITOR"),
+        (
+            "/emit",
+            "Toggle printing the buffer to stdout on exit, as --emit does",
+        ),
+        ("/exit", "Exit the application"),
+        (
+            "/file-to-data-uri",
+            "Load a file as a base64 data: URI for CSS or HTML: /file-to-data-uri <path>",
+        ),
+        (
+            "/hash-file",
+            "Hash a file without loading it: /hash-file <path> [md5|sha1|sha256]",
+        ),
+        (
+            "/image-preview",
+            "Preview PNG or JPEG data (data: URI, base64 or hex dump) in the terminal",
+        ),
+        (
+            "/macro",
+            "Record and replay steps: /macro record <name>, stop, run [name], or list",
+        ),
+        (
+            "/pipe",
+            "Replace the buffer with the output of a shell command fed the buffer: /pipe jq .",
+        ),
+        (
+            "/history",
+            "List the commands run this session and how long each took",
+        ),
+        (
+            "/mask",
+            "Toggle drawing the buffer as ••• while keeping it copyable",
+        ),
+        ("/redo", "Redo the last undone action"),
+        (
+            "/save",
+            "Write the buffer to a file, or a data: URI's decoded payload: /save <path>",
+        ),
+        (
+            "/set",
+            "Toggle options: /set line-numbers|wrap|keep-scroll|auto-copy|append on|off",
+        ),
+        (
+            "/set-var",
+            "Set a variable for $name in later commands: /set-var <name> [value]",
+        ),
+        (
+            "/stash",
+            "Encrypt the buffer with a passphrase and save it under a name",
+        ),
+        (
+            "/share",
+            "Upload the buffer to a paste service and copy the URL",
+        ),
+        (
+            "/snippet",
+            "Keep frequently used text: /snippet save <name>, load <name>, or list",
+        ),
+        (
+            "/spellcheck",
+            "Highlight misspelled words; /spellcheck fix [n] applies suggestions",
+        ),
+        ("/theme", "Switch color theme (dark, light, solarized)"),
+        ("/undo", "Undo the last buffer modification"),
+        ("/vars", "List the variables set with /set-var"),
+        (
+            "/watch",
+            "Load the clipboard whenever it changes and re-run the commands since the last entry",
+        ),
+        (
+            "/unstash",
+            "List stashes, or /unstash <name> to decrypt one into the buffer",
+        ),
+        (
+            "/whois",
+            "Look up the domain or IP in the buffer over RDAP and show the record as JSON",
+        ),
+    ];
+
+    fn builtin_commands() -> Vec<&'static str> {
+        TRANSFORMS
+            .iter()
+            .map(|transform| transform.name)
+            .chain(Self::APP_COMMANDS.iter().map(|(name, _)| *name))
+            .collect()
     }
 
-    fn get_filtered_commands(&self) -> Vec<&'static str> {
-        let input_text = self.input.to_string();
-        if !input_text.starts_with('/') {
+    fn get_available_commands(&self) -> Vec<&str> {
+        let mut commands = Self::builtin_commands();
+        commands.extend(self.plugins.iter().map(|plugin| plugin.name.as_str()));
+        commands.extend(self.scripts.iter().map(|script| script.name.as_str()));
+        commands.sort_unstable();
+        commands
+    }
+
+    fn get_filtered_commands(&self) -> Vec<&str> {
+        // Runs on every frame, so rule out pastes before copying the input
+        if self.input.len_chars() == 0
+            || self.input.char(0) != '/'
+            || self.input.len_lines() > 1
+            || self.prompting()
+        {
             return vec![];
         }
+        let input_text = self.input.to_string();
+
+        let available = self.get_available_commands();
+        if input_text == "/" {
+            // Frequent commands first, then everything else alphabetically
+            let frequent = self.frequent_commands();
+            let rest = available.into_iter().filter(|cmd| !frequent.contains(cmd));
+            return frequent.iter().copied().chain(rest).collect();
+        }
 
-        Self::get_available_commands()
+        available
             .into_iter()
             .filter(|cmd| cmd.starts_with(&input_text))
             .collect()
     }
 
-    fn get_command_help(command: &str) -> Option<&'static str> {
-        match command {
-            "/base64-decode" => Some("Decode base64-encoded text"),
-            "/base64-encode" => Some("Encode text as base64"),
-            "/copy" => Some("Copy buffer contents to clipboard"),
-            "/css-format" => Some("Format CSS code"),
-            "/css-minify" => Some("Minify CSS code"),
-            "/cuid" => Some("Generate a CUID (Collision-resistant Unique ID)"),
-            "/exit" => Some("Exit the application"),
-            "/json-format" => Some("Format JSON with indentation"),
-            "/json-minify" => Some("Minify JSON by removing whitespace"),
-            "/redo" => Some("Redo the last undone action"),
-            "/sha-256" => Some("Generate SHA-256 hash of buffer contents"),
-            "/undo" => Some("Undo the last buffer modification"),
-            "/unicode-unescape" => Some("Decode unicode escape sequences (\\uXXXX)"),
-            "/unicode-escape" => Some("Encode non-ASCII characters as unicode escapes"),
-            "/url-decode" => Some("Decode URL-encoded text"),
-            "/url-encode" => Some("Encode text for use in URLs"),
-            "/uuid" => Some("Generate a UUID v4"),
-            _ => None,
+    fn frequent_commands(&self) -> Vec<&str> {
+        self.usage
+            .frequent(&self.get_available_commands(), usage::FREQUENT_LIMIT)
+    }
+
+    fn get_command_help(&self, command: &str) -> Option<&str> {
+        if let Some(transform) = find_transform(command) {
+            return Some(transform.help);
+        }
+        if let Some(plugin) = self.plugins.iter().find(|plugin| plugin.name == command) {
+            return Some(&plugin.help);
         }
+        if let Some(script) = self.scripts.iter().find(|script| script.name == command) {
+            return Some(&script.help);
+        }
+        Self::APP_COMMANDS
+            .iter()
+            .find(|(name, _)| *name == command)
+            .map(|(_, help)| *help)
     }
 
     fn draw(&self, frame: &mut Frame) {
@@ -214,29 +710,118 @@ impl App {
     }
 
     fn handle_events(&mut self) -> Result<()> {
-        match event::read()? {
-            Event::Key(key) => self.handle_key_event(key),
+        // While watching, wake up regularly to check the clipboard, and
+        // once the input settles to detect its type
+        let timeout = [
+            self.watching.is_some().then_some(WATCH_INTERVAL),
+            self.input_badge_due
+                .map(|due| due.saturating_duration_since(Instant::now())),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        if let Some(timeout) = timeout
+            && !event::poll(timeout)?
+        {
+            if self
+                .input_badge_due
+                .is_some_and(|due| due <= Instant::now())
+            {
+                self.detect_input_type();
+            }
+            if self.watching.is_some() {
+                self.check_clipboard();
+            }
+            return Ok(());
+        }
+        let event = event::read()?;
+        let started = Instant::now();
+        self.handle_event(event);
+        self.track_input_changes();
+        self.debug.record_event(started.elapsed());
+        Ok(())
+    }
+
+    /// Schedules detection of the input's type for when typing pauses, so a
+    /// large paste isn't detected again on every keystroke.
+    fn track_input_changes(&mut self) {
+        let mut hasher = DefaultHasher::new();
+        for chunk in self.input.chunks() {
+            chunk.hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+        if hash != self.input_hash {
+            self.input_hash = hash;
+            self.input_badge = None;
+            self.input_badge_due = Some(Instant::now() + INPUT_DETECT_DELAY);
+        }
+    }
+
+    fn detect_input_type(&mut self) {
+        self.input_badge_due = None;
+        let input = self.input.to_string();
+        if self.prompting() || input.trim_start().starts_with('/') {
+            return;
+        }
+        self.input_badge = Some(detect(&input))
+            .filter(|content_type| !matches!(content_type, ContentType::Empty | ContentType::Text));
+    }
+
+    fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            buffer_bytes: self.buffer.len(),
+            buffer_lines: self.buffer.lines().count(),
+            rope_chars: self.input.len_chars(),
+            rope_bytes: self.input.len_bytes(),
+            rope_lines: self.input.len_lines(),
+            undo_entries: self.undo_stack.len(),
+            undo_bytes: self.undo_stack.iter().map(String::capacity).sum(),
+            redo_entries: self.redo_stack.len(),
+            redo_bytes: self.redo_stack.iter().map(String::capacity).sum(),
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            // Windows and the kitty protocol also report releases
+            Event::Key(key) if key.kind != KeyEventKind::Release => self.handle_key_event(key),
             Event::Mouse(mouse) => self.handle_mouse_event(mouse),
             Event::Paste(text) => {
-                let text_len = text.chars().count();
-                self.input.insert(self.cursor_pos, &text);
-                self.cursor_pos += text_len;
+                self.paste(&text);
                 self.autocomplete_index = None;
                 self.autocomplete_scroll = 0;
                 self.adjust_input_scroll();
             }
             _ => {}
         }
-        Ok(())
     }
 
     fn handle_key_event(&mut self, key: KeyEvent) {
         match key.code {
+            KeyCode::F(12) => {
+                self.debug.visible = !self.debug.visible;
+            }
             KeyCode::Char('c') | KeyCode::Char('d')
                 if key.modifiers.contains(KeyModifiers::CONTROL) =>
             {
                 self.exit = true;
             }
+            _ if self.image_preview.is_some() => self.close_image_preview(),
+            _ if self.snippet_picker.is_some() => self.handle_snippet_picker_key(key),
+            _ if self.history_picker.is_some() => self.handle_history_key(key),
+            _ if self.pending.is_some() => self.handle_pending_key(key),
+            _ if !self.prompting() && self.macros.iter().any(|m| m.matches(&key)) => {
+                let name = self
+                    .macros
+                    .iter()
+                    .find(|m| m.matches(&key))
+                    .map(|m| m.name.clone());
+                self.run_macro(name.as_deref());
+            }
+            KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.toggle_focus();
+            }
+            _ if self.focus == Focus::Buffer => self.handle_buffer_key(key),
             KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.cursor_pos = 0;
             }
@@ -247,64 +832,145 @@ impl App {
                 if key.modifiers.contains(KeyModifiers::CONTROL)
                     || key.modifiers.contains(KeyModifiers::SUPER) =>
             {
-                if let Ok(text) = self.clipboard.get_text() {
-                    let text_len = text.chars().count();
-                    self.input.insert(self.cursor_pos, &text);
-                    self.cursor_pos += text_len;
+                match clipboard::open(&mut self.clipboard)
+                    .and_then(|clipboard| clipboard.get_text())
+                {
+                    Ok(text) => self.paste(&text),
+                    Err(e) => self.error_message = Some(AppError::failed(e)),
                 }
             }
             KeyCode::Tab => {
-                let filtered = self.get_filtered_commands();
-                if !filtered.is_empty() {
+                let count = self.get_filtered_commands().len();
+                if count == 0 {
+                    self.toggle_focus();
+                } else {
                     if let Some(index) = self.autocomplete_index {
-                        self.autocomplete_index = Some((index + 1) % filtered.len());
+                        self.autocomplete_index = Some((index + 1) % count);
                     } else {
                         self.autocomplete_index = Some(0);
                     }
-                    self.adjust_autocomplete_scroll(filtered.len());
+                    self.adjust_autocomplete_scroll(count);
                 }
             }
             KeyCode::BackTab => {
-                let filtered = self.get_filtered_commands();
-                if !filtered.is_empty() {
+                let count = self.get_filtered_commands().len();
+                if count > 0 {
                     if let Some(index) = self.autocomplete_index {
                         self.autocomplete_index = if index == 0 {
-                            Some(filtered.len() - 1)
+                            Some(count - 1)
                         } else {
                             Some(index - 1)
                         };
                     } else {
-                        self.autocomplete_index = Some(filtered.len() - 1);
+                        self.autocomplete_index = Some(count - 1);
+                    }
+                    self.adjust_autocomplete_scroll(count);
+                }
+            }
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(command) = self.suggestion.take() {
+                    // A smart paste still sits in the input; load it first
+                    if self.input.len_chars() > 0 {
+                        self.submit(self.input.to_string());
+                        self.clear_input();
                     }
-                    self.adjust_autocomplete_scroll(filtered.len());
+                    self.submit(command.to_string());
                 }
             }
             KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.insert_newline();
             }
+            KeyCode::Char('z') | KeyCode::Char('Z')
+                if key.modifiers.contains(KeyModifiers::SUPER)
+                    && key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                if let Some((input, cursor)) = self.input_history.redo(&self.input, self.cursor_pos)
+                {
+                    self.restore_input(input, cursor);
+                }
+            }
+            KeyCode::Char('z')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    || key.modifiers.contains(KeyModifiers::SUPER) =>
+            {
+                if let Some((input, cursor)) = self.input_history.undo(&self.input, self.cursor_pos)
+                {
+                    self.restore_input(input, cursor);
+                }
+            }
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some((input, cursor)) = self.input_history.redo(&self.input, self.cursor_pos)
+                {
+                    self.restore_input(input, cursor);
+                }
+            }
+            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.cursor_pos = editor::prev_word_start(&self.input, self.cursor_pos);
+                self.adjust_input_scroll();
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.cursor_pos = editor::next_word_end(&self.input, self.cursor_pos);
+                self.adjust_input_scroll();
+            }
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.delete_word_before_cursor();
+            }
+            KeyCode::Backspace if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.delete_word_before_cursor();
+            }
+            KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let mut end = editor::line_end(&self.input, self.cursor_pos);
+                // At the end of a line, join it with the next one instead
+                if end == self.cursor_pos {
+                    end = editor::next_grapheme(&self.input, end);
+                }
+                self.input_history
+                    .record(&self.input, self.cursor_pos, EditKind::Other);
+                self.input.remove(self.cursor_pos..end);
+                self.autocomplete_index = None;
+                self.autocomplete_scroll = 0;
+                self.adjust_input_scroll();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let start = editor::line_start(&self.input, self.cursor_pos);
+                self.input_history
+                    .record(&self.input, self.cursor_pos, EditKind::Other);
+                self.input.remove(start..self.cursor_pos);
+                self.cursor_pos = start;
+                self.autocomplete_index = None;
+                self.autocomplete_scroll = 0;
+                self.adjust_input_scroll();
+            }
             KeyCode::Char(c) => {
+                self.input_history
+                    .record(&self.input, self.cursor_pos, EditKind::Insert);
+                if c.is_whitespace() {
+                    self.input_history.break_group();
+                }
                 self.input.insert_char(self.cursor_pos, c);
                 self.cursor_pos += 1;
                 self.autocomplete_index = None;
                 self.autocomplete_scroll = 0;
                 self.adjust_input_scroll();
             }
-            KeyCode::Backspace => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                    self.input.remove(self.cursor_pos..self.cursor_pos + 1);
-                    self.autocomplete_index = None;
-                    self.autocomplete_scroll = 0;
-                    self.adjust_input_scroll();
-                }
+            KeyCode::Backspace if self.cursor_pos > 0 => {
+                self.input_history
+                    .record(&self.input, self.cursor_pos, EditKind::Backspace);
+                let start = editor::prev_grapheme(&self.input, self.cursor_pos);
+                self.input.remove(start..self.cursor_pos);
+                self.cursor_pos = start;
+                self.autocomplete_index = None;
+                self.autocomplete_scroll = 0;
+                self.adjust_input_scroll();
             }
-            KeyCode::Delete => {
-                if self.cursor_pos < self.input.len_chars() {
-                    self.input.remove(self.cursor_pos..self.cursor_pos + 1);
-                    self.autocomplete_index = None;
-                    self.autocomplete_scroll = 0;
-                    self.adjust_input_scroll();
-                }
+            KeyCode::Delete if self.cursor_pos < self.input.len_chars() => {
+                self.input_history
+                    .record(&self.input, self.cursor_pos, EditKind::Delete);
+                let end = editor::next_grapheme(&self.input, self.cursor_pos);
+                self.input.remove(self.cursor_pos..end);
+                self.autocomplete_index = None;
+                self.autocomplete_scroll = 0;
+                self.adjust_input_scroll();
             }
             KeyCode::Up => {
                 let (current_line, current_col) = self.get_cursor_line_col();
@@ -315,28 +981,38 @@ impl App {
             }
             KeyCode::Down => {
                 let (current_line, current_col) = self.get_cursor_line_col();
-                let text = self.input.to_string();
-                let total_lines = if text.is_empty() {
-                    1
-                } else {
-                    text.lines().count().max(1)
-                };
-                if current_line + 1 < total_lines {
+                if current_line + 1 < self.input.len_lines() {
                     self.set_cursor_from_line_col(current_line + 1, current_col);
                     self.adjust_input_scroll();
                 }
             }
-            KeyCode::Left => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                    self.adjust_input_scroll();
-                }
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.scroll_horizontal(-8);
             }
-            KeyCode::Right => {
-                if self.cursor_pos < self.input.len_chars() {
-                    self.cursor_pos += 1;
-                    self.adjust_input_scroll();
-                }
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.scroll_horizontal(8);
+            }
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.cursor_pos = editor::prev_word_start(&self.input, self.cursor_pos);
+                self.adjust_input_scroll();
+            }
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.cursor_pos = editor::next_word_end(&self.input, self.cursor_pos);
+                self.adjust_input_scroll();
+            }
+            KeyCode::Left if self.cursor_pos > 0 => {
+                self.cursor_pos = editor::prev_grapheme(&self.input, self.cursor_pos);
+                self.adjust_input_scroll();
+            }
+            KeyCode::Right if self.cursor_pos < self.input.len_chars() => {
+                self.cursor_pos = editor::next_grapheme(&self.input, self.cursor_pos);
+                self.adjust_input_scroll();
+            }
+            KeyCode::Home if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_pos = 0;
+            }
+            KeyCode::End if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_pos = self.max_scroll();
             }
             KeyCode::Home => {
                 let (current_line, _) = self.get_cursor_line_col();
@@ -344,63 +1020,56 @@ impl App {
                 self.adjust_input_scroll();
             }
             KeyCode::End => {
-                let text = self.input.to_string();
-                let (current_line, _) = self.get_cursor_line_col();
-                let lines: Vec<&str> = text.lines().collect();
-                if current_line < lines.len() {
-                    let line_length = lines[current_line].chars().count();
-                    self.set_cursor_from_line_col(current_line, line_length);
-                } else {
-                    self.cursor_pos = self.input.len_chars();
-                }
+                self.cursor_pos = editor::line_end(&self.input, self.cursor_pos);
                 self.adjust_input_scroll();
             }
             KeyCode::Enter => {
-                if key.modifiers.contains(KeyModifiers::SHIFT)
-                    || key.modifiers.contains(KeyModifiers::ALT)
+                if key
+                    .modifiers
+                    .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT | KeyModifiers::CONTROL)
                 {
-                    // Shift+Enter does not get picked up in most
-                    // macOS terminal emulators:
+                    // Without the kitty keyboard protocol, Shift+Enter does
+                    // not get picked up in most macOS terminal emulators:
                     // https://github.com/crossterm-rs/crossterm/issues/685
                     self.insert_newline();
                     return;
                 }
-                // Check if autocomplete is active
-                let filtered = self.get_filtered_commands();
-                if let Some(index) = self.autocomplete_index {
-                    if let Some(command) = filtered.get(index) {
-                        self.input = Rope::from(command.to_string().as_str());
-                        self.cursor_pos = self.input.len_chars();
-                        self.autocomplete_index = None;
-                        self.autocomplete_scroll = 0;
-                        self.input_scroll_line = 0;
-                        return;
-                    }
-                }
-
-                if self.input.len_chars() > 0 {
-                    let input_text = self.input.to_string();
-                    let input_trimmed = input_text.trim();
-                    let first_word = input_trimmed.split_whitespace().next().unwrap_or("").trim();
-
-                    // Check if it exactly matches a valid command
-                    let is_valid_command = App::get_available_commands()
-                        .iter()
-                        .any(|cmd| *cmd == first_word);
-
-                    if is_valid_command {
-                        self.handle_command(input_trimmed);
-                    } else {
-                        // Save current buffer to undo stack before replacing
-                        self.push_undo();
-                        self.buffer = input_text;
-                    }
-
+                if let Some(action) = self.passphrase_prompt.take() {
+                    let passphrase = self.input.to_string();
                     self.input = Rope::new();
                     self.cursor_pos = 0;
+                    self.input_scroll_line = 0;
+                    // Don't leave the passphrase recoverable through Ctrl-Z
+                    self.input_history = InputHistory::default();
+                    self.run_passphrase_action(action, &passphrase);
+                    return;
+                }
+                if self.template_prompt.is_some() {
+                    let value = self.input.to_string();
+                    self.clear_input();
+                    self.answer_template_prompt(value);
+                    return;
+                }
+                // Check if autocomplete is active
+                let selected = self.autocomplete_index.and_then(|index| {
+                    self.get_filtered_commands()
+                        .get(index)
+                        .map(|command| command.to_string())
+                });
+                if let Some(command) = selected {
+                    self.input_history
+                        .record(&self.input, self.cursor_pos, EditKind::Other);
+                    self.input = Rope::from(command.as_str());
+                    self.cursor_pos = self.input.len_chars();
                     self.autocomplete_index = None;
                     self.autocomplete_scroll = 0;
                     self.input_scroll_line = 0;
+                    return;
+                }
+
+                if self.input.len_chars() > 0 {
+                    self.submit(self.input.to_string());
+                    self.clear_input();
                 }
             }
             KeyCode::PageUp => {
@@ -412,12 +1081,27 @@ impl App {
                     self.scroll_pos = (self.scroll_pos + 10).min(buffer_lines.saturating_sub(1));
                 }
             }
+            KeyCode::Esc if self.template_prompt.is_some() => {
+                self.template_prompt = None;
+                self.clear_input();
+                self.info_message = Some("Cancelled".to_string());
+            }
+            KeyCode::Esc if self.passphrase_prompt.is_some() => {
+                self.passphrase_prompt = None;
+                self.input = Rope::new();
+                self.cursor_pos = 0;
+                self.input_scroll_line = 0;
+                self.input_history = InputHistory::default();
+                self.info_message = Some("Cancelled".to_string());
+            }
             KeyCode::Esc => {
                 // Close autocomplete popup if open, otherwise exit
                 let filtered = self.get_filtered_commands();
                 if !filtered.is_empty() || self.autocomplete_index.is_some() {
                     self.autocomplete_index = None;
                     self.autocomplete_scroll = 0;
+                    self.input_history
+                        .record(&self.input, self.cursor_pos, EditKind::Other);
                     self.input = Rope::new();
                     self.cursor_pos = 0;
                     self.input_scroll_line = 0;
@@ -427,50 +1111,1178 @@ impl App {
         }
     }
 
-    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
-        match mouse.kind {
-            MouseEventKind::ScrollUp => {
-                self.scroll_pos = self.scroll_pos.saturating_sub(3);
-            }
-            MouseEventKind::ScrollDown => {
-                let buffer_lines = self.buffer.lines().count();
-                if buffer_lines > 0 {
-                    self.scroll_pos = (self.scroll_pos + 3).min(buffer_lines.saturating_sub(1));
+    fn handle_pending_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => match self.pending.take() {
+                Some(PendingAction::Share(service)) => self.share_buffer(&service),
+                Some(PendingAction::RunPlugin(index, args)) => {
+                    self.policy.trust(&self.plugins[index]);
+                    self.error_message = None;
+                    self.info_message = None;
+                    self.success_message = None;
+                    self.run_plugin(index, &args);
                 }
+                Some(PendingAction::LoadFile(path, _)) => self.load_file(&path),
+                Some(PendingAction::Overwrite(path)) => self.write_file(&path),
+                None => {}
+            },
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.pending = None;
+                self.info_message = Some("Cancelled".to_string());
             }
             _ => {}
         }
     }
 
-    fn insert_newline(&mut self) {
-        self.input.insert_char(self.cursor_pos, '\n');
-        self.cursor_pos += 1;
-        self.adjust_input_scroll();
+    fn run_passphrase_action(&mut self, action: PassphraseAction, passphrase: &str) {
+        self.error_message = None;
+        self.info_message = None;
+        self.success_message = None;
+        match action {
+            PassphraseAction::Stash(name) => match stash::save(&name, &self.buffer, passphrase) {
+                Ok(()) => self.info_message = Some(format!("Stashed buffer as '{}'", name)),
+                Err(e) => self.error_message = Some(AppError::failed(e)),
+            },
+            PassphraseAction::Unstash(name) => match stash::load(&name, passphrase) {
+                Ok(text) => {
+                    self.push_undo();
+                    self.buffer = text;
+                    self.scroll_pos = 0;
+                    self.info_message = Some(format!("Restored stash '{}'", name));
+                }
+                Err(e) => self.error_message = Some(AppError::failed(e)),
+            },
+            PassphraseAction::Transform(transform) => {
+                self.push_undo();
+                self.run_transform(transform, &[passphrase]);
+            }
+        }
     }
 
-    fn push_undo(&mut self) {
-        const MAX_STACK_SIZE: usize = 500;
+    /// Runs a line from the input box: a command if it starts with one, and
+    /// otherwise the new buffer contents.
+    fn submit(&mut self, input_text: String) {
+        let input_trimmed = input_text.trim();
+        let first_word = input_trimmed.split_whitespace().next().unwrap_or("");
 
-        self.undo_stack.push(self.buffer.clone());
+        // Check if it exactly matches a valid command
+        let is_valid_command = self.get_available_commands().contains(&first_word);
 
-        // Keep stack size under limit
-        if self.undo_stack.len() > MAX_STACK_SIZE {
-            self.undo_stack.remove(0);
-        }
+        let is_macro_command = first_word == "/macro";
+        let step = if is_valid_command {
+            input_trimmed.to_string()
+        } else {
+            input_text.clone()
+        };
+        // Macros are saved in the config, so they don't keep a passphrase
+        let macro_step = match Self::SECRET_ARGS.contains(&first_word) {
+            true => first_word.to_string(),
+            false => step.clone(),
+        };
 
-        // Clear redo stack on new action
-        self.redo_stack.clear();
-    }
+        self.suggestion = None;
+        if is_valid_command {
+            self.handle_command(input_trimmed);
+        } else {
+            // Save current buffer to undo stack before replacing
+            self.push_undo();
+            let appended = match split_append_suffix(&input_text) {
+                Some(text) => Some(text.to_string()),
+                None if self.append => Some(input_text),
+                None => {
+                    self.buffer = input_text;
+                    None
+                }
+            };
+            if let Some(text) = &appended {
+                self.append_to_buffer(text);
+            }
+            self.suggest_command();
+            if appended.is_some() && self.info_message.is_none() {
+                self.info_message = Some(format!(
+                    "Appended - {} lines in the buffer",
+                    self.buffer.lines().count()
+                ));
+            }
+        }
 
-    fn undo(&mut self) {
-        if let Some(previous_buffer) = self.undo_stack.pop() {
-            // Push current buffer to redo stack
-            self.redo_stack.push(self.buffer.clone());
+        if !is_valid_command {
+            self.pipeline.clear();
+        } else if self.error_message.is_none() {
+            self.pipeline.record(&step);
+        }
 
-            // Restore previous buffer
-            self.buffer = previous_buffer;
-            self.scroll_pos = 0;
-            self.info_message = Some("Undo".to_string());
+        // Only steps that worked are worth replaying
+        if !is_macro_command
+            && self.error_message.is_none()
+            && let Some((_, steps)) = self.recording.as_mut()
+        {
+            steps.push(macro_step);
+        }
+    }
+
+    /// Adds text on a new line after what's already in the buffer, and
+    /// scrolls to it.
+    fn append_to_buffer(&mut self, text: &str) {
+        if !self.buffer.is_empty() && !self.buffer.ends_with('\n') {
+            self.buffer.push('\n');
+        }
+        self.buffer.push_str(text);
+        self.scroll_pos = self.max_scroll();
+    }
+
+    fn toggle_watch(&mut self) {
+        if self.watching.take().is_some() {
+            self.info_message = Some("Stopped watching the clipboard".to_string());
+            return;
+        }
+        // Only later changes count, not whatever is on the clipboard now
+        match clipboard::open(&mut self.clipboard).and_then(|clipboard| clipboard.get_text()) {
+            Ok(text) => self.watching = Some(text),
+            Err(e @ ClipboardError::Unavailable(_)) => {
+                self.error_message = Some(AppError::failed(e));
+                return;
+            }
+            Err(ClipboardError::WriteOnly) => {
+                self.error_message = Some(AppError::failed(
+                    "/watch needs a readable clipboard, but only OSC 52 is available",
+                ));
+                return;
+            }
+            Err(_) => self.watching = Some(String::new()),
+        }
+        self.info_message = Some(if self.pipeline.is_empty() {
+            "Watching the clipboard - /watch again to stop".to_string()
+        } else {
+            format!(
+                "Watching the clipboard to run {} - /watch again to stop",
+                self.pipeline.steps().join(" → ")
+            )
+        });
+    }
+
+    /// Loads new clipboard contents and replays the pipeline on them.
+    fn check_clipboard(&mut self) {
+        let Ok(text) =
+            clipboard::open(&mut self.clipboard).and_then(|clipboard| clipboard.get_text())
+        else {
+            return;
+        };
+        if text.is_empty() || self.watching.as_ref() == Some(&text) {
+            return;
+        }
+
+        self.push_undo();
+        self.buffer = text;
+        self.scroll_pos = 0;
+        self.error_message = None;
+        let pipeline = self.pipeline.clone();
+        let finished = pipeline.replay(|step| {
+            self.handle_command(step);
+            self.error_message.is_none() && self.pending.is_none() && !self.prompting()
+        });
+        if finished {
+            let count = pipeline.steps().len();
+            self.info_message = Some(format!(
+                "Clipboard changed - ran {} command{}",
+                count,
+                if count == 1 { "" } else { "s" }
+            ));
+        }
+        // The pipeline may have copied its result; don't treat that as new
+        if self.watching.is_some() {
+            let text =
+                clipboard::open(&mut self.clipboard).and_then(|clipboard| clipboard.get_text());
+            self.watching = Some(text.unwrap_or_default());
+        }
+    }
+
+    /// Whether the input is answering a prompt rather than taking text or
+    /// commands.
+    fn prompting(&self) -> bool {
+        self.passphrase_prompt.is_some() || self.template_prompt.is_some()
+    }
+
+    fn clear_input(&mut self) {
+        self.input_history
+            .record(&self.input, self.cursor_pos, EditKind::Other);
+        self.input = Rope::new();
+        self.cursor_pos = 0;
+        self.autocomplete_index = None;
+        self.autocomplete_scroll = 0;
+        self.input_scroll_line = 0;
+    }
+
+    fn paste(&mut self, text: &str) {
+        self.input_history
+            .record(&self.input, self.cursor_pos, EditKind::Other);
+        self.input.insert(self.cursor_pos, text);
+        self.cursor_pos += text.chars().count();
+
+        // Offer to decode a lone token pasted into an empty input
+        if self.prompting() || self.input.len_chars() != text.chars().count() {
+            return;
+        }
+        if let Some(path) = paths::pasted_file(text) {
+            let size = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+            self.pending = Some(PendingAction::LoadFile(path, size));
+            return;
+        }
+        let content_type = detect(text);
+        if matches!(
+            content_type,
+            ContentType::Jwt | ContentType::Uuid | ContentType::Base64
+        ) && let Some(command) = suggest(text, content_type)
+        {
+            self.suggestion = Some(command);
+            self.info_message = Some(format!(
+                "Pasted a {} - Ctrl-T to run {}",
+                content_type.label(),
+                command
+            ));
+        }
+    }
+
+    fn suggest_command(&mut self) {
+        self.info_message = None;
+        self.success_message = None;
+        let content_type = self.buffer_content_type();
+        if let Some(command) = suggest(&self.buffer, content_type) {
+            self.offer_suggestion(content_type, command);
+        }
+    }
+
+    fn offer_suggestion(&mut self, content_type: ContentType, command: &'static str) {
+        self.suggestion = Some(command);
+        self.info_message = Some(format!(
+            "Looks like {} - try {} (Ctrl-T)",
+            content_type.label(),
+            command
+        ));
+    }
+
+    fn macro_command(&mut self, action: Option<&str>, name: Option<&str>) {
+        match action {
+            Some("record") => {
+                let Some(name) = name else {
+                    self.error_message = Some(AppError::usage("/macro record <name>"));
+                    return;
+                };
+                if let Err(e) = macros::validate_name(name) {
+                    self.error_message = Some(AppError::failed(e));
+                    return;
+                }
+                self.recording = Some((name.to_string(), Vec::new()));
+                self.info_message = Some(format!(
+                    "Recording macro '{}' - /macro stop to save it",
+                    name
+                ));
+            }
+            Some("stop") => {
+                let Some((name, steps)) = self.recording.take() else {
+                    self.error_message =
+                        Some(AppError::new(ErrorKind::Usage, "Not recording a macro"));
+                    return;
+                };
+                if let Err(e) = macros::save(&name, &steps) {
+                    self.error_message = Some(AppError::failed(e));
+                    return;
+                }
+                self.info_message = Some(format!("Saved macro '{}' ({} steps)", name, steps.len()));
+                match self.macros.iter_mut().find(|m| m.name == name) {
+                    Some(existing) => existing.steps = steps,
+                    None => self.macros.push(Macro {
+                        name: name.clone(),
+                        steps,
+                        key: None,
+                    }),
+                }
+                self.last_macro = Some(name);
+            }
+            Some("run") => self.run_macro(name),
+            Some("list") if self.macros.is_empty() => {
+                self.info_message =
+                    Some("No macros yet - /macro record <name> to create one".to_string());
+            }
+            Some("list") => {
+                let names: Vec<String> = self
+                    .macros
+                    .iter()
+                    .map(|m| format!("{} ({} steps)", m.name, m.steps.len()))
+                    .collect();
+                self.info_message = Some(format!("Macros: {}", names.join(", ")));
+            }
+            _ => {
+                self.error_message =
+                    Some(AppError::usage("/macro record <name>|stop|run [name]|list"));
+            }
+        }
+    }
+
+    /// Replays a macro's steps in order, stopping at the first one that
+    /// fails or asks for input.
+    fn run_macro(&mut self, name: Option<&str>) {
+        let Some(name) = name.map(str::to_string).or_else(|| self.last_macro.clone()) else {
+            self.error_message = Some(AppError::usage("/macro run <name>"));
+            return;
+        };
+        let Some(steps) = self
+            .macros
+            .iter()
+            .find(|m| m.name == name)
+            .map(|m| m.steps.clone())
+        else {
+            self.error_message = Some(AppError::failed(MacroError::NotFound(name)));
+            return;
+        };
+        if self.recording.is_some() {
+            self.error_message = Some(AppError::new(
+                ErrorKind::Usage,
+                "Can't run a macro while recording",
+            ));
+            return;
+        }
+
+        self.last_macro = Some(name.clone());
+        for (index, step) in steps.iter().enumerate() {
+            // A macro can't replay itself or another macro
+            if step.split_whitespace().next() == Some("/macro") {
+                continue;
+            }
+            self.submit(step.clone());
+            if let Some(error) = &mut self.error_message {
+                error.message = format!(
+                    "{} (macro '{}' stopped at step {})",
+                    error.message,
+                    name,
+                    index + 1
+                );
+                return;
+            }
+            if self.exit || self.pending.is_some() || self.prompting() {
+                return;
+            }
+        }
+        self.info_message = Some(format!("Ran macro '{}'", name));
+    }
+
+    fn list_stashes(&mut self) {
+        match stash::list() {
+            Ok(stashes) if stashes.is_empty() => {
+                self.info_message =
+                    Some("No stashes yet - /stash <name> to create one".to_string());
+            }
+            Ok(stashes) => {
+                let width = stashes
+                    .iter()
+                    .map(|(name, _)| name.len())
+                    .max()
+                    .unwrap_or(0);
+                self.push_undo();
+                self.buffer = stashes
+                    .iter()
+                    .map(|(name, modified)| {
+                        format!("{:<width$}  {}", name, stash::format_timestamp(*modified))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.scroll_pos = 0;
+                self.info_message = Some("/unstash <name> to restore".to_string());
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    /// `/template [snippet] [name=value ...]`. The template is the buffer,
+    /// or the named snippet with values taken from a JSON buffer; anything
+    /// still missing is asked for.
+    fn start_template(&mut self, args: &[&str]) {
+        let (snippet, assignments) = match args.first() {
+            Some(first) if !first.contains('=') => (Some(*first), &args[1..]),
+            _ => (None, args),
+        };
+        let (template, mut values) = match snippet {
+            Some(name) => {
+                let template = match snippets::load(name) {
+                    Ok(template) => template,
+                    Err(e) => {
+                        self.error_message = Some(AppError::failed(e));
+                        return;
+                    }
+                };
+                let values = match self.buffer.trim().is_empty() {
+                    true => BTreeMap::new(),
+                    false => match template_values(&template, &self.buffer) {
+                        Ok(values) => values,
+                        Err(e) => {
+                            self.error_message = Some(AppError::failed(e));
+                            return;
+                        }
+                    },
+                };
+                (template, values)
+            }
+            None if self.buffer.is_empty() => {
+                self.error_message = Some(AppError::empty_input());
+                return;
+            }
+            None => (self.buffer.clone(), BTreeMap::new()),
+        };
+        for arg in assignments {
+            match arg.split_once('=') {
+                Some((name, value)) if !name.is_empty() => {
+                    values.insert(name.to_string(), value.to_string());
+                }
+                _ => {
+                    self.error_message = Some(AppError::new(
+                        ErrorKind::Usage,
+                        format!("Expected name=value, got '{}'", arg),
+                    ));
+                    return;
+                }
+            }
+        }
+
+        let names = placeholders(&template);
+        for name in &names {
+            if !values.contains_key(name)
+                && let Some(value) = self.vars.get(name)
+            {
+                values.insert(name.clone(), value.clone());
+            }
+        }
+        if names.is_empty() {
+            self.error_message = Some(AppError::failed(
+                "No {{placeholder}} tokens in the template",
+            ));
+            return;
+        }
+        let remaining: Vec<String> = names
+            .iter()
+            .filter(|name| !values.contains_key(*name))
+            .cloned()
+            .collect();
+        let prompt = TemplatePrompt {
+            template,
+            values,
+            total: remaining.len(),
+            remaining,
+        };
+        match prompt.message() {
+            Some(message) => {
+                self.info_message = Some(message);
+                self.template_prompt = Some(prompt);
+            }
+            None => self.finish_template(prompt),
+        }
+    }
+
+    fn answer_template_prompt(&mut self, value: String) {
+        let Some(mut prompt) = self.template_prompt.take() else {
+            return;
+        };
+        if !prompt.remaining.is_empty() {
+            let name = prompt.remaining.remove(0);
+            prompt.values.insert(name, value);
+        }
+        match prompt.message() {
+            Some(message) => {
+                self.info_message = Some(message);
+                self.template_prompt = Some(prompt);
+            }
+            None => self.finish_template(prompt),
+        }
+    }
+
+    fn finish_template(&mut self, prompt: TemplatePrompt) {
+        let count = placeholders(&prompt.template).len();
+        self.push_undo();
+        self.buffer = fill_template(&prompt.template, &prompt.values);
+        self.scroll_pos = 0;
+        self.info_message = Some(format!(
+            "Filled {} placeholder{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+        self.auto_copy_result();
+    }
+
+    /// `/set-var <name> <value>` sets a variable and `/set-var <name>`
+    /// removes it. The value is the rest of the line, spaces included.
+    fn set_var(&mut self, args: &str) {
+        let (name, value) = args
+            .split_once(char::is_whitespace)
+            .map_or((args, ""), |(name, value)| (name, value.trim_start()));
+        if !is_var_name(name) {
+            self.error_message = Some(AppError::usage("/set-var <name> [value]"));
+            return;
+        }
+        if value.is_empty() {
+            self.info_message = Some(match self.vars.remove(name) {
+                Some(_) => format!("Removed ${}", name),
+                None => format!("${} isn't set", name),
+            });
+            return;
+        }
+        self.vars.insert(name.to_string(), value.to_string());
+        self.info_message = Some(format!("Set ${}", name));
+    }
+
+    fn list_vars(&mut self) {
+        if self.vars.is_empty() {
+            self.info_message =
+                Some("No variables yet - /set-var <name> <value> to add one".to_string());
+            return;
+        }
+        let vars: Vec<String> = self
+            .vars
+            .iter()
+            .map(|(name, value)| format!("${}={}", name, value))
+            .collect();
+        self.info_message = Some(format!("Variables: {}", vars.join(", ")));
+    }
+
+    fn snippet_command(&mut self, action: Option<&str>, name: Option<&str>) {
+        match (action, name) {
+            (Some("save"), Some(name)) => {
+                if self.buffer.is_empty() {
+                    self.error_message = Some(AppError::empty_input());
+                    return;
+                }
+                let verb = match snippets::exists(name) {
+                    true => "Updated",
+                    false => "Saved",
+                };
+                match snippets::save(name, &self.buffer) {
+                    Ok(()) => {
+                        self.success_message = Some(format!("{} snippet '{}'", verb, name));
+                    }
+                    Err(e) => self.error_message = Some(AppError::failed(e)),
+                }
+            }
+            (Some("load"), Some(name)) => self.load_snippet(name),
+            (Some("list"), None) | (None, None) => match snippets::list() {
+                Ok(list) if list.is_empty() => {
+                    self.info_message =
+                        Some("No snippets yet - /snippet save <name> to create one".to_string());
+                }
+                Ok(list) => self.snippet_picker = Some(SnippetPicker::new(list)),
+                Err(e) => self.error_message = Some(AppError::failed(e)),
+            },
+            _ => {
+                self.error_message =
+                    Some(AppError::usage("/snippet save <name> | load <name> | list"));
+            }
+        }
+    }
+
+    fn load_snippet(&mut self, name: &str) {
+        match snippets::load(name) {
+            Ok(text) => {
+                self.push_undo();
+                self.buffer = text;
+                self.scroll_pos = 0;
+                self.info_message = Some(format!("Loaded snippet '{}'", name));
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    fn handle_snippet_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = &mut self.snippet_picker else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => picker.up(),
+            KeyCode::Down | KeyCode::Char('j') => picker.down(),
+            KeyCode::Enter => {
+                let name = picker.selected_name().map(str::to_string);
+                self.snippet_picker = None;
+                if let Some(name) = name {
+                    self.load_snippet(&name);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.snippet_picker = None,
+            _ => {}
+        }
+    }
+
+    fn handle_history_key(&mut self, key: KeyEvent) {
+        let Some(picker) = &mut self.history_picker else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => picker.up(),
+            KeyCode::Down | KeyCode::Char('j') => picker.down(),
+            KeyCode::Enter => {
+                let input = picker
+                    .selected_entry(&self.history)
+                    .map(|entry| Rope::from(entry.input.as_str()));
+                self.history_picker = None;
+                if let Some(input) = input {
+                    self.input_history
+                        .record(&self.input, self.cursor_pos, EditKind::Other);
+                    let cursor = input.len_chars();
+                    self.restore_input(input, cursor);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.history_picker = None,
+            _ => {}
+        }
+    }
+
+    fn run_plugin(&mut self, index: usize, args: &[String]) {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match self.plugins[index].run(&self.buffer, &args) {
+            Ok(output) => {
+                self.push_undo();
+                self.buffer = output;
+                self.scroll_pos = 0;
+                self.auto_copy_result();
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    /// `/pipe`, replacing the buffer with the command's stdout. Anything it
+    /// wrote to stderr goes to the status bar.
+    fn pipe(&mut self, line: &str) {
+        if line.is_empty() {
+            self.error_message = Some(AppError::usage("/pipe <shell command>"));
+            return;
+        }
+        if let Verdict::Deny(reason) = self.policy.check_pipe() {
+            self.error_message = Some(AppError::new(ErrorKind::Denied, reason));
+            return;
+        }
+        match shell::pipe(line, &self.buffer, &self.vars) {
+            Ok(piped) => {
+                self.push_undo();
+                self.buffer = piped.stdout;
+                self.scroll_pos = 0;
+                self.info_message = Some(match piped.stderr.is_empty() {
+                    true => format!("Piped through {}", line),
+                    false => one_line(&piped.stderr),
+                });
+                self.auto_copy_result();
+            }
+            Err(e) => self.error_message = Some(AppError::failed(one_line(&e.to_string()))),
+        }
+    }
+
+    /// Copies the buffer to the system clipboard, or to a multiplexer paste
+    /// buffer with `--tmux`/`--screen`.
+    fn copy_buffer(&mut self, target: Option<&str>) {
+        if self.buffer.is_empty() {
+            self.error_message = Some(AppError::empty_input());
+            return;
+        }
+
+        match target {
+            Some("--tmux") => match multiplexer::tmux_copy(&self.buffer) {
+                Ok(()) => {
+                    self.info_message = Some("Copied to tmux paste buffer".to_string());
+                }
+                Err(e) => self.error_message = Some(AppError::failed(e)),
+            },
+            Some("--screen") => match multiplexer::screen_copy(&self.buffer) {
+                Ok(()) => {
+                    self.info_message = Some("Copied to screen paste buffer".to_string());
+                }
+                Err(e) => self.error_message = Some(AppError::failed(e)),
+            },
+            Some(other) => {
+                self.error_message = Some(AppError::new(
+                    ErrorKind::Usage,
+                    format!("Unknown option '{}' - expected --tmux or --screen", other),
+                ));
+            }
+            None => match clipboard::open(&mut self.clipboard)
+                .and_then(|clipboard| clipboard.set_text(&self.buffer))
+            {
+                Ok(_) => {
+                    self.info_message = Some("Copied to clipboard".to_string());
+                }
+                Err(e @ ClipboardError::Unavailable(_)) => {
+                    self.error_message = Some(AppError::failed(e));
+                }
+                Err(_) => match Multiplexer::detect() {
+                    Some(multiplexer) => {
+                        self.error_message = Some(AppError::failed(format!(
+                            "Failed to copy to clipboard - {}",
+                            multiplexer.copy_hint()
+                        )));
+                    }
+                    None => {
+                        self.error_message = Some(AppError::failed("Failed to copy to clipboard"));
+                    }
+                },
+            },
+        }
+    }
+
+    /// Copies a command's result when auto-copy is on, noting it after any
+    /// message the command already showed.
+    fn auto_copy_result(&mut self) {
+        if !self.auto_copy || self.buffer.is_empty() {
+            return;
+        }
+        match clipboard::open(&mut self.clipboard)
+            .and_then(|clipboard| clipboard.set_text(&self.buffer))
+        {
+            Ok(_) => {
+                self.info_message = Some(match self.info_message.take() {
+                    Some(message) => format!("{} (copied)", message),
+                    None => "Copied to clipboard".to_string(),
+                });
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    fn share_buffer(&mut self, service: &Service) {
+        self.info_message = None;
+        self.success_message = None;
+        match service.upload(&self.buffer) {
+            Ok(url) => {
+                let copied = clipboard::open(&mut self.clipboard)
+                    .and_then(|clipboard| clipboard.set_text(&url))
+                    .is_ok();
+                self.info_message = Some(if copied {
+                    format!("Shared to {} (copied)", url)
+                } else {
+                    format!("Shared to {}", url)
+                });
+            }
+            Err(e) => self.error_message = Some(AppError::from(e)),
+        }
+    }
+
+    fn whois(&mut self) {
+        if self.buffer.is_empty() {
+            self.error_message = Some(AppError::empty_input());
+            return;
+        }
+        match Query::parse(&self.buffer).and_then(|query| Ok((query.lookup()?, query))) {
+            Ok((record, query)) => {
+                self.buffer = record;
+                self.scroll_pos = 0;
+                self.info_message = Some(format!("RDAP record for {}", query));
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    /// `/json-diff` with the last different buffer on the undo stack as the
+    /// old side, for when the buffer holds a single document.
+    fn json_diff(&mut self, option: Option<&str>) {
+        if self.buffer.is_empty() {
+            self.error_message = Some(AppError::empty_input());
+            return;
+        }
+        let previous = self
+            .undo_stack
+            .iter()
+            .rev()
+            .find(|previous| **previous != self.buffer);
+        match json_diff(&self.buffer, previous.map(String::as_str), option) {
+            Ok(diff) => {
+                self.buffer = diff;
+                self.scroll_pos = 0;
+                self.auto_copy_result();
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    /// Reads a whole file for the buffer, refusing ones too big to work
+    /// with. Errors go to the status bar.
+    fn read_file(&mut self, path: &Path) -> Option<Vec<u8>> {
+        let read = std::fs::metadata(path).and_then(|metadata| {
+            if metadata.len() > MAX_LOADED_FILE_BYTES {
+                return Err(std::io::Error::other(format!(
+                    "too large to load ({}) - /hash-file can still checksum it",
+                    format_bytes(metadata.len() as f64)
+                )));
+            }
+            std::fs::read(path)
+        });
+        read.map_err(|e| {
+            self.error_message = Some(AppError::new(
+                ErrorKind::Io,
+                format!("{}: {}", path.display(), e),
+            ))
+        })
+        .ok()
+    }
+
+    /// `/file-to-data-uri`, with the MIME type taken from the contents or
+    /// the extension.
+    fn file_to_data_uri(&mut self, path: &str) {
+        if path.trim().is_empty() {
+            self.error_message = Some(AppError::usage("/file-to-data-uri <path>"));
+            return;
+        }
+        let path = paths::user_path(path);
+        let Some(bytes) = self.read_file(&path) else {
+            return;
+        };
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+        let mime = guess_mime(&name, &bytes);
+        self.buffer = to_data_uri(&bytes, mime);
+        self.scroll_pos = 0;
+        self.info_message = Some(format!(
+            "{} data: URI of {} ({})",
+            mime,
+            name,
+            format_bytes(bytes.len() as f64)
+        ));
+        self.auto_copy_result();
+    }
+
+    /// `/data-uri-decode`, described in the status bar so the URI stays in
+    /// the buffer for `/save`.
+    fn inspect_data_uri(&mut self) {
+        if self.buffer.is_empty() {
+            self.error_message = Some(AppError::empty_input());
+            return;
+        }
+        match DataUri::parse(&self.buffer) {
+            Ok(uri) => {
+                self.info_message = Some(format!("{} - /save <path> writes the payload", uri));
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    /// `/save`, asking first if the file exists.
+    fn save(&mut self, path: &str) {
+        if path.trim().is_empty() {
+            self.error_message = Some(AppError::usage("/save <path>"));
+            return;
+        }
+        let path = paths::user_path(path);
+        if path.exists() {
+            self.pending = Some(PendingAction::Overwrite(path));
+        } else {
+            self.write_file(&path);
+        }
+    }
+
+    /// Writes the buffer to `path`, or the decoded payload when the buffer
+    /// is a `data:` URI.
+    fn write_file(&mut self, path: &Path) {
+        let (bytes, what) = match DataUri::parse(&self.buffer) {
+            Ok(uri) => (uri.payload, format!("{} payload", uri.mime)),
+            Err(_) => (self.buffer.clone().into_bytes(), "buffer".to_string()),
+        };
+        match std::fs::write(path, &bytes) {
+            Ok(()) => {
+                self.info_message = Some(format!(
+                    "Saved {} ({}) to {}",
+                    what,
+                    format_bytes(bytes.len() as f64),
+                    path.display()
+                ));
+            }
+            Err(e) => {
+                self.error_message = Some(AppError::new(
+                    ErrorKind::Io,
+                    format!("{}: {}", path.display(), e),
+                ));
+            }
+        }
+    }
+
+    /// Replaces the buffer with a file's contents, clearing the pasted path
+    /// from the input. Binary files load as base64, which the decoding and
+    /// inspection commands accept.
+    fn load_file(&mut self, path: &Path) {
+        let Some(bytes) = self.read_file(path) else {
+            return;
+        };
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        self.load_bytes(&name, bytes);
+    }
+
+    fn load_bytes(&mut self, name: &str, bytes: Vec<u8>) {
+        self.clear_input();
+        self.push_undo();
+        let size = format_bytes(bytes.len() as f64);
+        if is_binary(&bytes) {
+            self.buffer = general_purpose::STANDARD.encode(&bytes);
+            self.info_message = Some(format!(
+                "Loaded {} ({}) as base64 - binary file",
+                name, size
+            ));
+        } else {
+            self.buffer = String::from_utf8(bytes).unwrap_or_default();
+            self.info_message = Some(format!("Loaded {} ({})", name, size));
+        }
+        self.scroll_pos = 0;
+    }
+
+    /// `--input` and `--run`: loads the file, or what was read from stdin,
+    /// then runs each command as if it were typed, stopping at the first
+    /// one that fails or asks for more input.
+    fn preload(&mut self, input: Option<&Path>, stdin: Option<Vec<u8>>, commands: &[String]) {
+        match (input, stdin) {
+            (_, Some(bytes)) => self.load_bytes("stdin", bytes),
+            (Some(path), None) => self.load_file(path),
+            (None, None) => {}
+        }
+        if self.error_message.is_some() {
+            return;
+        }
+        for command in commands {
+            self.submit(command.clone());
+            if self.error_message.is_some() || self.pending.is_some() || self.prompting() {
+                return;
+            }
+        }
+    }
+
+    /// `/hash-file`, reading the file in chunks so only the digest ever
+    /// reaches the buffer. A trailing algorithm name is optional, and the
+    /// rest of the arguments are the path, spaces included.
+    fn hash_file(&mut self, args: &[&str]) {
+        let (path, algorithm) = match args.split_last() {
+            Some((last, rest)) if !rest.is_empty() => match HashAlgorithm::parse(last) {
+                Ok(algorithm) => (rest.join(" "), algorithm),
+                Err(_) => (args.join(" "), HashAlgorithm::Sha256),
+            },
+            _ => (args.join(" "), HashAlgorithm::Sha256),
+        };
+        if path.trim().is_empty() {
+            self.error_message = Some(AppError::usage("/hash-file <path> [md5|sha1|sha256]"));
+            return;
+        }
+
+        let path = paths::user_path(&path);
+        let hashed = std::fs::File::open(&path).and_then(|file| {
+            let size = file.metadata()?.len();
+            Ok((hash_reader(file, algorithm)?, size))
+        });
+        match hashed {
+            Ok((digest, size)) => {
+                self.buffer = digest;
+                self.scroll_pos = 0;
+                self.info_message = Some(format!(
+                    "{} of {} ({})",
+                    algorithm.name(),
+                    path.display(),
+                    format_bytes(size as f64)
+                ));
+                self.auto_copy_result();
+            }
+            Err(e) => {
+                self.error_message = Some(AppError::new(
+                    ErrorKind::Io,
+                    format!("{}: {}", path.display(), e),
+                ));
+            }
+        }
+    }
+
+    /// `/show-invisibles`, keeping where the markers are so they can be
+    /// highlighted.
+    fn show_invisibles(&mut self) {
+        if self.buffer.is_empty() {
+            self.error_message = Some(AppError::empty_input());
+            return;
+        }
+        let count = count_invisibles(&self.buffer);
+        let (shown, ranges) = reveal_invisibles(&self.buffer);
+        self.buffer = shown;
+        self.invisibles = ranges;
+        self.scroll_pos = 0;
+        self.info_message = Some(match count {
+            0 => "No invisible characters besides spaces and tabs".to_string(),
+            1 => "Found 1 invisible character".to_string(),
+            count => format!("Found {} invisible characters", count),
+        });
+    }
+
+    /// `/verify-sha256 <digest>` and friends. The buffer stays as it is and
+    /// the verdict goes in the status bar, green or red.
+    fn verify_checksum(&mut self, algorithm: HashAlgorithm, expected: &str) {
+        if self.buffer.is_empty() {
+            self.error_message = Some(AppError::empty_input());
+            return;
+        }
+        let expected = Some(expected).filter(|expected| !expected.is_empty());
+        match verify_digest(&self.buffer, algorithm, expected) {
+            Ok(verification) if verification.matches => {
+                self.success_message = Some(format!("✓ {}", verification));
+            }
+            Ok(verification) => {
+                self.error_message = Some(AppError::new(ErrorKind::Mismatch, verification));
+            }
+            Err(e) => self.error_message = Some(AppError::failed(e)),
+        }
+    }
+
+    fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Input => Focus::Buffer,
+            Focus::Buffer => Focus::Input,
+        };
+        self.autocomplete_index = None;
+    }
+
+    /// Pager-style keys while the buffer has focus. Anything else is ignored
+    /// so stray keystrokes don't end up in the input.
+    fn handle_buffer_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Tab | KeyCode::Esc | KeyCode::Char('i') => self.focus = Focus::Input,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_by(1),
+            KeyCode::PageUp => self.scroll_by(-10),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_by(10),
+            KeyCode::Left | KeyCode::Char('h') => self.scroll_horizontal(-8),
+            KeyCode::Right | KeyCode::Char('l') => self.scroll_horizontal(8),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_pos = 0,
+            KeyCode::End | KeyCode::Char('G') => self.scroll_pos = self.max_scroll(),
+            _ => {}
+        }
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.buffer.lines().count().saturating_sub(1)
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        self.scroll_pos = self
+            .scroll_pos
+            .saturating_add_signed(delta)
+            .min(self.max_scroll());
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => {
+                self.scroll_pos = self.scroll_pos.saturating_sub(3);
+            }
+            MouseEventKind::ScrollDown => {
+                let buffer_lines = self.buffer.lines().count();
+                if buffer_lines > 0 {
+                    self.scroll_pos = (self.scroll_pos + 3).min(buffer_lines.saturating_sub(1));
+                }
+            }
+            MouseEventKind::ScrollLeft => self.scroll_horizontal(-3),
+            MouseEventKind::ScrollRight => self.scroll_horizontal(3),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(area) = self.scrollbar_area.get()
+                    && mouse.column == area.x
+                    && (area.y..area.y + area.height).contains(&mouse.row)
+                {
+                    self.dragging_scrollbar = true;
+                    self.scroll_to_row(area, mouse.row);
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging_scrollbar => {
+                if let Some(area) = self.scrollbar_area.get() {
+                    self.scroll_to_row(area, mouse.row);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.dragging_scrollbar = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Shifts the buffer view by `delta` columns, stopping at the end of the
+    /// longest line. Wrapped lines always fit, so this does nothing with wrap on.
+    fn scroll_horizontal(&mut self, delta: isize) {
+        if self.wrap {
+            return;
+        }
+        let max_col = self
+            .buffer
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(1);
+        self.scroll_col = self.scroll_col.saturating_add_signed(delta).min(max_col);
+    }
+
+    /// Maps a terminal row on the scrollbar track to a buffer scroll position.
+    fn scroll_to_row(&mut self, area: Rect, row: u16) {
+        let max_scroll = self.buffer.lines().count().saturating_sub(1);
+        let track = area.height.saturating_sub(1).max(1) as usize;
+        let offset = row
+            .saturating_sub(area.y)
+            .min(area.height.saturating_sub(1)) as usize;
+        self.scroll_pos = offset * max_scroll / track;
+    }
+
+    fn restore_input(&mut self, input: Rope, cursor: usize) {
+        self.input = input;
+        self.cursor_pos = cursor.min(self.input.len_chars());
+        self.autocomplete_index = None;
+        self.autocomplete_scroll = 0;
+        self.adjust_input_scroll();
+    }
+
+    fn delete_word_before_cursor(&mut self) {
+        let start = editor::prev_word_start(&self.input, self.cursor_pos);
+        self.input_history
+            .record(&self.input, self.cursor_pos, EditKind::Other);
+        self.input.remove(start..self.cursor_pos);
+        self.cursor_pos = start;
+        self.autocomplete_index = None;
+        self.autocomplete_scroll = 0;
+        self.adjust_input_scroll();
+    }
+
+    fn insert_newline(&mut self) {
+        self.input_history
+            .record(&self.input, self.cursor_pos, EditKind::Other);
+        self.input.insert_char(self.cursor_pos, '\n');
+        self.cursor_pos += 1;
+        self.adjust_input_scroll();
+    }
+
+    fn push_undo(&mut self) {
+        const MAX_STACK_SIZE: usize = 500;
+
+        self.undo_stack.push(self.buffer.clone());
+
+        // Keep stack size under limit
+        if self.undo_stack.len() > MAX_STACK_SIZE {
+            self.undo_stack.remove(0);
+        }
+
+        // Clear redo stack on new action
+        self.redo_stack.clear();
+        self.misspellings.clear();
+        self.invisibles.clear();
+        self.error_marker = None;
+        self.scroll_col = 0;
+    }
+
+    fn undo(&mut self) {
+        if let Some(previous_buffer) = self.undo_stack.pop() {
+            // Push current buffer to redo stack
+            self.redo_stack.push(self.buffer.clone());
+
+            // Restore previous buffer
+            self.buffer = previous_buffer;
+            self.misspellings.clear();
+            self.invisibles.clear();
+            self.error_marker = None;
+            self.scroll_pos = 0;
+            self.scroll_col = 0;
+            self.info_message = Some("Undo".to_string());
         }
     }
 
@@ -481,222 +2293,629 @@ impl App {
 
             // Restore next buffer
             self.buffer = next_buffer;
+            self.misspellings.clear();
+            self.invisibles.clear();
+            self.error_marker = None;
             self.scroll_pos = 0;
+            self.scroll_col = 0;
             self.info_message = Some("Redo".to_string());
         }
     }
 
-    fn handle_command(&mut self, input: &str) {
-        self.error_message = None;
-        self.info_message = None;
+    fn set_theme(&mut self, name: Option<&str>) {
+        let Some(name) = name else {
+            self.error_message = Some(AppError::new(
+                ErrorKind::Usage,
+                format!("Expected a theme name - one of {}", PRESETS.join(", ")),
+            ));
+            return;
+        };
 
-        match input {
-            "/undo" => {
-                self.undo();
+        match Theme::preset(name) {
+            Ok(theme) => {
+                // Keep the configured cursor style across presets
+                let cursor = self.theme.cursor;
+                let ascii = self.theme.ascii;
+                self.theme = Theme {
+                    cursor,
+                    ascii,
+                    ..theme
+                };
+                self.info_message = Some(format!("Theme set to {}", name.to_lowercase()));
+            }
+            Err(e) => {
+                self.error_message = Some(AppError::failed(e));
+            }
+        }
+    }
+
+    fn run_transform(&mut self, transform: &Transform, args: &[&str]) {
+        let input_type = self.buffer_content_type();
+        match transform.apply(&self.buffer, args) {
+            Ok(output) => {
+                self.buffer = output.text;
+                self.scroll_pos = 0;
+                self.cache_content_type(output.content_type);
+                // Only worth a hint when the transform revealed something new,
+                // like base64 that decodes to JSON
+                if output.content_type != input_type
+                    && let Some(command) = output.suggestions.first()
+                {
+                    self.offer_suggestion(output.content_type, command);
+                }
+                if Self::SECRET_OUTPUT.contains(&transform.name) {
+                    self.masked = true;
+                }
+                self.auto_copy_result();
+            }
+            Err(e) => {
+                let error = AppError::from(&e).in_command(transform.name);
+                if let Some((line, column)) = error.position {
+                    self.mark_error(line, column);
+                }
+                self.error_message = Some(error);
+            }
+        }
+    }
+
+    /// Highlights the 1-based `line` and byte `column` a parser complained about
+    /// and scrolls it into view, keeping the failed buffer on screen.
+    fn mark_error(&mut self, line: usize, column: usize) {
+        let Some(last) = self.buffer.lines().count().checked_sub(1) else {
+            return;
+        };
+        let index = line.saturating_sub(1).min(last);
+        let Some(text) = self.buffer.lines().nth(index) else {
+            return;
+        };
+        let line_start = text.as_ptr() as usize - self.buffer.as_ptr() as usize;
+        let offset = column_offset(text, column);
+        self.scroll_pos = index.saturating_sub(ERROR_CONTEXT_LINES);
+        if !self.wrap {
+            self.scroll_col = text[..offset]
+                .chars()
+                .count()
+                .saturating_sub(ERROR_CONTEXT_COLUMNS);
+        }
+        self.error_marker = Some(ErrorMarker {
+            line: index,
+            offset: line_start + offset,
+        });
+    }
+
+    fn set_option(&mut self, name: Option<&str>, value: Option<&str>) {
+        let usage =
+            AppError::usage("/set <line-numbers|wrap|keep-scroll|auto-copy|append> <on|off>");
+        let (Some(name), Some(value)) = (name, value) else {
+            self.error_message = Some(usage);
+            return;
+        };
+        let enabled = match value {
+            "on" => true,
+            "off" => false,
+            _ => {
+                self.error_message = Some(usage);
                 return;
             }
-            "/redo" => {
-                self.redo();
+        };
+
+        match name {
+            "line-numbers" => self.line_numbers = enabled,
+            "wrap" => self.wrap = enabled,
+            "keep-scroll" => self.keep_scroll = enabled,
+            "auto-copy" => self.auto_copy = enabled,
+            "append" => self.append = enabled,
+            _ => {
+                self.error_message = Some(AppError::new(
+                    ErrorKind::Usage,
+                    format!(
+                        "Unknown option '{}' - expected line-numbers, wrap, keep-scroll, auto-copy, or append",
+                        name
+                    ),
+                ));
                 return;
             }
-            _ => {}
         }
+        self.info_message = Some(format!("{} {}", name, value));
+    }
+
+    fn buffer_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.buffer.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Records a content type that's already known, e.g. from a transform's
+    /// output, so rendering doesn't detect it again.
+    fn cache_content_type(&self, content_type: ContentType) {
+        *self.content_type_cache.borrow_mut() = Some((self.buffer_hash(), content_type));
+    }
+
+    fn buffer_content_type(&self) -> ContentType {
+        let hash = self.buffer_hash();
+
+        let mut cache = self.content_type_cache.borrow_mut();
+        match *cache {
+            Some((cached_hash, content_type)) if cached_hash == hash => content_type,
+            _ => {
+                let content_type = detect(&self.buffer);
+                *cache = Some((hash, content_type));
+                content_type
+            }
+        }
+    }
+
+    fn handle_command(&mut self, typed: &str) {
+        let started = Instant::now();
+        let previous_scroll = self.scroll_pos;
+        let (input, copy) = split_copy_suffix(typed);
+        self.run_command(input);
+        if let Some(error) = &mut self.error_message
+            && error.command.is_none()
+            && error.kind != ErrorKind::UnknownCommand
+            && let Some(command) = input.split_whitespace().next()
+        {
+            error.command = Some(command.to_string());
+        }
+        let finished = self.error_message.is_none() && self.pending.is_none() && !self.prompting();
+        if let Some(target) = copy
+            && finished
+        {
+            let message = self.info_message.take();
+            self.copy_buffer(target);
+            if let Some(message) = message
+                && self.error_message.is_none()
+            {
+                self.info_message = Some(format!("{} (copied)", message));
+            }
+        }
+        if self.keep_scroll {
+            self.scroll_pos = previous_scroll.min(self.max_scroll());
+        }
+
+        let command = input.split_whitespace().next().unwrap_or("").to_string();
+        let success = self.error_message.is_none();
+        let elapsed = started.elapsed();
+        if success {
+            self.usage.record(&command);
+        }
+        notify::notify_finished(&self.notify, &command, success, elapsed);
+        if command != "/history" {
+            if self.history.len() == MAX_HISTORY {
+                self.history.remove(0);
+            }
+            let input = match Self::SECRET_ARGS.contains(&command.as_str()) {
+                true => command.clone(),
+                false => typed.trim().to_string(),
+            };
+            self.history.push(HistoryEntry {
+                input,
+                success,
+                elapsed,
+            });
+        }
+        self.last_outcome = Some(CommandOutcome {
+            command,
+            success,
+            elapsed,
+        });
+    }
+
+    fn run_command(&mut self, input: &str) {
+        self.error_message = None;
+        self.info_message = None;
+        self.success_message = None;
+        self.error_marker = None;
+
+        let input = interpolate_command(input, &self.vars, Self::VERBATIM_ARGS);
+        let input = input.as_str();
         let mut split = input.split_whitespace();
         let cmd = split.next().unwrap_or("");
 
-        // Save current buffer state before command execution
-        self.push_undo();
-
+        // Commands that don't touch the buffer skip the undo stack
         match cmd {
-            "/base64-decode" => {
+            "/stash" => {
                 if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
+                    self.error_message = Some(AppError::empty_input());
                     return;
                 }
 
-                match base64_decode(self.buffer.trim()) {
-                    Ok(decoded_string) => {
-                        self.buffer = decoded_string;
-                        self.scroll_pos = 0;
-                    }
-                    Err(e) => self.error_message = Some(e.to_string()),
-                }
-            }
-            "/base64-encode" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
+                let Some(name) = split.next() else {
+                    self.error_message = Some(AppError::usage("/stash <name>"));
                     return;
-                }
-
-                let encoded = base64_encode(self.buffer.as_str());
-                self.buffer = encoded;
-                self.scroll_pos = 0;
+                };
+                let verb = if stash::exists(name) {
+                    "Overwriting"
+                } else {
+                    "Stashing as"
+                };
+                self.info_message = Some(format!(
+                    "{} '{}' - enter a passphrase (Esc to cancel)",
+                    verb, name
+                ));
+                self.passphrase_prompt = Some(PassphraseAction::Stash(name.to_string()));
+                return;
             }
-            "/copy" => {
+            // Prompt rather than leave the passphrase in the input history
+            "/encrypt" | "/decrypt" if input.split_whitespace().nth(1).is_none() => {
                 if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
+                    self.error_message = Some(AppError::empty_input());
                     return;
                 }
-
-                match self.clipboard.set_text(&self.buffer) {
-                    Ok(_) => {
-                        self.info_message = Some("Copied to clipboard".to_string());
-                    }
-                    Err(_) => {
-                        self.error_message = Some("Error: Failed to copy to clipboard".to_string());
-                    }
-                }
+                self.info_message = Some("Enter a passphrase (Esc to cancel)".to_string());
+                self.passphrase_prompt = find_transform(cmd).map(PassphraseAction::Transform);
+                return;
             }
-            "/json-format" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
-                    return;
-                }
-
-                match json_format(&self.buffer) {
-                    Ok(formatted) => {
-                        self.buffer = formatted;
-                        self.scroll_pos = 0;
-                    }
-                    Err(e) => {
-                        self.error_message = Some(format!("Error: {}", e));
+            "/unstash" => {
+                match split.next() {
+                    Some(name) => {
+                        self.info_message = Some(format!(
+                            "Enter the passphrase for '{}' (Esc to cancel)",
+                            name
+                        ));
+                        self.passphrase_prompt = Some(PassphraseAction::Unstash(name.to_string()));
                     }
+                    None => self.list_stashes(),
                 }
+                return;
             }
-            "/json-minify" => {
+            "/share" => {
                 if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
+                    self.error_message = Some(AppError::empty_input());
                     return;
                 }
 
-                match json_minify(&self.buffer) {
-                    Ok(minified) => {
-                        self.buffer = minified;
-                        self.scroll_pos = 0;
-                    }
-                    Err(e) => {
-                        self.error_message = Some(format!("Error: {}", e));
-                    }
+                match Service::from_config(&self.share) {
+                    Ok(service) => self.pending = Some(PendingAction::Share(service)),
+                    Err(e) => self.error_message = Some(AppError::from(e)),
                 }
+                return;
             }
-            "/css-format" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
-                    return;
-                }
-
-                match css_format(&self.buffer) {
-                    Ok(formatted) => {
-                        self.buffer = formatted;
-                        self.scroll_pos = 0;
-                    }
-                    Err(e) => {
-                        self.error_message = Some(format!("Error: {}", e));
-                    }
-                }
+            "/theme" => {
+                self.set_theme(split.next());
+                return;
             }
-            "/css-minify" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
-                    return;
-                }
-
-                match css_minify(&self.buffer) {
-                    Ok(minified) => {
-                        self.buffer = minified;
-                        self.scroll_pos = 0;
+            "/set" => {
+                self.set_option(split.next(), split.next());
+                return;
+            }
+            "/macro" => {
+                self.macro_command(split.next(), split.next());
+                return;
+            }
+            "/snippet" => {
+                self.snippet_command(split.next(), split.next());
+                return;
+            }
+            "/template" => {
+                self.start_template(&split.collect::<Vec<_>>());
+                return;
+            }
+            "/set-var" => {
+                let args = input.trim_start()[cmd.len()..].trim();
+                self.set_var(args);
+                return;
+            }
+            "/vars" => {
+                self.list_vars();
+                return;
+            }
+            "/watch" => {
+                self.toggle_watch();
+                return;
+            }
+            "/history" => {
+                self.history_picker = Some(HistoryPicker::new(self.history.len()));
+                return;
+            }
+            "/mask" => {
+                self.masked = !self.masked;
+                self.info_message = Some(
+                    match self.masked {
+                        true => "Buffer masked - it can still be copied",
+                        false => "Buffer unmasked",
                     }
-                    Err(e) => {
-                        self.error_message = Some(format!("Error: {}", e));
+                    .to_string(),
+                );
+                return;
+            }
+            "/emit" => {
+                self.emit = !self.emit;
+                self.info_message = Some(
+                    match self.emit {
+                        true => "The buffer will be printed to stdout on exit",
+                        false => "The buffer won't be printed on exit",
                     }
-                }
+                    .to_string(),
+                );
+                return;
             }
-            "/cuid" => {
-                let new_cuid = cuid::cuid2();
-                self.buffer = new_cuid;
-                self.scroll_pos = 0;
+            "/undo" => {
+                self.undo();
+                return;
             }
-            "/exit" => {
-                self.exit = true;
+            "/verify-md5" | "/verify-sha1" | "/verify-sha256" => {
+                let algorithm = match cmd {
+                    "/verify-md5" => HashAlgorithm::Md5,
+                    "/verify-sha1" => HashAlgorithm::Sha1,
+                    _ => HashAlgorithm::Sha256,
+                };
+                self.verify_checksum(algorithm, &split.collect::<Vec<_>>().join(" "));
+                return;
             }
-            "/sha-256" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
-                    return;
-                }
-
-                let mut hasher = Sha256::new();
-                hasher.update(self.buffer.as_bytes());
-                let result = hasher.finalize();
-                self.buffer = format!("{:x}", result);
-                self.scroll_pos = 0;
+            "/redo" => {
+                self.redo();
+                return;
             }
-            "/unicode-unescape" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
-                    return;
-                }
+            "/image-preview" => {
+                self.preview_image();
+                return;
+            }
+            "/data-uri-decode" => {
+                self.inspect_data_uri();
+                return;
+            }
+            "/save" => {
+                self.save(&split.collect::<Vec<_>>().join(" "));
+                return;
+            }
+            "/edit" => {
+                self.edit_requested = true;
+                return;
+            }
+            "/pipe" => {
+                // Spacing and quotes matter to the shell, so take the rest as typed
+                self.pipe(input.trim_start()[cmd.len()..].trim());
+                return;
+            }
+            _ => {}
+        }
 
-                match unicode_unescape(&self.buffer) {
-                    Ok(decoded) => {
-                        self.buffer = decoded;
-                        self.scroll_pos = 0;
-                    }
-                    Err(e) => {
-                        self.error_message = Some(format!("Error: {}", e));
-                    }
+        // Plugins only touch the undo stack once the sandbox lets them run
+        if let Some(index) = self.plugins.iter().position(|plugin| plugin.name == cmd) {
+            let args: Vec<String> = split.map(str::to_string).collect();
+            match self.policy.check(&self.plugins[index]) {
+                Verdict::Allow => self.run_plugin(index, &args),
+                Verdict::Confirm => self.pending = Some(PendingAction::RunPlugin(index, args)),
+                Verdict::Deny(reason) => {
+                    self.error_message = Some(AppError::new(ErrorKind::Denied, reason))
                 }
             }
-            "/unicode-escape" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
-                    return;
+            return;
+        }
+
+        // Save current buffer state before command execution
+        self.push_undo();
+
+        if cmd == "/json-diff" {
+            self.json_diff(split.next());
+            return;
+        }
+        if cmd == "/show-invisibles" {
+            self.show_invisibles();
+            return;
+        }
+        if cmd == "/file-to-data-uri" {
+            self.file_to_data_uri(&split.collect::<Vec<_>>().join(" "));
+            return;
+        }
+        if cmd == "/hash-file" {
+            self.hash_file(&split.collect::<Vec<_>>());
+            return;
+        }
+
+        if Self::SECRET_ARGS.contains(&cmd)
+            && let Some(transform) = find_transform(cmd)
+        {
+            // Spaces are part of the passphrase, so take the rest as typed
+            self.run_transform(transform, &[input.trim_start()[cmd.len()..].trim()]);
+            return;
+        }
+
+        if let Some(transform) = find_transform(cmd) {
+            let args: Vec<&str> = split.collect();
+            self.run_transform(transform, &args);
+            return;
+        }
+
+        if let Some(script) = self.scripts.iter().find(|script| script.name == cmd) {
+            let args: Vec<&str> = split.collect();
+            match script.run(&self.buffer, &args) {
+                Ok(output) => {
+                    self.buffer = output;
+                    self.scroll_pos = 0;
+                    self.auto_copy_result();
                 }
+                Err(e) => self.error_message = Some(AppError::failed(e)),
+            }
+            return;
+        }
 
-                let encoded = unicode_escape(&self.buffer);
-                self.buffer = encoded;
-                self.scroll_pos = 0;
+        match cmd {
+            "/copy" => self.copy_buffer(split.next()),
+            "/exit" => {
+                self.exit = true;
             }
-            "/url-decode" => {
+            "/spellcheck" => {
                 if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
+                    self.error_message = Some(AppError::empty_input());
                     return;
                 }
 
-                match url_decode(&self.buffer) {
-                    Ok(decoded) => {
-                        self.buffer = decoded;
-                        self.scroll_pos = 0;
+                let misspellings = spellcheck(&self.buffer);
+                match split.next() {
+                    Some("fix") => {
+                        let only = match split.next().map(|n| n.parse::<usize>()) {
+                            Some(Ok(n)) if (1..=misspellings.len()).contains(&n) => Some(n - 1),
+                            Some(_) => {
+                                self.error_message = Some(AppError::new(
+                                    ErrorKind::Usage,
+                                    format!(
+                                        "Expected a misspelling number between 1 and {}",
+                                        misspellings.len()
+                                    ),
+                                ));
+                                return;
+                            }
+                            None => None,
+                        };
+                        let fixed_count = if only.is_some() {
+                            1
+                        } else {
+                            misspellings.len()
+                        };
+                        self.buffer = spellfix(&self.buffer, &misspellings, only);
+                        self.info_message = Some(format!(
+                            "Fixed {} misspelling{}",
+                            fixed_count,
+                            if fixed_count == 1 { "" } else { "s" }
+                        ));
                     }
-                    Err(e) => {
-                        self.error_message = Some(format!("Error: {}", e));
+                    Some(other) => {
+                        self.error_message = Some(AppError::new(
+                            ErrorKind::Usage,
+                            format!("Unknown spellcheck option '{}'", other),
+                        ));
+                    }
+                    None if misspellings.is_empty() => {
+                        self.info_message = Some("No misspellings found".to_string());
+                    }
+                    None => {
+                        let summary: Vec<String> = misspellings
+                            .iter()
+                            .enumerate()
+                            .map(|(i, m)| {
+                                format!("{}. {} → {}", i + 1, m.word, m.suggestions.join("/"))
+                            })
+                            .collect();
+                        self.info_message = Some(format!(
+                            "{} • /spellcheck fix [n] to apply",
+                            summary.join(", ")
+                        ));
+                        self.misspellings = misspellings;
                     }
                 }
             }
-            "/url-encode" => {
-                if self.buffer.is_empty() {
-                    self.error_message = Some(empty_buffer_msg());
-                    return;
-                }
-
-                let encoded = url_encode(&self.buffer);
-                self.buffer = encoded;
-                self.scroll_pos = 0;
-            }
-            "/uuid" => {
-                let new_uuid = Uuid::new_v4();
-                self.buffer = new_uuid.to_string();
-                self.scroll_pos = 0;
-            }
+            "/whois" => self.whois(),
             _ => {
-                self.error_message = Some(format!("Error: Unknown command '{}'", cmd));
+                self.error_message = Some(AppError::new(
+                    ErrorKind::UnknownCommand,
+                    format!("Unknown command '{}'", cmd),
+                ));
             }
         }
     }
 }
 
-fn empty_buffer_msg() -> String {
-    "Error: Buffer is empty".to_string()
+/// Splits a trailing `| /copy` (with an optional `--tmux`/`--screen`) off a
+/// command line, returning the command and the copy target if there was one.
+fn split_copy_suffix(input: &str) -> (&str, Option<Option<&str>>) {
+    let Some((command, suffix)) = input.rsplit_once('|') else {
+        return (input, None);
+    };
+    let mut words = suffix.split_whitespace();
+    if words.next() != Some("/copy") || command.trim().is_empty() {
+        return (input, None);
+    }
+    let target = words.next();
+    if words.next().is_some() {
+        return (input, None);
+    }
+    (command.trim_end(), Some(target))
+}
+
+/// Joins a multi-line message, like a command's stderr, to fit the status
+/// bar.
+fn one_line(message: &str) -> String {
+    message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
+/// Strips a trailing `>>` that asks for text to be appended rather than
+/// replace the buffer. It must follow whitespace, so text that merely ends
+/// in `>>`, like `Vec<Vec<u8>>`, is taken as it is.
+fn split_append_suffix(text: &str) -> Option<&str> {
+    let text = text.trim_end().strip_suffix(">>")?;
+    let trimmed = text.trim_end();
+    (!trimmed.is_empty() && trimmed.len() < text.len()).then_some(trimmed)
+}
+
+/// Styles the parts of `line` covered by buffer byte `ranges`, given the
+/// byte offset at which the line starts in the buffer.
+fn highlight_ranges<'a>(
+    line: &'a str,
+    line_start: usize,
+    ranges: &[(usize, usize)],
+    style: Style,
+) -> Line<'a> {
+    let line_end = line_start + line.len();
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for &(start, end) in ranges {
+        if end <= line_start || start >= line_end {
+            continue;
+        }
+        let start = start.max(line_start) - line_start;
+        let end = end.min(line_end) - line_start;
+        if start > pos {
+            spans.push(Span::raw(&line[pos..start]));
+        }
+        spans.push(Span::styled(&line[start..end], style));
+        pos = end;
+    }
+    if pos < line.len() {
+        spans.push(Span::raw(&line[pos..]));
+    }
+    Line::from(spans)
+}
+
+/// The parse error under the marked character, or above it on the last row,
+/// shifted left when it would run past the pane.
+fn render_error_callout(
+    message: &str,
+    area: Rect,
+    row: u16,
+    col: u16,
+    theme: &Theme,
+    buf: &mut ratatui::prelude::Buffer,
+) {
+    let (y, pointer) = match row + 1 < area.height {
+        true => (area.y + row + 1, "^"),
+        false if row > 0 => (area.y + row - 1, "v"),
+        false => return,
+    };
+    let text = format!("{} {}", pointer, message);
+    let width = (text.chars().count() as u16).min(area.width);
+    let x = (area.x + col).min(area.right().saturating_sub(width));
+    let callout = Rect {
+        x,
+        y,
+        width,
+        height: 1,
+    };
+    Clear.render(callout, buf);
+    Paragraph::new(text)
+        .style(Style::default().bg(theme.popup_bg).fg(theme.error))
+        .render(callout, buf);
+}
+
+/// Hard-wraps `line` every `width` characters. Minified output rarely has
+/// spaces to break on, so this doesn't try to keep words together.
+fn wrap_line(line: &str, width: usize) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for (count, (index, _)) in line.char_indices().enumerate() {
+        if count > 0 && count % width == 0 {
+            segments.push(&line[start..index]);
+            start = index;
+        }
+    }
+    segments.push(&line[start..]);
+    segments
 }
 
 impl Widget for &App {
@@ -705,12 +2924,12 @@ impl Widget for &App {
         Self: Sized,
     {
         // Calculate input lines and height
-        let input_text = self.input.to_string();
-        let mut input_line_count = input_text.lines().count().max(1);
-        // Account for trailing newline (lines() doesn't count the empty line after a trailing \n)
-        if input_text.ends_with('\n') {
-            input_line_count += 1;
-        }
+        // Only the visible lines are copied out of the rope
+        let masked_input = self.passphrase_prompt.is_some();
+        let input_line_count = match masked_input {
+            true => 1,
+            false => self.input.len_lines(),
+        };
         let max_visible_lines = 5;
         let visible_input_lines = input_line_count.min(max_visible_lines);
         let input_height = visible_input_lines as u16 + 2; // +2 for borders
@@ -724,9 +2943,17 @@ impl Widget for &App {
         .split(area);
 
         let title = Line::from(" pomp ".bold());
-        let buffer_block = Block::bordered()
-            .title(title.centered())
-            .border_set(border::EMPTY);
+        // The empty border reserves the same space, so focusing doesn't shift the layout
+        let buffer_block = if self.focus == Focus::Buffer {
+            Block::bordered()
+                .border_set(self.theme.border_set())
+                .border_style(Style::default().fg(self.theme.selected_bg))
+        } else {
+            Block::bordered()
+                .border_set(border::EMPTY)
+                .border_style(Style::default().fg(self.theme.border))
+        }
+        .title(title.centered());
 
         let buffer_inner = buffer_block.inner(chunks[0]);
         let visible_height = buffer_inner.height as usize;
@@ -735,38 +2962,151 @@ impl Widget for &App {
         let buffer_lines: Vec<&str> = self.buffer.lines().collect();
         let total_lines = buffer_lines.len();
         let start_line = self.scroll_pos.min(total_lines.saturating_sub(1));
-        let end_line = (start_line + visible_height).min(total_lines);
 
-        let visible_text = if buffer_lines.is_empty() {
-            String::new()
+        // The gutter is sized for the largest line number so it doesn't shift while scrolling
+        let gutter_width = if self.line_numbers {
+            total_lines.max(1).to_string().len() + 1
         } else {
-            buffer_lines[start_line..end_line].join("\n")
+            0
         };
+        let content_width = (buffer_inner.width as usize)
+            .saturating_sub(gutter_width)
+            .max(1);
+        let gutter_style = Style::default().fg(self.theme.border);
+        let ranges: Vec<(usize, usize)> = self
+            .misspellings
+            .iter()
+            .map(|m| (m.start, m.end))
+            .chain(self.invisibles.iter().copied())
+            .collect();
+        let highlight_style = Style::default().fg(self.theme.highlight).underlined();
+        let error_style = Style::default().fg(self.theme.error).reversed();
+        let mask_style = Style::default().fg(self.theme.info);
+        // Row and column within the buffer pane of the error marker, if shown
+        let mut error_cell: Option<(u16, u16)> = None;
+
+        let mut rows: Vec<Line> = Vec::new();
+        for (index, line) in buffer_lines.iter().enumerate().skip(start_line) {
+            if rows.len() >= visible_height {
+                break;
+            }
+            let segments = if self.wrap {
+                wrap_line(line, content_width)
+            } else {
+                let offset = line
+                    .char_indices()
+                    .nth(self.scroll_col)
+                    .map_or(line.len(), |(index, _)| index);
+                vec![&line[offset..]]
+            };
+            for (i, segment) in segments.into_iter().enumerate() {
+                if rows.len() >= visible_height {
+                    break;
+                }
+                // `lines()` yields subslices, so the pointer offset is the byte offset
+                let segment_start = segment.as_ptr() as usize - self.buffer.as_ptr() as usize;
+                let segment_end = segment_start + segment.len();
+                let line_end = line.as_ptr() as usize - self.buffer.as_ptr() as usize + line.len();
+                let mut row = match &self.error_marker {
+                    _ if self.masked => Line::styled(mask(segment), mask_style),
+                    // An error past the last character is shown on a space after it
+                    Some(marker)
+                        if marker.line == index
+                            && ((segment_start..segment_end).contains(&marker.offset)
+                                || (marker.offset == segment_end && segment_end == line_end)) =>
+                    {
+                        let within = marker.offset - segment_start;
+                        let end = segment[within..]
+                            .chars()
+                            .next()
+                            .map_or(marker.offset, |c| marker.offset + c.len_utf8());
+                        let mut row = highlight_ranges(
+                            segment,
+                            segment_start,
+                            &[(marker.offset, end)],
+                            error_style,
+                        );
+                        if end == marker.offset {
+                            row.spans.push(Span::styled(" ", error_style));
+                        }
+                        error_cell = Some((
+                            rows.len() as u16,
+                            (gutter_width + segment[..within].chars().count()) as u16,
+                        ));
+                        row
+                    }
+                    _ => highlight_ranges(segment, segment_start, &ranges, highlight_style),
+                };
+                if self.line_numbers {
+                    let number = if i == 0 {
+                        format!("{:>width$} ", index + 1, width = gutter_width - 1)
+                    } else {
+                        " ".repeat(gutter_width)
+                    };
+                    row.spans.insert(0, Span::styled(number, gutter_style));
+                }
+                rows.push(row);
+            }
+        }
+        let visible_text = Text::from(rows);
 
         Paragraph::new(visible_text)
             .block(buffer_block)
             .render(chunks[0], buf);
 
+        if let (Some(error), Some((row, col))) = (&self.error_message, error_cell) {
+            render_error_callout(&error.message, buffer_inner, row, col, &self.theme, buf);
+        }
+
+        if total_lines > visible_height {
+            let scrollbar_area = Rect {
+                x: chunks[0].right().saturating_sub(1),
+                width: 1,
+                ..buffer_inner
+            };
+            let mut scrollbar_state = ScrollbarState::new(total_lines)
+                .viewport_content_length(visible_height)
+                .position(start_line);
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .symbols(self.theme.scrollbar_set())
+                .style(Style::default().fg(self.theme.border));
+            ratatui::widgets::StatefulWidget::render(
+                scrollbar,
+                scrollbar_area,
+                buf,
+                &mut scrollbar_state,
+            );
+            self.scrollbar_area.set(Some(scrollbar_area));
+        } else {
+            self.scrollbar_area.set(None);
+        }
+
         // Render input with top and bottom borders that reach the edges
-        let input_block = Block::default()
+        let mut input_block = Block::default()
             .borders(Borders::TOP | Borders::BOTTOM)
-            .border_set(border::PLAIN);
+            .border_set(self.theme.border_set())
+            .border_style(Style::default().fg(self.theme.border));
+        if let Some(content_type) = self.input_badge {
+            let badge = Span::styled(
+                format!(" {} ", content_type.label()),
+                Style::default().fg(self.theme.info),
+            );
+            input_block = input_block.title(Line::from(badge).right_aligned());
+        }
 
         // Build input text with cursor and handle multiple lines
-        let mut all_lines: Vec<&str> = input_text.lines().collect();
-        // Account for trailing newline by adding an empty line
-        if input_text.ends_with('\n') {
-            all_lines.push("");
-        }
         let start_line = self
             .input_scroll_line
             .min(input_line_count.saturating_sub(1));
         let end_line = (start_line + max_visible_lines).min(input_line_count);
 
-        let visible_lines = if all_lines.is_empty() {
-            vec![""]
-        } else {
-            all_lines[start_line..end_line].to_vec()
+        let visible_lines: Vec<Cow<str>> = match masked_input {
+            true => vec!["•".repeat(self.input.len_chars()).into()],
+            false => (start_line..end_line)
+                .map(|line| editor::line_text(&self.input, line))
+                .collect(),
         };
 
         // Build text with cursor, adjusting for scrolled lines
@@ -787,6 +3127,7 @@ impl Widget for &App {
 
         let formatted_display = formatted_lines.join("\n");
 
+        let cursor = self.theme.cursor_glyph();
         let text_with_cursor = if cursor_line >= start_line && cursor_line < end_line {
             // Cursor is in visible area
             let line_offset = cursor_line - start_line;
@@ -803,27 +3144,43 @@ impl Widget for &App {
             char_pos += 2; // Current line prefix
             char_pos += cursor_col;
 
-            let before: String = formatted_display.chars().take(char_pos).collect();
-            let char_at_cursor = formatted_display.chars().nth(char_pos);
-            let after: String = formatted_display.chars().skip(char_pos + 1).collect();
+            // The cursor covers the whole grapheme under it, so it never
+            // splits an emoji or a letter from its accents
+            let split = formatted_display
+                .char_indices()
+                .nth(char_pos)
+                .map_or(formatted_display.len(), |(index, _)| index);
+            let (before, rest) = formatted_display.split_at(split);
+            let at_cursor = rest.graphemes(true).next();
+            let after = &rest[at_cursor.map_or(0, str::len)..];
 
             // If cursor is on a newline, show cursor but keep the newline
-            if char_at_cursor == Some('\n') {
-                format!("{}█\n{}", before, after)
+            if at_cursor == Some("\n") {
+                format!("{}{}\n{}", before, cursor, after)
             } else {
-                format!("{}█{}", before, after)
+                format!("{}{}{}", before, cursor, after)
             }
         } else {
             // Cursor not in visible area (shouldn't happen with proper scrolling)
-            format!("{}█", formatted_display)
+            format!("{}{}", formatted_display, cursor)
         };
 
-        // Check if the first word matches a command
-        let input_trimmed = input_text.trim();
-        let first_word = input_trimmed.split_whitespace().next().unwrap_or("");
-        let is_valid_command = App::get_available_commands()
-            .iter()
-            .any(|cmd| *cmd == first_word);
+        // Check if the first word matches a command, reading no further
+        // than the longest command could reach
+        let available = self.get_available_commands();
+        let longest = available.iter().map(|command| command.len()).max();
+        let first_word: String = match masked_input {
+            true => String::new(),
+            false => self
+                .input
+                .chars()
+                .skip_while(|c| c.is_whitespace())
+                .take_while(|c| !c.is_whitespace())
+                .take(longest.unwrap_or(0) + 1)
+                .collect(),
+        };
+        let first_word = first_word.as_str();
+        let is_valid_command = available.contains(&first_word);
 
         let input_paragraph = if is_valid_command && !first_word.is_empty() {
             // Find where the first word ends in the formatted text
@@ -881,6 +3238,11 @@ impl Widget for &App {
             let scroll_start = self.autocomplete_scroll;
             let scroll_end = (scroll_start + max_visible_items).min(filtered_commands.len());
             let visible_commands = &filtered_commands[scroll_start..scroll_end];
+            let frequent_count = if self.input.len_chars() == 1 {
+                self.frequent_commands().len()
+            } else {
+                0
+            };
 
             let items: Vec<ListItem> = visible_commands
                 .iter()
@@ -889,7 +3251,13 @@ impl Widget for &App {
                     let actual_i = scroll_start + visible_i;
                     let item = ListItem::new(*cmd);
                     if Some(actual_i) == self.autocomplete_index {
-                        item.style(Style::default().bg(Color::White).fg(Color::Black))
+                        item.style(
+                            Style::default()
+                                .bg(self.theme.selected_bg)
+                                .fg(self.theme.selected_fg),
+                        )
+                    } else if actual_i < frequent_count {
+                        item.style(Style::default().fg(self.theme.highlight))
                     } else {
                         item
                     }
@@ -899,30 +3267,117 @@ impl Widget for &App {
             // Clear the popup area to ensure opaque background
             Clear.render(popup_area, buf);
 
-            let list = List::new(items).block(
-                Block::bordered()
-                    .title("Commands")
-                    .border_set(border::PLAIN),
-            );
+            let list = List::new(items)
+                .style(
+                    Style::default()
+                        .bg(self.theme.popup_bg)
+                        .fg(self.theme.popup_fg),
+                )
+                .block(
+                    Block::bordered()
+                        .title("Commands")
+                        .border_set(self.theme.border_set())
+                        .border_style(Style::default().fg(self.theme.border)),
+                );
 
             list.render(popup_area, buf);
         }
 
-        // Render error or info message area
-        if let Some(error) = &self.error_message {
-            Paragraph::new(error.as_str())
-                .style(Style::default().fg(Color::Red))
-                .render(chunks[2], buf);
-        } else if let Some(index) = self.autocomplete_index {
-            // Show help for the highlighted command
-            if let Some(command) = filtered_commands.get(index) {
-                if let Some(help_text) = App::get_command_help(command) {
-                    let message = format!("{}: {}", command, help_text);
-                    Paragraph::new(message)
-                        .style(Style::default().fg(Color::Gray))
-                        .render(chunks[2], buf);
+        match &self.pending {
+            Some(PendingAction::Share(service)) => ConfirmDialog {
+                title: "Share",
+                summary: &format!(
+                    "Upload {} to {}?",
+                    format_bytes(self.buffer.len() as f64),
+                    service.destination()
+                ),
+                preview: &self.buffer,
+                masked: self.masked,
+                theme: &self.theme,
+            }
+            .render(chunks[0], buf),
+            Some(PendingAction::LoadFile(path, size)) => ConfirmDialog {
+                title: "Load file",
+                summary: &format!(
+                    "Load this file ({}) into the buffer?",
+                    format_bytes(*size as f64)
+                ),
+                preview: &path.display().to_string(),
+                masked: false,
+                theme: &self.theme,
+            }
+            .render(chunks[0], buf),
+            Some(PendingAction::Overwrite(path)) => ConfirmDialog {
+                title: "Save",
+                summary: "This file already exists. Overwrite it?",
+                preview: &path.display().to_string(),
+                masked: false,
+                theme: &self.theme,
+            }
+            .render(chunks[0], buf),
+            Some(PendingAction::RunPlugin(index, _)) => {
+                let plugin = &self.plugins[*index];
+                ConfirmDialog {
+                    title: "Run plugin",
+                    summary: &format!(
+                        "{} runs an external command with the buffer on stdin. Allow it?",
+                        plugin.name
+                    ),
+                    preview: &plugin.describe(),
+                    masked: false,
+                    theme: &self.theme,
                 }
+                .render(chunks[0], buf)
+            }
+            None => {}
+        }
+
+        if let Some(picker) = &self.snippet_picker {
+            SnippetList {
+                picker,
+                theme: &self.theme,
+            }
+            .render(chunks[0], buf);
+        }
+
+        if let Some(picker) = &self.history_picker {
+            HistoryList {
+                entries: &self.history,
+                picker,
+                theme: &self.theme,
+            }
+            .render(chunks[0], buf);
+        }
+
+        if let Some(preview) = &self.image_preview {
+            ImagePreviewDialog {
+                preview,
+                graphics: self.capabilities.graphics,
+                theme: &self.theme,
+            }
+            .render(chunks[0], buf);
+        }
+        self.image_preview_area
+            .set(self.image_preview.as_ref().map(|_| chunks[0]));
+
+        if self.debug.visible {
+            DebugOverlay {
+                stats: &self.debug,
+                memory: self.memory_stats(),
+                theme: &self.theme,
             }
+            .render(chunks[0], buf);
+        }
+
+        // Render the status bar: the current message plus buffer/input segments
+        let message = if let Some(success) = &self.success_message {
+            Some((success.clone(), self.theme.success))
+        } else if let Some(index) = self.autocomplete_index {
+            // Show help for the highlighted command
+            filtered_commands.get(index).and_then(|command| {
+                self.get_command_help(command)
+                    .map(|help_text| (format!("{}: {}", command, help_text), self.theme.info))
+            })
         } else if input_line_count > max_visible_lines {
             // Show remaining line count if input has more than 5 lines and we're at the bottom
             let remaining_above = self.input_scroll_line;
@@ -952,40 +3407,156 @@ impl Widget for &App {
                 String::new()
             };
 
-            if !message.is_empty() {
-                Paragraph::new(message)
-                    .style(Style::default().fg(Color::Gray))
-                    .render(chunks[2], buf);
+            (!message.is_empty()).then_some((message, self.theme.info))
+        } else {
+            self.info_message
+                .as_ref()
+                .map(|info| (info.clone(), self.theme.info))
+        };
+
+        let scroll_percent = if total_lines > visible_height {
+            let max_scroll = total_lines - visible_height;
+            Some(self.scroll_pos.min(max_scroll) * 100 / max_scroll)
+        } else {
+            None
+        };
+        let buffer_size = format_bytes(self.buffer.len() as f64);
+
+        StatusBar {
+            error: self.error_message.as_ref(),
+            message,
+            profile: paths::profile(),
+            recording: self.recording.as_ref().map(|(name, _)| name.as_str()),
+            watching: self.watching.is_some(),
+            appending: self.append,
+            masked: self.masked,
+            buffer_size: &buffer_size,
+            content_type: self.buffer_content_type().label(),
+            cursor: (cursor_line, cursor_col),
+            scroll_percent,
+            outcome: self.last_outcome.as_ref(),
+            theme: &self.theme,
+        }
+        .render(chunks[2], buf);
+    }
+}
+
+/// Commands kept for /history.
+const MAX_HISTORY: usize = 200;
+
+/// How often /watch checks the clipboard.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the input must stay unchanged before its type is detected.
+const INPUT_DETECT_DELAY: Duration = Duration::from_millis(150);
+
+fn run_server(addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let local = listener.local_addr()?;
+    if !local.ip().is_loopback() {
+        eprintln!(
+            "warning: {} is reachable from other machines and has no authentication",
+            local
+        );
+    }
+    eprintln!("pomp listening on {}", local);
+    rpc::serve(listener)
+}
+
+fn run_client(addr: &str, command: &[String]) -> Result<()> {
+    let result = Client::connect(addr).and_then(|mut client| match command.split_first() {
+        None => client.list().map(|commands| {
+            let width = commands
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or(0);
+            commands
+                .iter()
+                .map(|(name, help)| format!("{:<width$}  {}\n", name, help))
+                .collect()
+        }),
+        Some((name, args)) => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            client.run(name, &input, &args)
+        }
+    });
+
+    match result {
+        Ok(output) => {
+            print!("{}", output);
+            if !output.is_empty() && !output.ends_with('\n') {
+                println!();
             }
-        } else if let Some(info) = &self.info_message {
-            Paragraph::new(info.as_str())
-                .style(Style::default().fg(Color::Gray))
-                .render(chunks[2], buf);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
     }
 }
 
 fn main() -> Result<()> {
-    let mut terminal = ratatui::init();
+    let cli = Cli::parse();
+    let pipeline = cli.pipeline();
+    if let Some(profile) = cli.profile {
+        paths::set_profile(profile);
+    }
+    if cli.mcp {
+        return mcp::serve_stdio();
+    }
+    if let Some(addr) = cli.server {
+        return run_server(&addr);
+    }
+    if let Some(addr) = cli.connect {
+        return run_client(&addr, &cli.command);
+    }
+
+    // `--input -` has to be read before the UI takes over the terminal
+    let stdin = match &cli.input {
+        Some(path) if path.as_os_str() == "-" => {
+            let mut bytes = Vec::new();
+            std::io::stdin().read_to_end(&mut bytes)?;
+            Some(bytes)
+        }
+        _ => None,
+    };
+
+    let (config, config_error) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(AppError::failed(e))),
+    };
+    let mut capabilities = Capabilities::detect(config.compat.mode);
+
+    if cli.emit {
+        term::draw_on_stderr();
+    }
+    let mut terminal = term::init()?;
     terminal.clear()?;
-    crossterm::execute!(
-        std::io::stdout(),
-        crossterm::event::EnableMouseCapture,
-        crossterm::event::EnableBracketedPaste,
-        crossterm::event::PushKeyboardEnhancementFlags(
-            crossterm::event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
-        )
-    )?;
-
-    let mut app = App::default();
+    capabilities.enable()?;
+
+    let mut app = App::new(config, capabilities);
+    app.emit = cli.emit;
+    // Running a command clears the status bar, so hold on to a config or
+    // theme warning and show it after --run, unless a command there failed
+    // or is waiting on a prompt, which matters more
+    let startup_error = config_error.or(app.error_message.take());
+    app.preload(cli.input.as_deref(), stdin, &pipeline);
+    if app.error_message.is_none() && app.pending.is_none() && !app.prompting() {
+        app.error_message = startup_error;
+    }
     let result = app.run(&mut terminal);
 
-    crossterm::execute!(
-        std::io::stdout(),
-        crossterm::event::PopKeyboardEnhancementFlags,
-        crossterm::event::DisableMouseCapture,
-        crossterm::event::DisableBracketedPaste
-    )?;
-    ratatui::restore();
+    capabilities.disable()?;
+    term::restore();
+    if result.is_ok() && app.emit {
+        print!("{}", app.buffer);
+        if !app.buffer.is_empty() && !app.buffer.ends_with('\n') {
+            println!();
+        }
+    }
     result
 }
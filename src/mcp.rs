@@ -0,0 +1,115 @@
+//! A Model Context Protocol tool server over stdio, exposing every transform
+//! as a tool so assistants and editor agents run the same implementations
+//! as the TUI.
+//!
+//! Messages are newline-delimited JSON-RPC 2.0. Tools are named without the
+//! leading slash (`json-format`) and take an `input` string plus optional
+//! `args`.
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::{Value, json};
+
+use crate::cmds::{TRANSFORMS, Transform, find_transform};
+use crate::rpc::{INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR, error_response};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+fn tool(transform: &Transform) -> Value {
+    let required: &[&str] = if transform.needs_input {
+        &["input"]
+    } else {
+        &[]
+    };
+    json!({
+        "name": transform.name.trim_start_matches('/'),
+        "description": transform.help,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "input": { "type": "string", "description": "The text to transform" },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Extra words that would follow the command in pomp"
+                },
+            },
+            "required": required,
+        },
+    })
+}
+
+fn call_tool(params: &Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+    let transform =
+        find_transform(name).ok_or((INVALID_PARAMS, format!("Unknown tool '{}'", name)))?;
+
+    let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+    let input = arguments.get("input").and_then(Value::as_str).unwrap_or("");
+    let args: Vec<&str> = arguments
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| args.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    // Tool failures are results the model can read, not protocol errors
+    let (text, is_error) = match transform.apply(input, &args) {
+        Ok(output) => (output.text, false),
+        Err(e) => (e.to_string(), true),
+    };
+    Ok(json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    }))
+}
+
+/// Answers one message, or returns `None` for notifications.
+pub fn handle_message(line: &str) -> Option<String> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, e).to_string()),
+    };
+    // Notifications such as notifications/initialized have no id and get no reply
+    let id = message.get("id").cloned()?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match message.get("method").and_then(Value::as_str) {
+        Some("initialize") => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "pomp", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        Some("ping") => Ok(json!({})),
+        Some("tools/list") => Ok(json!({
+            "tools": TRANSFORMS.iter().map(tool).collect::<Vec<_>>(),
+        })),
+        Some("tools/call") => call_tool(&params),
+        Some(method) => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        None => Err((INVALID_PARAMS, "Missing 'method'".to_string())),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+        Err((code, message)) => error_response(id, code, message).to_string(),
+    })
+}
+
+/// Reads messages from stdin and writes replies to stdout until stdin closes.
+pub fn serve_stdio() -> io::Result<()> {
+    let stdout = io::stdout();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = handle_message(&line) {
+            let mut stdout = stdout.lock();
+            writeln!(stdout, "{}", reply)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[derive(Debug)]
+pub enum MultiplexerError {
+    NotInTmux,
+    NotInScreen,
+    CommandFailed(String),
+}
+
+impl fmt::Display for MultiplexerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotInTmux => {
+                write!(f, "Not running inside tmux")
+            }
+            Self::NotInScreen => {
+                write!(f, "Not running inside GNU screen")
+            }
+            Self::CommandFailed(msg) => {
+                write!(f, "Failed to load paste buffer - {}", msg)
+            }
+        }
+    }
+}
+
+/// The terminal multiplexer pomp is running inside, if any.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Multiplexer {
+    Tmux,
+    Screen,
+    Zellij,
+}
+
+impl Multiplexer {
+    pub fn detect() -> Option<Self> {
+        Self::detect_with(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()))
+    }
+
+    /// Picks the multiplexer from which of its variables `is_set`. tmux
+    /// started inside screen inherits `STY`, so `TMUX` is checked first.
+    fn detect_with(is_set: impl Fn(&str) -> bool) -> Option<Self> {
+        if is_set("TMUX") {
+            Some(Self::Tmux)
+        } else if is_set("STY") {
+            Some(Self::Screen)
+        } else if is_set("ZELLIJ") {
+            Some(Self::Zellij)
+        } else {
+            None
+        }
+    }
+
+    /// What to try when the system clipboard can't be reached from inside
+    /// it. Zellij has no paste buffer to load, but passes OSC 52 on.
+    pub fn copy_hint(self) -> &'static str {
+        match self {
+            Self::Tmux => "try /copy --tmux",
+            Self::Screen => "try /copy --screen",
+            Self::Zellij => "set [clipboard] backend = \"osc52\" to copy through zellij",
+        }
+    }
+}
+
+pub fn in_tmux() -> bool {
+    Multiplexer::detect() == Some(Multiplexer::Tmux)
+}
+
+pub fn in_screen() -> bool {
+    Multiplexer::detect() == Some(Multiplexer::Screen)
+}
+
+/// Arguments to `tmux` that load its paste buffer from stdin, with `-w`
+/// also forwarding it to the outer terminal's clipboard.
+fn tmux_args(forward: bool) -> &'static [&'static str] {
+    match forward {
+        true => &["load-buffer", "-w", "-"],
+        false => &["load-buffer", "-"],
+    }
+}
+
+/// Arguments to `screen` that load its paste register from `path`.
+fn screen_args(path: &str) -> [&str; 3] {
+    ["-X", "readbuf", path]
+}
+
+/// Runs `program` with `args`, feeding `text` on stdin.
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<(), MultiplexerError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| MultiplexerError::CommandFailed(e.to_string()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| MultiplexerError::CommandFailed(e.to_string()))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| MultiplexerError::CommandFailed(e.to_string()))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(MultiplexerError::CommandFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Loads `text` into the tmux paste buffer. `-w` also forwards it to the
+/// outer terminal's clipboard on tmux 3.2+, so retry without it on older
+/// versions.
+pub fn tmux_copy(text: &str) -> Result<(), MultiplexerError> {
+    if !in_tmux() {
+        return Err(MultiplexerError::NotInTmux);
+    }
+    pipe_to("tmux", tmux_args(true), text).or_else(|_| pipe_to("tmux", tmux_args(false), text))
+}
+
+/// Loads `text` into GNU screen's paste register via a temporary file, since
+/// `screen -X readbuf` can't read from stdin.
+pub fn screen_copy(text: &str) -> Result<(), MultiplexerError> {
+    if !in_screen() {
+        return Err(MultiplexerError::NotInScreen);
+    }
+    let path = std::env::temp_dir().join(format!("pomp-screen-{}", std::process::id()));
+    std::fs::write(&path, text).map_err(|e| MultiplexerError::CommandFailed(e.to_string()))?;
+    let path_arg = path.to_string_lossy().to_string();
+    let result = pipe_to("screen", &screen_args(&path_arg), "");
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(set: &[&str]) -> Option<Multiplexer> {
+        Multiplexer::detect_with(|name| set.contains(&name))
+    }
+
+    #[test]
+    fn test_detect_from_environment() {
+        assert_eq!(detect(&[]), None);
+        assert_eq!(detect(&["TMUX"]), Some(Multiplexer::Tmux));
+        assert_eq!(detect(&["STY"]), Some(Multiplexer::Screen));
+        assert_eq!(detect(&["ZELLIJ"]), Some(Multiplexer::Zellij));
+        // Nested sessions go by the innermost tmux
+        assert_eq!(detect(&["STY", "TMUX"]), Some(Multiplexer::Tmux));
+        assert_eq!(detect(&["ZELLIJ", "STY"]), Some(Multiplexer::Screen));
+        assert_eq!(detect(&["TERM", "HOME"]), None);
+    }
+
+    #[test]
+    fn test_copy_hints() {
+        assert_eq!(Multiplexer::Tmux.copy_hint(), "try /copy --tmux");
+        assert_eq!(Multiplexer::Screen.copy_hint(), "try /copy --screen");
+        assert!(Multiplexer::Zellij.copy_hint().contains("osc52"));
+    }
+
+    #[test]
+    fn test_load_commands() {
+        assert_eq!(tmux_args(true), ["load-buffer", "-w", "-"]);
+        assert_eq!(tmux_args(false), ["load-buffer", "-"]);
+        assert_eq!(screen_args("/tmp/x"), ["-X", "readbuf", "/tmp/x"]);
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::Write;
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::config::NotifyConfig;
+use crate::shell::shell;
+
+/// Fires the configured notifications once a command finishes, provided it
+/// took at least `threshold_ms`. Quick commands never notify.
+pub fn notify_finished(config: &NotifyConfig, command: &str, success: bool, elapsed: Duration) {
+    if elapsed < Duration::from_millis(config.threshold_ms) {
+        return;
+    }
+
+    let status = if success { "finished" } else { "failed" };
+    let message = format!("{} {} in {:.1}s", command, status, elapsed.as_secs_f64());
+
+    let mut stdout = crate::term::output();
+    if config.bell {
+        let _ = stdout.write_all(b"\x07");
+    }
+    if config.osc {
+        // OSC 9 is understood by iTerm2, WezTerm, kitty, and Windows Terminal
+        let _ = write!(stdout, "\x1b]9;pomp: {}\x07", message);
+    }
+    let _ = stdout.flush();
+
+    if let Some(hook) = &config.command {
+        let _ = shell(hook)
+            .env("POMP_COMMAND", command)
+            .env("POMP_STATUS", if success { "ok" } else { "error" })
+            .env("POMP_ELAPSED_MS", elapsed.as_millis().to_string())
+            .env("POMP_MESSAGE", &message)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum ProfileError {
+    InvalidName(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidName(name) => {
+                write!(
+                    f,
+                    "Invalid profile name '{}' - use letters, digits, '-' and '_'",
+                    name
+                )
+            }
+        }
+    }
+}
+
+pub fn validate_profile(name: &str) -> Result<String, ProfileError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(ProfileError::InvalidName(name.to_string()))
+    }
+}
+
+/// Selects the profile for the rest of the process. Only the first call has
+/// any effect, so it must happen before anything is loaded.
+pub fn set_profile(name: String) {
+    let _ = PROFILE.set(name);
+}
+
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+/// Keeps each profile's files under `profiles/<name>` so nothing is shared
+/// with the default profile or other profiles.
+fn with_profile(dir: PathBuf, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => dir.join("profiles").join(name),
+        None => dir,
+    }
+}
+
+/// `$XDG_CONFIG_HOME/pomp`, falling back to `~/.config/pomp` on every
+/// platform so the documented paths work the same on macOS.
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .map(|dir| with_profile(dir.join("pomp"), profile()))
+}
+
+/// `$XDG_DATA_HOME/pomp`, falling back to `~/.local/share/pomp`.
+pub fn data_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")))
+        .map(|dir| with_profile(dir.join("pomp"), profile()))
+}
+
+/// Turns a path as typed or dropped into the terminal into a real one:
+/// matching surrounding quotes and backslash-escaped spaces are undone and
+/// a leading `~` means the home directory.
+pub fn user_path(typed: &str) -> PathBuf {
+    let typed = typed.trim();
+    let unquoted = match typed.chars().next() {
+        Some(quote @ ('"' | '\'')) if typed.len() > 1 && typed.ends_with(quote) => {
+            &typed[1..typed.len() - 1]
+        }
+        _ => typed,
+    };
+    let path = unquoted.replace("\\ ", " ");
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(""), Some(home)) => home,
+        (Some(rest), Some(home)) if rest.starts_with('/') => home.join(&rest[1..]),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// The file a paste names, if it is nothing but the absolute path of an
+/// existing file - which is how terminals deliver a dropped file. Relative
+/// paths are left alone so pasting a word that happens to match a file in
+/// the current directory stays text.
+pub fn pasted_file(text: &str) -> Option<PathBuf> {
+    let text = text.trim();
+    if text.is_empty() || text.contains('\n') {
+        return None;
+    }
+    let path = user_path(text);
+    (path.is_absolute() && path.is_file()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_profile_nests_under_profiles() {
+        let base = PathBuf::from("/home/me/.config/pomp");
+        assert_eq!(with_profile(base.clone(), None), base);
+        assert_eq!(
+            with_profile(base, Some("work")),
+            PathBuf::from("/home/me/.config/pomp/profiles/work")
+        );
+    }
+
+    #[test]
+    fn test_validate_profile() {
+        assert!(validate_profile("client_a-2").is_ok());
+        assert!(validate_profile("").is_err());
+        assert!(validate_profile("../personal").is_err());
+    }
+
+    #[test]
+    fn test_user_path() {
+        let expected = PathBuf::from("/tmp/my disk.iso");
+        assert_eq!(user_path("'/tmp/my disk.iso'"), expected);
+        assert_eq!(user_path(" \"/tmp/my disk.iso\"\n"), expected);
+        assert_eq!(user_path("/tmp/my\\ disk.iso"), expected);
+        assert_eq!(user_path("~user/file"), PathBuf::from("~user/file"));
+    }
+
+    #[test]
+    fn test_pasted_file() {
+        let file = std::env::temp_dir().join("pomp paste test.txt");
+        std::fs::write(&file, "dropped").unwrap();
+        let dropped = format!("'{}'\n", file.display());
+        assert_eq!(pasted_file(&dropped), Some(file.clone()));
+        assert_eq!(pasted_file(&format!("{}\nmore", file.display())), None);
+        assert_eq!(
+            pasted_file(&std::env::temp_dir().display().to_string()),
+            None
+        );
+        assert_eq!(pasted_file("Cargo.toml"), None);
+        std::fs::remove_file(file).unwrap();
+    }
+}
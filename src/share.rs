@@ -0,0 +1,399 @@
+use std::fmt;
+use std::time::Duration;
+
+use pomp::error::{AppError, ErrorKind};
+use serde_json::{Value, json};
+
+use crate::config::ShareConfig;
+
+const ZERO_X_ZERO_URL: &str = "https://0x0.st";
+const GIST_URL: &str = "https://api.github.com/gists";
+/// Uploads block the UI, so a stalled service gives up after this long
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum ShareError {
+    UnknownService(String),
+    MissingUrl,
+    MissingToken,
+    Timeout,
+    RequestFailed(String),
+    InvalidResponse,
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownService(service) => {
+                write!(
+                    f,
+                    "Unknown share service '{}' - expected 0x0, gist, or custom",
+                    service
+                )
+            }
+            Self::MissingUrl => {
+                write!(f, "share.url must be set for the custom service")
+            }
+            Self::MissingToken => {
+                write!(f, "Gists need share.token or GITHUB_TOKEN to be set")
+            }
+            Self::Timeout => {
+                write!(
+                    f,
+                    "Upload timed out after {}s - the service may be down",
+                    TIMEOUT.as_secs()
+                )
+            }
+            Self::RequestFailed(msg) => {
+                write!(f, "Upload failed - {}", msg)
+            }
+            Self::InvalidResponse => {
+                write!(f, "Upload succeeded but no URL came back")
+            }
+        }
+    }
+}
+
+impl From<ureq::Error> for ShareError {
+    fn from(err: ureq::Error) -> ShareError {
+        match err {
+            ureq::Error::Timeout(_) => ShareError::Timeout,
+            err => ShareError::RequestFailed(err.to_string()),
+        }
+    }
+}
+
+impl From<ShareError> for AppError {
+    fn from(err: ShareError) -> AppError {
+        let kind = match err {
+            ShareError::Timeout | ShareError::RequestFailed(_) => ErrorKind::Io,
+            _ => ErrorKind::Failed,
+        };
+        AppError::new(kind, err)
+    }
+}
+
+/// A configured paste service, resolved before anything is sent so the
+/// confirmation prompt can name the destination.
+pub enum Service {
+    ZeroXZero,
+    Gist { token: String, public: bool },
+    Custom { url: String },
+}
+
+impl Service {
+    pub fn from_config(config: &ShareConfig) -> Result<Self, ShareError> {
+        match config.service.to_lowercase().as_str() {
+            "0x0" | "0x0.st" => Ok(Self::ZeroXZero),
+            "gist" => {
+                let token = config
+                    .token
+                    .clone()
+                    .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+                    .ok_or(ShareError::MissingToken)?;
+                Ok(Self::Gist {
+                    token,
+                    public: config.public,
+                })
+            }
+            "custom" => {
+                let url = config.url.clone().ok_or(ShareError::MissingUrl)?;
+                Ok(Self::Custom { url })
+            }
+            _ => Err(ShareError::UnknownService(config.service.clone())),
+        }
+    }
+
+    pub fn destination(&self) -> String {
+        match self {
+            Self::ZeroXZero => ZERO_X_ZERO_URL.to_string(),
+            Self::Gist { public: true, .. } => "a public GitHub gist".to_string(),
+            Self::Gist { public: false, .. } => "a secret GitHub gist".to_string(),
+            Self::Custom { url } => url.clone(),
+        }
+    }
+
+    /// Uploads `text` and returns the URL it can be viewed at. Blocks for
+    /// at most `TIMEOUT`.
+    pub fn upload(&self, text: &str) -> Result<String, ShareError> {
+        let response = self.request(text).send(TIMEOUT)?;
+        self.read_response(&response)
+    }
+
+    /// The request that uploads `text`, built apart from sending it so
+    /// what goes over the wire can be checked without a network.
+    fn request(&self, text: &str) -> Upload {
+        let user_agent = concat!("pomp/", env!("CARGO_PKG_VERSION")).to_string();
+        match self {
+            Self::ZeroXZero => {
+                let boundary = format!("pomp-{}", uuid::Uuid::new_v4().simple());
+                let body = format!(
+                    "--{boundary}\r\n\
+                     Content-Disposition: form-data; name=\"file\"; filename=\"pomp.txt\"\r\n\
+                     Content-Type: text/plain; charset=utf-8\r\n\r\n\
+                     {text}\r\n--{boundary}--\r\n"
+                );
+                Upload {
+                    url: ZERO_X_ZERO_URL.to_string(),
+                    headers: vec![
+                        ("User-Agent", user_agent),
+                        (
+                            "Content-Type",
+                            format!("multipart/form-data; boundary={}", boundary),
+                        ),
+                    ],
+                    body,
+                }
+            }
+            Self::Gist { token, public } => Upload {
+                url: GIST_URL.to_string(),
+                headers: vec![
+                    ("User-Agent", user_agent),
+                    ("Accept", "application/vnd.github+json".to_string()),
+                    ("Authorization", format!("Bearer {}", token)),
+                    ("Content-Type", "application/json".to_string()),
+                ],
+                body: json!({
+                    "description": "Shared from pomp",
+                    "public": public,
+                    "files": { "pomp.txt": { "content": text } },
+                })
+                .to_string(),
+            },
+            Self::Custom { url } => Upload {
+                url: url.clone(),
+                headers: vec![
+                    ("User-Agent", user_agent),
+                    ("Content-Type", "text/plain; charset=utf-8".to_string()),
+                ],
+                body: text.to_string(),
+            },
+        }
+    }
+
+    /// The URL in a successful response's body.
+    fn read_response(&self, response: &str) -> Result<String, ShareError> {
+        match self {
+            Self::ZeroXZero => non_empty(response),
+            Self::Gist { .. } => serde_json::from_str::<Value>(response)
+                .ok()
+                .and_then(|value| value["html_url"].as_str().map(|url| url.to_string()))
+                .ok_or(ShareError::InvalidResponse),
+            // Accept either a bare URL or a JSON object with a `url` field
+            Self::Custom { .. } => match serde_json::from_str::<Value>(response) {
+                Ok(value) => value["url"]
+                    .as_str()
+                    .map(|url| url.to_string())
+                    .ok_or(ShareError::InvalidResponse),
+                Err(_) => non_empty(response),
+            },
+        }
+    }
+}
+
+/// An upload ready to send: always a POST.
+struct Upload {
+    url: String,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Upload {
+    /// Posts the body and returns the response's, giving up after `timeout`.
+    fn send(&self, timeout: Duration) -> Result<String, ShareError> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(timeout))
+            .build()
+            .into();
+        let mut request = agent.post(&self.url);
+        for (name, value) in &self.headers {
+            request = request.header(*name, value);
+        }
+        Ok(request.send(&self.body)?.body_mut().read_to_string()?)
+    }
+}
+
+fn non_empty(response: &str) -> Result<String, ShareError> {
+    let url = response.trim();
+    if url.is_empty() {
+        Err(ShareError::InvalidResponse)
+    } else {
+        Ok(url.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn header<'a>(upload: &'a Upload, name: &str) -> &'a str {
+        upload
+            .headers
+            .iter()
+            .find(|(header, _)| *header == name)
+            .map_or("", |(_, value)| value)
+    }
+
+    /// A one-shot local server that answers with `response`, returning
+    /// its URL and the request it received.
+    fn serve(response: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/paste", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0; 1024];
+            while !request_complete(&request) {
+                let read = stream.read(&mut chunk).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&chunk[..read]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+
+    fn request_complete(request: &[u8]) -> bool {
+        let text = String::from_utf8_lossy(request);
+        let Some((head, body)) = text.split_once("\r\n\r\n") else {
+            return false;
+        };
+        let length = head
+            .lines()
+            .find_map(|line| {
+                line.to_lowercase()
+                    .strip_prefix("content-length: ")?
+                    .parse()
+                    .ok()
+            })
+            .unwrap_or(0);
+        body.len() >= length
+    }
+
+    #[test]
+    fn test_zero_x_zero_request() {
+        let upload = Service::ZeroXZero.request("hello");
+        assert_eq!(upload.url, "https://0x0.st");
+        assert!(header(&upload, "User-Agent").starts_with("pomp/"));
+        let boundary = header(&upload, "Content-Type")
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        assert!(upload.body.starts_with(&format!("--{}\r\n", boundary)));
+        assert!(upload.body.contains("name=\"file\"; filename=\"pomp.txt\""));
+        assert!(upload.body.contains("\r\n\r\nhello\r\n"));
+        assert!(upload.body.ends_with(&format!("--{}--\r\n", boundary)));
+    }
+
+    #[test]
+    fn test_gist_request() {
+        let service = Service::Gist {
+            token: "abc".to_string(),
+            public: false,
+        };
+        let upload = service.request("line \"one\"\n");
+        assert_eq!(upload.url, "https://api.github.com/gists");
+        assert_eq!(header(&upload, "Authorization"), "Bearer abc");
+        assert_eq!(header(&upload, "Accept"), "application/vnd.github+json");
+        let body: Value = serde_json::from_str(&upload.body).unwrap();
+        assert_eq!(body["public"], false);
+        assert_eq!(body["files"]["pomp.txt"]["content"], "line \"one\"\n");
+    }
+
+    #[test]
+    fn test_read_response() {
+        let gist = Service::Gist {
+            token: String::new(),
+            public: true,
+        };
+        assert_eq!(
+            gist.read_response(r#"{"html_url": "https://gist.github.com/1"}"#)
+                .unwrap(),
+            "https://gist.github.com/1"
+        );
+        assert!(matches!(
+            gist.read_response("not json"),
+            Err(ShareError::InvalidResponse)
+        ));
+
+        let custom = Service::Custom { url: String::new() };
+        assert_eq!(
+            custom.read_response(r#"{"url": "https://p/1"}"#).unwrap(),
+            "https://p/1"
+        );
+        assert_eq!(
+            custom.read_response("https://p/2\n").unwrap(),
+            "https://p/2"
+        );
+        assert!(matches!(
+            custom.read_response(r#"{"id": 1}"#),
+            Err(ShareError::InvalidResponse)
+        ));
+        assert!(matches!(
+            Service::ZeroXZero.read_response("  \n"),
+            Err(ShareError::InvalidResponse)
+        ));
+    }
+
+    #[test]
+    fn test_upload_to_custom_service() {
+        let (url, server) = serve("HTTP/1.1 200 OK\r\nContent-Length: 12\r\n\r\nhttps://p/1\n");
+        let service = Service::Custom { url };
+        assert_eq!(service.upload("hello").unwrap(), "https://p/1");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /paste HTTP/1.1\r\n"));
+        assert!(
+            request
+                .to_lowercase()
+                .contains("content-type: text/plain; charset=utf-8")
+        );
+        assert!(request.ends_with("\r\n\r\nhello"));
+    }
+
+    #[test]
+    fn test_failed_upload_is_an_io_error() {
+        let (url, server) =
+            serve("HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n");
+        let err = Service::Custom { url }.upload("hello").unwrap_err();
+        server.join().unwrap();
+        assert!(matches!(err, ShareError::RequestFailed(_)));
+        let err = AppError::from(err);
+        assert_eq!(err.kind, ErrorKind::Io);
+        assert!(err.message.starts_with("Upload failed - "));
+
+        let err = AppError::from(ShareError::InvalidResponse);
+        assert_eq!(err.kind, ErrorKind::Failed);
+        assert_eq!(err.message, "Upload succeeded but no URL came back");
+    }
+
+    #[test]
+    fn test_stalled_upload_times_out() {
+        // Accepts the connection but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let upload = Upload {
+            url: format!("http://{}/", listener.local_addr().unwrap()),
+            headers: Vec::new(),
+            body: "hello".to_string(),
+        };
+        let err = upload.send(Duration::from_millis(200)).unwrap_err();
+        assert!(matches!(err, ShareError::Timeout));
+        assert_eq!(AppError::from(err).kind, ErrorKind::Io);
+        drop(listener);
+    }
+
+    #[test]
+    fn test_timeout_has_its_own_message() {
+        let err = ShareError::from(ureq::Error::Timeout(ureq::Timeout::Global));
+        assert!(matches!(err, ShareError::Timeout));
+        assert_eq!(
+            err.to_string(),
+            "Upload timed out after 30s - the service may be down"
+        );
+    }
+}
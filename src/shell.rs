@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+#[derive(Debug)]
+pub enum PipeError {
+    SpawnFailed(String),
+    Failed(Option<i32>, String),
+    InvalidOutput,
+}
+
+impl fmt::Display for PipeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SpawnFailed(msg) => {
+                write!(f, "Failed to run the command - {}", msg)
+            }
+            Self::Failed(code, stderr) => {
+                match code {
+                    Some(code) => write!(f, "Command exited with status {}", code)?,
+                    None => write!(f, "Command was killed by a signal")?,
+                }
+                if !stderr.is_empty() {
+                    write!(f, " - {}", stderr)?;
+                }
+                Ok(())
+            }
+            Self::InvalidOutput => {
+                write!(f, "Command wrote output that is not valid UTF-8")
+            }
+        }
+    }
+}
+
+/// Builds a command that runs `command` through the platform shell.
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then the platform default.
+pub fn editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Opens `path` in `editor`, which may carry arguments like `code --wait`.
+pub fn edit_command(editor: &str, path: &Path) -> Command {
+    if cfg!(windows) {
+        shell(&format!("{} \"{}\"", editor, path.display()))
+    } else {
+        // Passing the path as "$1" means it never needs quoting
+        let mut command = shell(&format!("{} \"$1\"", editor));
+        command.arg(editor).arg(path);
+        command
+    }
+}
+
+/// Runs `command` with `input` on stdin and collects what it writes.
+pub fn run_with_input(command: &mut Command, input: &str) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Feed stdin from a thread so a command that writes before it has read
+    // everything can't deadlock on a full pipe
+    let mut stdin = child.stdin.take();
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+
+    let output = child.wait_with_output();
+    let _ = writer.join();
+    output
+}
+
+/// What `/pipe` got back: the new buffer and anything written to stderr.
+#[derive(Debug)]
+pub struct Piped {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Sends `input` through a shell command line, as `/pipe` does. The line
+/// isn't interpolated, so `vars` reach it as environment variables and
+/// the shell expands `$name` with its own quoting rules.
+pub fn pipe(line: &str, input: &str, vars: &BTreeMap<String, String>) -> Result<Piped, PipeError> {
+    let output = run_with_input(shell(line).envs(vars), input)
+        .map_err(|e| PipeError::SpawnFailed(e.to_string()))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() {
+        return Err(PipeError::Failed(output.status.code(), stderr));
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|_| PipeError::InvalidOutput)?;
+    Ok(Piped { stdout, stderr })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipe() {
+        let piped = pipe(
+            "sort | uniq -c | awk '{print $1}'; echo done >&2",
+            "b\na\nb\n",
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        assert_eq!(piped.stdout, "1\n2\n");
+        assert_eq!(piped.stderr, "done");
+
+        let error = pipe("echo 'no such filter' >&2; exit 2", "", &BTreeMap::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Command exited with status 2 - no such filter"
+        );
+    }
+
+    #[test]
+    fn test_pipe_passes_vars_to_the_shell() {
+        let vars = BTreeMap::from([("host".to_string(), "a b; echo no".to_string())]);
+        let piped = pipe("echo \"$host\" \"$HOME\"", "", &vars).unwrap();
+        let home = std::env::var("HOME").unwrap_or_default();
+        assert_eq!(piped.stdout, format!("a b; echo no {}\n", home));
+    }
+
+    #[test]
+    fn test_edit_command_passes_path() {
+        let path = std::env::temp_dir().join("pomp edit test.txt");
+        let status = edit_command("echo edited >", &path).status().unwrap();
+        assert!(status.success());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "edited\n");
+        std::fs::remove_file(path).unwrap();
+    }
+}
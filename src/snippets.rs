@@ -0,0 +1,143 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::paths::data_dir;
+
+/// Snippets are plain text files, so the folder can be filled and edited
+/// outside pomp too.
+const EXTENSION: &str = "txt";
+
+#[derive(Debug)]
+pub enum SnippetError {
+    NoDataDir,
+    InvalidName(String),
+    NotFound(String),
+    Io(String),
+}
+
+impl fmt::Display for SnippetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoDataDir => {
+                write!(f, "Could not determine the data directory")
+            }
+            Self::InvalidName(name) => {
+                write!(
+                    f,
+                    "Invalid snippet name '{}' - use letters, digits, '-', '_' or '.'",
+                    name
+                )
+            }
+            Self::NotFound(name) => {
+                write!(f, "No snippet named '{}'", name)
+            }
+            Self::Io(msg) => {
+                write!(f, "Snippet I/O failed - {}", msg)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SnippetError {
+    fn from(err: std::io::Error) -> SnippetError {
+        SnippetError::Io(err.to_string())
+    }
+}
+
+/// A saved snippet as listed, without its contents.
+pub struct SnippetInfo {
+    pub name: String,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+pub fn snippet_dir() -> Result<PathBuf, SnippetError> {
+    data_dir()
+        .map(|dir| dir.join("snippets"))
+        .ok_or(SnippetError::NoDataDir)
+}
+
+fn snippet_path(name: &str) -> Result<PathBuf, SnippetError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(SnippetError::InvalidName(name.to_string()));
+    }
+    Ok(snippet_dir()?.join(format!("{}.{}", name, EXTENSION)))
+}
+
+pub fn save(name: &str, text: &str) -> Result<(), SnippetError> {
+    let path = snippet_path(name)?;
+    fs::create_dir_all(snippet_dir()?)?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+pub fn load(name: &str) -> Result<String, SnippetError> {
+    let path = snippet_path(name)?;
+    if !path.exists() {
+        return Err(SnippetError::NotFound(name.to_string()));
+    }
+    Ok(String::from_utf8_lossy(&fs::read(&path)?).into_owned())
+}
+
+pub fn exists(name: &str) -> bool {
+    snippet_path(name).is_ok_and(|path| path.exists())
+}
+
+/// Saved snippets, most recently modified first.
+pub fn list() -> Result<Vec<SnippetInfo>, SnippetError> {
+    let dir = snippet_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snippets = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        // Files that couldn't be loaded by name aren't worth listing
+        if snippet_path(name).is_err() {
+            continue;
+        }
+        let metadata = fs::metadata(&path)?;
+        snippets.push(SnippetInfo {
+            name: name.to_string(),
+            size: metadata.len(),
+            modified: metadata.modified()?,
+        });
+    }
+    snippets.sort_by_key(|snippet| std::cmp::Reverse(snippet.modified));
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_names() {
+        assert!(snippet_path("create-user.json").is_ok());
+        assert!(snippet_path("select_2").is_ok());
+        assert!(snippet_path("../secrets").is_err());
+        assert!(snippet_path(".hidden").is_err());
+        assert!(snippet_path("two words").is_err());
+        assert!(snippet_path("").is_err());
+    }
+
+    #[test]
+    fn test_snippet_files_are_plain_text() {
+        let path = snippet_path("query.sql").unwrap();
+        assert_eq!(path.file_name().unwrap(), "query.sql.txt");
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use pomp::cmds::{CryptoError, DateTime, decrypt, encrypt};
+
+use crate::paths::data_dir;
+
+const EXTENSION: &str = "stash";
+
+#[derive(Debug)]
+pub enum StashError {
+    NoDataDir,
+    InvalidName(String),
+    NotFound(String),
+    Io(String),
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for StashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoDataDir => {
+                write!(f, "Could not determine the data directory")
+            }
+            Self::InvalidName(name) => {
+                write!(
+                    f,
+                    "Invalid stash name '{}' - use letters, digits, '-', '_' or '.'",
+                    name
+                )
+            }
+            Self::NotFound(name) => {
+                write!(f, "No stash named '{}'", name)
+            }
+            Self::Io(msg) => {
+                write!(f, "Stash I/O failed - {}", msg)
+            }
+            Self::Crypto(err) => {
+                write!(f, "{}", err)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for StashError {
+    fn from(err: std::io::Error) -> StashError {
+        StashError::Io(err.to_string())
+    }
+}
+
+impl From<CryptoError> for StashError {
+    fn from(err: CryptoError) -> StashError {
+        StashError::Crypto(err)
+    }
+}
+
+fn stash_dir() -> Result<PathBuf, StashError> {
+    data_dir()
+        .map(|dir| dir.join("stash"))
+        .ok_or(StashError::NoDataDir)
+}
+
+fn stash_path(name: &str) -> Result<PathBuf, StashError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(StashError::InvalidName(name.to_string()));
+    }
+    Ok(stash_dir()?.join(format!("{}.{}", name, EXTENSION)))
+}
+
+pub fn save(name: &str, text: &str, passphrase: &str) -> Result<(), StashError> {
+    let path = stash_path(name)?;
+    let encrypted = encrypt(text.as_bytes(), passphrase)?;
+    fs::create_dir_all(stash_dir()?)?;
+    fs::write(&path, encrypted)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+pub fn load(name: &str, passphrase: &str) -> Result<String, StashError> {
+    let path = stash_path(name)?;
+    if !path.exists() {
+        return Err(StashError::NotFound(name.to_string()));
+    }
+    let decrypted = decrypt(&fs::read(&path)?, passphrase)?;
+    Ok(String::from_utf8_lossy(&decrypted).into_owned())
+}
+
+pub fn exists(name: &str) -> bool {
+    stash_path(name).is_ok_and(|path| path.exists())
+}
+
+/// Stash names with their last-modified time, newest first.
+pub fn list() -> Result<Vec<(String, SystemTime)>, StashError> {
+    let dir = stash_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut stashes = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let modified = fs::metadata(&path)?.modified()?;
+        stashes.push((name.to_string(), modified));
+    }
+    stashes.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    Ok(stashes)
+}
+
+/// Formats a timestamp as `YYYY-MM-DD HH:MM UTC`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let time = DateTime::from_unix(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        time.year, time.month, time.day, time.hour, time.minute
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_timestamp() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(format_timestamp(time), "2024-02-29 12:34 UTC");
+        assert_eq!(
+            format_timestamp(SystemTime::UNIX_EPOCH),
+            "1970-01-01 00:00 UTC"
+        );
+    }
+
+    #[test]
+    fn test_stash_names() {
+        assert!(stash_path("api-token_2.txt").is_ok());
+        assert!(stash_path("../etc/passwd").is_err());
+        assert!(stash_path(".hidden").is_err());
+        assert!(stash_path("").is_err());
+    }
+}
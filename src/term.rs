@@ -0,0 +1,73 @@
+//! The stream the UI draws on. That's stdout, unless `--emit` keeps stdout
+//! for the final buffer, in which case everything goes to stderr.
+
+use std::io::{self, Stderr, Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossterm::execute;
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+
+static ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Moves the UI to stderr. Must happen before [`init`].
+pub fn draw_on_stderr() {
+    ON_STDERR.store(true, Ordering::Relaxed);
+}
+
+pub fn on_stderr() -> bool {
+    ON_STDERR.load(Ordering::Relaxed)
+}
+
+pub enum Output {
+    Stdout(Stdout),
+    Stderr(Stderr),
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::Stderr(stderr) => stderr.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::Stderr(stderr) => stderr.flush(),
+        }
+    }
+}
+
+/// Where escape sequences for the terminal go, whether ratatui draws them
+/// or they're written directly, like OSC 52 or graphics.
+pub fn output() -> Output {
+    match on_stderr() {
+        true => Output::Stderr(io::stderr()),
+        false => Output::Stdout(io::stdout()),
+    }
+}
+
+pub type Tui = Terminal<CrosstermBackend<Output>>;
+
+/// `ratatui::init` for whichever stream the UI is on: raw mode, the
+/// alternate screen, and a panic hook that puts the terminal back.
+pub fn init() -> io::Result<Tui> {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        hook(info);
+    }));
+    enable_raw_mode()?;
+    execute!(output(), EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(output()))
+}
+
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(output(), LeaveAlternateScreen);
+}
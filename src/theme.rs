@@ -0,0 +1,268 @@
+use std::fmt;
+
+use ratatui::style::Color;
+use ratatui::symbols::{border, scrollbar};
+
+use crate::compat::{ASCII_BORDER, ASCII_SCROLLBAR};
+
+use crate::config::ThemeConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CursorStyle {
+    Block,
+    Underline,
+    Bar,
+}
+
+impl CursorStyle {
+    pub fn glyph(&self) -> char {
+        match self {
+            Self::Block => '█',
+            Self::Underline => '▁',
+            Self::Bar => '▏',
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub popup_bg: Color,
+    pub popup_fg: Color,
+    pub selected_bg: Color,
+    pub selected_fg: Color,
+    pub border: Color,
+    pub error: Color,
+    /// Errors in how a command was used, like a missing argument
+    pub warning: Color,
+    pub info: Color,
+    /// Good news worth noticing, like a checksum that matches
+    pub success: Color,
+    pub highlight: Color,
+    pub cursor: CursorStyle,
+    /// Draw with ASCII only, for terminals without box-drawing glyphs
+    pub ascii: bool,
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    UnknownPreset(String),
+    InvalidColor(String, String),
+    InvalidCursor(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownPreset(name) => {
+                write!(
+                    f,
+                    "Unknown theme '{}' - expected one of {}",
+                    name,
+                    PRESETS.join(", ")
+                )
+            }
+            Self::InvalidColor(key, value) => {
+                write!(f, "Invalid color '{}' for theme.{}", value, key)
+            }
+            Self::InvalidCursor(value) => {
+                write!(
+                    f,
+                    "Invalid cursor '{}' - expected block, underline, or bar",
+                    value
+                )
+            }
+        }
+    }
+}
+
+pub const PRESETS: &[&str] = &["dark", "light", "solarized"];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Terminal defaults, matching pomp's original look
+    pub fn dark() -> Self {
+        Self {
+            popup_bg: Color::Reset,
+            popup_fg: Color::Reset,
+            selected_bg: Color::White,
+            selected_fg: Color::Black,
+            border: Color::Reset,
+            error: Color::Red,
+            warning: Color::Yellow,
+            info: Color::Gray,
+            success: Color::Green,
+            highlight: Color::Red,
+            cursor: CursorStyle::Block,
+            ascii: false,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            popup_bg: Color::White,
+            popup_fg: Color::Black,
+            selected_bg: Color::Blue,
+            selected_fg: Color::White,
+            border: Color::DarkGray,
+            error: Color::Red,
+            warning: Color::Rgb(0xb5, 0x89, 0x00),
+            info: Color::DarkGray,
+            success: Color::Green,
+            highlight: Color::Magenta,
+            cursor: CursorStyle::Block,
+            ascii: false,
+        }
+    }
+
+    pub fn solarized() -> Self {
+        Self {
+            popup_bg: Color::Rgb(0x07, 0x36, 0x42),
+            popup_fg: Color::Rgb(0x93, 0xa1, 0xa1),
+            selected_bg: Color::Rgb(0x26, 0x8b, 0xd2),
+            selected_fg: Color::Rgb(0xfd, 0xf6, 0xe3),
+            border: Color::Rgb(0x58, 0x6e, 0x75),
+            error: Color::Rgb(0xdc, 0x32, 0x2f),
+            warning: Color::Rgb(0xb5, 0x89, 0x00),
+            info: Color::Rgb(0x83, 0x94, 0x96),
+            success: Color::Rgb(0x85, 0x99, 0x00),
+            highlight: Color::Rgb(0xcb, 0x4b, 0x16),
+            cursor: CursorStyle::Block,
+            ascii: false,
+        }
+    }
+
+    pub fn border_set(&self) -> border::Set {
+        if self.ascii {
+            ASCII_BORDER
+        } else {
+            border::PLAIN
+        }
+    }
+
+    pub fn scrollbar_set(&self) -> scrollbar::Set {
+        if self.ascii {
+            ASCII_SCROLLBAR
+        } else {
+            scrollbar::DOUBLE_VERTICAL
+        }
+    }
+
+    /// The cursor glyph, degraded to `_` or `|` in ASCII mode since block
+    /// characters render poorly in raster console fonts.
+    pub fn cursor_glyph(&self) -> char {
+        match (self.ascii, self.cursor) {
+            (false, cursor) => cursor.glyph(),
+            (true, CursorStyle::Bar) => '|',
+            (true, _) => '_',
+        }
+    }
+
+    pub fn preset(name: &str) -> Result<Self, ThemeError> {
+        match name.to_lowercase().as_str() {
+            "dark" => Ok(Self::dark()),
+            "light" => Ok(Self::light()),
+            "solarized" => Ok(Self::solarized()),
+            _ => Err(ThemeError::UnknownPreset(name.to_string())),
+        }
+    }
+
+    /// Builds the theme from the `[theme]` config section: the preset first,
+    /// then any individually overridden colors.
+    pub fn from_config(config: &ThemeConfig) -> Result<Self, ThemeError> {
+        let mut theme = match &config.preset {
+            Some(name) => Self::preset(name)?,
+            None => Self::default(),
+        };
+
+        let overrides = [
+            ("popup_bg", &config.popup_bg, &mut theme.popup_bg),
+            ("popup_fg", &config.popup_fg, &mut theme.popup_fg),
+            ("selected_bg", &config.selected_bg, &mut theme.selected_bg),
+            ("selected_fg", &config.selected_fg, &mut theme.selected_fg),
+            ("border", &config.border, &mut theme.border),
+            ("error", &config.error, &mut theme.error),
+            ("warning", &config.warning, &mut theme.warning),
+            ("info", &config.info, &mut theme.info),
+            ("success", &config.success, &mut theme.success),
+            ("highlight", &config.highlight, &mut theme.highlight),
+        ];
+        for (key, value, color) in overrides {
+            if let Some(value) = value {
+                *color = value
+                    .parse()
+                    .map_err(|_| ThemeError::InvalidColor(key.to_string(), value.clone()))?;
+            }
+        }
+
+        if let Some(cursor) = &config.cursor {
+            theme.cursor = match cursor.to_lowercase().as_str() {
+                "block" => CursorStyle::Block,
+                "underline" => CursorStyle::Underline,
+                "bar" => CursorStyle::Bar,
+                _ => return Err(ThemeError::InvalidCursor(cursor.clone())),
+            };
+        }
+
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn load(toml: &str) -> Result<Theme, ThemeError> {
+        let config: Config = toml::from_str(toml).unwrap();
+        Theme::from_config(&config.theme)
+    }
+
+    #[test]
+    fn test_parse_colors() {
+        let theme = load(
+            "[theme]\n\
+             preset = \"Light\"\n\
+             error = \"#ff8800\"\n\
+             info = \"245\"\n\
+             border = \"lightblue\"\n\
+             cursor = \"Bar\"\n",
+        )
+        .unwrap();
+        assert_eq!(theme.error, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.info, Color::Indexed(245));
+        assert_eq!(theme.border, Color::LightBlue);
+        assert_eq!(theme.cursor, CursorStyle::Bar);
+        // Everything not overridden comes from the preset
+        assert_eq!(theme.popup_bg, Color::White);
+        assert_eq!(theme.highlight, Color::Magenta);
+    }
+
+    #[test]
+    fn test_unknown_keys_fall_back_to_the_preset() {
+        let theme = load("[theme]\nbackground = \"red\"\n").unwrap();
+        assert_eq!(theme.error, Theme::dark().error);
+        assert_eq!(theme.cursor, CursorStyle::Block);
+        let theme = load("").unwrap();
+        assert_eq!(theme.selected_bg, Theme::default().selected_bg);
+    }
+
+    #[test]
+    fn test_bad_theme_errors() {
+        let err = load("[theme]\nwarning = \"#12345\"\n").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid color '#12345' for theme.warning");
+        let err = load("[theme]\ncursor = \"blink\"\n").unwrap_err();
+        assert!(matches!(err, ThemeError::InvalidCursor(_)));
+        let err = load("[theme]\npreset = \"neon\"\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown theme 'neon' - expected one of dark, light, solarized"
+        );
+        // A value of the wrong type fails the whole file
+        assert!(toml::from_str::<Config>("[theme]\nerror = 1\n").is_err());
+    }
+}
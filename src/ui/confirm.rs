@@ -0,0 +1,106 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+
+use crate::theme::Theme;
+use crate::ui::mask;
+
+/// Longest preview line shown before truncating with an ellipsis.
+const MAX_PREVIEW_WIDTH: usize = 60;
+const MAX_PREVIEW_LINES: usize = 6;
+
+/// A centered yes/no prompt summarizing what is about to happen.
+pub struct ConfirmDialog<'a> {
+    pub title: &'a str,
+    pub summary: &'a str,
+    pub preview: &'a str,
+    /// Show the preview as bullets, for a buffer masked by /mask
+    pub masked: bool,
+    pub theme: &'a Theme,
+}
+
+impl Widget for ConfirmDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = vec![Line::from(self.summary), Line::default()];
+        let preview_lines: Vec<&str> = self.preview.lines().collect();
+        for line in preview_lines.iter().take(MAX_PREVIEW_LINES) {
+            let line = match self.masked {
+                true => mask(line),
+                false => line.to_string(),
+            };
+            let shown = if line.chars().count() > MAX_PREVIEW_WIDTH {
+                let truncated: String = line.chars().take(MAX_PREVIEW_WIDTH - 1).collect();
+                format!("{}…", truncated)
+            } else {
+                line
+            };
+            lines.push(Line::from(shown).fg(self.theme.info));
+        }
+        if preview_lines.len() > MAX_PREVIEW_LINES {
+            lines.push(
+                Line::from(format!(
+                    "… {} more lines",
+                    preview_lines.len() - MAX_PREVIEW_LINES
+                ))
+                .fg(self.theme.info),
+            );
+        }
+        lines.push(Line::default());
+        lines.push(Line::from("Enter/y to confirm • Esc/n to cancel").bold());
+
+        let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let dialog_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        Clear.render(dialog_area, buf);
+        Paragraph::new(lines)
+            .style(
+                Style::default()
+                    .bg(self.theme.popup_bg)
+                    .fg(self.theme.popup_fg),
+            )
+            .block(
+                Block::bordered()
+                    .title(format!(" {} ", self.title))
+                    .border_set(self.theme.border_set())
+                    .border_style(Style::default().fg(self.theme.border)),
+            )
+            .render(dialog_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered(masked: bool) -> String {
+        let area = Rect::new(0, 0, 70, 12);
+        let mut buf = Buffer::empty(area);
+        ConfirmDialog {
+            title: "Share",
+            summary: "Upload 7 B?",
+            preview: "hunter2",
+            masked,
+            theme: &Theme::default(),
+        }
+        .render(area, &mut buf);
+        buf.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_masked_preview_hides_the_buffer() {
+        assert!(rendered(false).contains("hunter2"));
+        let masked = rendered(true);
+        assert!(!masked.contains("hunter2"));
+        assert!(masked.contains("•••••••"));
+    }
+}
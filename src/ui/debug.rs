@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+
+use crate::theme::Theme;
+
+/// Weight of the newest sample in the moving averages.
+const SMOOTHING: f64 = 0.1;
+
+/// Timings collected by the event loop for the F12 overlay.
+#[derive(Debug, Default)]
+pub struct DebugStats {
+    pub visible: bool,
+    pub frames: u64,
+    pub last_render: Duration,
+    pub avg_render: Duration,
+    pub last_event: Duration,
+    pub avg_event: Duration,
+}
+
+fn smooth(average: Duration, sample: Duration) -> Duration {
+    if average.is_zero() {
+        return sample;
+    }
+    average.mul_f64(1.0 - SMOOTHING) + sample.mul_f64(SMOOTHING)
+}
+
+impl DebugStats {
+    pub fn record_render(&mut self, elapsed: Duration) {
+        self.frames += 1;
+        self.last_render = elapsed;
+        self.avg_render = smooth(self.avg_render, elapsed);
+    }
+
+    pub fn record_event(&mut self, elapsed: Duration) {
+        self.last_event = elapsed;
+        self.avg_event = smooth(self.avg_event, elapsed);
+    }
+}
+
+/// Snapshot of app state sizes shown next to the timings.
+pub struct MemoryStats {
+    pub buffer_bytes: usize,
+    pub buffer_lines: usize,
+    pub rope_chars: usize,
+    pub rope_bytes: usize,
+    pub rope_lines: usize,
+    pub undo_entries: usize,
+    pub undo_bytes: usize,
+    pub redo_entries: usize,
+    pub redo_bytes: usize,
+}
+
+pub struct DebugOverlay<'a> {
+    pub stats: &'a DebugStats,
+    pub memory: MemoryStats,
+    pub theme: &'a Theme,
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
+
+impl Widget for DebugOverlay<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let stats = self.stats;
+        let memory = &self.memory;
+        let lines = vec![
+            Line::from(format!("frames       {}", stats.frames)),
+            Line::from(format!(
+                "render       {} (avg {})",
+                millis(stats.last_render),
+                millis(stats.avg_render)
+            )),
+            Line::from(format!(
+                "event        {} (avg {})",
+                millis(stats.last_event),
+                millis(stats.avg_event)
+            )),
+            Line::from(format!(
+                "buffer       {} B, {} lines",
+                memory.buffer_bytes, memory.buffer_lines
+            )),
+            Line::from(format!(
+                "input rope   {} chars, {} B, {} lines",
+                memory.rope_chars, memory.rope_bytes, memory.rope_lines
+            )),
+            Line::from(format!(
+                "undo stack   {} entries, {} B",
+                memory.undo_entries, memory.undo_bytes
+            )),
+            Line::from(format!(
+                "redo stack   {} entries, {} B",
+                memory.redo_entries, memory.redo_bytes
+            )),
+        ];
+
+        let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let overlay_area = Rect {
+            x: area.x + area.width - width,
+            y: area.y,
+            width,
+            height,
+        };
+
+        Clear.render(overlay_area, buf);
+        Paragraph::new(lines)
+            .style(
+                Style::default()
+                    .bg(self.theme.popup_bg)
+                    .fg(self.theme.popup_fg),
+            )
+            .block(
+                Block::bordered()
+                    .title(" debug (F12) ")
+                    .border_set(self.theme.border_set())
+                    .border_style(Style::default().fg(self.theme.border)),
+            )
+            .render(overlay_area, buf);
+    }
+}
@@ -0,0 +1,79 @@
+use pomp::{AppError, Severity};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use crate::theme::Theme;
+
+/// The status bar's error: a label in the severity's color, the command
+/// that failed and the message.
+pub struct ErrorLine<'a> {
+    pub error: &'a AppError,
+    pub theme: &'a Theme,
+}
+
+impl ErrorLine<'_> {
+    fn color(&self) -> Color {
+        match self.error.severity() {
+            Severity::Warning => self.theme.warning,
+            Severity::Error => self.theme.error,
+        }
+    }
+
+    fn line(&self) -> Line<'_> {
+        let style = Style::default().fg(self.color());
+        let label = match self.error.severity() {
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        };
+        let mut spans = vec![Span::styled(label, style.bold())];
+        if let Some(command) = &self.error.command {
+            spans.push(Span::styled(" in ", style));
+            spans.push(Span::styled(command.as_str(), style.bold()));
+        }
+        spans.push(Span::styled(": ", style));
+        spans.push(Span::styled(self.error.message.as_str(), style));
+        Line::from(spans)
+    }
+}
+
+impl Widget for ErrorLine<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.line()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pomp::ErrorKind;
+
+    fn text(error: &AppError) -> String {
+        let theme = Theme::default();
+        ErrorLine {
+            error,
+            theme: &theme,
+        }
+        .line()
+        .to_string()
+    }
+
+    #[test]
+    fn test_error_line_text() {
+        let error = AppError::failed("Invalid JSON - expected value at line 1 column 7")
+            .in_command("/json-format");
+        assert_eq!(
+            text(&error),
+            "Error in /json-format: Invalid JSON - expected value at line 1 column 7"
+        );
+        assert_eq!(text(&AppError::empty_input()), "Warning: Buffer is empty");
+        assert_eq!(
+            text(&AppError::new(ErrorKind::Denied, "Blocked")),
+            "Error: Blocked"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+
+use crate::theme::Theme;
+use crate::ui::statusbar::{SLOW_COMMAND, format_elapsed};
+
+const MAX_VISIBLE: usize = 12;
+
+/// One command run this session, as it was typed.
+pub struct HistoryEntry {
+    pub input: String,
+    pub success: bool,
+    pub elapsed: Duration,
+}
+
+/// `/history`, newest first, open until a command is picked or the list
+/// is closed.
+pub struct HistoryPicker {
+    pub selected: usize,
+    len: usize,
+}
+
+impl HistoryPicker {
+    pub fn new(len: usize) -> Self {
+        Self { selected: 0, len }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1).min(self.len.saturating_sub(1));
+    }
+
+    /// The selected entry, counting from the newest.
+    pub fn selected_entry<'a>(&self, entries: &'a [HistoryEntry]) -> Option<&'a HistoryEntry> {
+        entries.iter().rev().nth(self.selected)
+    }
+
+    /// The first row shown, so the selection stays in view.
+    fn offset(&self, visible: usize) -> usize {
+        (self.selected + 1).saturating_sub(visible)
+    }
+}
+
+/// A centered list of commands with how long each took.
+pub struct HistoryList<'a> {
+    pub entries: &'a [HistoryEntry],
+    pub picker: &'a HistoryPicker,
+    pub theme: &'a Theme,
+}
+
+impl Widget for HistoryList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let picker = self.picker;
+        let visible = MAX_VISIBLE
+            .min(area.height.saturating_sub(4) as usize)
+            .max(1);
+        let offset = picker.offset(visible);
+        let max_input = (area.width as usize).saturating_sub(20).max(10);
+
+        let mut lines: Vec<Line> = self
+            .entries
+            .iter()
+            .rev()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(i, entry)| {
+                let (mark, color) = match (entry.success, self.theme.ascii) {
+                    (true, false) => ("✓", self.theme.info),
+                    (false, false) => ("✗", self.theme.error),
+                    (true, true) => ("ok", self.theme.info),
+                    (false, true) => ("!!", self.theme.error),
+                };
+                let elapsed_color = match entry.elapsed >= SLOW_COMMAND {
+                    true => self.theme.warning,
+                    false => self.theme.popup_fg,
+                };
+                let input: String = entry.input.chars().take(max_input).collect();
+                let line = Line::from(vec![
+                    Span::styled(format!("{:<2} ", mark), Style::default().fg(color)),
+                    Span::styled(
+                        format!("{:>8}  ", format_elapsed(entry.elapsed)),
+                        Style::default().fg(elapsed_color),
+                    ),
+                    Span::raw(input),
+                ]);
+                match i == picker.selected {
+                    true => line.style(
+                        Style::default()
+                            .bg(self.theme.selected_bg)
+                            .fg(self.theme.selected_fg),
+                    ),
+                    false => line,
+                }
+            })
+            .collect();
+        if self.entries.is_empty() {
+            lines.push(Line::from("No commands yet"));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from("Enter to edit and run again • Esc to close").bold());
+
+        let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let dialog_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        Clear.render(dialog_area, buf);
+        Paragraph::new(lines)
+            .style(
+                Style::default()
+                    .bg(self.theme.popup_bg)
+                    .fg(self.theme.popup_fg),
+            )
+            .block(
+                Block::bordered()
+                    .title(format!(" History ({}) ", self.entries.len()))
+                    .border_set(self.theme.border_set())
+                    .border_style(Style::default().fg(self.theme.border)),
+            )
+            .render(dialog_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(input: &str, millis: u64) -> HistoryEntry {
+        HistoryEntry {
+            input: input.to_string(),
+            success: true,
+            elapsed: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn test_newest_first() {
+        let entries = [entry("/json-format", 3), entry("/json-minify", 40)];
+        let mut picker = HistoryPicker::new(entries.len());
+        assert_eq!(
+            picker.selected_entry(&entries).unwrap().input,
+            "/json-minify"
+        );
+        picker.down();
+        picker.down();
+        assert_eq!(
+            picker.selected_entry(&entries).unwrap().input,
+            "/json-format"
+        );
+        assert!(HistoryPicker::new(0).selected_entry(&[]).is_none());
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_micros(420)), "0.42ms");
+        assert_eq!(format_elapsed(Duration::from_micros(123_400)), "123ms");
+        assert_eq!(format_elapsed(Duration::from_millis(2_500)), "2.50s");
+    }
+}
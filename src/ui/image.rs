@@ -0,0 +1,327 @@
+use std::fmt;
+
+use base64::{Engine as _, engine::general_purpose};
+use image::{Rgba, RgbaImage, imageops::FilterType};
+use pomp::cmds::image_info;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Clear, Widget},
+};
+
+use crate::compat::Graphics;
+use crate::theme::Theme;
+
+/// Kitty image id, so the preview can be deleted without touching images
+/// other programs placed.
+const KITTY_IMAGE_ID: u32 = 7_105_117;
+
+/// Longest payload kitty accepts per escape sequence.
+const KITTY_CHUNK: usize = 4096;
+
+/// Pixel size of a cell when the terminal doesn't report one.
+pub const DEFAULT_CELL_PIXELS: (u16, u16) = (8, 16);
+
+pub const KITTY_DELETE: &str = "\x1b_Ga=d,d=I,i=7105117,q=2\x1b\\";
+
+#[derive(Debug)]
+pub enum PreviewError {
+    NotAnImage,
+    Unsupported(&'static str),
+    Decode(image::ImageError),
+}
+
+impl fmt::Display for PreviewError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotAnImage => {
+                write!(f, "The buffer doesn't hold PNG or JPEG data")
+            }
+            Self::Unsupported(format) => {
+                write!(
+                    f,
+                    "{} images can't be previewed - only PNG and JPEG",
+                    format
+                )
+            }
+            Self::Decode(e) => {
+                write!(f, "Can't decode the image - {}", e)
+            }
+        }
+    }
+}
+
+/// A decoded image shown over the buffer until the next key press.
+pub struct ImagePreview {
+    format: &'static str,
+    image: RgbaImage,
+    /// Where the image was last drawn with an escape sequence, so it is only
+    /// sent again when the layout changes
+    pub drawn: Option<Rect>,
+}
+
+impl ImagePreview {
+    pub fn decode(bytes: &[u8]) -> Result<Self, PreviewError> {
+        let format = image_info(bytes).ok_or(PreviewError::NotAnImage)?.format;
+        if !matches!(format, "PNG" | "JPEG") {
+            return Err(PreviewError::Unsupported(format));
+        }
+        let image = image::load_from_memory(bytes).map_err(PreviewError::Decode)?;
+        Ok(Self {
+            format,
+            image: image.to_rgba8(),
+            drawn: None,
+        })
+    }
+
+    fn title(&self) -> String {
+        format!(
+            " {} {} × {} ",
+            self.format,
+            self.image.width(),
+            self.image.height()
+        )
+    }
+
+    /// The largest size in cells that fits `cols` × `rows` and keeps the
+    /// aspect ratio, taking a cell as two square pixels stacked.
+    fn fit(&self, cols: u16, rows: u16) -> (u16, u16) {
+        let (width, height) = (self.image.width() as f64, self.image.height() as f64);
+        let scale = (cols as f64 / width).min(rows as f64 * 2.0 / height);
+        let cols = (width * scale).round().clamp(1.0, cols as f64);
+        let rows = (height * scale / 2.0).round().clamp(1.0, rows as f64);
+        (cols as u16, rows as u16)
+    }
+
+    /// The cells the image itself covers inside the dialog drawn in `area`.
+    pub fn placement(&self, area: Rect) -> Rect {
+        let (cols, rows) = self.fit(
+            area.width.saturating_sub(4).max(1),
+            area.height.saturating_sub(4).max(1),
+        );
+        Rect {
+            x: area.x + (area.width.saturating_sub(cols)) / 2,
+            y: area.y + (area.height.saturating_sub(rows)) / 2,
+            width: cols.min(area.width),
+            height: rows.min(area.height),
+        }
+    }
+
+    fn scaled(&self, width: u32, height: u32) -> RgbaImage {
+        let filter = match width < self.image.width() {
+            true => FilterType::Triangle,
+            false => FilterType::Nearest,
+        };
+        image::imageops::resize(&self.image, width.max(1), height.max(1), filter)
+    }
+
+    /// The kitty graphics sequence placing the image over `placement`,
+    /// scaled to the cells' pixel size so no more is sent than is shown.
+    pub fn kitty(&self, placement: Rect, cell: (u16, u16)) -> String {
+        let pixels = self.scaled(
+            (placement.width * cell.0) as u32,
+            (placement.height * cell.1) as u32,
+        );
+        let data = general_purpose::STANDARD.encode(pixels.as_raw());
+        let chunks: Vec<&str> = data
+            .as_bytes()
+            .chunks(KITTY_CHUNK)
+            // base64 is ASCII, so every chunk is valid UTF-8
+            .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+            .collect();
+
+        let mut escape = String::with_capacity(data.len() + chunks.len() * 16);
+        for (i, chunk) in chunks.iter().enumerate() {
+            let more = (i + 1 < chunks.len()) as u8;
+            if i == 0 {
+                escape.push_str(&format!(
+                    "\x1b_Ga=T,f=32,s={},v={},c={},r={},i={},C=1,q=2,m={};{}\x1b\\",
+                    pixels.width(),
+                    pixels.height(),
+                    placement.width,
+                    placement.height,
+                    KITTY_IMAGE_ID,
+                    more,
+                    chunk
+                ));
+            } else {
+                escape.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+            }
+        }
+        escape
+    }
+
+    /// A sixel image covering `placement`, in the 216-color cube. Pixels
+    /// that are mostly transparent are left unpainted.
+    pub fn sixel(&self, placement: Rect, cell: (u16, u16)) -> String {
+        let pixels = self.scaled(
+            (placement.width * cell.0) as u32,
+            (placement.height * cell.1) as u32,
+        );
+        let (width, height) = pixels.dimensions();
+        let level = |value: u8| (value as u32 * 5 + 127) / 255;
+        let index = |pixel: &Rgba<u8>| match pixel[3] < 128 {
+            true => None,
+            false => Some((level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2])) as usize),
+        };
+
+        let mut escape = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+        for color in 0..216 {
+            let (r, g, b) = (color / 36, color / 6 % 6, color % 6);
+            escape.push_str(&format!("#{};2;{};{};{}", color, r * 20, g * 20, b * 20));
+        }
+        for band in (0..height).step_by(6) {
+            let rows = band..(band + 6).min(height);
+            let mut used = [false; 216];
+            for y in rows.clone() {
+                for x in 0..width {
+                    if let Some(color) = index(pixels.get_pixel(x, y)) {
+                        used[color] = true;
+                    }
+                }
+            }
+            for color in (0..216).filter(|&color| used[color]) {
+                escape.push_str(&format!("#{}", color));
+                let columns = (0..width).map(|x| {
+                    let bits = rows.clone().fold(0u8, |bits, y| {
+                        match index(pixels.get_pixel(x, y)) == Some(color) {
+                            true => bits | 1 << (y - band),
+                            false => bits,
+                        }
+                    });
+                    (63 + bits) as char
+                });
+                push_run_length(&mut escape, columns);
+                escape.push('$');
+            }
+            escape.push('-');
+        }
+        escape.push_str("\x1b\\");
+        escape
+    }
+}
+
+/// Sixel's `!<count><char>` repeats, for runs long enough to save space.
+fn push_run_length(escape: &mut String, columns: impl Iterator<Item = char>) {
+    let flush = |escape: &mut String, c: char, count: usize| match count {
+        0 => {}
+        1..=3 => escape.extend(std::iter::repeat_n(c, count)),
+        _ => escape.push_str(&format!("!{}{}", count, c)),
+    };
+    let mut run = ('?', 0);
+    for c in columns {
+        if c == run.0 {
+            run.1 += 1;
+        } else {
+            flush(escape, run.0, run.1);
+            run = (c, 1);
+        }
+    }
+    flush(escape, run.0, run.1);
+}
+
+fn color(pixel: &Rgba<u8>) -> Option<Color> {
+    (pixel[3] >= 128).then_some(Color::Rgb(pixel[0], pixel[1], pixel[2]))
+}
+
+/// A bordered box over the buffer holding the preview. With a graphics
+/// protocol the inside is left blank for the escape sequence to fill.
+pub struct ImagePreviewDialog<'a> {
+    pub preview: &'a ImagePreview,
+    pub graphics: Graphics,
+    pub theme: &'a Theme,
+}
+
+impl Widget for ImagePreviewDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let placement = self.preview.placement(area);
+        let title = self.preview.title();
+        let width = (placement.width.max(title.chars().count() as u16) + 2).min(area.width);
+        let dialog = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: placement.y.saturating_sub(1).max(area.y),
+            width,
+            height: (placement.height + 2).min(area.height),
+        };
+        Clear.render(dialog, buf);
+        Block::bordered()
+            .title(title)
+            .title_bottom(" any key closes ")
+            .border_set(self.theme.border_set())
+            .style(
+                Style::default()
+                    .bg(self.theme.popup_bg)
+                    .fg(self.theme.popup_fg),
+            )
+            .border_style(Style::default().fg(self.theme.border))
+            .render(dialog, buf);
+        if self.graphics != Graphics::HalfBlocks {
+            return;
+        }
+
+        let pixels = self
+            .preview
+            .scaled(placement.width as u32, placement.height as u32 * 2);
+        for row in 0..placement.height {
+            for col in 0..placement.width {
+                let top = color(pixels.get_pixel(col as u32, row as u32 * 2));
+                let bottom = color(pixels.get_pixel(col as u32, row as u32 * 2 + 1));
+                let cell = &mut buf[(placement.x + col, placement.y + row)];
+                match (top, bottom) {
+                    // Without block characters each cell is one color
+                    (Some(top), _) if self.theme.ascii => {
+                        cell.set_symbol(" ").set_bg(top);
+                    }
+                    (Some(top), Some(bottom)) => {
+                        cell.set_symbol("▀").set_fg(top).set_bg(bottom);
+                    }
+                    (Some(top), None) => {
+                        cell.set_symbol("▀").set_fg(top);
+                    }
+                    (None, Some(bottom)) => {
+                        cell.set_symbol("▄").set_fg(bottom);
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview(width: u32, height: u32) -> ImagePreview {
+        ImagePreview {
+            format: "PNG",
+            image: RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255])),
+            drawn: None,
+        }
+    }
+
+    #[test]
+    fn test_fit_keeps_aspect_ratio() {
+        // 200 × 100 pixels is 200 × 50 cells at two pixels per cell
+        assert_eq!(preview(200, 100).fit(80, 40), (80, 20));
+        assert_eq!(preview(100, 400).fit(80, 40), (20, 40));
+        assert_eq!(preview(1, 1000).fit(80, 40), (1, 40));
+    }
+
+    #[test]
+    fn test_sixel_run_lengths() {
+        let sixel = preview(10, 6).sixel(
+            Rect {
+                x: 0,
+                y: 0,
+                width: 10,
+                height: 6,
+            },
+            (1, 1),
+        );
+        assert!(sixel.starts_with("\x1bP0;1;0q\"1;1;10;6"));
+        // One band of ten fully set columns in pure red, index 5 × 36
+        assert!(sixel.ends_with("#180!10~$-\x1b\\"));
+    }
+}
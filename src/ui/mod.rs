@@ -0,0 +1,15 @@
+pub mod confirm;
+pub mod debug;
+pub mod error;
+pub mod history;
+pub mod image;
+pub mod snippets;
+pub mod statusbar;
+
+/// `line` with every visible character replaced by a bullet, keeping its
+/// width and spacing.
+pub fn mask(line: &str) -> String {
+    line.chars()
+        .map(|c| if c.is_whitespace() { c } else { '•' })
+        .collect()
+}
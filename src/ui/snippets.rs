@@ -0,0 +1,151 @@
+use pomp::cmds::format_bytes;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+
+use crate::snippets::SnippetInfo;
+use crate::stash::format_timestamp;
+use crate::theme::Theme;
+
+const MAX_VISIBLE: usize = 12;
+
+/// `/snippet list`, open until a snippet is loaded or the list is closed.
+pub struct SnippetPicker {
+    pub snippets: Vec<SnippetInfo>,
+    pub selected: usize,
+}
+
+impl SnippetPicker {
+    pub fn new(snippets: Vec<SnippetInfo>) -> Self {
+        Self {
+            snippets,
+            selected: 0,
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1).min(self.snippets.len().saturating_sub(1));
+    }
+
+    pub fn selected_name(&self) -> Option<&str> {
+        self.snippets
+            .get(self.selected)
+            .map(|snippet| snippet.name.as_str())
+    }
+
+    /// The first row shown, so the selection stays in view.
+    fn offset(&self, visible: usize) -> usize {
+        (self.selected + 1).saturating_sub(visible)
+    }
+}
+
+/// A centered list of saved snippets with their sizes and ages.
+pub struct SnippetList<'a> {
+    pub picker: &'a SnippetPicker,
+    pub theme: &'a Theme,
+}
+
+impl Widget for SnippetList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let picker = self.picker;
+        let visible = MAX_VISIBLE
+            .min(area.height.saturating_sub(4) as usize)
+            .max(1);
+        let offset = picker.offset(visible);
+        let name_width = picker
+            .snippets
+            .iter()
+            .map(|snippet| snippet.name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines: Vec<Line> = picker
+            .snippets
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(i, snippet)| {
+                let line = Line::from(format!(
+                    "{:<name_width$}  {:>9}  {}",
+                    snippet.name,
+                    format_bytes(snippet.size as f64),
+                    format_timestamp(snippet.modified)
+                ));
+                match i == picker.selected {
+                    true => line.style(
+                        Style::default()
+                            .bg(self.theme.selected_bg)
+                            .fg(self.theme.selected_fg),
+                    ),
+                    false => line,
+                }
+            })
+            .collect();
+        lines.push(Line::default());
+        lines.push(Line::from("Enter to load • Esc to close").bold());
+
+        let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let dialog_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        Clear.render(dialog_area, buf);
+        Paragraph::new(lines)
+            .style(
+                Style::default()
+                    .bg(self.theme.popup_bg)
+                    .fg(self.theme.popup_fg),
+            )
+            .block(
+                Block::bordered()
+                    .title(format!(" Snippets ({}) ", picker.snippets.len()))
+                    .border_set(self.theme.border_set())
+                    .border_style(Style::default().fg(self.theme.border)),
+            )
+            .render(dialog_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn picker(count: usize) -> SnippetPicker {
+        SnippetPicker::new(
+            (0..count)
+                .map(|i| SnippetInfo {
+                    name: format!("snippet-{}", i),
+                    size: 10,
+                    modified: SystemTime::UNIX_EPOCH,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_selection_stays_in_view() {
+        let mut picker = picker(20);
+        picker.up();
+        assert_eq!(picker.selected_name(), Some("snippet-0"));
+        for _ in 0..30 {
+            picker.down();
+        }
+        assert_eq!(picker.selected, 19);
+        assert_eq!(picker.offset(12), 8);
+        assert!(SnippetPicker::new(Vec::new()).selected_name().is_none());
+    }
+}
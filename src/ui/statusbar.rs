@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use pomp::AppError;
+
+use crate::theme::Theme;
+use crate::ui::error::ErrorLine;
+
+/// Runs at least this long are shown in the warning color.
+pub const SLOW_COMMAND: Duration = Duration::from_millis(500);
+
+/// Result of the most recently executed command.
+pub struct CommandOutcome {
+    pub command: String,
+    pub success: bool,
+    pub elapsed: Duration,
+}
+
+/// `0.42ms` below 10ms, whole milliseconds below a second, then seconds.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let millis = elapsed.as_secs_f64() * 1000.0;
+    if millis < 10.0 {
+        format!("{:.2}ms", millis)
+    } else if millis < 1000.0 {
+        format!("{:.0}ms", millis)
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
+
+/// The bottom line of the screen: a transient message on the left and
+/// persistent segments describing the buffer and input on the right.
+pub struct StatusBar<'a> {
+    /// Shown instead of the message when set
+    pub error: Option<&'a AppError>,
+    pub message: Option<(String, Color)>,
+    pub profile: Option<&'a str>,
+    /// Name of the macro being recorded
+    pub recording: Option<&'a str>,
+    pub watching: bool,
+    /// Whether submitted text is added to the buffer instead of replacing it
+    pub appending: bool,
+    /// Whether the buffer is drawn as bullets
+    pub masked: bool,
+    pub buffer_size: &'a str,
+    pub content_type: &'a str,
+    pub cursor: (usize, usize),
+    pub scroll_percent: Option<usize>,
+    pub outcome: Option<&'a CommandOutcome>,
+    pub theme: &'a Theme,
+}
+
+impl StatusBar<'_> {
+    fn segments(&self) -> Vec<Span<'_>> {
+        let info = Style::default().fg(self.theme.info);
+        let separator = Span::styled(if self.theme.ascii { " | " } else { " │ " }, info);
+        let mut segments = Vec::new();
+
+        if let Some(profile) = self.profile {
+            segments.push(Span::styled(
+                format!("[{}]", profile),
+                Style::default().fg(self.theme.highlight),
+            ));
+            segments.push(separator.clone());
+        }
+
+        if let Some(name) = self.recording {
+            let mark = if self.theme.ascii { "REC" } else { "● REC" };
+            segments.push(Span::styled(
+                format!("{} {}", mark, name),
+                Style::default().fg(self.theme.error),
+            ));
+            segments.push(separator.clone());
+        }
+
+        if self.watching {
+            let mark = if self.theme.ascii {
+                "WATCH"
+            } else {
+                "◉ WATCH"
+            };
+            segments.push(Span::styled(
+                mark,
+                Style::default().fg(self.theme.highlight),
+            ));
+            segments.push(separator.clone());
+        }
+
+        if self.appending {
+            let mark = if self.theme.ascii {
+                ">> APPEND"
+            } else {
+                "» APPEND"
+            };
+            segments.push(Span::styled(
+                mark,
+                Style::default().fg(self.theme.highlight),
+            ));
+            segments.push(separator.clone());
+        }
+
+        if self.masked {
+            let mark = if self.theme.ascii { "***" } else { "•••" };
+            segments.push(Span::styled(
+                format!("{} MASKED", mark),
+                Style::default().fg(self.theme.highlight),
+            ));
+            segments.push(separator.clone());
+        }
+
+        if let Some(outcome) = self.outcome {
+            let (mark, color) = match (outcome.success, self.theme.ascii) {
+                (true, false) => ("✓", self.theme.info),
+                (false, false) => ("✗", self.theme.error),
+                (true, true) => ("ok", self.theme.info),
+                (false, true) => ("failed", self.theme.error),
+            };
+            segments.push(Span::styled(
+                format!("{} {} ", mark, outcome.command),
+                Style::default().fg(color),
+            ));
+            let elapsed_color = match outcome.elapsed >= SLOW_COMMAND {
+                true => self.theme.warning,
+                false => color,
+            };
+            segments.push(Span::styled(
+                format_elapsed(outcome.elapsed),
+                Style::default().fg(elapsed_color),
+            ));
+            segments.push(separator.clone());
+        }
+
+        segments.push(Span::styled(self.buffer_size.to_string(), info));
+        segments.push(separator.clone());
+        segments.push(Span::styled(self.content_type.to_string(), info));
+        segments.push(separator.clone());
+        segments.push(Span::styled(
+            format!("{}:{}", self.cursor.0 + 1, self.cursor.1 + 1),
+            info,
+        ));
+        if let Some(percent) = self.scroll_percent {
+            segments.push(separator);
+            segments.push(Span::styled(format!("{}%", percent), info));
+        }
+        segments
+    }
+}
+
+impl Widget for StatusBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let segments = Line::from(self.segments());
+        let segments_width = (segments.width() as u16).min(area.width);
+
+        let message_area = Rect {
+            width: area.width.saturating_sub(segments_width + 1),
+            ..area
+        };
+        let segments_area = Rect {
+            x: area.x + area.width - segments_width,
+            width: segments_width,
+            ..area
+        };
+
+        if let Some(error) = self.error {
+            ErrorLine {
+                error,
+                theme: self.theme,
+            }
+            .render(message_area, buf);
+        } else if let Some((message, color)) = &self.message {
+            Paragraph::new(message.as_str())
+                .style(Style::default().fg(*color))
+                .render(message_area, buf);
+        }
+        segments.render(segments_area, buf);
+    }
+}
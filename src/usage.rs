@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths::data_dir;
+
+/// How many commands the frequent section of the autocomplete popup shows.
+pub const FREQUENT_LIMIT: usize = 5;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Entry {
+    count: u64,
+    /// Seconds since the Unix epoch
+    last_used: u64,
+}
+
+/// Per-command usage counts, kept in `usage.json` in the data directory so
+/// they survive restarts.
+#[derive(Debug, Default)]
+pub struct Usage {
+    entries: HashMap<String, Entry>,
+    path: Option<PathBuf>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+impl Usage {
+    /// Loads saved counts, starting empty if the file is missing or corrupt.
+    pub fn load() -> Self {
+        let path = data_dir().map(|dir| dir.join("usage.json"));
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    fn bump(&mut self, command: &str, timestamp: u64) {
+        let entry = self.entries.entry(command.to_string()).or_default();
+        entry.count += 1;
+        entry.last_used = timestamp;
+    }
+
+    /// Counts a run of `command` and saves. Failing to save only loses the
+    /// statistics, so errors are ignored.
+    pub fn record(&mut self, command: &str) {
+        self.bump(command, now());
+        if let Some(path) = &self.path
+            && let Ok(contents) = serde_json::to_string(&self.entries)
+        {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            let _ = fs::write(path, contents);
+        }
+    }
+
+    /// The most used of `available`, most recently used first among ties.
+    pub fn frequent<'a>(&self, available: &[&'a str], limit: usize) -> Vec<&'a str> {
+        let mut used: Vec<(&'a str, &Entry)> = available
+            .iter()
+            .filter_map(|name| self.entries.get(*name).map(|entry| (*name, entry)))
+            .collect();
+        used.sort_by(|(_, a), (_, b)| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| b.last_used.cmp(&a.last_used))
+        });
+        used.into_iter().take(limit).map(|(name, _)| name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequent_orders_by_count_then_recency() {
+        let mut usage = Usage::default();
+        usage.bump("/json-format", 10);
+        usage.bump("/json-format", 11);
+        usage.bump("/base64-decode", 12);
+        usage.bump("/copy", 20);
+        usage.bump("/removed-plugin", 30);
+
+        let available = ["/base64-decode", "/copy", "/json-format", "/undo"];
+        assert_eq!(
+            usage.frequent(&available, 5),
+            ["/json-format", "/copy", "/base64-decode"]
+        );
+        assert_eq!(usage.frequent(&available, 1), ["/json-format"]);
+    }
+}
@@ -0,0 +1,43 @@
+use pomp::mcp::handle_message;
+use serde_json::Value;
+
+fn reply(message: &str) -> Value {
+    serde_json::from_str(&handle_message(message).unwrap()).unwrap()
+}
+
+#[test]
+fn test_initialize_and_notifications() {
+    let response = reply(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#);
+    assert_eq!(response["result"]["serverInfo"]["name"], "pomp");
+    assert!(response["result"]["capabilities"]["tools"].is_object());
+
+    let notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+    assert_eq!(handle_message(notification), None);
+}
+
+#[test]
+fn test_tools_list_strips_slash() {
+    let response = reply(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#);
+    let tools = response["result"]["tools"].as_array().unwrap();
+    let json_format = tools
+        .iter()
+        .find(|tool| tool["name"] == "json-format")
+        .unwrap();
+    assert_eq!(json_format["inputSchema"]["required"][0], "input");
+}
+
+#[test]
+fn test_tools_call() {
+    let response = reply(
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call",
+            "params":{"name":"convert","arguments":{"input":"1500","args":["ms","s"]}}}"#,
+    );
+    assert_eq!(response["result"]["content"][0]["text"], "1.5");
+    assert_eq!(response["result"]["isError"], false);
+
+    let response = reply(
+        r#"{"jsonrpc":"2.0","id":4,"method":"tools/call",
+            "params":{"name":"json-format","arguments":{"input":"{"}}}"#,
+    );
+    assert_eq!(response["result"]["isError"], true);
+}
@@ -0,0 +1,47 @@
+use std::net::TcpListener;
+
+use pomp::rpc::{Client, RpcError, handle_request, serve};
+
+#[test]
+fn test_handle_request_errors() {
+    let response = handle_request("not json");
+    assert!(response.contains("-32700"));
+
+    let response = handle_request(r#"{"jsonrpc":"2.0","id":7,"method":"nope"}"#);
+    assert!(response.contains(r#""id":7"#));
+    assert!(response.contains("-32601"));
+
+    let response = handle_request(
+        r#"{"jsonrpc":"2.0","id":1,"method":"run","params":{"command":"json-format","input":"[1 2]"}}"#,
+    );
+    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+    let data = &response["error"]["data"];
+    assert_eq!(data["kind"], "invalid-input");
+    assert_eq!(data["command"], "/json-format");
+    assert_eq!((&data["line"], &data["column"]), (&1.into(), &4.into()));
+}
+
+#[test]
+fn test_client_round_trip() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || serve(listener));
+
+    let mut client = Client::connect(addr).unwrap();
+    assert_eq!(
+        client.run("/base64-encode", "hello", &[]).unwrap(),
+        "aGVsbG8="
+    );
+    assert_eq!(client.run("convert", "1500", &["ms", "s"]).unwrap(), "1.5");
+    assert!(matches!(
+        client.run("/json-format", "{", &[]),
+        Err(RpcError::Remote(-32000, _))
+    ));
+    assert!(
+        client
+            .list()
+            .unwrap()
+            .iter()
+            .any(|(name, _)| name == "/json-format")
+    );
+}
@@ -0,0 +1,76 @@
+use pomp::{TRANSFORMS, TransformError, transform};
+
+#[test]
+fn test_transform_with_and_without_slash() {
+    let encoded = transform("/base64-encode", "hello", &[]).unwrap();
+    assert_eq!(encoded, "aGVsbG8=");
+    assert_eq!(transform("base64-decode", &encoded, &[]).unwrap(), "hello");
+}
+
+#[test]
+fn test_transform_args() {
+    let converted = transform("convert", "1500\n250", &["ms", "s"]).unwrap();
+    assert_eq!(converted, "1.5\n0.25");
+    assert!(matches!(
+        transform("convert", "1", &["ms", "kg"]),
+        Err(TransformError::Failed(_))
+    ));
+}
+
+#[test]
+fn test_base64_decode_gunzip_json_format_chain() {
+    let compressed = transform("gzip", "{\"a\":1}", &[]).unwrap();
+    let formatted = ["base64-decode", "gunzip", "json-format"]
+        .iter()
+        .try_fold(compressed, |buffer, name| transform(name, &buffer, &[]))
+        .unwrap();
+    assert_eq!(formatted, "{\n  \"a\": 1\n}");
+}
+
+#[test]
+fn test_transform_errors() {
+    assert!(matches!(
+        transform("json-format", "", &[]),
+        Err(TransformError::EmptyInput)
+    ));
+    assert!(matches!(
+        transform("no-such-command", "x", &[]),
+        Err(TransformError::UnknownTransform(_))
+    ));
+}
+
+#[test]
+fn test_parse_error_positions() {
+    let json = transform("json-format", "{\n  \"a\": 1,\n  \"b\" 2\n}", &[]).unwrap_err();
+    assert_eq!(json.position(), Some((3, 7)));
+    let css = transform("css-format", "a {\n  color: red;\n}\n}", &[]).unwrap_err();
+    assert_eq!(css.position(), Some((4, 2)));
+    assert_eq!(TransformError::EmptyInput.position(), None);
+}
+
+#[test]
+fn test_generators_ignore_input() {
+    let uuid = transform("uuid", "", &[]).unwrap();
+    assert_eq!(uuid.len(), 36);
+    assert!(TRANSFORMS.iter().all(|t| t.name.starts_with('/')));
+}
+
+#[test]
+fn test_apply_tags_output() {
+    let decode = pomp::cmds::find_transform("base64-decode").unwrap();
+    let output = decode.apply("eyJhIjogMX0=", &[]).unwrap();
+    assert_eq!(output.text, "{\"a\": 1}");
+    assert_eq!(output.content_type, pomp::detect::ContentType::Json);
+    assert_eq!(output.suggestions, ["/json-format"]);
+
+    // A transform never suggests running itself again
+    let format = pomp::cmds::find_transform("json-format").unwrap();
+    let output = format.apply("{\"a\":1}", &[]).unwrap();
+    assert!(!output.suggestions.contains(&"/json-format"));
+
+    // Transforms that know their output type skip detection
+    let random = pomp::cmds::find_transform("random-string").unwrap();
+    let output = random.apply("", &["hex"]).unwrap();
+    assert_eq!(output.content_type, pomp::detect::ContentType::Text);
+    assert!(output.suggestions.is_empty());
+}